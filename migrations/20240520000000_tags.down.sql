@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS "tag_relations";
+DROP TABLE IF EXISTS "tag_groups";
+DROP TABLE IF EXISTS "tags";
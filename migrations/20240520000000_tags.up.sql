@@ -0,0 +1,40 @@
+-- Add up migration script here
+CREATE TABLE
+    "tags" (
+        id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+        "user" UUID NOT NULL REFERENCES "users" (id) ON DELETE CASCADE,
+        name VARCHAR(100) NOT NULL,
+        "createdAt" TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL DEFAULT NOW(),
+            "updatedAt" TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL DEFAULT NOW(),
+            CONSTRAINT tags_user_name_key UNIQUE ("user", name)
+    );
+
+CREATE TABLE
+    "tag_groups" (
+        id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+        "user" UUID NOT NULL REFERENCES "users" (id) ON DELETE CASCADE,
+        name VARCHAR(100) NOT NULL,
+        color VARCHAR(50) NOT NULL,
+        "createdAt" TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL DEFAULT NOW(),
+            "updatedAt" TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL DEFAULT NOW(),
+            CONSTRAINT tag_groups_user_name_key UNIQUE ("user", name)
+    );
+
+-- tag나 group이 삭제되면 relation도 함께 삭제됨
+CREATE TABLE
+    "tag_relations" (
+        "user" UUID NOT NULL REFERENCES "users" (id) ON DELETE CASCADE,
+        tag_id UUID NOT NULL REFERENCES "tags" (id) ON DELETE CASCADE,
+        group_id UUID NOT NULL REFERENCES "tag_groups" (id) ON DELETE CASCADE,
+        PRIMARY KEY (tag_id, group_id)
+    );
+
+CREATE INDEX tag_relations_group_id_idx ON tag_relations (group_id);
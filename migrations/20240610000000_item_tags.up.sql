@@ -0,0 +1,16 @@
+-- Add up migration script here
+-- mongodb의 task, memo, habit, note에 붙인 tag
+-- tag가 삭제되면 함께 삭제되고, tag 이름은 조회할 때 join하므로 rename도 바로 반영됨
+CREATE TABLE
+    "item_tags" (
+        "user" UUID NOT NULL REFERENCES "users" (id) ON DELETE CASCADE,
+        tag_id UUID NOT NULL REFERENCES "tags" (id) ON DELETE CASCADE,
+        item_type VARCHAR(20) NOT NULL,
+        item_oid VARCHAR(24) NOT NULL,
+        "createdAt" TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL DEFAULT NOW(),
+            PRIMARY KEY ("user", tag_id, item_type, item_oid)
+    );
+
+CREATE INDEX item_tags_item_idx ON item_tags ("user", item_type, item_oid);
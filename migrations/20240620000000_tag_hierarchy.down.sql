@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS item_tags_tag_id_idx;
+ALTER TABLE "tag_groups" DROP COLUMN IF EXISTS parent_id;
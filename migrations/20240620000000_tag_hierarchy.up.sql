@@ -0,0 +1,9 @@
+-- Add up migration script here
+-- 상위 group이 삭제되면 하위 group은 최상위로 올라감
+ALTER TABLE "tag_groups"
+ADD COLUMN parent_id UUID REFERENCES "tag_groups" (id) ON DELETE SET NULL;
+
+CREATE INDEX tag_groups_parent_id_idx ON tag_groups (parent_id);
+
+-- tag별 사용 횟수, merge
+CREATE INDEX item_tags_tag_id_idx ON item_tags (tag_id);
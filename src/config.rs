@@ -1,4 +1,4 @@
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Config {
     pub database_url: String,
     pub client_origin: String,
@@ -22,6 +22,8 @@ pub struct Config {
     pub google_oauth_client_id: String,
     pub google_oauth_client_secret: String,
     pub google_oauth_redirect_url: String,
+
+    pub trash_retention_days: i64,
 }
 
 impl Config {
@@ -52,6 +54,12 @@ impl Config {
         let google_oauth_redirect_url = std::env::var("GOOGLE_OAUTH_REDIRECT_URL")
             .expect("GOOGLE_OAUTH_REDIRECT_URL must be set");
 
+        // 휴지통 보관 기간(일), 기본 30일
+        let trash_retention_days = std::env::var("TRASH_RETENTION_DAYS")
+            .ok()
+            .and_then(|days| days.parse::<i64>().ok())
+            .unwrap_or(30);
+
         Config {
             database_url,
             client_origin,
@@ -71,6 +79,7 @@ impl Config {
             google_oauth_client_id,
             google_oauth_client_secret,
             google_oauth_redirect_url,
+            trash_retention_days,
         }
     }
 }
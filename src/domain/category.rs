@@ -2,13 +2,19 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use std::{collections::HashSet, str::FromStr};
+use std::str::FromStr;
 
 use super::sub::property::PropertyModel;
-use crate::infra::types::{PropertyType, QueryFilterOptions, StatusType};
+use crate::infra::types::{
+    PageOptions, PropertyType, QueryFilterOptions, QueryOptions, StatusType,
+};
 use crate::{
     domain::error::{Error::*, Result},
     domain::repo::base::{self, MongoRepo},
+    domain::repo::index::{IndexDef, IndexKind::*},
+    domain::repo::query::{FieldKind, Load::*, QueryFields, SelectFields},
+    domain::repo::storage::Storage,
+    domain::repo::transaction::Transaction,
     infra::db::error::Error as DBError,
     interface::dto::category::{
         req::{CreateCategoryReq, UpdateCategoryReq},
@@ -17,7 +23,7 @@ use crate::{
 };
 use mongodb::{
     bson::{self, doc, oid::ObjectId, Bson, Document},
-    Database,
+    ClientSession,
 };
 
 #[allow(non_snake_case)]
@@ -27,10 +33,13 @@ pub struct CategoryModel {
     pub id: ObjectId,
     #[serde(with = "bson::serde_helpers::uuid_1_as_binary")]
     pub user: Uuid,
-    pub name: String,                                 
+    pub name: String,
     pub color: String,
     pub status: StatusType,
+    #[serde(default)]
     pub props: Vec<PropertyModel>,
+    #[serde(default)]
+    pub version: i64,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub createdAt: DateTime<Utc>,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
@@ -42,20 +51,30 @@ pub struct CategoryService;
 
 impl MongoRepo for CategoryService {
     const COLL_NAME: &'static str = "categories";
+    const QUERY_FIELDS: QueryFields = &[
+        ("name", FieldKind::Str),
+        ("color", FieldKind::Str),
+        ("createdAt", FieldKind::DateTime),
+        ("updatedAt", FieldKind::DateTime),
+    ];
+    const SELECT_FIELDS: SelectFields = &[
+        ("user", Always),
+        ("name", Always),
+        ("color", Always),
+        ("status", Always),
+        ("props", Always),
+        ("createdAt", Always),
+        ("updatedAt", Always),
+        ("version", Always),
+    ];
+    const INDEXES: &'static [IndexDef] = &[IndexDef {
+        keys: &[("user", Asc), ("_id", Asc)],
+        ..IndexDef::DEFAULT
+    }];
     type Model = CategoryModel;
     type ModelResponse = CategoryRes;
-    fn convert_doc_to_response(category: &CategoryModel) -> Result<CategoryRes> {
-        let category_response = CategoryRes {
-            user: category.user,
-            id: category.id.to_hex(),
-            name: category.name.to_owned(),
-            color: category.color.to_owned(),
-            props: category.props.to_owned(),
-            createdAt: category.createdAt,
-            updatedAt: category.updatedAt,
-            status: category.status.to_owned(),
-        };
-        Ok(category_response)
+    fn convert_doc_to_response(category: &CategoryModel) -> CategoryRes {
+        CategoryRes::from_model(category)
     }
 
     fn create_doc<CreateCategoryReq: Serialize>(
@@ -72,13 +91,15 @@ impl MongoRepo for CategoryService {
             PropertyType::MultiSelect,
             None,
         )];
-        let _serialized_props =
+        let serialized_props =
             bson::to_bson(&props).map_err(|e| DB(DBError::MongoSerializeBsonError(e)))?;
         let datetime = Utc::now();
 
         let mut doc_with_dates = doc! {
             "user": user,
             "status": "InProgress",
+            "props": serialized_props,
+            "version": 1,
             "createdAt": datetime,
             "updatedAt": datetime,
         };
@@ -90,36 +111,35 @@ impl MongoRepo for CategoryService {
 impl CategoryService {
     //mongodb에서 category를 가져옴.
     pub async fn fetch_categories(
-        db: &Database,
-        limit: i64,
-        page: i64,
+        db: &dyn Storage,
+        page_opts: PageOptions,
+        query_opts: QueryOptions,
         user: &Uuid,
     ) -> Result<CategoryListRes> {
         let filter_opts = QueryFilterOptions {
             find_filter: None,
             proj_opts: None,
-            limit,
-            page,
+            page_opts,
+            query_opts,
         };
-        let categories_result = base::fetch::<Self>(db, filter_opts, user)
-            .await
-            .expect("category 응답을 받아오지 못했습니다.");
+        let categories_result = base::fetch::<Self>(db, filter_opts, user).await?;
 
         Ok(CategoryListRes {
             status: "success",
-            results: categories_result.len(),
-            categories: categories_result,
+            results: categories_result.items.len(),
+            categories: categories_result.items,
+            next_cursor: categories_result.next_cursor,
+            has_more: categories_result.has_more,
+            total: categories_result.total,
         })
     }
 
     pub async fn create_category(
-        db: &Database,
+        db: &dyn Storage,
         body: &CreateCategoryReq,
         user: &Uuid,
     ) -> Result<SingleCategoryRes> {
-        let category_result = base::create::<Self, CreateCategoryReq>(db, body, user, None)
-            .await
-            .expect("category 생성에 실패했습니다.");
+        let category_result = base::create::<Self, CreateCategoryReq>(db, body, user).await?;
 
         Ok(SingleCategoryRes {
             status: "success",
@@ -129,10 +149,12 @@ impl CategoryService {
         })
     }
 
-    pub async fn get_category(db: &Database, id: &str, user: &Uuid) -> Result<SingleCategoryRes> {
-        let category_result = base::get::<Self>(db, id, user)
-            .await
-            .expect("category를 가져오는데 실패했습니다.");
+    pub async fn get_category(
+        db: &dyn Storage,
+        id: &str,
+        user: &Uuid,
+    ) -> Result<SingleCategoryRes> {
+        let category_result = base::get::<Self>(db, id, user).await?;
 
         Ok(SingleCategoryRes {
             status: "success",
@@ -143,14 +165,13 @@ impl CategoryService {
     }
 
     pub async fn update_category(
-        db: &Database,
+        db: &dyn Storage,
         id: &str,
         body: &UpdateCategoryReq, //color, name
         user: &Uuid,
     ) -> Result<SingleCategoryRes> {
-        let category_result = base::update::<Self, UpdateCategoryReq>(db, id, body, user)
-            .await
-            .expect("category 업데이트에 실패했습니다.");
+        let category_result =
+            base::update::<Self, UpdateCategoryReq>(db, id, body, None, user).await?;
 
         // TODO: category를 포함하는 task들의 category정보 변경
 
@@ -162,263 +183,335 @@ impl CategoryService {
         })
     }
 
-    pub async fn delete_category(db: &Database, id: &str, target_id: Option<&str>) -> Result<()> {
-        if let Some(target_id) = target_id {
-            Self::move_prop_to_target_category(db, id, target_id)
-                .await
-                .expect("property 복제에 실패했습니다.");
+    pub async fn delete_category(
+        db: &dyn Storage,
+        id: &str,
+        target_id: Option<&str>,
+        user: &Uuid,
+    ) -> Result<()> {
+        let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+
+        // property 이동과 category 삭제는 하나의 transaction으로 실행함.
+        let mut tx = Transaction::start(db).await?;
+        let result = async {
+            if let Some(target_id) = target_id {
+                Self::move_prop_to_target_category(db, id, target_id, tx.session(), user).await?;
+            }
+
+            let filter = doc! { "_id": oid };
+            match base::delete_many::<Self>(db, filter, None, tx.session(), user).await? {
+                0 => Err(NotFoundError(id.to_string())),
+                _ => Ok(()),
+            }
+        }
+        .await;
+
+        tx.finish(result).await
+    }
+
+    async fn find_category(
+        db: &dyn Storage,
+        oid: &ObjectId,
+        session: Option<&mut ClientSession>,
+        user: &Uuid,
+    ) -> Result<CategoryModel> {
+        let filter = doc! { "_id": oid, "user": user, "deletedAt": { "$exists": false } };
+        match db.find_one(Self::COLL_NAME, filter, None, session).await? {
+            Some(doc) => Ok(bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?),
+            None => Err(NotFoundError(oid.to_hex())),
         }
-        base::delete::<Self>(db, id).await
     }
 
     async fn move_prop_to_target_category(
-        db: &Database,
+        db: &dyn Storage,
         delete_category_id: &str,
         target_category_id: &str,
+        mut session: Option<&mut ClientSession>,
+        user: &Uuid,
     ) -> Result<()> {
-        let coll = db.collection::<CategoryModel>(Self::COLL_NAME);
         let delete_category_id =
             ObjectId::from_str(delete_category_id).map_err(DBError::MongoGetOidError)?;
         let target_category_id =
             ObjectId::from_str(target_category_id).map_err(DBError::MongoGetOidError)?;
 
         // Step 1: Get the props of the category to be deleted
-        let delete_category = match coll.find_one(doc! {"_id": delete_category_id}, None).await {
-            Ok(Some(doc)) => doc,
-            Ok(None) => return Err(NotFoundError(delete_category_id.to_string())),
-            Err(e) => return Err(DB(DBError::MongoQueryError(e))),
-        };
-
-        let props_to_move = delete_category.props;
+        let delete_category =
+            Self::find_category(db, &delete_category_id, session.as_deref_mut(), user).await?;
 
         // Step 2: Get the target category's existing props
-        let target_category = match coll.find_one(doc! {"_id": target_category_id}, None).await {
-            Ok(Some(doc)) => doc,
-            Ok(None) => return Err(NotFoundError(delete_category_id.to_string())),
-            Err(e) => return Err(DB(DBError::MongoQueryError(e))),
-        };
+        let target_category =
+            Self::find_category(db, &target_category_id, session.as_deref_mut(), user).await?;
 
-        // 이름 중복 여부를 확인하는 작업의 효율성을 높이기 위해 HashSet 사용
+        // 같은 이름의 property가 있으면 option만 합치고, 없으면 그대로 옮김.
         // TODO: 어떤 option을 복제할지 역시 입력으로 받아와야함.
-        let existing_property_names: HashSet<_> = target_category
-            .props
-            .iter()
-            .map(|prop| &prop.name)
-            .collect();
-        let mut existing_property_options: HashSet<String> = HashSet::new();
-
-        for prop in &target_category.props {
-            if let Some(options) = &prop.options {
-                existing_property_options.extend(options.clone());
-            }
-        }
-
-        // Filter and process props to move
-        let mut filtered_props_to_move = Vec::new();
-
-        for prop in props_to_move {
-            if existing_property_names.contains(&prop.name) {
-                if let Some(options) = &prop.options {
-                    let new_options: Vec<String> = options
-                        .iter()
-                        .filter(|option| !existing_property_options.contains(*option))
-                        .cloned()
-                        .collect();
-
-                    if !new_options.is_empty() {
-                        let mut updated_prop = prop.clone();
-                        updated_prop.options = Some(new_options);
-                        filtered_props_to_move.push(updated_prop);
+        let mut merged_props = target_category.props;
+        let mut changed = false;
+        for prop in delete_category.props {
+            match merged_props.iter_mut().find(|p| p.name == prop.name) {
+                Some(existing) => {
+                    if !matches!(
+                        existing.prop_type,
+                        PropertyType::MultiSelect | PropertyType::SingleSelect
+                    ) {
+                        continue;
                     }
+                    let options = existing.options.get_or_insert_with(Vec::new);
+                    for option in prop.options.unwrap_or_default() {
+                        if !options.contains(&option) {
+                            options.push(option);
+                            changed = true;
+                        }
+                    }
+                }
+                None => {
+                    merged_props.push(prop);
+                    changed = true;
                 }
-            } else {
-                filtered_props_to_move.push(prop);
             }
         }
 
-        // Convert filtered props to Bson
-        let props_to_move_bson: Vec<Bson> = filtered_props_to_move
-            .into_iter()
-            .map(|prop| bson::to_bson(&prop).unwrap())
-            .collect();
-
-        // Step 3: Add these filtered props to the target category
-        if !props_to_move_bson.is_empty() {
-            coll.update_one(
-                doc! {"_id": target_category_id},
-                doc! {"$push": {"props": {"$each": props_to_move_bson}}},
-                None,
-            )
-            .await
-            .map_err(DBError::MongoQueryError)?;
+        // Step 3: Save the merged props to the target category
+        if changed {
+            let props_bson = bson::to_bson(&merged_props)
+                .map_err(|e| DB(DBError::MongoSerializeBsonError(e)))?;
+            let filter = doc! { "_id": target_category_id, "user": user };
+            let update = doc! {
+                "$set": {
+                    "props": props_bson,
+                    "updatedAt": Bson::DateTime(Utc::now().into()),
+                },
+                "$inc": { "version": 1 },
+            };
+            db.update_one(Self::COLL_NAME, filter, update, session)
+                .await?;
         }
 
         Ok(())
     }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use std::str::FromStr;
-
-//     use super::*;
-//     use crate::{domain::types::StatusType, infra::db::MongoDB};
-//     use dotenv::dotenv;
-//     use mongodb::options::UpdateOptions;
-
-//     async fn setup() -> Database {
-//         dotenv().ok();
-//         std::env::set_var("RUST_BACKTRACE", "0");
-//         let mongodb = MongoDB::init_test().await.unwrap();
-
-//         // 시드 데이터 생성
-//         let user = Uuid::from_bytes([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-//         let seeds = vec![
-//             CategoryModel {
-//                 id: ObjectId::from_str("507f1f77bcf86cd799439011").unwrap(),
-//                 user,
-//                 name: "Work".to_string(),
-//                 color: "Red".to_string(),
-//                 status: StatusType::InProgress,
-//                 props: vec![
-//                     PropertyModel::new(
-//                         ObjectId::new(),
-//                         "Tags".to_string(),
-//                         PropertyType::MultiSelect,
-//                         Some(vec!["FE".to_string(), "BE".to_string(), "OP".to_string()]),
-//                     ),
-//                     PropertyModel::new(
-//                         ObjectId::new(),
-//                         "Deadline".to_string(),
-//                         PropertyType::DateTime,
-//                         None,
-//                     ),
-//                 ],
-//                 createdAt: Utc::now(),
-//                 updatedAt: Utc::now(),
-//             },
-//             CategoryModel {
-//                 id: ObjectId::from_str("507f1f77bcf86cd799439013").unwrap(),
-//                 user,
-//                 name: "Personal".to_string(),
-//                 color: "Blue".to_string(),
-//                 status: StatusType::Archived,
-//                 props: vec![
-//                     PropertyModel::new(
-//                         ObjectId::new(),
-//                         "Tags".to_string(),
-//                         PropertyType::MultiSelect,
-//                         Some(vec!["FE".to_string(), "BE".to_string(), "OP".to_string()]),
-//                     ),
-//                     PropertyModel::new(
-//                         ObjectId::new(),
-//                         "Summary".to_string(),
-//                         PropertyType::Text,
-//                         None,
-//                     ),
-//                 ],
-//                 createdAt: Utc::now(),
-//                 updatedAt: Utc::now(),
-//             },
-//         ];
-
-//         // 시드 데이터를 MongoDB에 삽입
-//         for seed in seeds {
-//             let filter = doc! { "_id": seed.id };
-//             let update = doc! { "$setOnInsert": bson::to_bson(&seed).unwrap() };
-//             let options = UpdateOptions::builder().upsert(true).build();
-
-//             let result = mongodb
-//                 .db
-//                 .collection::<CategoryModel>("categories")
-//                 .update_one(filter, update, options)
-//                 .await
-//                 .expect("cannot insert seed data");
-
-//             // if result.upserted_id.is_some() {
-//             //     println!(
-//             //         "✅ 새로운 노트 시드 데이터가 추가되었습니다. ID: {}",
-//             //         seed.id
-//             //     );
-//             // } else {
-//             //     println!("이미 존재하는 노트 시드 데이터입니다. ID: {}", seed.id);
-//             // }
-//         }
-
-//         mongodb.db
-//     }
-
-//     #[tokio::test]
-//     async fn test_create_category() {
-//         let db = setup().await;
-//         let user_id = Uuid::from_bytes([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-//         let body = CreateCategoryReq {
-//             name: "Test Category1".to_string(),
-//             color: "red".to_string(),
-//         };
-
-//         let res = CategoryService::create_category(&db, &body, &user_id).await;
-//         claim::assert_ok!(&res);
-//         let res = res.unwrap();
-//         claim::assert_matches!(res.status, "success");
-//         assert_eq!(res.data.category.name, body.name);
-//     }
-
-//     #[tokio::test]
-//     async fn test_fetch_categories() {
-//         let db = setup().await;
-//         let user_id = Uuid::from_bytes([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-//         let limit = 10;
-//         let page = 1;
-
-//         let res = CategoryService::fetch_categories(&db, limit, page, &user_id).await;
-//         claim::assert_ok!(&res);
-//         let res = res.unwrap();
-//         claim::assert_matches!(res.status, "success");
-//     }
-
-//     #[tokio::test]
-//     async fn test_get_category() {
-//         let db = setup().await;
-//         let user_id = Uuid::from_bytes([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-//         let category_id = "507f1f77bcf86cd799439013";
-
-//         let res = CategoryService::get_category(&db, category_id, &user_id).await;
-//         claim::assert_ok!(&res);
-//         let res = res.unwrap();
-//         claim::assert_matches!(res.status, "success");
-//         assert_eq!(res.data.category.id, category_id);
-//     }
-
-//     #[tokio::test]
-//     async fn test_update_category() {
-//         let db = setup().await;
-//         let user_id = Uuid::from_bytes([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-//         let category_id = "507f1f77bcf86cd799439013";
-//         let body = UpdateCategoryReq {
-//             name: Some("Updated Category".to_string()),
-//             color: None,
-//             status: Some(StatusType::Archived),
-//         };
-
-//         let res = CategoryService::update_category(&db, category_id, &body, &user_id).await;
-//         claim::assert_ok!(&res);
-//         let res = res.unwrap();
-//         claim::assert_matches!(res.status, "success");
-//         assert_eq!(res.data.category.name, body.name.unwrap());
-//         // if let Some(content) = body.content{
-//         //     assert_eq!(res.data.category.content, content);
-//         // }
-//         // else {dbg!(res.data.category.content);} //기존값 유지
-//     }
-
-//     #[tokio::test]
-//     async fn test_delete_category() {
-//         let db = setup().await;
-//         let category_id = "507f1f77bcf86cd799439011";
-//         let target_id = "507f1f77bcf86cd799439013";
-
-//         let res = CategoryService::delete_category(&db, category_id, Some(target_id)).await;
-//         claim::assert_ok!(&res);
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::infra::db::MongoDB;
+
+    const USER: Uuid = Uuid::from_bytes([0; 16]);
+
+    async fn setup() -> MongoDB {
+        let mongodb = MongoDB::init_memory();
+
+        // 시드 데이터 생성
+        let seeds = vec![
+            CategoryModel {
+                id: ObjectId::from_str("507f1f77bcf86cd799439011").unwrap(),
+                user: USER,
+                name: "Work".to_string(),
+                color: "Red".to_string(),
+                status: StatusType::InProgress,
+                props: vec![
+                    PropertyModel::new(
+                        ObjectId::new(),
+                        "Tags".to_string(),
+                        PropertyType::MultiSelect,
+                        Some(vec!["FE".to_string(), "BE".to_string(), "OP".to_string()]),
+                    ),
+                    PropertyModel::new(
+                        ObjectId::new(),
+                        "Deadline".to_string(),
+                        PropertyType::DateTime,
+                        None,
+                    ),
+                ],
+                version: 1,
+                createdAt: Utc::now(),
+                updatedAt: Utc::now(),
+            },
+            CategoryModel {
+                id: ObjectId::from_str("507f1f77bcf86cd799439013").unwrap(),
+                user: USER,
+                name: "Personal".to_string(),
+                color: "Blue".to_string(),
+                status: StatusType::Archived,
+                props: vec![
+                    PropertyModel::new(
+                        ObjectId::new(),
+                        "Tags".to_string(),
+                        PropertyType::MultiSelect,
+                        Some(vec!["FE".to_string(), "QA".to_string()]),
+                    ),
+                    PropertyModel::new(
+                        ObjectId::new(),
+                        "Summary".to_string(),
+                        PropertyType::Text,
+                        None,
+                    ),
+                ],
+                version: 1,
+                createdAt: Utc::now(),
+                updatedAt: Utc::now(),
+            },
+        ];
+
+        // 시드 데이터를 MongoDB에 삽입
+        for seed in seeds {
+            let doc = bson::to_document(&seed).unwrap();
+            mongodb
+                .db
+                .insert_one(CategoryService::COLL_NAME, doc, None)
+                .await
+                .expect("cannot insert seed data");
+        }
+
+        mongodb
+    }
+
+    #[tokio::test]
+    async fn test_create_category() {
+        let mongodb = setup().await;
+        let body = CreateCategoryReq {
+            name: "Test Category1".to_string(),
+            color: "red".to_string(),
+        };
+
+        let res = CategoryService::create_category(&mongodb.db, &body, &USER).await;
+        claim::assert_ok!(&res);
+        let res = res.unwrap();
+        claim::assert_matches!(res.status, "success");
+        assert_eq!(res.data.category.name, body.name);
+        assert_eq!(res.data.category.status, StatusType::InProgress);
+        // 기본 property
+        assert_eq!(res.data.category.props.len(), 1);
+        assert_eq!(res.data.category.props[0].name, "Tags");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_categories() {
+        let mongodb = setup().await;
+        let page_opts = PageOptions {
+            limit: 10,
+            page: 1,
+            cursor: None,
+            with_total: true,
+        };
+
+        let res = CategoryService::fetch_categories(
+            &mongodb.db,
+            page_opts,
+            QueryOptions::default(),
+            &USER,
+        )
+        .await;
+        claim::assert_ok!(&res);
+        let res = res.unwrap();
+        claim::assert_matches!(res.status, "success");
+        assert_eq!(res.total, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_get_category() {
+        let mongodb = setup().await;
+        let category_id = "507f1f77bcf86cd799439013";
+
+        let res = CategoryService::get_category(&mongodb.db, category_id, &USER).await;
+        claim::assert_ok!(&res);
+        let res = res.unwrap();
+        claim::assert_matches!(res.status, "success");
+        assert_eq!(res.data.category.id, category_id);
+
+        // 다른 user의 category는 찾을 수 없음
+        let other = Uuid::from_bytes([1; 16]);
+        let res = CategoryService::get_category(&mongodb.db, category_id, &other).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_update_category() {
+        let mongodb = setup().await;
+        let category_id = "507f1f77bcf86cd799439013";
+        let body = UpdateCategoryReq {
+            name: Some("Updated Category".to_string()),
+            color: None,
+            status: Some(StatusType::InProgress),
+        };
+
+        let res = CategoryService::update_category(&mongodb.db, category_id, &body, &USER).await;
+        claim::assert_ok!(&res);
+        let res = res.unwrap();
+        claim::assert_matches!(res.status, "success");
+        assert_eq!(res.data.category.name, body.name.unwrap());
+        assert_eq!(res.data.category.color, "Blue"); //기존값 유지
+        assert_eq!(res.data.category.status, StatusType::InProgress);
+        assert_eq!(res.data.category.version, 2);
+    }
+
+    #[tokio::test]
+    async fn test_delete_category() {
+        let mongodb = setup().await;
+        let category_id = "507f1f77bcf86cd799439011";
+        let target_id = "507f1f77bcf86cd799439013";
+
+        let res =
+            CategoryService::delete_category(&mongodb.db, category_id, Some(target_id), &USER)
+                .await;
+        claim::assert_ok!(&res);
+
+        let res = CategoryService::get_category(&mongodb.db, category_id, &USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+
+        // 대상 category에 없는 property는 그대로 옮겨짐
+        let res = CategoryService::get_category(&mongodb.db, target_id, &USER)
+            .await
+            .unwrap();
+        let names: Vec<&str> = res
+            .data
+            .category
+            .props
+            .iter()
+            .map(|prop| prop.name.as_str())
+            .collect();
+        assert!(names.contains(&"Deadline"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_category_merges_props() {
+        let mongodb = setup().await;
+        let category_id = "507f1f77bcf86cd799439011";
+        let target_id = "507f1f77bcf86cd799439013";
+
+        CategoryService::delete_category(&mongodb.db, category_id, Some(target_id), &USER)
+            .await
+            .unwrap();
+
+        let target = CategoryService::get_category(&mongodb.db, target_id, &USER)
+            .await
+            .unwrap()
+            .data
+            .category;
+        let names: Vec<&str> = target.props.iter().map(|prop| prop.name.as_str()).collect();
+        assert_eq!(names, vec!["Tags", "Summary", "Deadline"]);
+        // 같은 이름의 property는 하나로 합쳐지고, 없던 option만 더해짐
+        let options = target.props[0].options.clone().unwrap();
+        assert_eq!(options, vec!["FE", "QA", "BE", "OP"]);
+        assert_eq!(target.version, 2);
+    }
+
+    #[tokio::test]
+    async fn test_delete_category_with_missing_target() {
+        let mongodb = setup().await;
+        let category_id = "507f1f77bcf86cd799439011";
+        let target_id = ObjectId::new().to_hex();
+
+        let res =
+            CategoryService::delete_category(&mongodb.db, category_id, Some(&target_id), &USER)
+                .await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+
+        // 합치기에 실패하면 삭제하지 않음
+        let res = CategoryService::get_category(&mongodb.db, category_id, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.data.category.props.len(), 2);
+    }
+}
@@ -3,8 +3,17 @@ use mongodb::bson::{self, oid::ObjectId};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::repo::index::{IndexDef, IndexKind::*};
 use super::sub::daily_item::{DailyEventModel, DailyHabitModel, DailyTaskModel, TimerResultModel};
 
+pub const COLL_NAME: &str = "daily";
+// user의 날짜마다 daily 문서는 하나
+pub const INDEXES: &[IndexDef] = &[IndexDef {
+    keys: &[("user", Asc), ("date", Asc)],
+    unique: true,
+    ..IndexDef::DEFAULT
+}];
+
 #[allow(non_snake_case)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DailyModel {
@@ -19,6 +28,8 @@ pub struct DailyModel {
     pub events: Vec<DailyEventModel>,
     pub habits: Vec<DailyHabitModel>,
     pub timer_results: Vec<TimerResultModel>,
+    #[serde(default)]
+    pub version: i64,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub createdAt: DateTime<Utc>,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
@@ -24,6 +24,18 @@ pub enum Error {
     TypedError(String),
     NotRemovedError(String),
 
+    // If-Match의 version이 현재 문서의 version과 다름
+    PreconditionFailed(String),
+
+    // task의 반복 규칙(RRULE)이 잘못됨
+    InvalidRecurrence(String),
+
+    // 허용되지 않는 task status 전이
+    InvalidTransition(String),
+
+    // 이미 실행 중인 timer가 있거나, 멈출 timer가 없음
+    TimerConflict(String),
+
 }
 
 impl IntoResponse for Error {
@@ -60,6 +72,34 @@ impl IntoResponse for Error {
                     message: "You do not have access to this note".to_string(),
                 },
             ),
+            Error::PreconditionFailed(id) => (
+                StatusCode::PRECONDITION_FAILED,
+                ErrorResponse {
+                    status: "fail".to_string(),
+                    message: format!("ID: {} has been modified by another request", id),
+                },
+            ),
+            Error::InvalidRecurrence(e) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    status: "fail".to_string(),
+                    message: format!("Invalid recurrence: {}", e),
+                },
+            ),
+            Error::InvalidTransition(e) => (
+                StatusCode::CONFLICT,
+                ErrorResponse {
+                    status: "fail".to_string(),
+                    message: format!("Invalid status transition: {}", e),
+                },
+            ),
+            Error::TimerConflict(e) => (
+                StatusCode::CONFLICT,
+                ErrorResponse {
+                    status: "fail".to_string(),
+                    message: format!("Timer conflict: {}", e),
+                },
+            ),
             Error::TypedError(e) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 ErrorResponse {
@@ -1,11 +1,13 @@
 use chrono::{DateTime, Utc};
 use mongodb::bson::doc;
+use mongodb::bson::Document;
 use mongodb::bson::{self, oid::ObjectId};
-use mongodb::{bson::Document, Database};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::infra::types::QueryFilterOptions;
+use crate::infra::types::{PageOptions, QueryFilterOptions, QueryOptions};
+use crate::interface::dto::batch::{req::BatchUpdateReq, res::BatchRes};
+use crate::interface::dto::history::res::ChangeListRes;
 use crate::interface::dto::habit::{
     req::{CreateHabitReq, UpdateHabitReq},
     res::{HabitData, HabitListRes, HabitRes, SingleHabitRes},
@@ -13,6 +15,9 @@ use crate::interface::dto::habit::{
 use crate::{
     domain::error::{Error::*, Result},
     domain::repo::base::{self, MongoRepo},
+    domain::repo::index::{IndexDef, IndexKind::*},
+    domain::repo::query::{FieldKind, Load::*, QueryFields, SelectFields},
+    domain::repo::storage::Storage,
     domain::sub::habit_record::HabitRecord,
     infra::db::error::Error as DBError,
     infra::types::StatusType,
@@ -30,6 +35,8 @@ pub struct HabitModel {
     pub color: String,
     pub records: Option<Vec<HabitRecord>>,
     pub status: StatusType,
+    #[serde(default)]
+    pub version: i64,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub createdAt: DateTime<Utc>,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
@@ -41,6 +48,35 @@ pub struct HabitService;
 
 impl MongoRepo for HabitService {
     const COLL_NAME: &'static str = "habits";
+    const QUERY_FIELDS: QueryFields = &[
+        ("name", FieldKind::Str),
+        ("color", FieldKind::Str),
+        ("status", FieldKind::Str),
+        ("createdAt", FieldKind::DateTime),
+        ("updatedAt", FieldKind::DateTime),
+    ];
+    const SELECT_FIELDS: SelectFields = &[
+        ("user", Always),
+        ("name", Always),
+        ("icon", Always),
+        ("color", Always),
+        ("records", Lazy),
+        ("status", Always),
+        ("createdAt", Always),
+        ("updatedAt", Always),
+        ("version", Always),
+    ];
+    const INDEXES: &'static [IndexDef] = &[
+        IndexDef {
+            keys: &[("user", Asc), ("_id", Asc)],
+            ..IndexDef::DEFAULT
+        },
+        // sync pull
+        IndexDef {
+            keys: &[("user", Asc), ("updatedAt", Asc)],
+            ..IndexDef::DEFAULT
+        },
+    ];
     type Model = HabitModel;
     type ModelResponse = HabitRes;
     fn convert_doc_to_response(habit: &HabitModel) -> HabitRes {
@@ -58,6 +94,7 @@ impl MongoRepo for HabitService {
         let mut doc_with_dates = doc! {
             "user": user,
             "status": "InProgress",
+            "version": 1,
             "createdAt": datetime,
             "updatedAt": datetime,
         };
@@ -69,9 +106,9 @@ impl MongoRepo for HabitService {
 impl HabitService {
     //mongodb에서 habit를 가져옴.
     pub async fn fetch_habits(
-        db: &Database,
-        limit: i64,
-        page: i64,
+        db: &dyn Storage,
+        page_opts: PageOptions,
+        query_opts: QueryOptions,
         start_month: &str,
         end_month: &str,
         user: &Uuid,
@@ -89,44 +126,48 @@ impl HabitService {
         let filter_opts = QueryFilterOptions {
             find_filter: Some(find_filter),
             proj_opts: None,
-            limit,
-            page,
+            page_opts,
+            query_opts,
         };
         
         tracing::info!("filter_opts: {:?}", filter_opts.find_filter);
 
-        let habits_result = base::fetch::<Self>(db, filter_opts, user)
-            .await
-            .expect("habit 응답을 받아오지 못했습니다.");
+        let habits_result = base::fetch::<Self>(db, filter_opts, user).await?;
 
         Ok(HabitListRes {
             status: "success",
-            results: habits_result.len(),
-            habits: habits_result,
+            results: habits_result.items.len(),
+            habits: habits_result.items,
+            next_cursor: habits_result.next_cursor,
+            has_more: habits_result.has_more,
+            total: habits_result.total,
         })
     }
 
     pub async fn create_habit(
-        db: &Database,
+        db: &dyn Storage,
         body: &CreateHabitReq,
         user: &Uuid,
     ) -> Result<SingleHabitRes> {
-        let habit_result = base::create::<Self, CreateHabitReq>(db, body, user, None)
+        let habit_result = base::create::<Self, CreateHabitReq>(db, body, user)
             .await
             .expect("habit 생성에 실패했습니다.");
 
         Ok(SingleHabitRes {
             status: "success",
             data: HabitData {
-                habit: habit_result,
+                habit: habit_result.into(),
             },
         })
     }
 
-    pub async fn get_habit(db: &Database, id: &str, user: &Uuid) -> Result<SingleHabitRes> {
-        let habit_result = base::get::<Self>(db, id, user)
-            .await
-            .expect("habit를 가져오는데 실패했습니다.");
+    pub async fn get_habit(
+        db: &dyn Storage,
+        id: &str,
+        fields: Option<&str>,
+        user: &Uuid,
+    ) -> Result<SingleHabitRes> {
+        let habit_result = base::get_fields::<Self>(db, id, fields, user).await?;
 
         Ok(SingleHabitRes {
             status: "success",
@@ -137,24 +178,81 @@ impl HabitService {
     }
 
     pub async fn update_habit(
-        db: &Database,
+        db: &dyn Storage,
         id: &str,
         body: &UpdateHabitReq,
+        if_match: Option<i64>,
         user: &Uuid,
     ) -> Result<SingleHabitRes> {
-        let habit_result = base::update::<Self, UpdateHabitReq>(db, id, body, user)
-            .await
-            .expect("habit 업데이트에 실패했습니다.");
+        let habit_result =
+            base::update::<Self, UpdateHabitReq>(db, id, body, if_match, user).await?;
 
         Ok(SingleHabitRes {
             status: "success",
             data: HabitData {
-                habit: habit_result,
+                habit: habit_result.into(),
+            },
+        })
+    }
+
+    pub async fn fetch_habit_history(
+        db: &dyn Storage,
+        id: &str,
+        page_opts: PageOptions,
+        user: &Uuid,
+    ) -> Result<ChangeListRes> {
+        let changes = base::fetch_history::<Self>(db, id, &page_opts, user).await?;
+        Ok(ChangeListRes::from_page(changes))
+    }
+
+    pub async fn revert_habit(
+        db: &dyn Storage,
+        id: &str,
+        revision: &str,
+        user: &Uuid,
+    ) -> Result<SingleHabitRes> {
+        let habit_result = base::revert::<Self>(db, id, revision, user).await?;
+
+        Ok(SingleHabitRes {
+            status: "success",
+            data: HabitData {
+                habit: habit_result.into(),
             },
         })
     }
 
-    pub async fn delete_habit(db: &Database, id: &str) -> Result<()> {
-        base::delete::<Self>(db, id).await
+    pub async fn bulk_create_habits(
+        db: &dyn Storage,
+        bodies: &[CreateHabitReq],
+        user: &Uuid,
+    ) -> Result<BatchRes<HabitRes>> {
+        let results = base::bulk_create::<Self, CreateHabitReq>(db, bodies, user).await?;
+        Ok(BatchRes::from_results(results))
+    }
+
+    pub async fn bulk_update_habits(
+        db: &dyn Storage,
+        items: &[BatchUpdateReq<UpdateHabitReq>],
+        user: &Uuid,
+    ) -> Result<BatchRes<HabitRes>> {
+        let items: Vec<(&str, Option<i64>, &UpdateHabitReq)> = items
+            .iter()
+            .map(|item| (item.id.as_str(), item.version, &item.body))
+            .collect();
+        let results = base::bulk_update::<Self, UpdateHabitReq>(db, &items, user).await?;
+        Ok(BatchRes::from_results(results))
+    }
+
+    pub async fn bulk_delete_habits(
+        db: &dyn Storage,
+        ids: &[String],
+        user: &Uuid,
+    ) -> Result<BatchRes<()>> {
+        let results = base::bulk_delete::<Self>(db, ids, user).await?;
+        Ok(BatchRes::without_data(results))
+    }
+
+    pub async fn delete_habit(db: &dyn Storage, id: &str, user: &Uuid) -> Result<()> {
+        base::delete::<Self>(db, id, user).await
     }
 }
@@ -0,0 +1,41 @@
+use super::{
+    category::CategoryService,
+    daily,
+    habit::HabitService,
+    memo::MemoService,
+    note::{NoteModel, NoteService},
+    repo::history,
+    repo::index::{coll_indexes, elem_indexes, indexes_of, CollIndexes},
+    sub::{
+        chat::ChatMsgService,
+        daily_item::{DailyEventModel, DailyHabitModel, DailyItemService, DailyTaskModel},
+        property::PropertyService,
+        schedule_item::{ScheduleItemService, ScheduledAt},
+    },
+    settings::SettingsService,
+    task::{TaskModel, TaskService},
+    task_template::TaskTemplateService,
+};
+
+/// MongoDB::init에서 맞춰질 모든 컬렉션의 인덱스 선언.
+/// 새로운 MongoRepo/MongoArrayRepo를 추가하면 여기에도 등록해야 함.
+pub fn registry() -> Vec<CollIndexes> {
+    vec![
+        coll_indexes::<TaskService>(),
+        elem_indexes::<ChatMsgService<TaskModel>>(),
+        coll_indexes::<TaskTemplateService>(),
+        coll_indexes::<SettingsService>(),
+        coll_indexes::<MemoService>(),
+        coll_indexes::<HabitService>(),
+        coll_indexes::<CategoryService>(),
+        indexes_of(daily::COLL_NAME, daily::INDEXES),
+        elem_indexes::<DailyItemService<DailyTaskModel>>(),
+        elem_indexes::<DailyItemService<DailyEventModel>>(),
+        elem_indexes::<DailyItemService<DailyHabitModel>>(),
+        coll_indexes::<NoteService>(),
+        elem_indexes::<ChatMsgService<NoteModel>>(),
+        elem_indexes::<PropertyService>(),
+        elem_indexes::<ScheduleItemService<ScheduledAt>>(),
+        indexes_of(history::COLL_NAME, history::INDEXES),
+    ]
+}
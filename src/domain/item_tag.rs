@@ -0,0 +1,328 @@
+use mongodb::bson::{doc, oid::ObjectId};
+use sqlx::{FromRow, Pool, Postgres};
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::{
+    domain::error::{Error::*, Result},
+    domain::repo::query::FieldSelection,
+    domain::repo::storage::Storage,
+    infra::db::error::Error as DBError,
+    infra::types::{ItemType, QueryOptions},
+    interface::dto::item_tag::res::{ItemTagListRes, ItemTagRes},
+    interface::dto::{memo::res::MemoRes, task::res::TaskRes},
+};
+
+impl ItemType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemType::Task => "task",
+            ItemType::Memo => "memo",
+            ItemType::Habit => "habit",
+            ItemType::Note => "note",
+        }
+    }
+
+    pub fn coll_name(&self) -> &'static str {
+        match self {
+            ItemType::Task => "tasks",
+            ItemType::Memo => "memos",
+            ItemType::Habit => "habits",
+            ItemType::Note => "notes",
+        }
+    }
+}
+
+/// tag 정보를 inline으로 담는 응답
+pub trait Tagged {
+    fn item_id(&self) -> &str;
+    fn set_tags(&mut self, tags: Vec<ItemTagRes>);
+}
+
+impl Tagged for TaskRes {
+    fn item_id(&self) -> &str {
+        &self.id
+    }
+
+    fn set_tags(&mut self, tags: Vec<ItemTagRes>) {
+        self.tags = Some(tags);
+    }
+}
+
+impl Tagged for MemoRes {
+    fn item_id(&self) -> &str {
+        &self.id
+    }
+
+    fn set_tags(&mut self, tags: Vec<ItemTagRes>) {
+        self.tags = Some(tags);
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+struct ItemTagRow {
+    item_oid: String,
+    id: Uuid,
+    name: String,
+}
+
+pub struct ItemTagService;
+
+impl ItemTagService {
+    /// item에 tag를 붙임. 이미 붙어 있으면 그대로 둠.
+    pub async fn tag_item(
+        pg: &Pool<Postgres>,
+        db: &dyn Storage,
+        user: &Uuid,
+        item_type: ItemType,
+        item_id: &str,
+        tag_id: Uuid,
+    ) -> Result<ItemTagListRes> {
+        // mongodb에 살아있는 item인지 확인
+        let oid = ObjectId::from_str(item_id).map_err(DBError::MongoGetOidError)?;
+        let filter = doc! {"_id": oid, "user": user, "deletedAt": {"$exists": false}};
+        if db
+            .find_one(item_type.coll_name(), filter, Some(doc! {"_id": 1}), None)
+            .await?
+            .is_none()
+        {
+            return Err(NotFoundError(item_id.to_string()));
+        }
+
+        let (exists,) = sqlx::query_as::<_, (bool,)>(
+            r#"SELECT EXISTS(SELECT 1 FROM tags WHERE id = $1 AND "user" = $2)"#,
+        )
+        .bind(tag_id)
+        .bind(user)
+        .fetch_one(pg)
+        .await
+        .map_err(DBError::from)?;
+        if !exists {
+            return Err(DB(DBError::EntityNotFound {
+                entity: "tags",
+                id: tag_id,
+            }));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO item_tags ("user", tag_id, item_type, item_oid)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT DO NOTHING
+        "#,
+        )
+        .bind(user)
+        .bind(tag_id)
+        .bind(item_type.as_str())
+        .bind(oid.to_hex())
+        .execute(pg)
+        .await
+        .map_err(DBError::from)?;
+
+        Self::fetch_item_tags(pg, user, item_type, item_id).await
+    }
+
+    pub async fn untag_item(
+        pg: &Pool<Postgres>,
+        user: &Uuid,
+        item_type: ItemType,
+        item_id: &str,
+        tag_id: Uuid,
+    ) -> Result<()> {
+        let res = sqlx::query(
+            r#"
+            DELETE FROM item_tags
+            WHERE "user" = $1 AND tag_id = $2 AND item_type = $3 AND item_oid = $4
+        "#,
+        )
+        .bind(user)
+        .bind(tag_id)
+        .bind(item_type.as_str())
+        .bind(item_id)
+        .execute(pg)
+        .await
+        .map_err(DBError::from)?;
+
+        if res.rows_affected() == 0 {
+            return Err(DB(DBError::EntityNotFound {
+                entity: "item_tags",
+                id: tag_id,
+            }));
+        }
+        Ok(())
+    }
+
+    /// 영구 삭제된 item들의 tag 연결을 지움(모든 user 대상).
+    pub async fn remove_items(
+        pg: &Pool<Postgres>,
+        item_type: ItemType,
+        item_ids: &[String],
+    ) -> Result<u64> {
+        if item_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let res = sqlx::query(
+            r#"
+            DELETE FROM item_tags
+            WHERE item_type = $1 AND item_oid = ANY($2)
+        "#,
+        )
+        .bind(item_type.as_str())
+        .bind(item_ids)
+        .execute(pg)
+        .await
+        .map_err(DBError::from)?;
+        Ok(res.rows_affected())
+    }
+
+    pub async fn fetch_item_tags(
+        pg: &Pool<Postgres>,
+        user: &Uuid,
+        item_type: ItemType,
+        item_id: &str,
+    ) -> Result<ItemTagListRes> {
+        let mut tags = Self::tags_of(pg, user, item_type, &[item_id.to_string()]).await?;
+        let tags = tags.remove(item_id).unwrap_or_default();
+
+        Ok(ItemTagListRes {
+            status: "success",
+            results: tags.len(),
+            tags,
+        })
+    }
+
+    /// item id별로 붙은 tag(이름순)
+    pub async fn tags_of(
+        pg: &Pool<Postgres>,
+        user: &Uuid,
+        item_type: ItemType,
+        item_ids: &[String],
+    ) -> Result<HashMap<String, Vec<ItemTagRes>>> {
+        if item_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let rows = sqlx::query_as::<_, ItemTagRow>(
+            r#"
+            SELECT it.item_oid, t.id, t.name
+            FROM item_tags it
+            JOIN tags t ON t.id = it.tag_id
+            WHERE it."user" = $1
+                AND it.item_type = $2
+                AND it.item_oid = ANY($3)
+            ORDER BY t.name
+        "#,
+        )
+        .bind(user)
+        .bind(item_type.as_str())
+        .bind(item_ids)
+        .fetch_all(pg)
+        .await
+        .map_err(DBError::from)?;
+
+        let mut tags: HashMap<String, Vec<ItemTagRes>> = HashMap::new();
+        for row in rows {
+            tags.entry(row.item_oid).or_default().push(ItemTagRes {
+                id: row.id.to_string(),
+                name: row.name,
+            });
+        }
+        Ok(tags)
+    }
+
+    /// 응답의 tags를 채움. tag가 없는 item은 빈 목록.
+    /// ?fields=가 주어졌는데 tags가 없으면 채우지 않음.
+    /// tags는 부가 정보이므로 postgres 조회에 실패하면 tags 없이 응답함.
+    pub async fn attach_tags<T: Tagged>(
+        pg: &Pool<Postgres>,
+        user: &Uuid,
+        item_type: ItemType,
+        fields: Option<&str>,
+        items: Vec<&mut T>,
+    ) {
+        if !FieldSelection::requests(fields, "tags") {
+            return;
+        }
+        let ids: Vec<String> = items
+            .iter()
+            .map(|item| item.item_id().to_string())
+            .collect();
+        let mut tags = match Self::tags_of(pg, user, item_type, &ids).await {
+            Ok(tags) => tags,
+            Err(e) => {
+                tracing::warn!("failed to load item tags: {:?}", e);
+                return;
+            }
+        };
+        for item in items {
+            let item_tags = tags.remove(item.item_id()).unwrap_or_default();
+            item.set_tags(item_tags);
+        }
+    }
+
+    /// ?tag=, ?tag_group=가 주어지면 조건에 맞는 item id를 query_opts.ids에 채움.
+    /// 둘 다 주어지면 두 조건을 모두 만족하는 item만 남김.
+    pub async fn resolve_filter(
+        pg: &Pool<Postgres>,
+        user: &Uuid,
+        item_type: ItemType,
+        query_opts: &mut QueryOptions,
+    ) -> Result<()> {
+        let tags = split_names(query_opts.tag.as_deref());
+        let groups = split_names(query_opts.tag_group.as_deref());
+        if tags.is_empty() && groups.is_empty() {
+            return Ok(());
+        }
+
+        // tag, tag group은 id 혹은 이름으로 찾음. tag group은 하위 group까지 포함
+        let item_oids = sqlx::query_as::<_, (String,)>(
+            r#"
+            WITH RECURSIVE groups AS (
+                SELECT id FROM tag_groups
+                WHERE "user" = $1 AND (id::text = ANY($4) OR name = ANY($4))
+                UNION
+                SELECT tg.id FROM tag_groups tg
+                JOIN groups g ON tg.parent_id = g.id
+            )
+            SELECT it.item_oid
+            FROM item_tags it
+            JOIN tags t ON t.id = it.tag_id
+            LEFT JOIN tag_relations tr
+                ON tr.tag_id = t.id AND tr.group_id IN (SELECT id FROM groups)
+            WHERE it."user" = $1
+                AND it.item_type = $2
+            GROUP BY it.item_oid
+            HAVING (cardinality($3::text[]) = 0
+                    OR bool_or(t.id::text = ANY($3) OR t.name = ANY($3)))
+                AND (cardinality($4::text[]) = 0
+                    OR bool_or(tr.group_id IS NOT NULL))
+        "#,
+        )
+        .bind(user)
+        .bind(item_type.as_str())
+        .bind(&tags)
+        .bind(&groups)
+        .fetch_all(pg)
+        .await
+        .map_err(DBError::from)?;
+
+        query_opts.ids = Some(
+            item_oids
+                .iter()
+                .filter_map(|(oid,)| ObjectId::from_str(oid).ok())
+                .collect(),
+        );
+        Ok(())
+    }
+}
+
+fn split_names(raw: Option<&str>) -> Vec<String> {
+    raw.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
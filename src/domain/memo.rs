@@ -1,19 +1,24 @@
 use chrono::{DateTime, Utc};
 use mongodb::bson::doc;
+use mongodb::bson::Document;
 use mongodb::bson::{self, oid::ObjectId};
-use mongodb::{bson::Document, Database};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::infra::types::QueryFilterOptions;
+use crate::infra::types::{PageOptions, QueryFilterOptions, QueryOptions};
+use crate::interface::dto::batch::{req::BatchUpdateReq, res::BatchRes};
+use crate::interface::dto::history::res::ChangeListRes;
 use crate::interface::dto::memo::{
     req::{CreateMemoReq, UpdateMemoReq},
     res::{MemoData, MemoListRes, MemoRes, SingleMemoRes},
 };
 
 use crate::{
-    domain::error::{Error::*, Result},
+    domain::error::Result,
     domain::repo::base::{self, MongoRepo},
+    domain::repo::index::{IndexDef, IndexKind::*},
+    domain::repo::query::{FieldKind, Load::*, QueryFields, SelectFields},
+    domain::repo::storage::Storage,
     infra::db::error::Error as DBError,
 };
 
@@ -25,8 +30,12 @@ pub struct MemoModel {
     #[serde(with = "bson::serde_helpers::uuid_1_as_binary")]
     pub user: Uuid,
     pub title: String,
+    // ?fields=로 요청되지 않으면 읽지 않음
+    #[serde(default)]
     pub content: String,
     pub color: String,
+    #[serde(default)]
+    pub version: i64,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub createdAt: DateTime<Utc>,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
@@ -37,6 +46,34 @@ pub struct MemoService;
 
 impl MongoRepo for MemoService {
     const COLL_NAME: &'static str = "memos";
+    const QUERY_FIELDS: QueryFields = &[
+        ("title", FieldKind::Str),
+        ("color", FieldKind::Str),
+        ("createdAt", FieldKind::DateTime),
+        ("updatedAt", FieldKind::DateTime),
+    ];
+    const SELECT_FIELDS: SelectFields = &[
+        ("user", Always),
+        ("title", Always),
+        ("content", Lazy),
+        ("color", Always),
+        ("createdAt", Always),
+        ("updatedAt", Always),
+        ("version", Always),
+        // postgres의 item_tags에서 채움
+        ("tags", Attached),
+    ];
+    const INDEXES: &'static [IndexDef] = &[
+        IndexDef {
+            keys: &[("user", Asc), ("color", Asc)],
+            ..IndexDef::DEFAULT
+        },
+        // sync pull
+        IndexDef {
+            keys: &[("user", Asc), ("updatedAt", Asc)],
+            ..IndexDef::DEFAULT
+        },
+    ];
     type Model = MemoModel;
     type ModelResponse = MemoRes;
     fn convert_doc_to_response(memo: &MemoModel) -> MemoRes {
@@ -50,6 +87,7 @@ impl MongoRepo for MemoService {
         let datetime = Utc::now();
         let mut doc_with_dates = doc! {
             "user": user,
+            "version": 1,
             "createdAt": datetime,
             "updatedAt": datetime,
             "content": "",
@@ -62,64 +100,73 @@ impl MongoRepo for MemoService {
 impl MemoService {
     //mongodb에서 memo를 가져옴.
     pub async fn fetch_memos(
-        db: &Database,
-        limit: i64,
-        page: i64,
+        db: &dyn Storage,
+        page_opts: PageOptions,
+        query_opts: QueryOptions,
         user: &Uuid,
     ) -> Result<MemoListRes> {
         let filter_opts = QueryFilterOptions {
             find_filter: None,
             proj_opts: None,
-            limit,
-            page,
+            page_opts,
+            query_opts,
         };
         let memos_result = base::fetch::<Self>(db, filter_opts, user).await?;
 
         Ok(MemoListRes {
             status: "success",
-            results: memos_result.len(),
-            memos: memos_result,
+            results: memos_result.items.len(),
+            memos: memos_result.items,
+            next_cursor: memos_result.next_cursor,
+            has_more: memos_result.has_more,
+            total: memos_result.total,
         })
     }
 
     pub async fn fetch_memos_by_color(
-        db: &Database,
+        db: &dyn Storage,
         color: &str,
-        limit: i64,
-        page: i64,
+        page_opts: PageOptions,
         user: &Uuid,
     ) -> Result<MemoListRes> {
         let filter_opts = QueryFilterOptions {
             find_filter: Some(doc! {"user":user,"color":color}),
             proj_opts: None,
-            limit,
-            page,
+            page_opts,
+            query_opts: QueryOptions::default(),
         };
         let memos_result = base::fetch::<Self>(db, filter_opts, user).await?;
 
         Ok(MemoListRes {
             status: "success",
-            results: memos_result.len(),
-            memos: memos_result,
+            results: memos_result.items.len(),
+            memos: memos_result.items,
+            next_cursor: memos_result.next_cursor,
+            has_more: memos_result.has_more,
+            total: memos_result.total,
         })
     }
 
     pub async fn create_memo(
-        db: &Database,
+        db: &dyn Storage,
         body: &CreateMemoReq,
         user: &Uuid,
     ) -> Result<SingleMemoRes> {
-        let memo_result =
-            base::create::<Self, CreateMemoReq>(db, body, user, Some(vec!["color"])).await?;
+        let memo_result = base::create::<Self, CreateMemoReq>(db, body, user).await?;
 
         Ok(SingleMemoRes {
             status: "success",
-            data: MemoData { memo: memo_result },
+            data: MemoData { memo: memo_result.into() },
         })
     }
 
-    pub async fn get_memo(db: &Database, id: &str, user: &Uuid) -> Result<SingleMemoRes> {
-        let memo_result = base::get::<Self>(db, id, user).await?;
+    pub async fn get_memo(
+        db: &dyn Storage,
+        id: &str,
+        fields: Option<&str>,
+        user: &Uuid,
+    ) -> Result<SingleMemoRes> {
+        let memo_result = base::get_fields::<Self>(db, id, fields, user).await?;
 
         Ok(SingleMemoRes {
             status: "success",
@@ -128,167 +175,378 @@ impl MemoService {
     }
 
     pub async fn update_memo(
-        db: &Database,
+        db: &dyn Storage,
         id: &str,
         body: &UpdateMemoReq,
+        if_match: Option<i64>,
         user: &Uuid,
     ) -> Result<SingleMemoRes> {
-        let memo_result = base::update::<Self, UpdateMemoReq>(db, id, body, user).await?;
+        let memo_result =
+            base::update::<Self, UpdateMemoReq>(db, id, body, if_match, user).await?;
 
         Ok(SingleMemoRes {
             status: "success",
-            data: MemoData { memo: memo_result },
+            data: MemoData { memo: memo_result.into() },
+        })
+    }
+
+    pub async fn fetch_memo_history(
+        db: &dyn Storage,
+        id: &str,
+        page_opts: PageOptions,
+        user: &Uuid,
+    ) -> Result<ChangeListRes> {
+        let changes = base::fetch_history::<Self>(db, id, &page_opts, user).await?;
+        Ok(ChangeListRes::from_page(changes))
+    }
+
+    pub async fn revert_memo(
+        db: &dyn Storage,
+        id: &str,
+        revision: &str,
+        user: &Uuid,
+    ) -> Result<SingleMemoRes> {
+        let memo_result = base::revert::<Self>(db, id, revision, user).await?;
+
+        Ok(SingleMemoRes {
+            status: "success",
+            data: MemoData { memo: memo_result.into() },
         })
     }
 
-    pub async fn delete_memo(db: &Database, id: &str) -> Result<()> {
-        base::delete::<Self>(db, id).await
+    pub async fn bulk_create_memos(
+        db: &dyn Storage,
+        bodies: &[CreateMemoReq],
+        user: &Uuid,
+    ) -> Result<BatchRes<MemoRes>> {
+        let results = base::bulk_create::<Self, CreateMemoReq>(db, bodies, user).await?;
+        Ok(BatchRes::from_results(results))
+    }
+
+    pub async fn bulk_update_memos(
+        db: &dyn Storage,
+        items: &[BatchUpdateReq<UpdateMemoReq>],
+        user: &Uuid,
+    ) -> Result<BatchRes<MemoRes>> {
+        let items: Vec<(&str, Option<i64>, &UpdateMemoReq)> = items
+            .iter()
+            .map(|item| (item.id.as_str(), item.version, &item.body))
+            .collect();
+        let results = base::bulk_update::<Self, UpdateMemoReq>(db, &items, user).await?;
+        Ok(BatchRes::from_results(results))
+    }
+
+    pub async fn bulk_delete_memos(
+        db: &dyn Storage,
+        ids: &[String],
+        user: &Uuid,
+    ) -> Result<BatchRes<()>> {
+        let results = base::bulk_delete::<Self>(db, ids, user).await?;
+        Ok(BatchRes::without_data(results))
+    }
+
+    pub async fn delete_memo(db: &dyn Storage, id: &str, user: &Uuid) -> Result<()> {
+        base::delete::<Self>(db, id, user).await
     }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use std::str::FromStr;
-
-//     use super::*;
-//     use crate::infra::db::MongoDB;
-//     use dotenv::dotenv;
-//     use mongodb::{bson::oid::ObjectId, options::UpdateOptions};
-
-//     async fn setup() -> Database {
-//         dotenv().ok();
-//         std::env::set_var("RUST_BACKTRACE", "0");
-//         let mongodb = MongoDB::init_test().await.unwrap();
-
-//         // 시드 데이터 생성
-//         let user = Uuid::from_bytes([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-//         let seeds = vec![
-//             MemoModel {
-//                 id: ObjectId::from_str("507f1f77bcf86cd799439011").unwrap(),
-//                 user,
-//                 title: "첫 번째 노트".to_string(),
-//                 content: "첫 번째 노트의 내용입니다.".to_string(),
-//                 color: "#f97316".to_string(),
-//                 createdAt: Utc::now(),
-//                 updatedAt: Utc::now(),
-//             },
-//             MemoModel {
-//                 id: ObjectId::from_str("507f191e810c19729de860ea").unwrap(),
-//                 user,
-//                 title: "두 번째 노트".to_string(),
-//                 content: "두 번째 노트의 내용입니다.".to_string(),
-//                 color: "#06b6d4".to_string(),
-//                 createdAt: Utc::now(),
-//                 updatedAt: Utc::now(),
-//             },
-//             MemoModel {
-//                 id: ObjectId::from_str("507f191e810c19729de860ec").unwrap(),
-//                 user,
-//                 title: "세 번째 노트".to_string(),
-//                 content: "세 번째 노트의 내용입니다.".to_string(),
-//                 color: "#84cc16".to_string(),
-//                 createdAt: Utc::now(),
-//                 updatedAt: Utc::now(),
-//             },
-//         ];
-
-//         // 시드 데이터를 MongoDB에 삽입
-//         for seed in seeds {
-//             let filter = doc! { "_id": seed.id };
-//             let update = doc! { "$setOnInsert": bson::to_bson(&seed).unwrap() };
-//             let options = UpdateOptions::builder().upsert(true).build();
-
-//             let result = mongodb
-//                 .db
-//                 .collection::<MemoModel>("memos")
-//                 .update_one(filter, update, options)
-//                 .await
-//                 .expect("cannot insert seed data");
-//             // if result.upserted_id.is_some() {
-//             //     println!(
-//             //         "✅ 새로운 노트 시드 데이터가 추가되었습니다. ID: {}",
-//             //         seed.id
-//             //     );
-//             // } else {
-//             //     println!("이미 존재하는 노트 시드 데이터입니다. ID: {}", seed.id);
-//             // }
-//         }
-
-//         mongodb.db
-//     }
-
-//     #[tokio::test]
-//     async fn test_create_memo() {
-//         let db = setup().await;
-//         let user_id = Uuid::from_bytes([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-//         let body = CreateMemoReq {
-//             user: user_id,
-//             title: "Test Memo".to_string(),
-//             color: "#71717a".to_string(),
-//         };
-
-//         let res = MemoService::create_memo(&db, &body, &user_id).await;
-//         claim::assert_ok!(&res);
-//         let res = res.unwrap();
-//         claim::assert_matches!(res.status, "success");
-//         assert_eq!(res.data.memo.title, body.title);
-//     }
-
-//     #[tokio::test]
-//     async fn test_fetch_memos() {
-//         let db = setup().await;
-//         let user_id = Uuid::from_bytes([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-//         let limit = 10;
-//         let page = 1;
-
-//         let res = MemoService::fetch_memos(&db, limit, page, &user_id).await;
-//         claim::assert_ok!(&res);
-//         let res = res.unwrap();
-//         claim::assert_matches!(res.status, "success");
-//     }
-
-//     #[tokio::test]
-//     async fn test_get_memo() {
-//         let db = setup().await;
-//         let user_id = Uuid::from_bytes([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-//         let memo_id = "507f1f77bcf86cd799439011";
-
-//         let res = MemoService::get_memo(&db, memo_id, &user_id).await;
-//         claim::assert_ok!(&res);
-//         let res = res.unwrap();
-//         claim::assert_matches!(res.status, "success");
-//         assert_eq!(res.data.memo.id, memo_id);
-//     }
-
-//     #[tokio::test]
-//     async fn test_update_memo() {
-//         let db = setup().await;
-//         let user_id = Uuid::from_bytes([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-//         let memo_id = "507f1f77bcf86cd799439011";
-//         let body = UpdateMemoReq {
-//             title: Some("Updated Title".to_string()),
-//             content: None, // No change to content
-//             color: Some("#10b981".to_string()),
-//         };
-
-//         let res = MemoService::update_memo(&db, memo_id, &body, &user_id).await;
-//         claim::assert_ok!(&res);
-//         let res = res.unwrap();
-//         claim::assert_matches!(res.status, "success");
-//         assert_eq!(res.data.memo.title, body.title.unwrap());
-//         if let Some(content) = body.content {
-//             assert_eq!(res.data.memo.content, content);
-//         } else {
-//             dbg!(res.data.memo.content);
-//         } //기존값 유지
-//     }
-
-//     #[tokio::test]
-//     async fn test_delete_memo() {
-//         let db = setup().await;
-//         let memo_id = "507f191e810c19729de860ec";
-
-//         let res = MemoService::delete_memo(&db, memo_id).await;
-//         claim::assert_ok!(&res);
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::domain::error::Error::*;
+    use crate::infra::db::MongoDB;
+
+    const USER: Uuid = Uuid::from_bytes([0; 16]);
+
+    async fn setup() -> MongoDB {
+        let mongodb = MongoDB::init_memory();
+
+        // 시드 데이터 생성
+        let seeds = vec![
+            ("507f1f77bcf86cd799439011", "첫 번째 노트", "#f97316"),
+            ("507f191e810c19729de860ea", "두 번째 노트", "#06b6d4"),
+            ("507f191e810c19729de860ec", "세 번째 노트", "#84cc16"),
+        ];
+        for (id, title, color) in seeds {
+            let seed = MemoModel {
+                id: ObjectId::from_str(id).unwrap(),
+                user: USER,
+                title: title.to_string(),
+                content: format!("{}의 내용입니다.", title),
+                color: color.to_string(),
+                version: 1,
+                createdAt: Utc::now(),
+                updatedAt: Utc::now(),
+            };
+            let doc = bson::to_document(&seed).unwrap();
+            mongodb
+                .db
+                .insert_one(MemoService::COLL_NAME, doc, None)
+                .await
+                .expect("cannot insert seed data");
+        }
+
+        mongodb
+    }
+
+    #[tokio::test]
+    async fn test_create_memo() {
+        let mongodb = setup().await;
+        let body = CreateMemoReq {
+            title: "Test Memo".to_string(),
+            color: "#71717a".to_string(),
+        };
+
+        let res = MemoService::create_memo(&mongodb.db, &body, &USER).await;
+        claim::assert_ok!(&res);
+        let res = res.unwrap();
+        claim::assert_matches!(res.status, "success");
+        assert_eq!(res.data.memo.title, body.title);
+        assert_eq!(res.data.memo.version, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_memos() {
+        let mongodb = setup().await;
+        let page_opts = PageOptions {
+            limit: 2,
+            page: 1,
+            cursor: None,
+            with_total: true,
+        };
+
+        let res =
+            MemoService::fetch_memos(&mongodb.db, page_opts, QueryOptions::default(), &USER).await;
+        claim::assert_ok!(&res);
+        let res = res.unwrap();
+        claim::assert_matches!(res.status, "success");
+        assert_eq!(res.results, 2);
+        assert_eq!(res.total, Some(3));
+        assert!(res.has_more);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_memos_with_query() {
+        let mongodb = setup().await;
+        let query_opts = QueryOptions {
+            filter: Some("color!=#84cc16".to_string()),
+            sort: Some("-title".to_string()),
+            ..Default::default()
+        };
+
+        // 내림차순 정렬을 cursor로 끝까지 따라감
+        let mut titles = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page_opts = PageOptions {
+                limit: 1,
+                page: 1,
+                cursor,
+                with_total: true,
+            };
+            let res = MemoService::fetch_memos(&mongodb.db, page_opts, query_opts.clone(), &USER)
+                .await
+                .unwrap();
+            assert_eq!(res.total, Some(2));
+            titles.extend(res.memos.into_iter().map(|memo| memo.into_inner().title));
+            if !res.has_more {
+                break;
+            }
+            cursor = res.next_cursor;
+        }
+        assert_eq!(titles, vec!["첫 번째 노트", "두 번째 노트"]);
+
+        // 허용되지 않은 필드
+        let query_opts = QueryOptions {
+            filter: Some("user=x".to_string()),
+            ..Default::default()
+        };
+        let page_opts = PageOptions {
+            limit: 10,
+            page: 1,
+            cursor: None,
+            with_total: false,
+        };
+        let res = MemoService::fetch_memos(&mongodb.db, page_opts, query_opts, &USER).await;
+        claim::assert_matches!(res, Err(DB(DBError::InvalidQuery(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_memos_by_tag_ids() {
+        let mongodb = setup().await;
+        let page_opts = PageOptions {
+            limit: 10,
+            page: 1,
+            cursor: None,
+            with_total: true,
+        };
+
+        // ?tag=로 찾은 item id만 가져옴
+        let tagged = ObjectId::from_str("507f191e810c19729de860ea").unwrap();
+        let query_opts = QueryOptions {
+            ids: Some(vec![tagged]),
+            ..Default::default()
+        };
+        let res = MemoService::fetch_memos(&mongodb.db, page_opts.clone(), query_opts, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.total, Some(1));
+        assert_eq!(res.memos[0].id, tagged.to_hex());
+
+        // 조건에 맞는 tag가 없으면 빈 목록
+        let query_opts = QueryOptions {
+            ids: Some(vec![]),
+            ..Default::default()
+        };
+        let res = MemoService::fetch_memos(&mongodb.db, page_opts, query_opts, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.results, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_memos_with_fields() {
+        let mongodb = setup().await;
+        let page_opts = PageOptions {
+            limit: 10,
+            page: 1,
+            cursor: None,
+            with_total: false,
+        };
+        let query_opts = QueryOptions {
+            fields: Some("title,version".to_string()),
+            ..Default::default()
+        };
+
+        let res = MemoService::fetch_memos(&mongodb.db, page_opts.clone(), query_opts, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.results, 3);
+        // content는 db에서 읽지 않고, 응답에는 id와 선택한 필드만 남음
+        assert!(res.memos.iter().all(|memo| memo.content.is_empty()));
+        let json = serde_json::to_value(&res.memos[0]).unwrap();
+        let mut keys: Vec<&str> = json.as_object().unwrap().keys().map(|k| k.as_str()).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["id", "title", "version"]);
+
+        let memo_id = "507f1f77bcf86cd799439011";
+        let res = MemoService::get_memo(&mongodb.db, memo_id, Some("content"), &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.data.memo.content, "첫 번째 노트의 내용입니다.");
+        let json = serde_json::to_value(&res.data.memo).unwrap();
+        assert_eq!(json.as_object().unwrap().len(), 2);
+
+        // 허용되지 않은 필드
+        let query_opts = QueryOptions {
+            fields: Some("title,user_password".to_string()),
+            ..Default::default()
+        };
+        let res = MemoService::fetch_memos(&mongodb.db, page_opts, query_opts, &USER).await;
+        claim::assert_matches!(res, Err(DB(DBError::InvalidQuery(_))));
+    }
+
+    #[tokio::test]
+    async fn test_get_memo() {
+        let mongodb = setup().await;
+        let memo_id = "507f1f77bcf86cd799439011";
+
+        let res = MemoService::get_memo(&mongodb.db, memo_id, None, &USER).await;
+        claim::assert_ok!(&res);
+        let res = res.unwrap();
+        claim::assert_matches!(res.status, "success");
+        assert_eq!(res.data.memo.id, memo_id);
+
+        // 다른 user의 memo는 찾을 수 없음
+        let other = Uuid::from_bytes([1; 16]);
+        let res = MemoService::get_memo(&mongodb.db, memo_id, None, &other).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_update_memo() {
+        let mongodb = setup().await;
+        let memo_id = "507f1f77bcf86cd799439011";
+        let body = UpdateMemoReq {
+            title: Some("Updated Title".to_string()),
+            content: None, // No change to content
+            color: Some("#10b981".to_string()),
+        };
+
+        let res = MemoService::update_memo(&mongodb.db, memo_id, &body, Some(1), &USER).await;
+        claim::assert_ok!(&res);
+        let res = res.unwrap();
+        claim::assert_matches!(res.status, "success");
+        assert_eq!(res.data.memo.title, "Updated Title");
+        assert_eq!(res.data.memo.content, "첫 번째 노트의 내용입니다."); //기존값 유지
+        assert_eq!(res.data.memo.version, 2);
+
+        // 이전 version으로는 업데이트할 수 없음
+        let res = MemoService::update_memo(&mongodb.db, memo_id, &body, Some(1), &USER).await;
+        claim::assert_matches!(res, Err(PreconditionFailed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_memo_history_revert() {
+        let mongodb = setup().await;
+        let memo_id = "507f1f77bcf86cd799439011";
+        let page_opts = PageOptions {
+            limit: 10,
+            page: 1,
+            cursor: None,
+            with_total: true,
+        };
+
+        for title in ["v2", "v3"] {
+            let body = UpdateMemoReq {
+                title: Some(title.to_string()),
+                content: None,
+                color: None,
+            };
+            MemoService::update_memo(&mongodb.db, memo_id, &body, None, &USER)
+                .await
+                .unwrap();
+        }
+
+        // 최신순
+        let res = MemoService::fetch_memo_history(&mongodb.db, memo_id, page_opts.clone(), &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.total, Some(2));
+        assert_eq!(res.changes[0].version, 3);
+        assert_eq!(res.changes[0].diff[0].field, "title");
+        assert_eq!(res.changes[0].diff[0].before, Some("v2".into()));
+        let first = res.changes[1].id.clone();
+
+        // 첫번째 변경 직후(v2)로 되돌림
+        let res = MemoService::revert_memo(&mongodb.db, memo_id, &first, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.data.memo.title, "v2");
+        assert_eq!(res.data.memo.version, 4);
+
+        let res = MemoService::fetch_memo_history(&mongodb.db, memo_id, page_opts, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.total, Some(3));
+
+        // 다른 문서의 revision으로는 되돌릴 수 없음
+        let other_id = "507f191e810c19729de860ea";
+        let res = MemoService::revert_memo(&mongodb.db, other_id, &first, &USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_delete_memo() {
+        let mongodb = setup().await;
+        let memo_id = "507f191e810c19729de860ec";
+
+        let res = MemoService::delete_memo(&mongodb.db, memo_id, &USER).await;
+        claim::assert_ok!(&res);
+
+        let res = MemoService::get_memo(&mongodb.db, memo_id, None, &USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+    }
+}
@@ -1,12 +1,22 @@
+pub mod category;
 pub mod daily;
 pub mod error;
 pub mod task;
+pub mod task_template;
+pub mod timer;
 pub mod habit;
+pub mod indexes;
+pub mod item_tag;
 pub mod memo;
 pub mod repo;
 pub mod schedule;
+pub mod settings;
 pub mod sub;
-//pub mod note;
+pub mod sync;
+pub mod note;
 pub mod tag_group;
 pub mod tag;
-pub mod tag_relation;
\ No newline at end of file
+pub mod tag_relation;
+#[cfg(test)]
+pub(crate) mod test_utils;
+pub mod trash;
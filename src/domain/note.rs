@@ -1,42 +1,36 @@
 use chrono::{DateTime, Utc};
-use futures::TryStreamExt;
 use std::str::FromStr;
 
-use mongodb::{
-    bson::{self, doc, oid::ObjectId, Document},
-    options::AggregateOptions,
-    Database,
-};
+use mongodb::bson::{self, doc, oid::ObjectId, Bson, Document};
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::sub::{
-    chat::MsgModel,
-    property::PropertyService,
-    note_propV::{PropValueModel, PropValueService},
-};
+use super::sub::{chat::MsgModel, note_propV::PropValueModel, property::PropertyService};
 
 use crate::{
     domain::{
         error::{Error::*, Result},
         repo::base::{self, MongoRepo},
+        repo::index::{IndexDef, IndexKind::*},
+        repo::query::{FieldKind, Load::*, QueryFields, SelectFields},
+        repo::storage::{FindOpts, GraphLookup, Storage},
+        repo::transaction::Transaction,
         sub::note_page::PageModel,
     },
     infra::{
         db::error::Error as DBError,
-        types::{ChatType, PropertyType, QueryFilterOptions},
+        types::{ChatType, PageOptions, PropertyType, QueryFilterOptions, QueryOptions},
     },
     interface::dto::{
-        sub::note_propV::{req::UpdatePropValueReq, res::PropValueListRes},
         note::{
             req::{CreateNoteReq, NoteFetchOptions, UpdateNoteReq},
-            res::{SingleNoteRes, NoteData, NoteListRes, NoteRes},
+            res::{NoteData, NoteListRes, NoteRes, SingleNoteRes},
         },
+        sub::note_propV::res::{PropValueListRes, PropValueRes},
     },
 };
 
-
 #[allow(non_snake_case)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct NoteModel {
@@ -50,8 +44,10 @@ pub struct NoteModel {
     pub category_color: String,
     pub category_name: String,
 
+    #[serde(default)]
     pub prop_values: Vec<PropValueModel>,
 
+    #[serde(default)]
     pub pages: Vec<PageModel>,
     pub connected_task: Option<ObjectId>,
     pub parent_id: Option<ObjectId>,
@@ -60,6 +56,8 @@ pub struct NoteModel {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chat_msgs: Option<Vec<MsgModel>>,
 
+    #[serde(default)]
+    pub version: i64,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub createdAt: DateTime<Utc>,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
@@ -71,11 +69,37 @@ pub struct NoteService;
 
 impl MongoRepo for NoteService {
     const COLL_NAME: &'static str = "notes";
+    const QUERY_FIELDS: QueryFields = &[
+        ("title", FieldKind::Str),
+        ("category_name", FieldKind::Str),
+        ("createdAt", FieldKind::DateTime),
+        ("updatedAt", FieldKind::DateTime),
+    ];
+    const SELECT_FIELDS: SelectFields = &[
+        ("user", Always),
+        ("title", Always),
+        ("category_id", Always),
+        ("category_color", Always),
+        ("category_name", Always),
+        ("prop_values", Always),
+        ("pages", Always),
+        ("connected_task", Always),
+        ("parent_id", Always),
+        ("chat_type", Always),
+        ("chat_msgs", Lazy),
+        ("createdAt", Always),
+        ("updatedAt", Always),
+        ("version", Always),
+    ];
+    const INDEXES: &'static [IndexDef] = &[IndexDef {
+        keys: &[("category_id", Asc), ("user", Asc)],
+        ..IndexDef::DEFAULT
+    }];
     type Model = NoteModel;
     type ModelResponse = NoteRes;
 
-    fn convert_doc_to_response(note: &NoteModel) -> Result<NoteRes> {
-        Ok(NoteRes::from_model(note))
+    fn convert_doc_to_response(note: &NoteModel) -> NoteRes {
+        NoteRes::from_model(note)
     }
 
     fn create_doc<CreateNoteReq: Serialize>(user: &Uuid, body: &CreateNoteReq) -> Result<Document> {
@@ -89,6 +113,9 @@ impl MongoRepo for NoteService {
             "user": user,
             "complete":false,
             "chat_type": "Note",
+            "prop_values": [],
+            "pages": [],
+            "version": 1,
             "createdAt": datetime,
             "updatedAt": datetime,
         };
@@ -99,65 +126,64 @@ impl MongoRepo for NoteService {
 
 impl NoteService {
     pub async fn fetch_notes(
-        db: &Database,
-        limit: i64,
-        page: i64,
+        db: &dyn Storage,
+        page_opts: PageOptions,
+        query_opts: QueryOptions,
         user: &Uuid,
     ) -> Result<NoteListRes> {
         let filter_opts = QueryFilterOptions {
-            find_filter: Some(doc! {"user": user}),
+            find_filter: None,
             proj_opts: Some(NoteFetchOptions::build_projection()),
-            limit,
-            page,
+            page_opts,
+            query_opts,
         };
-        let notes_result = base::fetch::<Self>(db, filter_opts, user)
-            .await
-            .expect("note 응답을 받아오지 못했습니다.");
+        let notes_result = base::fetch::<Self>(db, filter_opts, user).await?;
 
         Ok(NoteListRes {
             status: "success",
-            results: notes_result.len(),
-            notes: notes_result,
+            results: notes_result.items.len(),
+            notes: notes_result.items,
+            next_cursor: notes_result.next_cursor,
+            has_more: notes_result.has_more,
+            total: notes_result.total,
         })
     }
 
     pub async fn fetch_notes_by_category(
-        db: &Database,
-        limit: i64,
-        page: i64,
+        db: &dyn Storage,
         category_id: &str,
+        page_opts: PageOptions,
+        query_opts: QueryOptions,
         user: &Uuid,
     ) -> Result<NoteListRes> {
+        let category_oid = ObjectId::from_str(category_id).map_err(DBError::MongoGetOidError)?;
         let filter_opts = QueryFilterOptions {
-            find_filter: Some(doc! {"user": user,"category_id":category_id}),
+            find_filter: Some(doc! {"user": user,"category_id":category_oid}),
             proj_opts: Some(NoteFetchOptions::build_projection()),
-            limit,
-            page,
+            page_opts,
+            query_opts,
         };
 
-        let notes_result = base::fetch::<Self>(db, filter_opts, user)
-            .await
-            .expect("note 응답을 받아오지 못했습니다.");
+        let notes_result = base::fetch::<Self>(db, filter_opts, user).await?;
 
         Ok(NoteListRes {
             status: "success",
-            results: notes_result.len(),
-            notes: notes_result,
+            results: notes_result.items.len(),
+            notes: notes_result.items,
+            next_cursor: notes_result.next_cursor,
+            has_more: notes_result.has_more,
+            total: notes_result.total,
         })
     }
 
     pub async fn create_note(
-        db: &Database,
+        db: &dyn Storage,
         body: &mut CreateNoteReq,
         user: &Uuid,
     ) -> Result<SingleNoteRes> {
-        if let Ok(prop_values) = Self::get_prop_values(db, &body.category_id).await {
-            body.prop_values = Some(prop_values);
-        }
-        let note_result =
-            base::create::<Self, CreateNoteReq>(db, body, user, Some(vec!["category_id"]))
-                .await
-                .expect("note 생성에 실패했습니다.");
+        // category의 property마다 빈 값을 만듦. 다른 user의 category이면 NotFound.
+        body.prop_values = Some(Self::get_prop_values(db, &body.category_id, user).await?);
+        let note_result = base::create::<Self, CreateNoteReq>(db, body, user).await?;
 
         Ok(SingleNoteRes {
             status: "success",
@@ -165,10 +191,8 @@ impl NoteService {
         })
     }
 
-    pub async fn get_note(db: &Database, id: &str, user: &Uuid) -> Result<SingleNoteRes> {
-        let note_result = base::get::<Self>(db, id, user)
-            .await
-            .expect("note를 가져오는데 실패했습니다.");
+    pub async fn get_note(db: &dyn Storage, id: &str, user: &Uuid) -> Result<SingleNoteRes> {
+        let note_result = base::get::<Self>(db, id, user).await?;
 
         Ok(SingleNoteRes {
             status: "success",
@@ -177,14 +201,12 @@ impl NoteService {
     }
 
     pub async fn update_note(
-        db: &Database,
+        db: &dyn Storage,
         id: &str,
         body: &UpdateNoteReq,
         user: &Uuid,
     ) -> Result<SingleNoteRes> {
-        let note_result = base::update::<Self, UpdateNoteReq>(db, id, body, user)
-            .await
-            .expect("note 업데이트에 실패했습니다.");
+        let note_result = base::update::<Self, UpdateNoteReq>(db, id, body, None, user).await?;
 
         Ok(SingleNoteRes {
             status: "success",
@@ -192,82 +214,133 @@ impl NoteService {
         })
     }
 
-    pub async fn delete_note(db: &Database, id: &str) -> Result<()> {
-        base::delete::<Self>(db, id).await
+    pub async fn delete_note(db: &dyn Storage, id: &str, user: &Uuid) -> Result<()> {
+        base::delete::<Self>(db, id, user).await
     }
 
     // category 갱신에 따른 note 갱신
     pub async fn update_notes_for_category_change(
-        db: &Database,
+        db: &dyn Storage,
         category_id: &str,
         new_category_name: &str,
         new_category_color: &str,
         user: &Uuid,
     ) -> Result<NoteListRes> {
-        let notes_collection = db.collection::<NoteModel>("notes");
-
-        let mut cursor = notes_collection
-            .find(doc! { "category_id": category_id, "user": user }, None)
-            .await?;
+        let category_oid = ObjectId::from_str(category_id).map_err(DBError::MongoGetOidError)?;
+        let filter = doc! {
+            "category_id": category_oid,
+            "user": user,
+            "deletedAt": { "$exists": false },
+        };
 
-        let mut notes_results = Vec::new();
+        // 모든 note가 함께 갱신되거나, 하나도 갱신되지 않도록 transaction으로 실행함.
+        let mut tx = Transaction::start(db).await?;
+        let result = async {
+            let update_doc = doc! {
+                "$set": {
+                    "category_name": new_category_name,
+                    "category_color": new_category_color,
+                    "updatedAt": Bson::DateTime(Utc::now().into()),
+                },
+                "$inc": { "version": 1 },
+            };
+            db.update_many(Self::COLL_NAME, filter.clone(), update_doc, tx.session())
+                .await?;
 
-        while let Some(note) = cursor.try_next().await? {
-            let update_note_req = UpdateNoteReq {
-                category_name: Some(new_category_name.to_string()),
-                category_color: Some(new_category_color.to_string()),
+            let opts = FindOpts {
+                projection: Some(NoteFetchOptions::build_projection()),
                 ..Default::default()
             };
+            db.find(Self::COLL_NAME, filter, opts, tx.session()).await
+        }
+        .await;
+        let notes = tx.finish(result).await?;
 
-            let note_result =
-                base::update::<Self, UpdateNoteReq>(db, &note.id.to_hex(), &update_note_req, user)
-                    .await
-                    .expect("note 업데이트에 실패했습니다.");
-
-            notes_results.push(note_result);
+        let mut notes_results = Vec::new();
+        for note in notes {
+            let note: NoteModel =
+                bson::from_document(note).map_err(DBError::MongoDeserializeBsonError)?;
+            notes_results.push(Self::convert_doc_to_response(&note).into());
         }
 
         Ok(NoteListRes {
             status: "success",
             results: notes_results.len(),
             notes: notes_results,
+            next_cursor: None,
+            has_more: false,
+            total: None,
         })
     }
 
+    // property 갱신에 따른 note의 property 값 갱신.
+    // prop_type이 바뀌면 기존 값이 새 타입에 맞지 않을 수 있으므로 값을 비움.
     pub async fn update_notes_for_property_change(
-        db: &Database,
+        db: &dyn Storage,
         category_id: &str,
         prop_id: &str,
-        new_prop_name: &str,
-        new_prop_type: &PropertyType,
-        user_id: &Uuid,
+        new_prop_name: Option<&str>,
+        new_prop_type: Option<&PropertyType>,
+        user: &Uuid,
     ) -> Result<PropValueListRes> {
-        let notes_collection = db.collection::<NoteModel>("notes");
         let category_oid = ObjectId::from_str(category_id).map_err(DBError::MongoGetOidError)?;
         let prop_oid = ObjectId::from_str(prop_id).map_err(DBError::MongoGetOidError)?;
-        let mut cursor = notes_collection
-            .find(doc! { "category_id": category_oid, "user": user_id }, None)
-            .await?;
-
-        let mut prop_results = Vec::new();
+        let filter = doc! {
+            "category_id": category_oid,
+            "user": user,
+            "deletedAt": { "$exists": false },
+            "prop_values._id": prop_oid,
+        };
 
-        while let Some(note) = cursor.try_next().await? {
-            if let Some(prop_value) = note.prop_values.iter().find(|p| p.prop_id == prop_oid) {
-                let values = prop_value.values.as_ref().map(|val| vec![val.clone()]);
+        let mut tx = Transaction::start(db).await?;
+        let result = async {
+            let opts = FindOpts {
+                projection: Some(doc! { "prop_values": 1 }),
+                ..Default::default()
+            };
+            let notes = db.find(Self::COLL_NAME, filter, opts, tx.session()).await?;
+
+            let mut prop_results = Vec::new();
+            for note in notes {
+                let note_id = note.get_object_id("_id").map_err(DBError::MongoDataError)?;
+                let mut prop_values: Vec<PropValueModel> =
+                    bson::from_bson(note.get("prop_values").cloned().unwrap_or_default())
+                        .map_err(DBError::MongoDeserializeBsonError)?;
+
+                for prop_value in prop_values.iter_mut().filter(|p| p.prop_id == prop_oid) {
+                    if let Some(name) = new_prop_name {
+                        prop_value.prop_name = name.to_string();
+                    }
+                    if let Some(prop_type) = new_prop_type {
+                        if prop_value.prop_type != *prop_type {
+                            prop_value.prop_type = prop_type.clone();
+                            prop_value.values = None;
+                        }
+                    }
+                    prop_results.push(PropValueRes::from_model(prop_value));
+                }
 
-                let update_prop_req = UpdatePropValueReq {
-                    name: Some(new_prop_name.to_string()),
-                    values,
-                    prop_type: Some(new_prop_type.to_owned()),
+                let prop_values_bson = bson::to_bson(&prop_values)
+                    .map_err(|e| DB(DBError::MongoSerializeBsonError(e)))?;
+                let update_doc = doc! {
+                    "$set": {
+                        "prop_values": prop_values_bson,
+                        "updatedAt": Bson::DateTime(Utc::now().into()),
+                    },
+                    "$inc": { "version": 1 },
                 };
-
-                let prop_result =
-                    PropValueService::update_propV(db, category_id, prop_id, &update_prop_req)
-                        .await?;
-
-                prop_results.push(prop_result.data.propV);
+                db.update_one(
+                    Self::COLL_NAME,
+                    doc! { "_id": note_id, "user": user },
+                    update_doc,
+                    tx.session(),
+                )
+                .await?;
             }
+            Ok(prop_results)
         }
+        .await;
+        let prop_results = tx.finish(result).await?;
 
         Ok(PropValueListRes {
             status: "success",
@@ -277,110 +350,280 @@ impl NoteService {
     }
 
     // utils
-    pub async fn get_prop_values(db: &Database, category_id: &str) -> Result<Vec<PropValueModel>> {
-        let properties = PropertyService::fetch_properties(db, category_id)
-            .await
-            .expect("properties를 가져오는데 실패했습니다.")
+    pub async fn get_prop_values(
+        db: &dyn Storage,
+        category_id: &str,
+        user: &Uuid,
+    ) -> Result<Vec<PropValueModel>> {
+        let properties = PropertyService::fetch_properties(db, category_id, user)
+            .await?
             .props;
 
-        let prop_values = properties
-            .iter()
-            .map(|prop| {
-                let prop_oid = ObjectId::from_str(&prop.id)
-                    .map_err(DBError::MongoGetOidError)
-                    .unwrap();
-                PropValueModel {
-                    prop_id: prop_oid,
-                    prop_name: prop.name.clone(),
-                    prop_type: prop.prop_type.clone(),
-                    values: None,
-                }
-            })
-            .collect::<Vec<PropValueModel>>();
+        let mut prop_values = Vec::new();
+        for prop in properties {
+            let prop_oid = ObjectId::from_str(&prop.id).map_err(DBError::MongoGetOidError)?;
+            prop_values.push(PropValueModel {
+                prop_id: prop_oid,
+                prop_name: prop.name,
+                prop_type: prop.prop_type,
+                values: None,
+            });
+        }
         Ok(prop_values)
     }
 
     // subnote
-    pub async fn add_page(self, db: &Database, id: &str, user: &Uuid) -> Result<SingleNoteRes> {
+    pub async fn add_page(db: &dyn Storage, id: &str, user: &Uuid) -> Result<SingleNoteRes> {
         let note_oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+        let filter = doc! { "_id": note_oid, "user": user, "deletedAt": { "$exists": false } };
 
         // Retrieve the original note
-        let coll = db.collection::<NoteModel>("notes");
-        let mut original_note = match coll
-            .find_one(doc! { "_id": &note_oid, "user": user }, None)
-            .await
+        let original_note: NoteModel = match db
+            .find_one(Self::COLL_NAME, filter.clone(), None, None)
+            .await?
         {
-            Ok(Some(doc)) => doc,
-            Ok(None) => return Err(NotFoundError(note_oid.to_string())),
-            Err(e) => return Err(DB(DBError::MongoQueryError(e))),
+            Some(doc) => bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?,
+            None => return Err(NotFoundError(note_oid.to_string())),
         };
 
-        // Add the new subnote to the original note's subnotes
+        // Add the new page to the original note's pages
         let new_page = PageModel::new_page(&original_note);
-        original_note.pages.push(new_page);
-
-        // Update the original note in the database
-        let subnotes_bson = bson::to_bson(&original_note.pages)
-            .map_err(|e| DB(DBError::MongoSerializeBsonError(e)))?;
-
-        coll.update_one(
-            doc! { "_id": &note_oid, "user": user },
-            doc! { "$set": { "subnotes": subnotes_bson }},
-            None,
-        )
-        .await?;
+        let page_bson =
+            bson::to_bson(&new_page).map_err(|e| DB(DBError::MongoSerializeBsonError(e)))?;
+        let update_doc = doc! {
+            "$push": { "pages": page_bson },
+            "$set": { "updatedAt": Bson::DateTime(Utc::now().into()) },
+            "$inc": { "version": 1 },
+        };
 
-        let note_result = Self::convert_doc_to_response(&original_note)?;
+        let updated_note: NoteModel = match db
+            .find_one_and_update(Self::COLL_NAME, filter, update_doc, None, None)
+            .await?
+        {
+            Some(doc) => bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?,
+            None => return Err(NotFoundError(note_oid.to_string())),
+        };
 
         Ok(SingleNoteRes {
             status: "success",
-            data: NoteData { note: note_result },
+            data: NoteData {
+                note: Self::convert_doc_to_response(&updated_note),
+            },
         })
     }
 
-    pub async fn get_subnotes(db: &Database, id: &str, user: &Uuid) -> Result<SingleNoteRes> {
-        // Parse the note id
+    // parent_id를 따라 내려가며 하위 note들을 찾음(가까운 것부터).
+    pub async fn get_subnotes(db: &dyn Storage, id: &str, user: &Uuid) -> Result<NoteListRes> {
         let note_oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+        let filter = doc! { "_id": note_oid, "user": user, "deletedAt": { "$exists": false } };
+        if db.count(Self::COLL_NAME, filter.clone(), None).await? == 0 {
+            return Err(NotFoundError(note_oid.to_string()));
+        }
 
-        // Define the aggregation pipeline
-        let pipeline = vec![
-            doc! { "$match": { "_id": &note_oid, "user": user }},
-            doc! {
-                "$graphLookup": {
-                    "from": "notes",
-                    "startWith": "$_id",
-                    "connectFromField": "_id",
-                    "connectToField": "parent_id",
-                    "as": "subnotes",
-                    "maxDepth": 5,
-                    "depthField": "depth"
-                }
-            },
-        ];
-
-        let options = AggregateOptions::builder().allow_disk_use(true).build();
-        let notes_collection = db.collection::<NoteModel>("notes");
-        let mut cursor = notes_collection.aggregate(pipeline, options).await?;
+        let lookup = GraphLookup {
+            start: filter,
+            connect_from: "_id",
+            connect_to: "parent_id",
+            restrict: doc! { "user": user, "deletedAt": { "$exists": false } },
+            projection: Some(NoteFetchOptions::build_projection()),
+        };
         let mut notes = Vec::new();
-
-        while let Some(result) = cursor.try_next().await? {
-            notes.push(bson::from_document::<NoteModel>(result));
+        for doc in db.graph_lookup(Self::COLL_NAME, lookup).await? {
+            let note: NoteModel =
+                bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?;
+            notes.push(Self::convert_doc_to_response(&note).into());
         }
 
-        // Assuming there's only one note matched, as we queried by _id
-        let updated_note = match notes.into_iter().next() {
-            Some(Ok(note)) => note,
-            Some(Err(e)) => return Err(DB(DBError::MongoDeserializeBsonError(e))),
-            None => return Err(NotFoundError(note_oid.to_string())),
+        Ok(NoteListRes {
+            status: "success",
+            results: notes.len(),
+            notes,
+            next_cursor: None,
+            has_more: false,
+            total: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{category::CategoryService, sub::note_propV::PropValueService};
+    use crate::infra::db::MongoDB;
+    use crate::infra::types::PropValueType;
+    use crate::interface::dto::{
+        category::req::CreateCategoryReq,
+        sub::{note_propV::req::UpdatePropValueReq, property::req::CreatePropertyReq},
+    };
+
+    const USER: Uuid = Uuid::from_bytes([0; 16]);
+
+    // "Work" category(Tags, Deadline)를 만들고 (category id, Deadline id)를 반환
+    async fn create_category(db: &dyn Storage, user: &Uuid) -> (String, String) {
+        let body = CreateCategoryReq {
+            name: "Work".to_string(),
+            color: "Red".to_string(),
         };
+        let category = CategoryService::create_category(db, &body, user)
+            .await
+            .unwrap()
+            .data
+            .category;
+        let prop = CreatePropertyReq {
+            name: "Deadline".to_string(),
+            prop_type: PropertyType::DateTime,
+        };
+        let prop = PropertyService::add_property(db, &category.id, &prop, user)
+            .await
+            .unwrap()
+            .data
+            .prop;
+        (category.id, prop.id)
+    }
 
-        // convert doc to Res
-        let note_result = Self::convert_doc_to_response(&updated_note)?;
+    async fn create_note(db: &dyn Storage, category_id: &str, user: &Uuid) -> NoteRes {
+        let mut body = CreateNoteReq {
+            title: "meeting".to_string(),
+            category_id: category_id.to_string(),
+            category_color: "Red".to_string(),
+            category_name: "Work".to_string(),
+            prop_values: None,
+            pages: None,
+            parent_id: None,
+            chat_type: None,
+        };
+        NoteService::create_note(db, &mut body, user)
+            .await
+            .unwrap()
+            .data
+            .note
+    }
 
-        // Return the updated note
-        Ok(SingleNoteRes {
-            status: "success",
-            data: NoteData { note: note_result },
-        })
+    #[tokio::test]
+    async fn test_update_notes_for_category_change() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let (category_id, _) = create_category(db, &USER).await;
+        let (other_category_id, _) = create_category(db, &USER).await;
+        let note = create_note(db, &category_id, &USER).await;
+        let other_note = create_note(db, &other_category_id, &USER).await;
+
+        let res =
+            NoteService::update_notes_for_category_change(db, &category_id, "Job", "Blue", &USER)
+                .await
+                .unwrap();
+        assert_eq!(res.results, 1);
+
+        let note = NoteService::get_note(db, &note.id, &USER)
+            .await
+            .unwrap()
+            .data
+            .note;
+        assert_eq!(note.category_name, "Job");
+        assert_eq!(note.category_color, "Blue");
+        assert_eq!(note.version, 2);
+
+        // 다른 category의 note는 그대로
+        let other_note = NoteService::get_note(db, &other_note.id, &USER)
+            .await
+            .unwrap()
+            .data
+            .note;
+        assert_eq!(other_note.category_name, "Work");
+        assert_eq!(other_note.version, 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_notes_for_property_change() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let (category_id, prop_id) = create_category(db, &USER).await;
+        let note = create_note(db, &category_id, &USER).await;
+        let tags_id = note.prop_values[1].id.clone();
+
+        // 값을 넣어둔 뒤 이름을 바꾸면 값은 유지됨
+        let body = UpdatePropValueReq {
+            prop_name: None,
+            values: Some(PropValueType::Single("2024-06-01".to_string())),
+            prop_type: None,
+        };
+        PropValueService::update_propV(db, &note.id, &prop_id, &body, &USER)
+            .await
+            .unwrap();
+        let res = NoteService::update_notes_for_property_change(
+            db,
+            &category_id,
+            &prop_id,
+            Some("Due"),
+            None,
+            &USER,
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.results, 1);
+        assert_eq!(res.propVs[0].prop_name, "Due");
+        claim::assert_some!(&res.propVs[0].value);
+
+        // 타입이 바뀌면 기존 값은 비워짐
+        let res = NoteService::update_notes_for_property_change(
+            db,
+            &category_id,
+            &prop_id,
+            None,
+            Some(&PropertyType::Text),
+            &USER,
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.propVs[0].prop_type, PropertyType::Text);
+        claim::assert_none!(&res.propVs[0].value);
+
+        let note = NoteService::get_note(db, &note.id, &USER)
+            .await
+            .unwrap()
+            .data
+            .note;
+        assert_eq!(note.version, 4);
+        let names: Vec<&str> = note
+            .prop_values
+            .iter()
+            .map(|p| p.prop_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Due", "Tags"]);
+        assert_eq!(note.prop_values[1].id, tags_id);
+    }
+
+    #[tokio::test]
+    async fn test_propagation_skips_other_user() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let other = Uuid::from_bytes([1; 16]);
+        let (category_id, prop_id) = create_category(db, &USER).await;
+        let note = create_note(db, &category_id, &USER).await;
+
+        let res =
+            NoteService::update_notes_for_category_change(db, &category_id, "Job", "Blue", &other)
+                .await
+                .unwrap();
+        assert_eq!(res.results, 0);
+        let res = NoteService::update_notes_for_property_change(
+            db,
+            &category_id,
+            &prop_id,
+            Some("Due"),
+            None,
+            &other,
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.results, 0);
+
+        let note = NoteService::get_note(db, &note.id, &USER)
+            .await
+            .unwrap()
+            .data
+            .note;
+        assert_eq!(note.category_name, "Work");
+        assert_eq!(note.version, 1);
     }
 }
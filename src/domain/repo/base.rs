@@ -1,156 +1,270 @@
 use chrono::Utc;
-use futures::StreamExt;
 use mongodb::bson::{doc, oid::ObjectId};
 use mongodb::bson::{Bson, Document};
-use mongodb::options::{FindOptions, IndexOptions};
-use mongodb::{bson, Database, IndexModel};
+use mongodb::{bson, ClientSession};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::str::FromStr;
+use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::domain::error::{Error::*, Result};
 use crate::infra::db::error::Error as DBError;
-use crate::infra::types::QueryFilterOptions;
-
-use super::utils::{find_mdoc_by_id, update_doc_ret_model};
+use crate::infra::types::{BatchResult, Page, PageOptions, Partial, QueryFilterOptions};
+
+use super::history::{self, ChangeModel};
+use super::index::IndexDef;
+use super::query::{is_truthy, FieldSelection, ListQuery, QueryFields, SelectFields};
+use super::storage::{FindOpts, Storage};
+use super::transaction::Transaction;
+use super::utils::{
+    decode_cursor, doc_version, encode_cursor, find_mdoc_by_id, keyset_filter,
+    not_found_or_stale, update_doc_in_session, update_doc_ret_model, version_filter,
+};
 
 pub trait MongoRepo {
     type Model: Debug;
     type ModelResponse;
     const COLL_NAME: &'static str;
+    // cursor pagination 정렬 기준(_id는 항상 두번째 기준으로 사용)
+    const SORT_KEY: &'static str = "_id";
+    // MongoDB::init 시점에 한번 맞춰지는 인덱스 선언
+    const INDEXES: &'static [IndexDef] = &[];
+    // list의 ?filter=&sort=에 쓸 수 있는 필드
+    const QUERY_FIELDS: QueryFields = &[];
+    // ?fields=로 고를 수 있는 응답 필드
+    const SELECT_FIELDS: SelectFields = &[];
     fn convert_doc_to_response(doc: &Self::Model) -> Self::ModelResponse;
     fn create_doc<Schema: Serialize>(user: &Uuid, body: &Schema) -> Result<Document>;
 }
 
 // S: Service
 pub async fn fetch<S>(
-    db: &Database,
+    db: &dyn Storage,
     filter_opts: QueryFilterOptions,
     user: &Uuid,
-) -> Result<Vec<S::ModelResponse>>
+) -> Result<Page<Partial<S::ModelResponse>>>
 where
     S: MongoRepo,
     S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
 {
-    let coll = db.collection::<S::Model>(S::COLL_NAME);
-
-    let (find_filter, proj_opts, limit, page) = (
-        filter_opts.find_filter.unwrap_or(doc! {"user": user}),
-        filter_opts.proj_opts.unwrap_or_default(),
-        filter_opts.limit,
-        filter_opts.page,
-    );
-
-    let find_options = FindOptions::builder()
-        .projection(proj_opts)
-        .sort(doc!{"parent_id":1})
-        .limit(limit)
-        .skip(u64::try_from((page - 1) * limit).unwrap())
-        .build();
-
-    let mut cursor = coll
-        .find(find_filter, find_options)
-        .await
-        .map_err(DBError::MongoQueryError)?;
-
-    let mut json_result: Vec<S::ModelResponse> = Vec::new();
-    while let Some(result) = cursor.next().await {
-        match result {
-            Ok(doc) => {
-                // tracing::info!("doc: {:?}", &doc);
-                let res = S::convert_doc_to_response(&doc);
-                json_result.push(res);
-            }
-            Err(e) => return Err(DB(DBError::MongoQueryError(e))),
+    // find_filter가 주어지더라도 user 조건은 항상 덮어씀.
+    let mut find_filter = filter_opts.find_filter.unwrap_or_default();
+    find_filter.insert("user", user);
+    find_filter.insert("deletedAt", doc! { "$exists": false });
+
+    // ?filter=&sort=는 repo가 허용한 필드만 사용
+    let query = ListQuery::parse(&filter_opts.query_opts, S::QUERY_FIELDS)?;
+    let selection =
+        FieldSelection::parse(filter_opts.query_opts.fields.as_deref(), S::SELECT_FIELDS)?;
+    if let Some(query_filter) = query.to_filter() {
+        find_filter = doc! { "$and": [find_filter, query_filter] };
+    }
+    // ?tag=, ?tag_group=로 찾은 item만
+    if let Some(ids) = &filter_opts.query_opts.ids {
+        find_filter = doc! { "$and": [find_filter, { "_id": { "$in": ids } }] };
+    }
+
+    // _id는 항상 마지막 정렬 기준으로 사용
+    let mut sort_keys = query.to_sort().unwrap_or_else(|| doc! { S::SORT_KEY: 1 });
+    sort_keys.remove("_id");
+
+    let PageOptions {
+        limit,
+        page,
+        cursor,
+        with_total,
+    } = filter_opts.page_opts;
+
+    let total = if with_total {
+        Some(db.count(S::COLL_NAME, find_filter.clone(), None).await?)
+    } else {
+        None
+    };
+
+    // cursor가 있으면 keyset, 없으면 기존 page 방식
+    let skip = match cursor {
+        Some(cursor) => {
+            let keys = match decode_cursor(&cursor) {
+                Some((Bson::Array(keys), id)) if keys.len() == sort_keys.len() => Some((keys, id)),
+                _ => None,
+            };
+            let (keys, id) = keys.ok_or(DBError::InvalidCursor(cursor.clone()))?;
+            let keyset = keyset_filter(&sort_keys, &keys, id);
+            find_filter = doc! { "$and": [find_filter, keyset] };
+            0
+        }
+        None => (page - 1).max(0) * limit,
+    };
+
+    // 다음 페이지 존재 여부를 알기 위해 하나 더 가져옴.
+    let mut proj_opts = filter_opts.proj_opts.unwrap_or_default();
+    if let Some(selection) = &selection {
+        proj_opts = selection.projection(S::SELECT_FIELDS, proj_opts);
+    }
+    // cursor를 만들 수 있도록 sort key는 항상 읽음
+    let inclusion = is_inclusion(&proj_opts);
+    for sort_key in sort_keys.keys() {
+        if inclusion {
+            proj_opts.insert(sort_key, 1);
+        } else {
+            proj_opts.remove(sort_key);
         }
     }
 
-    Ok(json_result)
+    let mut sort = sort_keys.clone();
+    sort.insert("_id", 1);
+
+    let find_options = FindOpts {
+        projection: Some(proj_opts),
+        sort: Some(sort),
+        limit: Some(if limit > 0 { limit + 1 } else { 0 }),
+        skip: Some(u64::try_from(skip).unwrap_or_default()),
+    };
+
+    let mut docs = db
+        .find(S::COLL_NAME, find_filter, find_options, None)
+        .await?;
+
+    let has_more = limit > 0 && docs.len() as i64 > limit;
+    if has_more {
+        docs.truncate(limit as usize);
+    }
+
+    let next_cursor = match docs.last() {
+        Some(last) if has_more => Some(encode_cursor(
+            Bson::Array(
+                sort_keys
+                    .keys()
+                    .map(|key| last.get(key).cloned().unwrap_or(Bson::Null))
+                    .collect(),
+            ),
+            last.get("_id").cloned().unwrap_or(Bson::Null),
+        )),
+        _ => None,
+    };
+
+    let fields = selected_fields(selection);
+    let mut json_result: Vec<Partial<S::ModelResponse>> = Vec::new();
+    for doc in docs {
+        let model: S::Model =
+            bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?;
+        json_result.push(Partial::new(
+            S::convert_doc_to_response(&model),
+            fields.clone(),
+        ));
+    }
+
+    Ok(Page {
+        items: json_result,
+        next_cursor,
+        has_more,
+        total,
+    })
+}
+
+// projection이 포함(1) 방식일 때만 sort key를 추가할 수 있음.
+fn is_inclusion(proj_opts: &Document) -> bool {
+    proj_opts.iter().any(|(_, v)| is_truthy(v))
+}
+
+fn selected_fields(selection: Option<FieldSelection>) -> Option<Arc<[&'static str]>> {
+    selection.map(|selection| selection.fields.into())
 }
 
 pub async fn create<S, Schema>(
-    db: &Database,
+    db: &dyn Storage,
     body: &Schema,
     user: &Uuid,
-    indexes: Option<Vec<&str>>,
 ) -> Result<S::ModelResponse>
 where
     S: MongoRepo,
     S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
     Schema: Serialize,
 {
-    let coll = db.collection::<S::Model>(S::COLL_NAME);
-    let doc_coll = db.collection::<Document>(S::COLL_NAME);
-
-    let document = S::create_doc::<Schema>(user, body)?;
-
-    if let Some(index_fields) = indexes {
-        let mut index_doc = doc! {};
-        for field in index_fields {
-            index_doc.insert(field, 1);
-        }
-
-        index_doc.insert("user", 1);
-
-        // TODO: unique index 설정(tag group, tag의 경우 동일 이름을 가진 tag group을 생성할 수 없음)
-        let index = IndexModel::builder()
-            .keys(index_doc)
-            .options(IndexOptions::builder().unique(false).build())
-            .build();
+    create_with_id::<S, Schema>(db, None, body, user).await
+}
 
-        coll.create_index(index, None)
-            .await
-            .map_err(DBError::MongoError)?;
+/// id가 주어지면 그 id로 생성함(offline client가 만든 id 등).
+pub async fn create_with_id<S, Schema>(
+    db: &dyn Storage,
+    id: Option<ObjectId>,
+    body: &Schema,
+    user: &Uuid,
+) -> Result<S::ModelResponse>
+where
+    S: MongoRepo,
+    S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
+    Schema: Serialize,
+{
+    let mut document = S::create_doc::<Schema>(user, body)?;
+    if let Some(id) = id {
+        document.insert("_id", id);
     }
 
     // 생성된 문서를 db에 추가.
-    let insert_result = match doc_coll.insert_one(&document, None).await {
-        Ok(result) => result,
-        Err(e) => {
-            if e.to_string()
-                .contains("E11000 duplicate key error collection")
-            {
-                return Err(MongoDuplicateError(e));
-            }
-            return Err(DB(DBError::MongoQueryError(e)));
-        }
-    };
+    let inserted_id = db.insert_one(S::COLL_NAME, document, None).await?;
 
     // 삽입된 문서의 id추출
-    let new_id = insert_result
-        .inserted_id
-        .as_object_id()
-        .expect("issue with new _id");
+    let new_id = inserted_id.as_object_id().expect("issue with new _id");
 
     // 문서 삽입이 잘 되었는지 확인 및 반환.
-    let doc = find_mdoc_by_id(&coll, &new_id, doc! {"_id": new_id, "user":user}).await?;
+    let doc: S::Model =
+        find_mdoc_by_id(db, S::COLL_NAME, &new_id, doc! {"_id": new_id, "user":user}).await?;
 
     Ok(S::convert_doc_to_response(&doc))
 }
 
-pub async fn get<S>(db: &Database, id: &str, user: &Uuid) -> Result<S::ModelResponse>
+pub async fn get<S>(db: &dyn Storage, id: &str, user: &Uuid) -> Result<S::ModelResponse>
+where
+    S: MongoRepo,
+    S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
+{
+    Ok(get_fields::<S>(db, id, None, user).await?.into_inner())
+}
+
+/// ?fields=가 주어지면 요청되지 않은 Lazy 필드는 읽지 않고, 응답에는 선택된 필드만 남김.
+pub async fn get_fields<S>(
+    db: &dyn Storage,
+    id: &str,
+    fields: Option<&str>,
+    user: &Uuid,
+) -> Result<Partial<S::ModelResponse>>
 where
     S: MongoRepo,
     S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
 {
-    let coll = db.collection::<S::Model>(S::COLL_NAME);
+    let selection = FieldSelection::parse(fields, S::SELECT_FIELDS)?;
 
     // model의 id를 ObjectId로 변환
     let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
 
     // id를 이용해 문서를 찾음.
-    let doc = find_mdoc_by_id(&coll, &oid, doc! {"_id": oid, "user":user}).await?;
+    let filter = doc! {"_id": oid, "user": user, "deletedAt": {"$exists": false}};
+    let projection = selection
+        .as_ref()
+        .map(|selection| selection.projection(S::SELECT_FIELDS, Document::new()));
+    let doc = match db.find_one(S::COLL_NAME, filter, projection, None).await? {
+        Some(doc) => doc,
+        None => return Err(NotFoundError(oid.to_string())),
+    };
+    let model: S::Model = bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?;
 
-    Ok(S::convert_doc_to_response(&doc))
+    Ok(Partial::new(
+        S::convert_doc_to_response(&model),
+        selected_fields(selection),
+    ))
 }
 
+/// if_match가 주어지면 version이 같을 때만 업데이트하고, 다르면 PreconditionFailed.
 pub async fn update<S, Schema>(
-    db: &Database,
+    db: &dyn Storage,
     id: &str,
     body: &Schema,
+    if_match: Option<i64>,
     user: &Uuid,
 ) -> Result<S::ModelResponse>
 where
@@ -158,29 +272,64 @@ where
     S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
     Schema: Serialize,
 {
-    let coll = db.collection::<S::Model>(S::COLL_NAME);
+    let mut tx = Transaction::start(db).await?;
+    let result = update_with_session::<S, Schema>(db, id, body, if_match, user, tx.session()).await;
+    tx.finish(result).await
+}
 
+/// update와 같지만 호출한 쪽의 session(transaction) 안에서 실행함.
+pub async fn update_with_session<S, Schema>(
+    db: &dyn Storage,
+    id: &str,
+    body: &Schema,
+    if_match: Option<i64>,
+    user: &Uuid,
+    session: Option<&mut ClientSession>,
+) -> Result<S::ModelResponse>
+where
+    S: MongoRepo,
+    S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
+    Schema: Serialize,
+{
     let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
 
     let mut update_doc = bson::to_document(body).map_err(DBError::MongoSerializeBsonError)?;
     update_doc.insert("updatedAt", Bson::DateTime(Utc::now().into()));
 
-    let doc = update_doc_ret_model(
-        &coll,
-        &oid,
+    let filter = doc! {"_id": oid, "user": user, "deletedAt": {"$exists": false}};
+    let mut find_filter = filter.clone();
+    if let Some(version) = if_match {
+        find_filter.insert("version", version_filter(version));
+    }
+
+    let update_doc = doc! {
+        "$set": update_doc,
+        "$inc": { "version": 1 },
+    };
+    let doc = match update_doc_in_session(
+        db,
+        S::COLL_NAME,
+        find_filter,
+        update_doc,
         None,
-        doc! {
-            "$set": update_doc,
-        },
-        doc! {"_id": oid,"user":user},
+        user,
+        session,
     )
-    .await?;
+    .await?
+    {
+        Some(doc) => doc,
+        None if if_match.is_some() => {
+            return Err(not_found_or_stale(db, S::COLL_NAME, id, filter).await)
+        }
+        None => return Err(NotFoundError(id.to_string())),
+    };
+    let model: S::Model = bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?;
 
-    Ok(S::convert_doc_to_response(&doc))
+    Ok(S::convert_doc_to_response(&model))
 }
 
 pub async fn update_unset_fields<S>(
-    db: &Database,
+    db: &dyn Storage,
     id: &str,
     unset_fields: &[&str],
     user: &Uuid,
@@ -189,8 +338,6 @@ where
     S: MongoRepo,
     S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
 {
-    let coll = db.collection::<S::Model>(S::COLL_NAME);
-
     let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
 
     // $unset 연산자를 위한 Document 생성
@@ -205,34 +352,387 @@ where
     update_operator.insert("$set", doc! {
         "updatedAt": Bson::DateTime(Utc::now().into()),
     });
+    update_operator.insert("$inc", doc! { "version": 1 });
 
     // 업데이트 실행
-    let doc = update_doc_ret_model(
-        &coll,
+    let doc = update_doc_ret_model::<S::Model>(
+        db,
+        S::COLL_NAME,
         &oid,
         None,
         update_operator,
-        doc! {"_id": oid, "user": user},
+        doc! {"_id": oid, "user": user, "deletedAt": {"$exists": false}},
+        user,
     )
     .await?;
 
     Ok(S::convert_doc_to_response(&doc))
 }
 
+// region:    --- History
+
+/// 문서의 change 목록(최신순).
+pub async fn fetch_history<S: MongoRepo>(
+    db: &dyn Storage,
+    id: &str,
+    page_opts: &PageOptions,
+    user: &Uuid,
+) -> Result<Page<ChangeModel>> {
+    let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+
+    // 다른 user의 문서이거나 존재하지 않는 경우
+    let filter = doc! {"_id": oid, "user": user, "deletedAt": {"$exists": false}};
+    if db.count(S::COLL_NAME, filter, None).await? == 0 {
+        return Err(NotFoundError(id.to_string()));
+    }
 
-pub async fn delete<S: MongoRepo>(db: &Database, id: &str) -> Result<()> {
-    let doc_coll = db.collection::<Document>(S::COLL_NAME);
+    history::fetch(db, S::COLL_NAME, &oid, page_opts, user).await
+}
+
+/// 문서를 revision(change id)이 적용된 직후의 상태로 되돌림.
+/// 이후의 change들을 최신순으로 되돌려 목표 상태를 만들며, 되돌린 것도 change로 기록됨.
+pub async fn revert<S>(
+    db: &dyn Storage,
+    id: &str,
+    revision: &str,
+    user: &Uuid,
+) -> Result<S::ModelResponse>
+where
+    S: MongoRepo,
+    S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
+{
+    let (current, target) = revert_target::<S>(db, id, revision, user).await?;
+    apply_revert::<S>(db, id, &current, &target, user).await
+}
 
+/// revert 전에 목표 상태를 검증해야 할 때 사용. (현재 문서, revision 시점의 문서)를 반환.
+pub async fn revert_target<S: MongoRepo>(
+    db: &dyn Storage,
+    id: &str,
+    revision: &str,
+    user: &Uuid,
+) -> Result<(Document, Document)> {
     let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
-    let filter = doc! {"_id": oid };
+    let revision_oid = ObjectId::from_str(revision).map_err(DBError::MongoGetOidError)?;
 
-    let result = doc_coll
-        .delete_one(filter, None)
-        .await
-        .map_err(DBError::MongoQueryError)?;
+    let filter = doc! {"_id": oid, "user": user, "deletedAt": {"$exists": false}};
+    let current = match db.find_one(S::COLL_NAME, filter, None, None).await? {
+        Some(doc) => doc,
+        None => return Err(NotFoundError(id.to_string())),
+    };
+
+    let mut target = current.clone();
+    for change in history::changes_after(db, S::COLL_NAME, &oid, &revision_oid, user).await? {
+        history::undo(&mut target, &change);
+    }
+    Ok((current, target))
+}
 
-    match result.deleted_count {
+/// revert_target으로 계산한 목표 상태를 적용. current를 읽은 뒤 바뀌었으면 PreconditionFailed.
+pub async fn apply_revert<S>(
+    db: &dyn Storage,
+    id: &str,
+    current: &Document,
+    target: &Document,
+    user: &Uuid,
+) -> Result<S::ModelResponse>
+where
+    S: MongoRepo,
+    S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
+{
+    let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+    let filter = doc! {"_id": oid, "user": user, "deletedAt": {"$exists": false}};
+
+    let mut set_doc = Document::new();
+    let mut unset_doc = Document::new();
+    for diff in history::diff(current, target) {
+        if let Some(value) = target.get(&diff.field) {
+            set_doc.insert(diff.field, value.clone());
+        } else {
+            unset_doc.insert(diff.field, Bson::Int32(1));
+        }
+    }
+
+    // 이미 revision의 상태
+    if set_doc.is_empty() && unset_doc.is_empty() {
+        let model: S::Model =
+            bson::from_document(current.clone()).map_err(DBError::MongoDeserializeBsonError)?;
+        return Ok(S::convert_doc_to_response(&model));
+    }
+
+    set_doc.insert("updatedAt", Bson::DateTime(Utc::now().into()));
+    let mut update_doc = doc! { "$set": set_doc, "$inc": { "version": 1 } };
+    if !unset_doc.is_empty() {
+        update_doc.insert("$unset", unset_doc);
+    }
+
+    // 목표 상태를 계산한 version일 때만 적용
+    let mut find_filter = filter.clone();
+    find_filter.insert("version", version_filter(doc_version(current)));
+
+    let result = update_doc_ret_model::<S::Model>(
+        db,
+        S::COLL_NAME,
+        &oid,
+        None,
+        update_doc,
+        find_filter,
+        user,
+    )
+    .await;
+
+    let doc = match result {
+        Err(NotFoundError(_)) => return Err(not_found_or_stale(db, S::COLL_NAME, id, filter).await),
+        result => result?,
+    };
+
+    Ok(S::convert_doc_to_response(&doc))
+}
+
+// endregion: --- History
+
+// soft delete: deletedAt을 기록하고 fetch/get/update에서 제외함.
+pub async fn delete<S: MongoRepo>(db: &dyn Storage, id: &str, user: &Uuid) -> Result<()> {
+    let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+    let filter = doc! {"_id": oid, "user": user };
+
+    match delete_many::<S>(db, filter, None, None, user).await? {
         0 => Err(NotFoundError(id.to_string())),
         _ => Ok(()),
     }
 }
+
+/// filter에 맞는 문서들을 soft delete.
+/// deleted_with가 주어지면 함께 삭제된 root 문서의 id를 기록하여, restore시 같이 복구함.
+/// session이 주어지면 해당 transaction 안에서 실행됨.
+pub async fn delete_many<S: MongoRepo>(
+    db: &dyn Storage,
+    mut filter: Document,
+    deleted_with: Option<&ObjectId>,
+    session: Option<&mut ClientSession>,
+    user: &Uuid,
+) -> Result<u64> {
+    filter.insert("user", user);
+    filter.insert("deletedAt", doc! { "$exists": false });
+
+    let now = Bson::DateTime(Utc::now().into());
+    let mut set_doc = doc! { "deletedAt": now.clone(), "updatedAt": now };
+    if let Some(root) = deleted_with {
+        set_doc.insert("deletedWith", root);
+    }
+
+    let update_doc = doc! { "$set": set_doc, "$inc": { "version": 1 } };
+    db.update_many(S::COLL_NAME, filter, update_doc, session)
+        .await
+}
+
+/// filter에 맞는 문서들의 필드를 한번에 제거.
+/// session이 주어지면 해당 transaction 안에서 실행됨.
+pub async fn unset_fields_many<S: MongoRepo>(
+    db: &dyn Storage,
+    mut filter: Document,
+    unset_fields: &[&str],
+    session: Option<&mut ClientSession>,
+    user: &Uuid,
+) -> Result<u64> {
+    filter.insert("user", user);
+    filter.insert("deletedAt", doc! { "$exists": false });
+
+    let mut unset_doc = Document::new();
+    for field in unset_fields {
+        unset_doc.insert(*field, Bson::Int32(1));
+    }
+    let update_doc = doc! {
+        "$unset": unset_doc,
+        "$set": { "updatedAt": Bson::DateTime(Utc::now().into()) },
+        "$inc": { "version": 1 },
+    };
+
+    db.update_many(S::COLL_NAME, filter, update_doc, session)
+        .await
+}
+
+// region:    --- Bulk
+
+// bulk 요청 하나에 담을 수 있는 최대 item 수
+pub const BATCH_MAX: usize = 100;
+
+pub fn check_batch_size(len: usize) -> Option<DBError> {
+    (len > BATCH_MAX).then_some(DBError::ListLimitOverMax {
+        max: BATCH_MAX as i64,
+        actual: len as i64,
+    })
+}
+
+/// 여러 문서를 insert_many(ordered: false)로 한번에 생성.
+/// 일부가 실패해도 나머지는 생성되며, 결과는 요청 순서대로 반환함.
+pub async fn bulk_create<S, Schema>(
+    db: &dyn Storage,
+    bodies: &[Schema],
+    user: &Uuid,
+) -> Result<Vec<BatchResult<S::ModelResponse>>>
+where
+    S: MongoRepo,
+    S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
+    Schema: Serialize,
+{
+    if let Some(e) = check_batch_size(bodies.len()) {
+        return Err(DB(e));
+    }
+    // 생성 전에 _id를 정해두어, 어떤 item이 생성되었는지 알 수 있게 함.
+    let mut failed: HashMap<usize, String> = HashMap::new();
+    let mut targets: Vec<(usize, ObjectId)> = Vec::new();
+    let mut docs: Vec<Document> = Vec::new();
+    for (index, body) in bodies.iter().enumerate() {
+        match S::create_doc::<Schema>(user, body) {
+            Ok(mut document) => {
+                let oid = ObjectId::new();
+                document.insert("_id", oid);
+                docs.push(document);
+                targets.push((index, oid));
+            }
+            Err(e) => {
+                failed.insert(index, e.to_string());
+            }
+        }
+    }
+
+    // write error의 index는 docs 기준
+    for (pos, message) in db.insert_many(S::COLL_NAME, docs, None).await? {
+        if let Some((index, _)) = targets.get(pos) {
+            failed.insert(*index, message);
+        }
+    }
+
+    let inserted: Vec<ObjectId> = targets
+        .iter()
+        .filter(|(index, _)| !failed.contains_key(index))
+        .map(|(_, oid)| *oid)
+        .collect();
+    let mut models = find_by_ids::<S>(db, &inserted, user).await?;
+
+    let oids: HashMap<usize, ObjectId> = targets.into_iter().collect();
+    Ok((0..bodies.len())
+        .map(|index| {
+            let oid = oids.get(&index);
+            let outcome = match (failed.remove(&index), oid.and_then(|oid| models.remove(oid))) {
+                (Some(message), _) => Err(message),
+                (None, Some(model)) => Ok(S::convert_doc_to_response(&model)),
+                (None, None) => Err("doc not created".to_string()),
+            };
+            BatchResult {
+                index,
+                id: oid.filter(|_| outcome.is_ok()).map(|oid| oid.to_hex()),
+                outcome,
+            }
+        })
+        .collect())
+}
+
+/// 여러 문서를 하나씩 update로 업데이트(version 확인과 history 기록을 그대로 따름).
+/// item마다 이전 문서를 읽고 history를 같은 transaction에 남겨야 하므로 bulk_write로 묶지 않음.
+/// 대신 item 수만큼 왕복하므로 BATCH_MAX로 제한함.
+/// item마다 (id, If-Match version, body)를 받으며, 결과는 요청 순서대로 반환함.
+pub async fn bulk_update<S, Schema>(
+    db: &dyn Storage,
+    items: &[(&str, Option<i64>, &Schema)],
+    user: &Uuid,
+) -> Result<Vec<BatchResult<S::ModelResponse>>>
+where
+    S: MongoRepo,
+    S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
+    Schema: Serialize,
+{
+    if let Some(e) = check_batch_size(items.len()) {
+        return Err(DB(e));
+    }
+
+    let mut results = Vec::with_capacity(items.len());
+    for (index, (id, if_match, body)) in items.iter().enumerate() {
+        let outcome = update::<S, Schema>(db, id, body, *if_match, user).await;
+        results.push(BatchResult {
+            index,
+            id: Some(id.to_string()),
+            outcome: outcome.map_err(|e| e.to_string()),
+        });
+    }
+    Ok(results)
+}
+
+/// 여러 문서를 한번에 soft delete. 없는 문서는 실패로 표시함.
+pub async fn bulk_delete<S>(
+    db: &dyn Storage,
+    ids: &[String],
+    user: &Uuid,
+) -> Result<Vec<BatchResult<()>>>
+where
+    S: MongoRepo,
+    S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
+{
+    if let Some(e) = check_batch_size(ids.len()) {
+        return Err(DB(e));
+    }
+
+    let oids: Vec<Option<ObjectId>> = ids.iter().map(|id| ObjectId::from_str(id).ok()).collect();
+    let valid: Vec<ObjectId> = oids.iter().flatten().copied().collect();
+
+    // 지울 수 있는(본인 소유이고 삭제되지 않은) 문서만 골라 한번에 삭제
+    let found: HashSet<ObjectId> = find_by_ids::<S>(db, &valid, user)
+        .await?
+        .into_keys()
+        .collect();
+    if !found.is_empty() {
+        let filter = doc! { "_id": { "$in": found.iter().collect::<Vec<_>>() } };
+        delete_many::<S>(db, filter, None, None, user).await?;
+    }
+
+    Ok(ids
+        .iter()
+        .zip(oids)
+        .enumerate()
+        .map(|(index, (id, oid))| BatchResult {
+            index,
+            id: Some(id.to_string()),
+            outcome: match oid {
+                Some(oid) if found.contains(&oid) => Ok(()),
+                Some(_) => Err(format!("ID: {} not found", id)),
+                None => Err(format!("ID: {} is not a valid id", id)),
+            },
+        })
+        .collect())
+}
+
+// 본인 소유이고 삭제되지 않은 문서들을 _id 기준으로 조회
+async fn find_by_ids<S>(
+    db: &dyn Storage,
+    oids: &[ObjectId],
+    user: &Uuid,
+) -> Result<HashMap<ObjectId, S::Model>>
+where
+    S: MongoRepo,
+    S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
+{
+    let mut models = HashMap::new();
+    if oids.is_empty() {
+        return Ok(models);
+    }
+
+    let filter = doc! {
+        "_id": { "$in": oids },
+        "user": user,
+        "deletedAt": { "$exists": false },
+    };
+    for doc in db
+        .find(S::COLL_NAME, filter, FindOpts::default(), None)
+        .await?
+    {
+        let oid = doc.get_object_id("_id").map_err(DBError::MongoDataError)?;
+        let model: S::Model =
+            bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?;
+        models.insert(oid, model);
+    }
+
+    Ok(models)
+}
+
+// endregion: --- Bulk
@@ -1,15 +1,21 @@
-use super::utils::update_doc_ret_doc;
+use super::history;
+use super::index::IndexDef;
+use super::storage::Storage;
+use super::transaction::Transaction;
+use super::utils::{
+    decode_cursor, encode_cursor, not_found_or_stale, update_doc_ret_doc, version_filter,
+};
 use crate::domain::error::{Error::*, Result};
 use crate::infra::db::error::Error as DBError;
+use crate::infra::types::{Page, PageOptions};
 use chrono::Utc;
-use futures::TryStreamExt;
 use mongodb::bson::Document;
 use mongodb::bson::{self, doc, oid::ObjectId, Bson};
-use mongodb::options::{AggregateOptions, IndexOptions};
-use mongodb::{Database, IndexModel};
+use mongodb::ClientSession;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::str::FromStr;
+use uuid::Uuid;
 
 pub trait MongoArrayRepo {
     type CollModel: DeserializeOwned + Serialize + Unpin + Send + Sync;
@@ -19,13 +25,16 @@ pub trait MongoArrayRepo {
     type ElemRes: Serialize + DeserializeOwned + Unpin + Send + Sync;
     const COLL_NAME: &'static str;
     const ARR_NAME: &'static str;
+    // 배열 원소 기준 경로로 선언(ARR_NAME이 앞에 붙음)
+    const ELEM_INDEXES: &'static [IndexDef] = &[];
     fn convert_doc_to_response(doc: &Self::ElemModel) -> Result<Self::ElemRes>;
     fn create_doc(body: &Self::CreateElemReq) -> Result<mongodb::bson::Document> {
         let ser_data = bson::to_bson(body).map_err(|e| DB(DBError::MongoSerializeBsonError(e)))?;
         let doc = ser_data.as_document().unwrap();
 
         let mut doc_with_date = doc! {
-            "id": ObjectId::new(),
+            "_id": ObjectId::new(),
+            "version": 1,
             "createdAt": Utc::now(),
         };
 
@@ -35,87 +44,115 @@ pub trait MongoArrayRepo {
 }
 
 // S: Service
+// 배열은 저장된 순서(최신 원소가 앞)를 그대로 정렬 기준으로 사용하며,
+// cursor는 마지막으로 받은 원소의 _id를 가리킴.
 pub async fn fetch_elems<S>(
-    db: &Database,
+    db: &dyn Storage,
     src_id: &str,
-    limit: Option<i64>,
-    page: Option<i64>,
-) -> Result<Vec<S::ElemRes>>
+    page_opts: &PageOptions,
+    user: &Uuid,
+) -> Result<Page<S::ElemRes>>
 where
     S: MongoArrayRepo,
     S::CollModel: DeserializeOwned + Serialize + Unpin + Send + Sync,
 {
-    let doc_coll = db.collection::<Document>(S::COLL_NAME);
     let oid = ObjectId::from_str(src_id).map_err(DBError::MongoGetOidError)?;
 
-    // 집계 파이프라인 설정
-    let pipeline = match (limit, page) {
-        (Some(limit), Some(page)) => {
-            let skip = (page - 1) * limit;
-            vec![
-                doc! {
-                    "$match": { "_id": oid }
-                },
-                doc! {
-                    "$project": {
-                        S::ARR_NAME: {
-                            "$slice": [format!("${}", S::ARR_NAME), skip, limit]
-                        }
-                    }
-                },
-            ]
+    // 다른 user의 document이거나 존재하지 않는 경우
+    let doc = match db
+        .find_one(
+            S::COLL_NAME,
+            doc! { "_id": oid, "user": user, "deletedAt": { "$exists": false } },
+            Some(doc! { S::ARR_NAME: 1 }),
+            None,
+        )
+        .await?
+    {
+        Some(doc) => doc,
+        None => return Err(NotFoundError(oid.to_string())),
+    };
+
+    let array = match doc.get_array(S::ARR_NAME) {
+        Ok(array) => array,
+        Err(mongodb::bson::document::ValueAccessError::NotPresent) => {
+            return Ok(Page {
+                total: page_opts.with_total.then_some(0),
+                ..Page::empty()
+            })
         }
-        _ => {
-            vec![doc! {
-                "$match": { "_id": oid }
-            }]
+        Err(e) => return Err(DB(DBError::MongoDataError(e))),
+    };
+
+    let start = match &page_opts.cursor {
+        Some(cursor) => {
+            let (_, cursor_id) =
+                decode_cursor(cursor).ok_or(DBError::InvalidCursor(cursor.to_string()))?;
+            let pos = array
+                .iter()
+                .position(|elem| {
+                    elem.as_document()
+                        .and_then(|doc| doc.get("_id"))
+                        .filter(|id| **id == cursor_id)
+                        .is_some()
+                })
+                .ok_or(DBError::InvalidCursor(cursor.to_string()))?;
+            pos + 1
         }
+        None => ((page_opts.page - 1).max(0) * page_opts.limit) as usize,
     };
 
-    // 집계 쿼리 실행
-    let aggregate_options = AggregateOptions::builder().build();
-    let mut cursor = doc_coll
-        .aggregate(pipeline, aggregate_options)
-        .await
-        .map_err(DBError::MongoError)?;
+    let end = match page_opts.limit {
+        limit if limit > 0 => (start + limit as usize).min(array.len()),
+        _ => array.len(),
+    };
+    let has_more = end < array.len();
 
+    // 배열 내 원소들의 타입을 Response로 변환후, elems에 추가
     let mut elems: Vec<S::ElemRes> = Vec::new();
-
-    while let Some(doc) = cursor.try_next().await.map_err(DBError::MongoError)? {
-        let array = match doc.get_array(S::ARR_NAME) {
-            Ok(array) => array,
-            Err(e) => return Err(DB(DBError::MongoDataError(e))),
+    let mut last_id = Bson::Null;
+    for elem_bson in array.iter().take(end).skip(start) {
+        let Some(elem_doc) = elem_bson.as_document() else {
+            continue;
         };
-        // 배열 내 원소들의 타입을 Response로 변환후, elems에 추가
-        while let Some(elem_bson) = array.iter().next() {
-            if let Ok(doc) = elem_bson
-                .as_document()
-                .ok_or(DBError::MongoDeserializeBsonError)
-            {
-                let elem: S::ElemModel = bson::from_bson(Bson::Document(doc.clone()))
-                    .map_err(DBError::MongoDeserializeBsonError)?;
-                elems.push(S::convert_doc_to_response(&elem)?);
-            };
-        }
+        last_id = elem_doc.get("_id").cloned().unwrap_or(Bson::Null);
+
+        let elem: S::ElemModel = bson::from_bson(Bson::Document(elem_doc.clone()))
+            .map_err(DBError::MongoDeserializeBsonError)?;
+        elems.push(S::convert_doc_to_response(&elem)?);
     }
 
-    Ok(elems)
+    Ok(Page {
+        items: elems,
+        next_cursor: has_more.then(|| encode_cursor(Bson::Null, last_id)),
+        has_more,
+        total: page_opts.with_total.then_some(array.len() as u64),
+    })
 }
 
-pub async fn get_elem<S>(db: &Database, src_id: &str, elem_id: &str) -> Result<S::ElemRes>
+pub async fn get_elem<S>(
+    db: &dyn Storage,
+    src_id: &str,
+    elem_id: &str,
+    user: &Uuid,
+) -> Result<S::ElemRes>
 where
     S: MongoArrayRepo,
     S::CollModel: DeserializeOwned + Serialize + Unpin + Send + Sync,
 {
-    let doc_coll = db.collection::<Document>(S::COLL_NAME);
-
     let oid = ObjectId::from_str(src_id).map_err(DBError::MongoGetOidError)?;
     let elem_oid = ObjectId::from_str(elem_id).map_err(DBError::MongoGetOidError)?;
 
-    let doc = match doc_coll.find_one(doc! { "_id": oid }, None).await {
-        Ok(Some(doc)) => doc,
-        Ok(None) => return Err(NotFoundError(oid.to_string())),
-        Err(e) => return Err(DB(DBError::MongoQueryError(e))),
+    let doc = match db
+        .find_one(
+            S::COLL_NAME,
+            doc! { "_id": oid, "user": user, "deletedAt": { "$exists": false } },
+            None,
+            None,
+        )
+        .await?
+    {
+        Some(doc) => doc,
+        None => return Err(NotFoundError(oid.to_string())),
     };
 
     let array = doc
@@ -141,38 +178,36 @@ where
 }
 
 pub async fn add_elem<S>(
-    db: &Database,
+    db: &dyn Storage,
     src_id: &str,
     new_elem: &S::CreateElemReq,
-    index: Option<&str>,
+    user: &Uuid,
 ) -> Result<S::ElemRes>
 where
     S: MongoArrayRepo,
     S::CollModel: DeserializeOwned + Serialize + Unpin + Send + Sync,
 {
-    let coll = db.collection::<S::CollModel>(S::COLL_NAME);
-
-    // 인덱스 생성
-    if let Some(index_field) = index {
-        let index_model = IndexModel::builder()
-            .keys(doc! { index_field: 1 })
-            .options(IndexOptions::builder().unique(false).build())
-            .build();
-        coll.create_index(index_model, None)
-            .await
-            .map_err(DBError::MongoError)?;
-    }
-
     let oid = ObjectId::from_str(src_id).map_err(DBError::MongoGetOidError)?;
 
     // 배열의 맨 앞에 element 추가. -> 최신순
     let new_elem_doc = S::create_doc(new_elem)?;
     let update_doc = doc! {
-        "$push": { S::ARR_NAME: {"$each":new_elem_doc,"$position":0 }},
-        "$set": { "updatedAt": Bson::DateTime(Utc::now().into()) }
+        "$push": { S::ARR_NAME: { "$each": [new_elem_doc], "$position": 0 } },
+        "$set": { "updatedAt": Bson::DateTime(Utc::now().into()) },
+        "$inc": { "version": 1 },
     };
 
-    match update_doc_ret_doc(&coll, &oid, None, update_doc, doc! { "_id": oid }).await {
+    match update_doc_ret_doc(
+        db,
+        S::COLL_NAME,
+        &oid,
+        None,
+        update_doc,
+        doc! { "_id": oid, "user": user, "deletedAt": { "$exists": false } },
+        user,
+    )
+    .await
+    {
         Ok(updated_doc) => {
             let array = match updated_doc.get_array(S::ARR_NAME) {
                 Ok(array) => array,
@@ -200,22 +235,36 @@ where
     }
 }
 
-pub async fn remove_elem<S>(db: &Database, src_id: &str, elem_id: &str) -> Result<()>
+pub async fn remove_elem<S>(
+    db: &dyn Storage,
+    src_id: &str,
+    elem_id: &str,
+    user: &Uuid,
+) -> Result<()>
 where
     S: MongoArrayRepo,
     S::CollModel: DeserializeOwned + Serialize + Unpin + Send + Sync,
 {
-    let coll = db.collection::<S::CollModel>(S::COLL_NAME);
-
     let oid = ObjectId::from_str(src_id).map_err(DBError::MongoGetOidError)?;
     let elem_oid = ObjectId::from_str(elem_id).map_err(DBError::MongoGetOidError)?;
 
     let update_doc = doc! {
         "$pull": { S::ARR_NAME: doc! { "_id": elem_oid } },
-        "$set": { "updatedAt": Bson::DateTime(Utc::now().into()) }
+        "$set": { "updatedAt": Bson::DateTime(Utc::now().into()) },
+        "$inc": { "version": 1 },
     };
 
-    match update_doc_ret_doc(&coll, &oid, None, update_doc, doc! { "_id": oid }).await {
+    match update_doc_ret_doc(
+        db,
+        S::COLL_NAME,
+        &oid,
+        None,
+        update_doc,
+        doc! { "_id": oid, "user": user, "deletedAt": { "$exists": false } },
+        user,
+    )
+    .await
+    {
         Ok(updated_doc) => {
             let array = updated_doc
                 .get_array(S::ARR_NAME)
@@ -239,46 +288,71 @@ where
     }
 }
 
+/// if_match가 주어지면 원소의 version이 같을 때만 업데이트하고, 다르면 PreconditionFailed.
 pub async fn update_elem<S>(
-    db: &Database,
+    db: &dyn Storage,
     src_id: &str,
     elem_id: &str,
     update_elem: &S::UpdateElemReq,
+    if_match: Option<i64>,
+    user: &Uuid,
 ) -> Result<S::ElemRes>
 where
     S: MongoArrayRepo,
     S::CollModel: DeserializeOwned + Serialize + Unpin + Send + Sync,
     S::UpdateElemReq: Serialize,
 {
-    let coll = db.collection::<S::CollModel>(S::COLL_NAME);
-
     let oid = ObjectId::from_str(src_id).map_err(DBError::MongoGetOidError)?;
     let elem_oid = ObjectId::from_str(elem_id).map_err(DBError::MongoGetOidError)?;
 
-    let mut update_doc = doc! {
+    let mut set_doc = doc! {
         "updatedAt": Bson::DateTime(Utc::now().into())
     };
 
-    let update_elem_bson =
-        bson::to_bson(update_elem).map_err(DBError::MongoSerializeBsonError)?;
+    let update_elem_bson = bson::to_bson(update_elem).map_err(DBError::MongoSerializeBsonError)?;
 
     if let Bson::Document(update_elem_doc) = update_elem_bson {
         for (key, value) in update_elem_doc {
-            update_doc.insert(format!("{}.$[elem].{}", S::ARR_NAME, key), value);
+            set_doc.insert(format!("{}.$[elem].{}", S::ARR_NAME, key), value);
         }
     }
 
+    let mut inc_doc = doc! { "version": 1 };
+    inc_doc.insert(format!("{}.$[elem].version", S::ARR_NAME), 1);
+
+    let update_doc = doc! { "$set": set_doc, "$inc": inc_doc };
     let array_filters = doc! { "elem._id": elem_oid };
 
-    match update_doc_ret_doc(
-        &coll,
+    // 원소의 version 조건은 array_filters가 아닌 문서 filter에 넣어야 매칭 실패를 알 수 있음.
+    let filter = doc! { "_id": oid, "user": user, "deletedAt": { "$exists": false } };
+    let mut find_filter = filter.clone();
+    if let Some(version) = if_match {
+        find_filter.insert(
+            S::ARR_NAME,
+            doc! { "$elemMatch": { "_id": elem_oid, "version": version_filter(version) } },
+        );
+    }
+
+    let result = update_doc_ret_doc(
+        db,
+        S::COLL_NAME,
         &oid,
         Some(array_filters),
         update_doc,
-        doc! { "_id": oid },
+        find_filter,
+        user,
     )
-    .await
-    {
+    .await;
+    let result = match result {
+        Err(NotFoundError(_)) if if_match.is_some() => {
+            let mut elem_filter = filter;
+            elem_filter.insert(S::ARR_NAME, doc! { "$elemMatch": { "_id": elem_oid } });
+            Err(not_found_or_stale(db, S::COLL_NAME, elem_id, elem_filter).await)
+        }
+        result => result,
+    };
+
+    match result {
         Ok(updated_doc) => {
             let array = updated_doc
                 .get_array(S::ARR_NAME)
@@ -303,3 +377,175 @@ where
         Err(e) => Err(e),
     }
 }
+
+/// 원소를 같은 배열 안의 new_index 위치로 옮김.
+/// new_index가 배열 길이 이상이면 맨 뒤로 옮겨짐.
+pub async fn move_elem<S>(
+    db: &dyn Storage,
+    src_id: &str,
+    elem_id: &str,
+    new_index: usize,
+    user: &Uuid,
+) -> Result<S::ElemRes>
+where
+    S: MongoArrayRepo,
+{
+    let oid = ObjectId::from_str(src_id).map_err(DBError::MongoGetOidError)?;
+    let elem_oid = ObjectId::from_str(elem_id).map_err(DBError::MongoGetOidError)?;
+
+    // 같은 배열에 $pull과 $push를 한번에 할 수 없으므로 transaction으로 묶음.
+    let mut tx = Transaction::start(db).await?;
+    let result = async {
+        let before = find_parent::<S>(db, &oid, user, tx.session()).await?;
+        let elem = take_elem::<S>(db, &oid, &elem_oid, user, tx.session()).await?;
+        put_elem::<S>(db, &oid, elem.clone(), new_index, user, tx.session()).await?;
+        record_moved::<S>(db, before, &oid, user, tx.session()).await?;
+        Ok(elem)
+    }
+    .await;
+    let elem = tx.finish(result).await?;
+
+    let elem: S::ElemModel =
+        bson::from_document(elem).map_err(DBError::MongoDeserializeBsonError)?;
+    S::convert_doc_to_response(&elem)
+}
+
+/// 원소를 src 문서의 배열에서 dst 문서의 같은 배열 맨 앞으로 옮김.
+pub async fn transfer_elem<S>(
+    db: &dyn Storage,
+    src_id: &str,
+    dst_id: &str,
+    elem_id: &str,
+    user: &Uuid,
+) -> Result<S::ElemRes>
+where
+    S: MongoArrayRepo,
+{
+    let src_oid = ObjectId::from_str(src_id).map_err(DBError::MongoGetOidError)?;
+    let dst_oid = ObjectId::from_str(dst_id).map_err(DBError::MongoGetOidError)?;
+    let elem_oid = ObjectId::from_str(elem_id).map_err(DBError::MongoGetOidError)?;
+
+    // transaction이 없는 서버에서도 원소를 잃지 않도록, 꺼내기 전에 두 문서의 소유를 확인함.
+    // 두 문서가 함께 바뀌거나, 둘 다 바뀌지 않도록 transaction으로 묶음.
+    let mut tx = Transaction::start(db).await?;
+    let result = async {
+        // transaction이 없는 서버에서도 원소를 잃지 않도록, 꺼내기 전에 두 문서의 소유를 확인함.
+        let src_before = find_parent::<S>(db, &src_oid, user, tx.session())
+            .await?
+            .ok_or(NotFoundError(src_oid.to_hex()))?;
+        let dst_before = find_parent::<S>(db, &dst_oid, user, tx.session())
+            .await?
+            .ok_or(NotFoundError(dst_oid.to_hex()))?;
+        let elem = take_elem::<S>(db, &src_oid, &elem_oid, user, tx.session()).await?;
+        put_elem::<S>(db, &dst_oid, elem.clone(), 0, user, tx.session()).await?;
+        record_moved::<S>(db, Some(src_before), &src_oid, user, tx.session()).await?;
+        record_moved::<S>(db, Some(dst_before), &dst_oid, user, tx.session()).await?;
+        Ok(elem)
+    }
+    .await;
+    let elem = tx.finish(result).await?;
+
+    let elem: S::ElemModel =
+        bson::from_document(elem).map_err(DBError::MongoDeserializeBsonError)?;
+    S::convert_doc_to_response(&elem)
+}
+
+// history 비교를 위해 문서 전체를 읽음.
+async fn find_parent<S: MongoArrayRepo>(
+    db: &dyn Storage,
+    oid: &ObjectId,
+    user: &Uuid,
+    session: Option<&mut ClientSession>,
+) -> Result<Option<Document>> {
+    let filter = doc! { "_id": oid, "user": user, "deletedAt": { "$exists": false } };
+    db.find_one(S::COLL_NAME, filter, None, session).await
+}
+
+// 원소를 옮긴 뒤, 옮기기 전 문서와 비교하여 history에 기록.
+async fn record_moved<S: MongoArrayRepo>(
+    db: &dyn Storage,
+    before: Option<Document>,
+    oid: &ObjectId,
+    user: &Uuid,
+    mut session: Option<&mut ClientSession>,
+) -> Result<()> {
+    let Some(before) = before else {
+        return Ok(());
+    };
+    match find_parent::<S>(db, oid, user, session.as_deref_mut()).await? {
+        Some(after) => {
+            history::record_update(db, S::COLL_NAME, &before, &after, user, session).await
+        }
+        None => Ok(()),
+    }
+}
+
+// 배열에서 원소를 꺼내고(pull), 꺼낸 원소를 반환.
+async fn take_elem<S: MongoArrayRepo>(
+    db: &dyn Storage,
+    oid: &ObjectId,
+    elem_oid: &ObjectId,
+    user: &Uuid,
+    mut session: Option<&mut ClientSession>,
+) -> Result<Document> {
+    let filter = doc! { "_id": oid, "user": user, "deletedAt": { "$exists": false } };
+    let projection = doc! { S::ARR_NAME: { "$elemMatch": { "_id": elem_oid } } };
+
+    let parent = match db
+        .find_one(
+            S::COLL_NAME,
+            filter.clone(),
+            Some(projection),
+            session.as_deref_mut(),
+        )
+        .await?
+    {
+        Some(doc) => doc,
+        None => return Err(NotFoundError(oid.to_hex())),
+    };
+
+    let elem = match parent
+        .get_array(S::ARR_NAME)
+        .ok()
+        .and_then(|array| array.first())
+        .and_then(Bson::as_document)
+    {
+        Some(elem) => elem.clone(),
+        None => return Err(NotFoundError(elem_oid.to_hex())),
+    };
+
+    let update_doc = doc! {
+        "$pull": { S::ARR_NAME: { "_id": elem_oid } },
+        "$set": { "updatedAt": Bson::DateTime(Utc::now().into()) },
+        "$inc": { "version": 1 },
+    };
+    db.update_one(S::COLL_NAME, filter, update_doc, session)
+        .await?;
+
+    Ok(elem)
+}
+
+// 배열의 index 위치에 원소를 넣음.
+async fn put_elem<S: MongoArrayRepo>(
+    db: &dyn Storage,
+    oid: &ObjectId,
+    elem: Document,
+    index: usize,
+    user: &Uuid,
+    session: Option<&mut ClientSession>,
+) -> Result<()> {
+    let filter = doc! { "_id": oid, "user": user, "deletedAt": { "$exists": false } };
+    let update_doc = doc! {
+        "$push": { S::ARR_NAME: { "$each": [elem], "$position": index as i64 } },
+        "$set": { "updatedAt": Bson::DateTime(Utc::now().into()) },
+        "$inc": { "version": 1 },
+    };
+
+    match db
+        .update_one(S::COLL_NAME, filter, update_doc, session)
+        .await?
+    {
+        0 => Err(NotFoundError(oid.to_hex())),
+        _ => Ok(()),
+    }
+}
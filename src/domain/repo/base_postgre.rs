@@ -1,35 +1,232 @@
 use crate::infra::db::error::Error as DBError;
-use sqlb::{Field, HasFields};
+use crate::infra::types::{Page, PageOptions, QueryOptions};
+
+use super::query::{FieldKind, ListQuery, QueryFields, Value};
+use super::utils::{decode_cursor, encode_cursor};
+use chrono::{DateTime, NaiveDate, Utc};
+use mongodb::bson::{self, Bson};
+use serde::Serialize;
+use sqlb::{Field, HasFields, Whereable};
 use sqlx::postgres::PgRow;
 use sqlx::{FromRow, Pool, Postgres};
+use std::str::FromStr;
 use uuid::Uuid;
 pub type Result<T> = core::result::Result<T, DBError>;
 
 pub trait PostgreRepo {
     const TABLE: &'static str;
-    type Entity: HasFields + for<'r> FromRow<'r, PgRow> + Unpin + Send;
+    // list의 ?filter=&sort=에 쓸 수 있는 column
+    const QUERY_FIELDS: QueryFields = &[];
+    type Entity: HasFields + for<'r> FromRow<'r, PgRow> + Serialize + Unpin + Send;
     type Res;
     fn convert_entity_to_response(entity: &Self::Entity) -> Self::Res;
+    fn entity_id(entity: &Self::Entity) -> Uuid;
 }
 
-pub async fn fetch<S>(db: &Pool<Postgres>, user: &Uuid) -> Result<Vec<S::Res>>
+// (sort key들, id) 기준 keyset pagination. cursor가 없으면 page 방식.
+// sqlb는 조건을 AND로만 묶을 수 있으므로, cursor 이후의 목록은
+// "앞의 sort key들은 cursor와 같고 다음 key가 뒤"인 구간들로 나누어 순서대로 가져옴.
+pub async fn fetch<S>(
+    db: &Pool<Postgres>,
+    user: &Uuid,
+    page_opts: &PageOptions,
+    query_opts: &QueryOptions,
+) -> Result<Page<S::Res>>
 where
     S: PostgreRepo,
 {
-    let entities: Vec<S::Entity> = sqlb::select()
-        .table(S::TABLE)
-        .columns(S::Entity::field_names())
-        .and_where("user", "=", user)
-        .order_by("id")
-        .fetch_all(db)
-        .await?;
+    let query = ListQuery::parse(query_opts, S::QUERY_FIELDS)?;
+    let after = match &page_opts.cursor {
+        Some(cursor) => Some(
+            decode_keyset(cursor, &query, S::QUERY_FIELDS)
+                .ok_or(DBError::InvalidCursor(cursor.to_string()))?,
+        ),
+        None => None,
+    };
+
+    let total = if page_opts.with_total {
+        let count = sqlb::select()
+            .table(S::TABLE)
+            .columns(&["COUNT(*)"])
+            .and_where("user", "=", user);
+        let (count,) = where_query(count, &query)?
+            .fetch_one::<_, (i64,)>(db)
+            .await?;
+        Some(count as u64)
+    } else {
+        None
+    };
+
+    let mut order_bys: Vec<String> = query
+        .sort
+        .iter()
+        .map(|key| match key.desc {
+            true => format!("!{}", key.field),
+            false => key.field.to_string(),
+        })
+        .collect();
+    order_bys.push("id".to_string());
+    let order_bys: Vec<&str> = order_bys.iter().map(String::as_str).collect();
+
+    // 다음 페이지 존재 여부를 알기 위해 하나 더 가져옴.
+    let fetch_limit = page_opts.limit + 1;
+    let mut entities: Vec<S::Entity> = Vec::new();
+    match after {
+        Some((keys, id)) => {
+            // sort key가 모두 같은 구간(id만 뒤)부터 첫 key가 뒤인 구간까지
+            for equal in (0..=keys.len()).rev() {
+                let select = sqlb::select()
+                    .table(S::TABLE)
+                    .columns(S::Entity::field_names())
+                    .and_where("user", "=", user)
+                    .order_bys(&order_bys);
+                let mut select = where_query(select, &query)?;
+                for (key, value) in query.sort.iter().zip(&keys).take(equal) {
+                    select = where_value(select, key.field, "=", value)
+                        .ok_or_else(|| unsupported(key.field))?;
+                }
+                select = match (query.sort.get(equal), keys.get(equal)) {
+                    (Some(key), Some(value)) => {
+                        let op = if key.desc { "<" } else { ">" };
+                        where_value(select, key.field, op, value)
+                            .ok_or_else(|| unsupported(key.field))?
+                    }
+                    _ => select.and_where("id", ">", id),
+                };
+                if page_opts.limit > 0 {
+                    select = select.limit(fetch_limit - entities.len() as i64);
+                }
+
+                entities.extend(select.fetch_all::<_, S::Entity>(db).await?);
+                if page_opts.limit > 0 && entities.len() as i64 >= fetch_limit {
+                    break;
+                }
+            }
+        }
+        None => {
+            let select = sqlb::select()
+                .table(S::TABLE)
+                .columns(S::Entity::field_names())
+                .and_where("user", "=", user)
+                .order_bys(&order_bys);
+            let mut select = where_query(select, &query)?;
+            if page_opts.limit > 0 {
+                select = select
+                    .offset((page_opts.page - 1).max(0) * page_opts.limit)
+                    .limit(fetch_limit);
+            }
+            entities = select.fetch_all(db).await?;
+        }
+    }
+
+    let has_more = page_opts.limit > 0 && entities.len() as i64 > page_opts.limit;
+    if has_more {
+        entities.truncate(page_opts.limit as usize);
+    }
+
+    let next_cursor = match entities.last() {
+        Some(last) if has_more => encode_keyset(last, S::entity_id(last), &query),
+        _ => None,
+    };
 
     let mut results = Vec::new();
     for entity in entities {
         results.push(S::convert_entity_to_response(&entity));
     }
 
-    Ok(results)
+    Ok(Page {
+        items: results,
+        next_cursor,
+        has_more,
+        total,
+    })
+}
+
+// cursor에는 entity의 sort key 값들을 문자열로 담음(timestamptz의 정밀도를 잃지 않도록)
+fn encode_keyset<E: Serialize>(entity: &E, id: Uuid, query: &ListQuery) -> Option<String> {
+    let entity = bson::to_document(entity).ok()?;
+    let keys = query
+        .sort
+        .iter()
+        .map(|key| match entity.get(key.field) {
+            Some(Bson::String(s)) => Bson::String(s.clone()),
+            Some(value) => Bson::String(value.to_string()),
+            None => Bson::Null,
+        })
+        .collect();
+    Some(encode_cursor(
+        Bson::Array(keys),
+        Bson::String(id.to_string()),
+    ))
+}
+
+// encode_keyset으로 만든 cursor를 sort key 값들과 id로 복원. 잘못된 cursor면 None.
+fn decode_keyset(
+    cursor: &str,
+    query: &ListQuery,
+    fields: QueryFields,
+) -> Option<(Vec<Value>, Uuid)> {
+    let (Bson::Array(keys), Bson::String(id)) = decode_cursor(cursor)? else {
+        return None;
+    };
+    if keys.len() != query.sort.len() {
+        return None;
+    }
+
+    let mut values = Vec::new();
+    for (key, raw) in query.sort.iter().zip(keys) {
+        let (_, kind) = fields.iter().find(|(field, _)| *field == key.field)?;
+        let value = match (kind, raw) {
+            (FieldKind::Str, Bson::String(raw)) => Value::Str(raw),
+            (kind, Bson::String(raw)) => Value::parse(*kind, &raw)?,
+            _ => return None,
+        };
+        values.push(value);
+    }
+    Some((values, Uuid::parse_str(&id).ok()?))
+}
+
+// ?filter=의 조건들을 where에 추가.
+// sqlb는 바인딩한 값 하나씩 AND로만 묶을 수 있으므로 null과 여러 값(a|b)은 지원하지 않음.
+fn where_query<'a, W: Whereable<'a>>(mut builder: W, query: &ListQuery) -> Result<W> {
+    for cond in &query.conditions {
+        let [value] = cond.values.as_slice() else {
+            return Err(unsupported(cond.field));
+        };
+        builder = where_value(builder, cond.field, cond.op.sql(), value)
+            .ok_or_else(|| unsupported(cond.field))?;
+    }
+    Ok(builder)
+}
+
+// `field op value` 조건 하나를 where에 추가. 지원하지 않는 값이면 None.
+fn where_value<'a, W: Whereable<'a>>(
+    builder: W,
+    field: &str,
+    op: &'static str,
+    value: &Value,
+) -> Option<W> {
+    let builder = match value {
+        Value::Str(s) => builder.and_where(field, op, s.clone()),
+        Value::Int(n) => builder.and_where(field, op, *n),
+        Value::Float(n) => builder.and_where(field, op, *n),
+        Value::Bool(b) => builder.and_where(field, op, *b),
+        Value::Date(s) => match NaiveDate::from_str(s) {
+            Ok(date) => builder.and_where(field, op, date),
+            Err(_) => {
+                let dt = DateTime::parse_from_rfc3339(s).ok()?;
+                builder.and_where(field, op, dt.with_timezone(&Utc))
+            }
+        },
+        Value::DateTime(dt) => builder.and_where(field, op, *dt),
+        Value::Uuid(uuid) => builder.and_where(field, op, *uuid),
+        Value::Null | Value::ObjectId(_) => return None,
+    };
+    Some(builder)
+}
+
+fn unsupported(field: &str) -> DBError {
+    DBError::InvalidQuery(format!("unsupported filter on {}", field))
 }
 
 pub async fn create<S, Shcema>(db: &Pool<Postgres>, user: &Uuid, body: Shcema) -> Result<S::Res>
@@ -81,7 +278,8 @@ where
     S: PostgreRepo,
     Schema: HasFields,
 {
-    let fields = body.not_none_fields();
+    let mut fields = body.not_none_fields();
+    fields.push(Field::from(("updatedAt", Utc::now())));
     let count = sqlb::update()
         .table(S::TABLE)
         .and_where("id", "=", id)
@@ -100,13 +298,14 @@ where
     }
 }
 
-pub async fn delete<S>(db: &Pool<Postgres>, id: Uuid) -> Result<()>
+pub async fn delete<S>(db: &Pool<Postgres>, user: &Uuid, id: Uuid) -> Result<()>
 where
     S: PostgreRepo,
 {
     let count = sqlb::delete()
         .table(S::TABLE)
         .and_where("id", "=", id)
+        .and_where("user", "=", user)
         .exec(db)
         .await?;
 
@@ -120,3 +319,44 @@ where
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::tag::{TagModel, TagService};
+
+    #[test]
+    fn test_keyset_cursor() {
+        let query_opts = QueryOptions {
+            sort: Some("-createdAt,name".to_string()),
+            ..Default::default()
+        };
+        let query = ListQuery::parse(&query_opts, TagService::QUERY_FIELDS).unwrap();
+        let created_at = DateTime::parse_from_rfc3339("2024-03-04T09:00:00.123456Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let tag = TagModel {
+            id: Uuid::new_v4(),
+            user: Uuid::new_v4(),
+            name: "null".to_string(),
+            createdAt: created_at,
+            updatedAt: created_at,
+        };
+
+        // microsecond까지 그대로 복원되고, 문자열 "null"은 null로 바뀌지 않음
+        let cursor = encode_keyset(&tag, tag.id, &query).unwrap();
+        let (keys, id) = decode_keyset(&cursor, &query, TagService::QUERY_FIELDS).unwrap();
+        assert_eq!(
+            keys,
+            vec![Value::DateTime(created_at), Value::Str("null".to_string())]
+        );
+        assert_eq!(id, tag.id);
+
+        // sort가 다르면 쓸 수 없음
+        let other = ListQuery::parse(&QueryOptions::default(), TagService::QUERY_FIELDS).unwrap();
+        assert_eq!(
+            decode_keyset(&cursor, &other, TagService::QUERY_FIELDS),
+            None
+        );
+    }
+}
@@ -0,0 +1,440 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::{self, doc, oid::ObjectId, Bson, Document};
+use mongodb::ClientSession;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use uuid::Uuid;
+
+use crate::domain::error::{Error::*, Result};
+use crate::infra::db::error::Error as DBError;
+use crate::infra::types::{Page, PageOptions};
+
+use super::index::{IndexDef, IndexKind::*};
+use super::storage::{FindOpts, Storage};
+use super::utils::{decode_cursor, doc_version, encode_cursor};
+
+pub const COLL_NAME: &str = "changes";
+pub const INDEXES: &[IndexDef] = &[IndexDef {
+    keys: &[
+        ("user", Asc),
+        ("coll", Asc),
+        ("entity_id", Asc),
+        ("_id", Desc),
+    ],
+    ..IndexDef::DEFAULT
+}];
+
+// 매 update마다 바뀌는 관리용 필드는 기록하지 않음.
+const IGNORED_FIELDS: &[&str] = &[
+    "_id",
+    "user",
+    "version",
+    "createdAt",
+    "updatedAt",
+    "deletedAt",
+    "deletedWith",
+];
+
+/// 필드 하나의 변경 내용. 값이 없던(혹은 제거된) 쪽은 None.
+/// 배열 원소가 _id를 가진 문서면 원소 단위로 기록하며,
+/// position은 변경 전 배열에서의 위치(추가된 원소는 변경 후 위치).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    pub field: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elem_id: Option<ObjectId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Bson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Bson>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChangeModel {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    #[serde(with = "bson::serde_helpers::uuid_1_as_binary")]
+    pub actor: Uuid,
+    #[serde(with = "bson::serde_helpers::uuid_1_as_binary")]
+    pub user: Uuid,
+    pub coll: String,
+    pub entity_id: ObjectId,
+    // 변경 후 문서의 version
+    pub version: i64,
+    pub diff: Vec<FieldDiff>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub createdAt: DateTime<Utc>,
+}
+
+// region:    --- Record
+
+/// 업데이트 전후 문서를 비교하여 바뀐 필드를 기록.
+/// update와 같은 session에서 기록하여, 기록이 실패하면 update도 함께 되돌릴 수 있게 함.
+pub async fn record_update(
+    db: &dyn Storage,
+    coll: &str,
+    before: &Document,
+    after: &Document,
+    user: &Uuid,
+    session: Option<&mut ClientSession>,
+) -> Result<()> {
+    let Ok(entity_id) = after.get_object_id("_id") else {
+        return Ok(());
+    };
+    let diff = diff(before, after);
+    if diff.is_empty() {
+        return Ok(());
+    }
+
+    record(
+        db,
+        coll,
+        &entity_id,
+        doc_version(after),
+        diff,
+        user,
+        session,
+    )
+    .await
+}
+
+pub async fn record(
+    db: &dyn Storage,
+    coll: &str,
+    entity_id: &ObjectId,
+    version: i64,
+    diff: Vec<FieldDiff>,
+    user: &Uuid,
+    session: Option<&mut ClientSession>,
+) -> Result<()> {
+    let change = ChangeModel {
+        id: ObjectId::new(),
+        actor: *user,
+        user: *user,
+        coll: coll.to_string(),
+        entity_id: *entity_id,
+        version,
+        diff,
+        createdAt: Utc::now(),
+    };
+    let doc = bson::to_document(&change).map_err(DBError::MongoSerializeBsonError)?;
+    db.insert_one(COLL_NAME, doc, session).await?;
+
+    Ok(())
+}
+
+// endregion: --- Record
+
+// region:    --- Diff
+
+/// 두 문서의 최상위 필드를 비교.
+pub fn diff(before: &Document, after: &Document) -> Vec<FieldDiff> {
+    let fields = before
+        .keys()
+        .chain(after.keys().filter(|k| !before.contains_key(*k)))
+        .filter(|k| !IGNORED_FIELDS.contains(&k.as_str()));
+
+    let mut diffs = Vec::new();
+    for field in fields {
+        let (b, a) = (before.get(field), after.get(field));
+        if b == a {
+            continue;
+        }
+
+        if let (Some(Bson::Array(b)), Some(Bson::Array(a))) = (b, a) {
+            if let Some(elem_diffs) = diff_elems(field, b, a) {
+                diffs.extend(elem_diffs);
+                continue;
+            }
+        }
+
+        diffs.push(FieldDiff {
+            field: field.to_string(),
+            elem_id: None,
+            position: None,
+            before: b.cloned(),
+            after: a.cloned(),
+        });
+    }
+
+    diffs
+}
+
+// 원소가 모두 _id를 가진 문서이고, 남아있는 원소들의 순서가 그대로일 때만 원소 단위로 비교.
+// 그렇지 않으면(순서 변경 등) None을 반환하여 필드 전체를 기록하게 함.
+fn diff_elems(field: &str, before: &[Bson], after: &[Bson]) -> Option<Vec<FieldDiff>> {
+    let before = elems_with_id(before)?;
+    let after = elems_with_id(after)?;
+
+    let before_ids: HashSet<ObjectId> = before.iter().map(|(id, _)| *id).collect();
+    let after_ids: HashSet<ObjectId> = after.iter().map(|(id, _)| *id).collect();
+    let kept = |elems: &[(ObjectId, &Document)], other: &HashSet<ObjectId>| -> Vec<ObjectId> {
+        elems
+            .iter()
+            .map(|(id, _)| *id)
+            .filter(|id| other.contains(id))
+            .collect()
+    };
+    if kept(&before, &after_ids) != kept(&after, &before_ids) {
+        return None;
+    }
+
+    let elem_diff =
+        |id: ObjectId, position: usize, b: Option<&Document>, a: Option<&Document>| FieldDiff {
+            field: field.to_string(),
+            elem_id: Some(id),
+            position: i64::try_from(position).ok(),
+            before: b.cloned().map(Bson::Document),
+            after: a.cloned().map(Bson::Document),
+        };
+
+    let mut diffs = Vec::new();
+    for (position, (id, b)) in before.iter().enumerate() {
+        match after.iter().find(|(a_id, _)| a_id == id) {
+            Some((_, a)) if a == b => {}
+            found => diffs.push(elem_diff(*id, position, Some(b), found.map(|(_, a)| *a))),
+        }
+    }
+    for (position, (id, a)) in after.iter().enumerate() {
+        if !before_ids.contains(id) {
+            diffs.push(elem_diff(*id, position, None, Some(a)));
+        }
+    }
+
+    Some(diffs)
+}
+
+fn elems_with_id(array: &[Bson]) -> Option<Vec<(ObjectId, &Document)>> {
+    array
+        .iter()
+        .map(|elem| {
+            let doc = elem.as_document()?;
+            Some((doc.get_object_id("_id").ok()?, doc))
+        })
+        .collect()
+}
+
+/// 문서에 적용된 change 하나를 되돌림.
+pub fn undo(state: &mut Document, change: &ChangeModel) {
+    let mut elem_fields: Vec<&str> = Vec::new();
+    for diff in &change.diff {
+        match (&diff.elem_id, &diff.before) {
+            (Some(_), _) => {
+                if !elem_fields.contains(&diff.field.as_str()) {
+                    elem_fields.push(&diff.field);
+                }
+            }
+            (None, Some(before)) => {
+                state.insert(diff.field.clone(), before.clone());
+            }
+            (None, None) => {
+                state.remove(&diff.field);
+            }
+        }
+    }
+
+    // 바뀐 원소를 모두 빼낸 뒤, 이전 값을 원래 위치에 앞에서부터 다시 넣음.
+    for field in elem_fields {
+        let diffs: Vec<&FieldDiff> = change
+            .diff
+            .iter()
+            .filter(|d| d.field == field && d.elem_id.is_some())
+            .collect();
+        let ids: Vec<Bson> = diffs
+            .iter()
+            .filter_map(|d| d.elem_id.map(Bson::ObjectId))
+            .collect();
+
+        let mut array: Vec<Bson> = state
+            .get_array(field)
+            .map(|array| {
+                array
+                    .iter()
+                    .filter(|elem| {
+                        elem.as_document()
+                            .and_then(|doc| doc.get("_id"))
+                            .is_none_or(|id| !ids.contains(id))
+                    })
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut restored: Vec<(i64, &Bson)> = diffs
+            .iter()
+            .filter_map(|d| Some((d.position.unwrap_or_default(), d.before.as_ref()?)))
+            .collect();
+        restored.sort_by_key(|(position, _)| *position);
+        for (position, elem) in restored {
+            let index = usize::try_from(position)
+                .unwrap_or_default()
+                .min(array.len());
+            array.insert(index, elem.clone());
+        }
+
+        state.insert(field, array);
+    }
+}
+
+// endregion: --- Diff
+
+// region:    --- Query
+
+/// 문서의 change 목록(최신순). cursor는 마지막으로 받은 change의 _id를 가리킴.
+pub async fn fetch(
+    db: &dyn Storage,
+    coll: &str,
+    entity_id: &ObjectId,
+    page_opts: &PageOptions,
+    user: &Uuid,
+) -> Result<Page<ChangeModel>> {
+    let mut filter = doc! { "user": user, "coll": coll, "entity_id": entity_id };
+
+    let total = match page_opts.with_total {
+        true => Some(db.count(COLL_NAME, filter.clone(), None).await?),
+        false => None,
+    };
+
+    let skip = match &page_opts.cursor {
+        Some(cursor) => {
+            let (_, id) = decode_cursor(cursor).ok_or(DBError::InvalidCursor(cursor.clone()))?;
+            filter.insert("_id", doc! { "$lt": id });
+            0
+        }
+        None => (page_opts.page - 1).max(0) * page_opts.limit,
+    };
+
+    let limit = page_opts.limit;
+    let find_options = FindOpts {
+        sort: Some(doc! { "_id": -1 }),
+        skip: Some(u64::try_from(skip).unwrap_or_default()),
+        limit: Some(if limit > 0 { limit + 1 } else { 0 }),
+        ..FindOpts::default()
+    };
+    let mut docs = db.find(COLL_NAME, filter, find_options, None).await?;
+
+    let has_more = limit > 0 && docs.len() as i64 > limit;
+    if has_more {
+        docs.truncate(limit as usize);
+    }
+
+    let mut changes: Vec<ChangeModel> = Vec::new();
+    for doc in docs {
+        changes.push(bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?);
+    }
+    let next_cursor = match changes.last() {
+        Some(last) if has_more => Some(encode_cursor(Bson::Null, Bson::ObjectId(last.id))),
+        _ => None,
+    };
+
+    Ok(Page {
+        items: changes,
+        next_cursor,
+        has_more,
+        total,
+    })
+}
+
+/// revision(change id) 이후의 change들(최신순).
+/// revision이 해당 문서의 change가 아니면 NotFoundError.
+pub async fn changes_after(
+    db: &dyn Storage,
+    coll: &str,
+    entity_id: &ObjectId,
+    revision: &ObjectId,
+    user: &Uuid,
+) -> Result<Vec<ChangeModel>> {
+    let filter = doc! { "user": user, "coll": coll, "entity_id": entity_id };
+
+    let mut revision_filter = filter.clone();
+    revision_filter.insert("_id", revision);
+    if db.count(COLL_NAME, revision_filter, None).await? == 0 {
+        return Err(NotFoundError(revision.to_hex()));
+    }
+
+    let mut after_filter = filter;
+    after_filter.insert("_id", doc! { "$gt": revision });
+    let find_options = FindOpts {
+        sort: Some(doc! { "_id": -1 }),
+        ..FindOpts::default()
+    };
+
+    let mut changes = Vec::new();
+    for doc in db.find(COLL_NAME, after_filter, find_options, None).await? {
+        changes.push(bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?);
+    }
+    Ok(changes)
+}
+
+// endregion: --- Query
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(diff: Vec<FieldDiff>) -> ChangeModel {
+        ChangeModel {
+            id: ObjectId::new(),
+            actor: Uuid::nil(),
+            user: Uuid::nil(),
+            coll: "tasks".to_string(),
+            entity_id: ObjectId::new(),
+            version: 2,
+            diff,
+            createdAt: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_diff_and_undo_fields() {
+        let before = doc! { "_id": 1, "title": "a", "color": "red", "version": 1 };
+        let after = doc! { "_id": 1, "title": "b", "content": "c", "version": 2 };
+
+        let diff = diff(&before, &after);
+        let fields: Vec<&str> = diff.iter().map(|d| d.field.as_str()).collect();
+        assert_eq!(fields, vec!["title", "color", "content"]);
+
+        let mut state = after.clone();
+        undo(&mut state, &change(diff));
+        state.insert("version", 1);
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn test_diff_and_undo_elems() {
+        let (e1, e2, e3) = (ObjectId::new(), ObjectId::new(), ObjectId::new());
+        let before = doc! { "chat_msgs": [
+            { "_id": e1, "msg": "one" },
+            { "_id": e2, "msg": "two" },
+            { "_id": e3, "msg": "three" },
+        ] };
+        let e4 = ObjectId::new();
+        let after = doc! { "chat_msgs": [
+            { "_id": e4, "msg": "four" },
+            { "_id": e1, "msg": "one" },
+            { "_id": e3, "msg": "THREE" },
+        ] };
+
+        let diff = diff(&before, &after);
+        assert_eq!(diff.len(), 3);
+        assert!(diff.iter().all(|d| d.elem_id.is_some()));
+
+        let mut state = after.clone();
+        undo(&mut state, &change(diff));
+        assert_eq!(state, before);
+
+        // 순서가 바뀌면 필드 전체를 기록
+        let moved = doc! { "chat_msgs": [
+            { "_id": e2, "msg": "two" },
+            { "_id": e1, "msg": "one" },
+            { "_id": e3, "msg": "three" },
+        ] };
+        let diff = super::diff(&before, &moved);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].elem_id, None);
+    }
+}
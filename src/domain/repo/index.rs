@@ -0,0 +1,261 @@
+use std::time::Duration;
+
+use futures::TryStreamExt;
+use mongodb::bson::{Bson, Document};
+use mongodb::error::ErrorKind;
+use mongodb::options::IndexOptions;
+use mongodb::{Database, IndexModel};
+
+use crate::domain::error::Result;
+use crate::infra::db::error::Error as DBError;
+
+use super::base::MongoRepo;
+use super::base_array::MongoArrayRepo;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndexKind {
+    Asc,
+    Desc,
+    Text,
+    Geo2dSphere,
+}
+
+impl IndexKind {
+    fn to_bson(self) -> Bson {
+        match self {
+            IndexKind::Asc => Bson::Int32(1),
+            IndexKind::Desc => Bson::Int32(-1),
+            IndexKind::Text => Bson::String("text".to_string()),
+            IndexKind::Geo2dSphere => Bson::String("2dsphere".to_string()),
+        }
+    }
+}
+
+/// 컬렉션에 선언하는 인덱스.
+/// name이 없으면 mongodb 기본 규칙(`field_1_field_-1`)으로 이름을 만듦.
+/// partial_filter가 있으면 (field, 값)이 모두 일치하는 문서만 인덱스에 포함됨(partial index).
+#[derive(Debug, Clone, Copy)]
+pub struct IndexDef {
+    pub name: Option<&'static str>,
+    pub keys: &'static [(&'static str, IndexKind)],
+    pub unique: bool,
+    pub ttl_secs: Option<u64>,
+    pub partial_filter: &'static [(&'static str, &'static str)],
+}
+
+impl IndexDef {
+    pub const DEFAULT: IndexDef = IndexDef {
+        name: None,
+        keys: &[],
+        unique: false,
+        ttl_secs: None,
+        partial_filter: &[],
+    };
+}
+
+/// 한 컬렉션에 적용될 인덱스 목록(배열 원소 인덱스는 배열 이름이 앞에 붙음)
+#[derive(Debug, Clone)]
+pub struct CollIndexes {
+    pub coll_name: &'static str,
+    pub indexes: Vec<IndexSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexSpec {
+    pub name: String,
+    pub keys: Document,
+    pub unique: bool,
+    pub ttl_secs: Option<u64>,
+    pub partial_filter: Option<Document>,
+}
+
+impl IndexSpec {
+    fn from_def(def: &IndexDef, prefix: Option<&str>) -> Self {
+        let mut keys = Document::new();
+        for (field, kind) in def.keys {
+            let path = match prefix {
+                Some(prefix) => format!("{}.{}", prefix, field),
+                None => field.to_string(),
+            };
+            keys.insert(path, kind.to_bson());
+        }
+
+        let name = match def.name {
+            Some(name) => name.to_string(),
+            None => keys
+                .iter()
+                .map(|(k, v)| match v {
+                    Bson::String(s) => format!("{}_{}", k, s),
+                    v => format!("{}_{}", k, v),
+                })
+                .collect::<Vec<_>>()
+                .join("_"),
+        };
+
+        let partial_filter = (!def.partial_filter.is_empty()).then(|| {
+            def.partial_filter
+                .iter()
+                .map(|(field, value)| (field.to_string(), Bson::String(value.to_string())))
+                .collect::<Document>()
+        });
+
+        IndexSpec {
+            name,
+            keys,
+            unique: def.unique,
+            ttl_secs: def.ttl_secs,
+            partial_filter,
+        }
+    }
+
+    fn to_model(&self) -> IndexModel {
+        let options = IndexOptions::builder()
+            .name(self.name.clone())
+            .unique(self.unique)
+            .expire_after(self.ttl_secs.map(Duration::from_secs))
+            .partial_filter_expression(self.partial_filter.clone())
+            .build();
+
+        IndexModel::builder()
+            .keys(self.keys.clone())
+            .options(options)
+            .build()
+    }
+
+    fn is_text(&self) -> bool {
+        self.keys
+            .values()
+            .any(|v| matches!(v, Bson::String(s) if s == "text"))
+    }
+
+    // 서버에 있는 인덱스와 선언이 같은지 비교
+    fn matches(&self, existing: &IndexModel) -> bool {
+        let opts = existing.options.as_ref();
+        let unique = opts.and_then(|o| o.unique).unwrap_or(false);
+        let ttl = opts.and_then(|o| o.expire_after).map(|d| d.as_secs());
+        let partial_filter = opts.and_then(|o| o.partial_filter_expression.as_ref());
+
+        // text 인덱스는 서버에서 _fts/_ftsx 키로 바뀌므로 키 비교는 생략
+        let same_keys = self.is_text() || same_keys(&self.keys, &existing.keys);
+
+        same_keys
+            && unique == self.unique
+            && ttl == self.ttl_secs
+            && partial_filter == self.partial_filter.as_ref()
+    }
+}
+
+// 숫자 타입(i32/i64/f64)이 달라도 같은 방향이면 같은 키로 봄.
+fn same_keys(declared: &Document, existing: &Document) -> bool {
+    let normalize = |v: &Bson| match v {
+        Bson::Int32(n) => Bson::Double(*n as f64),
+        Bson::Int64(n) => Bson::Double(*n as f64),
+        v => v.clone(),
+    };
+
+    declared.len() == existing.len()
+        && declared
+            .iter()
+            .zip(existing.iter())
+            .all(|((dk, dv), (ek, ev))| dk == ek && normalize(dv) == normalize(ev))
+}
+
+pub fn coll_indexes<S: MongoRepo>() -> CollIndexes {
+    indexes_of(S::COLL_NAME, S::INDEXES)
+}
+
+/// MongoRepo가 없는 컬렉션(예: changes)의 인덱스 선언.
+pub fn indexes_of(coll_name: &'static str, defs: &[IndexDef]) -> CollIndexes {
+    CollIndexes {
+        coll_name,
+        indexes: defs
+            .iter()
+            .map(|def| IndexSpec::from_def(def, None))
+            .collect(),
+    }
+}
+
+pub fn elem_indexes<S: MongoArrayRepo>() -> CollIndexes {
+    CollIndexes {
+        coll_name: S::COLL_NAME,
+        indexes: S::ELEM_INDEXES
+            .iter()
+            .map(|def| IndexSpec::from_def(def, Some(S::ARR_NAME)))
+            .collect(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexDrift {
+    // 선언되었지만 없어서 새로 만든 인덱스
+    Created { coll: String, name: String },
+    // 같은 이름이지만 키/옵션이 다른 인덱스(자동으로 지우지 않음)
+    Mismatched { coll: String, name: String },
+    // 서버에만 있는 인덱스(자동으로 지우지 않음)
+    Undeclared { coll: String, name: String },
+}
+
+/// 선언된 인덱스와 실제 인덱스를 맞춤.
+/// 없는 인덱스는 만들고, 다르거나 선언되지 않은 인덱스는 drift로 보고만 함.
+pub async fn sync_indexes(db: &Database, registry: Vec<CollIndexes>) -> Result<Vec<IndexDrift>> {
+    // 같은 컬렉션에 대한 선언(예: tasks와 tasks.chat_msgs)을 합침
+    let mut merged: Vec<CollIndexes> = Vec::new();
+    for coll in registry {
+        match merged.iter_mut().find(|c| c.coll_name == coll.coll_name) {
+            Some(existing) => existing.indexes.extend(coll.indexes),
+            None => merged.push(coll),
+        }
+    }
+
+    let mut drifts = Vec::new();
+    for coll_indexes in merged {
+        let coll = db.collection::<Document>(coll_indexes.coll_name);
+
+        let existing: Vec<IndexModel> = match coll.list_indexes(None).await {
+            Ok(cursor) => cursor.try_collect().await.map_err(DBError::MongoError)?,
+            // 컬렉션이 아직 없으면(NamespaceNotFound) 인덱스도 없음
+            Err(e) if matches!(e.kind.as_ref(), ErrorKind::Command(c) if c.code == 26) => {
+                Vec::new()
+            }
+            Err(e) => return Err(DBError::MongoError(e).into()),
+        };
+        let existing_name = |model: &IndexModel| {
+            model
+                .options
+                .as_ref()
+                .and_then(|o| o.name.clone())
+                .unwrap_or_default()
+        };
+
+        for spec in &coll_indexes.indexes {
+            match existing.iter().find(|m| existing_name(m) == spec.name) {
+                Some(model) if spec.matches(model) => {}
+                Some(_) => drifts.push(IndexDrift::Mismatched {
+                    coll: coll_indexes.coll_name.to_string(),
+                    name: spec.name.clone(),
+                }),
+                None => {
+                    coll.create_index(spec.to_model(), None)
+                        .await
+                        .map_err(DBError::MongoError)?;
+                    drifts.push(IndexDrift::Created {
+                        coll: coll_indexes.coll_name.to_string(),
+                        name: spec.name.clone(),
+                    });
+                }
+            }
+        }
+
+        for model in &existing {
+            let name = existing_name(model);
+            if name != "_id_" && !coll_indexes.indexes.iter().any(|s| s.name == name) {
+                drifts.push(IndexDrift::Undeclared {
+                    coll: coll_indexes.coll_name.to_string(),
+                    name,
+                });
+            }
+        }
+    }
+
+    Ok(drifts)
+}
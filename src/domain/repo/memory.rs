@@ -0,0 +1,1165 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use async_trait::async_trait;
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use mongodb::ClientSession;
+
+use crate::domain::error::{Error, Error::*, Result};
+use crate::infra::db::error::Error as DBError;
+
+use super::storage::{FindOpts, GraphLookup, Storage};
+
+/// Mongo 없이 동작하는 테스트용 저장소.
+/// 이 repo에서 사용하는 filter/update/projection 연산자만 지원하며,
+/// 인덱스(unique 포함)와 transaction은 없음(각 작업이 바로 반영됨).
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    colls: Mutex<HashMap<String, Vec<Document>>>,
+}
+
+impl MemoryStorage {
+    fn colls(&self) -> MutexGuard<'_, HashMap<String, Vec<Document>>> {
+        // 다른 테스트가 panic하더라도 데이터는 그대로 사용
+        self.colls.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn find(
+        &self,
+        coll: &str,
+        filter: Document,
+        opts: FindOpts,
+        _session: Option<&mut ClientSession>,
+    ) -> Result<Vec<Document>> {
+        let colls = self.colls();
+        let mut docs = Vec::new();
+        for doc in colls.get(coll).into_iter().flatten() {
+            if matches(doc, &filter).map_err(storage_error)? {
+                docs.push(doc.clone());
+            }
+        }
+
+        if let Some(sort) = &opts.sort {
+            docs.sort_by(|a, b| compare_by(a, b, sort));
+        }
+
+        let skip = opts.skip.unwrap_or_default() as usize;
+        let docs = docs.into_iter().skip(skip);
+        let docs: Vec<Document> = match opts.limit.unwrap_or_default() {
+            0 => docs.collect(),
+            limit => docs.take(limit.unsigned_abs() as usize).collect(),
+        };
+
+        match &opts.projection {
+            Some(projection) => docs
+                .iter()
+                .map(|doc| project(doc, projection))
+                .collect::<QueryResult<_>>()
+                .map_err(storage_error),
+            None => Ok(docs),
+        }
+    }
+
+    async fn find_one(
+        &self,
+        coll: &str,
+        filter: Document,
+        projection: Option<Document>,
+        _session: Option<&mut ClientSession>,
+    ) -> Result<Option<Document>> {
+        let colls = self.colls();
+        for doc in colls.get(coll).into_iter().flatten() {
+            if matches(doc, &filter).map_err(storage_error)? {
+                return match &projection {
+                    Some(projection) => project(doc, projection).map(Some).map_err(storage_error),
+                    None => Ok(Some(doc.clone())),
+                };
+            }
+        }
+        Ok(None)
+    }
+
+    async fn count(
+        &self,
+        coll: &str,
+        filter: Document,
+        _session: Option<&mut ClientSession>,
+    ) -> Result<u64> {
+        let colls = self.colls();
+        let mut count = 0;
+        for doc in colls.get(coll).into_iter().flatten() {
+            if matches(doc, &filter).map_err(storage_error)? {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    async fn graph_lookup(&self, coll: &str, graph: GraphLookup) -> Result<Vec<Document>> {
+        let colls = self.colls();
+        let docs: &[Document] = colls.get(coll).map(Vec::as_slice).unwrap_or_default();
+
+        let mut frontier: Vec<Bson> = Vec::new();
+        for doc in docs {
+            if matches(doc, &graph.start).map_err(storage_error)? {
+                let values = lookup(doc, graph.connect_from);
+                frontier.extend(expand(&values).into_iter().cloned());
+            }
+        }
+
+        // 같은 문서는 처음(가장 가까운 depth에서) 찾은 것만 남김
+        let mut found: Vec<Document> = Vec::new();
+        let mut depth = 0i64;
+        while !frontier.is_empty() {
+            let mut next = Vec::new();
+            for doc in docs {
+                let connected = eq_any_of(&lookup(doc, graph.connect_to), &frontier);
+                if !connected
+                    || found.iter().any(|f| f.get("_id") == doc.get("_id"))
+                    || !matches(doc, &graph.restrict).map_err(storage_error)?
+                {
+                    continue;
+                }
+                let values = lookup(doc, graph.connect_from);
+                next.extend(expand(&values).into_iter().cloned());
+                let mut doc = doc.clone();
+                doc.insert("depth", depth);
+                found.push(doc);
+            }
+            frontier = next;
+            depth += 1;
+        }
+
+        match graph.projection {
+            Some(mut projection) => {
+                projection.insert("depth", 1);
+                found
+                    .iter()
+                    .map(|doc| project(doc, &projection))
+                    .collect::<QueryResult<_>>()
+                    .map_err(storage_error)
+            }
+            None => Ok(found),
+        }
+    }
+
+    async fn insert_one(
+        &self,
+        coll: &str,
+        doc: Document,
+        _session: Option<&mut ClientSession>,
+    ) -> Result<Bson> {
+        let mut colls = self.colls();
+        insert(colls.entry(coll.to_string()).or_default(), coll, doc)
+            .map_err(|message| MongoDuplicateError(mongodb::error::Error::custom(message)))
+    }
+
+    async fn insert_many(
+        &self,
+        coll: &str,
+        docs: Vec<Document>,
+        _session: Option<&mut ClientSession>,
+    ) -> Result<Vec<(usize, String)>> {
+        let mut colls = self.colls();
+        let stored = colls.entry(coll.to_string()).or_default();
+
+        let mut failed = Vec::new();
+        for (index, doc) in docs.into_iter().enumerate() {
+            if let Err(e) = insert(stored, coll, doc) {
+                failed.push((index, e));
+            }
+        }
+        Ok(failed)
+    }
+
+    async fn find_one_and_update(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        array_filters: Option<Vec<Document>>,
+        _session: Option<&mut ClientSession>,
+    ) -> Result<Option<Document>> {
+        let mut colls = self.colls();
+        for doc in colls.entry(coll.to_string()).or_default() {
+            if matches(doc, &filter).map_err(storage_error)? {
+                apply_update(doc, &update, array_filters.as_deref().unwrap_or_default())
+                    .map_err(storage_error)?;
+                return Ok(Some(doc.clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn update_one(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        _session: Option<&mut ClientSession>,
+    ) -> Result<u64> {
+        let mut colls = self.colls();
+        for doc in colls.entry(coll.to_string()).or_default() {
+            if matches(doc, &filter).map_err(storage_error)? {
+                apply_update(doc, &update, &[]).map_err(storage_error)?;
+                return Ok(1);
+            }
+        }
+        Ok(0)
+    }
+
+    async fn upsert_one(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        _session: Option<&mut ClientSession>,
+    ) -> Result<()> {
+        let mut colls = self.colls();
+        let stored = colls.entry(coll.to_string()).or_default();
+        for doc in stored.iter_mut() {
+            if matches(doc, &filter).map_err(storage_error)? {
+                return apply_update(doc, &update, &[]).map_err(storage_error);
+            }
+        }
+
+        // filter 중 값이 바로 주어진 필드만 새 문서에 들어감
+        let mut doc: Document = filter
+            .into_iter()
+            .filter(|(key, value)| match value {
+                Bson::Document(value) => !is_operator_doc(value),
+                _ => !key.starts_with('$'),
+            })
+            .collect();
+        if let Ok(on_insert) = update.get_document("$setOnInsert") {
+            apply_update(&mut doc, &doc! { "$set": on_insert }, &[]).map_err(storage_error)?;
+        }
+        apply_update(&mut doc, &update, &[]).map_err(storage_error)?;
+        insert(stored, coll, doc)
+            .map(|_| ())
+            .map_err(|message| MongoDuplicateError(mongodb::error::Error::custom(message)))
+    }
+
+    async fn update_many(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        _session: Option<&mut ClientSession>,
+    ) -> Result<u64> {
+        let mut colls = self.colls();
+        let mut modified = 0;
+        for doc in colls.entry(coll.to_string()).or_default() {
+            if matches(doc, &filter).map_err(storage_error)? {
+                let before = doc.clone();
+                apply_update(doc, &update, &[]).map_err(storage_error)?;
+                if *doc != before {
+                    modified += 1;
+                }
+            }
+        }
+        Ok(modified)
+    }
+
+    async fn delete_many(&self, coll: &str, filter: Document) -> Result<u64> {
+        let mut colls = self.colls();
+        let stored = colls.entry(coll.to_string()).or_default();
+
+        let before = stored.len();
+        let mut result = Ok(());
+        stored.retain(|doc| match matches(doc, &filter) {
+            Ok(matched) => !matched,
+            Err(e) => {
+                result = Err(e);
+                true
+            }
+        });
+        result.map_err(storage_error)?;
+
+        Ok((before - stored.len()) as u64)
+    }
+
+    async fn start_transaction(&self) -> Result<Option<ClientSession>> {
+        Ok(None)
+    }
+}
+
+// 내부 함수들은 message만 반환하고, Storage 메서드에서 Error로 변환함.
+type QueryResult<T> = std::result::Result<T, String>;
+
+fn unsupported(what: &str) -> String {
+    format!("unsupported operator: {}", what)
+}
+
+fn storage_error(message: String) -> Error {
+    DB(DBError::MemoryStorageError(message))
+}
+
+// _id가 없으면 만들어 맨 앞에 넣음.
+fn insert(stored: &mut Vec<Document>, coll: &str, doc: Document) -> QueryResult<Bson> {
+    let id = doc
+        .get("_id")
+        .cloned()
+        .unwrap_or_else(|| Bson::ObjectId(ObjectId::new()));
+
+    if stored.iter().any(|d| d.get("_id") == Some(&id)) {
+        return Err(format!(
+            "E11000 duplicate key error collection: {} _id: {}",
+            coll, id
+        ));
+    }
+
+    let mut new_doc = doc! { "_id": id.clone() };
+    new_doc.extend(doc);
+    stored.push(new_doc);
+    Ok(id)
+}
+
+// region:    --- Filter
+
+fn matches(doc: &Document, filter: &Document) -> QueryResult<bool> {
+    for (key, cond) in filter {
+        let matched = match key.as_str() {
+            "$and" => {
+                let mut all = true;
+                for sub in sub_filters(key, cond)? {
+                    all = all && matches(doc, sub)?;
+                }
+                all
+            }
+            "$or" | "$nor" => {
+                let mut any = false;
+                for sub in sub_filters(key, cond)? {
+                    any = any || matches(doc, sub)?;
+                }
+                if key == "$or" {
+                    any
+                } else {
+                    !any
+                }
+            }
+            op if op.starts_with('$') => return Err(unsupported(op)),
+            path => match_field(&lookup(doc, path), cond)?,
+        };
+
+        if !matched {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn sub_filters<'a>(op: &str, cond: &'a Bson) -> QueryResult<Vec<&'a Document>> {
+    match cond {
+        Bson::Array(filters) => filters
+            .iter()
+            .map(|f| f.as_document().ok_or_else(|| unsupported(op)))
+            .collect(),
+        _ => Err(unsupported(op)),
+    }
+}
+
+// 경로의 값들. 중간에 배열이 있으면 각 원소에서 찾음.
+fn lookup<'a>(doc: &'a Document, path: &str) -> Vec<&'a Bson> {
+    let segments: Vec<&str> = path.split('.').collect();
+    let mut values = Vec::new();
+    if let Some(value) = doc.get(segments[0]) {
+        collect_values(value, &segments[1..], &mut values);
+    }
+    values
+}
+
+fn collect_values<'a>(value: &'a Bson, path: &[&str], values: &mut Vec<&'a Bson>) {
+    let Some((head, rest)) = path.split_first() else {
+        values.push(value);
+        return;
+    };
+
+    match value {
+        Bson::Document(doc) => {
+            if let Some(value) = doc.get(*head) {
+                collect_values(value, rest, values);
+            }
+        }
+        Bson::Array(array) => match head.parse::<usize>() {
+            Ok(index) => {
+                if let Some(value) = array.get(index) {
+                    collect_values(value, rest, values);
+                }
+            }
+            Err(_) => {
+                for elem in array.iter().filter(|elem| elem.as_document().is_some()) {
+                    collect_values(elem, path, values);
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+fn is_operator_doc(doc: &Document) -> bool {
+    doc.keys().next().is_some_and(|key| key.starts_with('$'))
+}
+
+fn match_field(values: &[&Bson], cond: &Bson) -> QueryResult<bool> {
+    match cond {
+        Bson::Document(ops) if is_operator_doc(ops) => {
+            for (op, arg) in ops {
+                if !match_op(values, op, arg)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        cond => Ok(eq_any(values, cond)),
+    }
+}
+
+fn match_op(values: &[&Bson], op: &str, arg: &Bson) -> QueryResult<bool> {
+    let matched = match op {
+        "$eq" => eq_any(values, arg),
+        "$ne" => !eq_any(values, arg),
+        "$gt" | "$gte" | "$lt" | "$lte" => expand(values).into_iter().any(|value| {
+            rank(value) == rank(arg)
+                && match compare(value, arg) {
+                    Ordering::Greater => matches!(op, "$gt" | "$gte"),
+                    Ordering::Equal => matches!(op, "$gte" | "$lte"),
+                    Ordering::Less => matches!(op, "$lt" | "$lte"),
+                }
+        }),
+        "$in" | "$nin" => {
+            let Bson::Array(targets) = arg else {
+                return Err(unsupported(op));
+            };
+            let found = targets.iter().any(|target| eq_any(values, target));
+            if op == "$in" {
+                found
+            } else {
+                !found
+            }
+        }
+        "$exists" => truthy(arg) != values.is_empty(),
+        "$all" => {
+            let Bson::Array(targets) = arg else {
+                return Err(unsupported(op));
+            };
+            targets.iter().all(|target| eq_any(values, target))
+        }
+        "$size" => values.iter().any(|value| match value {
+            Bson::Array(array) => as_i64(arg) == Some(array.len() as i64),
+            _ => false,
+        }),
+        "$elemMatch" => {
+            let Bson::Document(sub) = arg else {
+                return Err(unsupported(op));
+            };
+            let mut found = false;
+            for value in values {
+                let Bson::Array(array) = value else {
+                    continue;
+                };
+                for elem in array {
+                    let matched = match elem {
+                        _ if is_operator_doc(sub) => match_field(&[elem], arg)?,
+                        Bson::Document(elem) => matches(elem, sub)?,
+                        _ => false,
+                    };
+                    if matched {
+                        found = true;
+                        break;
+                    }
+                }
+            }
+            found
+        }
+        "$not" => !match_field(values, arg)?,
+        op => return Err(unsupported(op)),
+    };
+    Ok(matched)
+}
+
+// 값이 배열이면 배열 자체와 원소들을 모두 비교 대상으로 봄.
+fn expand<'a>(values: &[&'a Bson]) -> Vec<&'a Bson> {
+    let mut expanded = Vec::new();
+    for value in values {
+        expanded.push(*value);
+        if let Bson::Array(array) = value {
+            expanded.extend(array.iter());
+        }
+    }
+    expanded
+}
+
+// values 중 하나라도 targets 중 하나와 같음(null은 연결하지 않음)
+fn eq_any_of(values: &[&Bson], targets: &[Bson]) -> bool {
+    targets
+        .iter()
+        .filter(|target| !matches!(target, Bson::Null))
+        .any(|target| !values.is_empty() && eq_any(values, target))
+}
+
+// null은 필드가 없는 경우와도 같음.
+fn eq_any(values: &[&Bson], target: &Bson) -> bool {
+    if values.is_empty() {
+        return matches!(target, Bson::Null);
+    }
+    expand(values)
+        .into_iter()
+        .any(|value| bson_eq(value, target))
+}
+
+fn truthy(value: &Bson) -> bool {
+    match value {
+        Bson::Boolean(b) => *b,
+        Bson::Int32(n) => *n != 0,
+        Bson::Int64(n) => *n != 0,
+        Bson::Double(n) => *n != 0.0,
+        _ => false,
+    }
+}
+
+fn as_i64(value: &Bson) -> Option<i64> {
+    match value {
+        Bson::Int32(n) => Some(*n as i64),
+        Bson::Int64(n) => Some(*n),
+        Bson::Double(n) => Some(*n as i64),
+        _ => None,
+    }
+}
+
+fn as_f64(value: &Bson) -> Option<f64> {
+    match value {
+        Bson::Int32(n) => Some(*n as f64),
+        Bson::Int64(n) => Some(*n as f64),
+        Bson::Double(n) => Some(*n),
+        _ => None,
+    }
+}
+
+// endregion: --- Filter
+
+// region:    --- Compare
+
+// mongodb의 타입 정렬 순서
+fn rank(value: &Bson) -> u8 {
+    match value {
+        Bson::MinKey => 0,
+        Bson::Null | Bson::Undefined => 1,
+        Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_) | Bson::Decimal128(_) => 2,
+        Bson::String(_) | Bson::Symbol(_) => 3,
+        Bson::Document(_) => 4,
+        Bson::Array(_) => 5,
+        Bson::Binary(_) => 6,
+        Bson::ObjectId(_) => 7,
+        Bson::Boolean(_) => 8,
+        Bson::DateTime(_) => 9,
+        Bson::Timestamp(_) => 10,
+        Bson::RegularExpression(_) => 11,
+        Bson::MaxKey => 13,
+        _ => 12,
+    }
+}
+
+fn compare(a: &Bson, b: &Bson) -> Ordering {
+    let by_rank = rank(a).cmp(&rank(b));
+    if by_rank != Ordering::Equal {
+        return by_rank;
+    }
+
+    match (a, b) {
+        (Bson::String(a), Bson::String(b)) => a.cmp(b),
+        (Bson::Document(a), Bson::Document(b)) => {
+            for ((ak, av), (bk, bv)) in a.iter().zip(b.iter()) {
+                let ord = ak.cmp(bk).then_with(|| compare(av, bv));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            a.len().cmp(&b.len())
+        }
+        (Bson::Array(a), Bson::Array(b)) => {
+            for (av, bv) in a.iter().zip(b.iter()) {
+                let ord = compare(av, bv);
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            a.len().cmp(&b.len())
+        }
+        (Bson::Binary(a), Bson::Binary(b)) => a.bytes.cmp(&b.bytes),
+        (Bson::ObjectId(a), Bson::ObjectId(b)) => a.bytes().cmp(&b.bytes()),
+        (Bson::Boolean(a), Bson::Boolean(b)) => a.cmp(b),
+        (Bson::DateTime(a), Bson::DateTime(b)) => a.cmp(b),
+        (Bson::Timestamp(a), Bson::Timestamp(b)) => {
+            (a.time, a.increment).cmp(&(b.time, b.increment))
+        }
+        (a, b) => match (as_f64(a), as_f64(b)) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            _ if a == b => Ordering::Equal,
+            _ => a.to_string().cmp(&b.to_string()),
+        },
+    }
+}
+
+fn bson_eq(a: &Bson, b: &Bson) -> bool {
+    compare(a, b) == Ordering::Equal
+}
+
+fn compare_by(a: &Document, b: &Document, sort: &Document) -> Ordering {
+    for (key, direction) in sort {
+        let av = lookup(a, key).first().copied().unwrap_or(&Bson::Null);
+        let bv = lookup(b, key).first().copied().unwrap_or(&Bson::Null);
+        let ord = compare(av, bv);
+        let ord = match as_i64(direction) {
+            Some(d) if d < 0 => ord.reverse(),
+            _ => ord,
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    Ordering::Equal
+}
+
+// endregion: --- Compare
+
+// region:    --- Update
+
+type Modifier<'a> = dyn FnMut(&mut Document, &str) -> QueryResult<()> + 'a;
+
+fn apply_update(
+    doc: &mut Document,
+    update: &Document,
+    array_filters: &[Document],
+) -> QueryResult<()> {
+    if !is_operator_doc(update) {
+        return Err(unsupported("replacement update"));
+    }
+
+    for (op, fields) in update {
+        let Bson::Document(fields) = fields else {
+            return Err(unsupported(op));
+        };
+
+        for (path, value) in fields {
+            let path: Vec<&str> = path.split('.').collect();
+            match op.as_str() {
+                "$set" => modify(doc, &path, array_filters, true, &mut |parent, key| {
+                    parent.insert(key, value.clone());
+                    Ok(())
+                })?,
+                "$unset" => modify(doc, &path, array_filters, false, &mut |parent, key| {
+                    parent.remove(key);
+                    Ok(())
+                })?,
+                "$inc" => modify(doc, &path, array_filters, true, &mut |parent, key| {
+                    let next = match parent.get(key) {
+                        Some(current) => add_numbers(current, value)?,
+                        None => value.clone(),
+                    };
+                    parent.insert(key, next);
+                    Ok(())
+                })?,
+                "$push" | "$addToSet" => {
+                    modify(doc, &path, array_filters, true, &mut |parent, key| {
+                        push(parent, key, value, op == "$addToSet")
+                    })?
+                }
+                "$pull" => modify(doc, &path, array_filters, false, &mut |parent, key| {
+                    let Some(Bson::Array(array)) = parent.get_mut(key) else {
+                        return Ok(());
+                    };
+                    let mut kept = Vec::new();
+                    for elem in array.drain(..) {
+                        if !pull_matches(&elem, value)? {
+                            kept.push(elem);
+                        }
+                    }
+                    *array = kept;
+                    Ok(())
+                })?,
+                // 새로 만들어지는 문서에만 적용됨(upsert_one)
+                "$setOnInsert" => {}
+                op => return Err(unsupported(op)),
+            }
+        }
+    }
+    Ok(())
+}
+
+// path의 마지막 key를 가진 document를 찾아 modifier를 적용.
+// create가 true면 중간 경로가 없을 때 만듦.
+fn modify(
+    doc: &mut Document,
+    path: &[&str],
+    array_filters: &[Document],
+    create: bool,
+    modifier: &mut Modifier,
+) -> QueryResult<()> {
+    let Some((head, rest)) = path.split_first() else {
+        return Ok(());
+    };
+    if rest.is_empty() {
+        return modifier(doc, head);
+    }
+
+    if !doc.contains_key(*head) {
+        if !create {
+            return Ok(());
+        }
+        doc.insert(*head, Document::new());
+    }
+
+    match doc.get_mut(*head) {
+        Some(Bson::Document(child)) => modify(child, rest, array_filters, create, modifier),
+        Some(Bson::Array(array)) => modify_elems(array, rest, array_filters, create, modifier),
+        _ => Err(unsupported(&path.join("."))),
+    }
+}
+
+fn modify_elems(
+    array: &mut [Bson],
+    path: &[&str],
+    array_filters: &[Document],
+    create: bool,
+    modifier: &mut Modifier,
+) -> QueryResult<()> {
+    let Some((head, rest)) = path.split_first() else {
+        return Ok(());
+    };
+    if rest.is_empty() {
+        return Err(unsupported(head));
+    }
+
+    let mut targets: Vec<&mut Document> = Vec::new();
+    match *head {
+        "$[]" => targets.extend(array.iter_mut().filter_map(Bson::as_document_mut)),
+        ident if ident.starts_with("$[") && ident.ends_with(']') => {
+            let ident = &ident[2..ident.len() - 1];
+            let filters: Vec<&Document> = array_filters
+                .iter()
+                .filter(|f| {
+                    f.keys()
+                        .any(|k| k == ident || k.starts_with(&format!("{}.", ident)))
+                })
+                .collect();
+            if filters.is_empty() {
+                return Err(unsupported(&format!("no array filter for {}", ident)));
+            }
+
+            for elem in array.iter_mut() {
+                let wrapped = doc! { ident: elem.clone() };
+                let mut matched = true;
+                for filter in &filters {
+                    matched = matched && matches(&wrapped, filter)?;
+                }
+                if let (true, Some(elem)) = (matched, elem.as_document_mut()) {
+                    targets.push(elem);
+                }
+            }
+        }
+        index => match index.parse::<usize>() {
+            Ok(index) => targets.extend(array.get_mut(index).and_then(Bson::as_document_mut)),
+            Err(_) => return Err(unsupported(index)),
+        },
+    }
+
+    for target in targets {
+        modify(target, rest, array_filters, create, modifier)?;
+    }
+    Ok(())
+}
+
+fn add_numbers(a: &Bson, b: &Bson) -> QueryResult<Bson> {
+    let added = match (a, b) {
+        (Bson::Int32(a), Bson::Int32(b)) => match a.checked_add(*b) {
+            Some(n) => Bson::Int32(n),
+            None => Bson::Int64(*a as i64 + *b as i64),
+        },
+        (Bson::Double(_), _) | (_, Bson::Double(_)) => match (as_f64(a), as_f64(b)) {
+            (Some(a), Some(b)) => Bson::Double(a + b),
+            _ => return Err(unsupported("$inc on non-numeric field")),
+        },
+        _ => match (as_i64(a), as_i64(b)) {
+            (Some(a), Some(b)) => Bson::Int64(a + b),
+            _ => return Err(unsupported("$inc on non-numeric field")),
+        },
+    };
+    Ok(added)
+}
+
+// $each, $position, $slice를 지원.
+fn push(parent: &mut Document, key: &str, value: &Bson, unique: bool) -> QueryResult<()> {
+    let (items, position, slice) = match value {
+        Bson::Document(modifiers) if modifiers.contains_key("$each") => {
+            let Ok(items) = modifiers.get_array("$each") else {
+                return Err(unsupported("$each"));
+            };
+            (
+                items.clone(),
+                modifiers.get("$position").and_then(as_i64),
+                modifiers.get("$slice").and_then(as_i64),
+            )
+        }
+        value => (vec![value.clone()], None, None),
+    };
+
+    if !parent.contains_key(key) {
+        parent.insert(key, Bson::Array(Vec::new()));
+    }
+    let Some(Bson::Array(array)) = parent.get_mut(key) else {
+        return Err(unsupported("$push on non-array field"));
+    };
+
+    let items: Vec<Bson> = match unique {
+        true => items
+            .into_iter()
+            .filter(|item| !array.iter().any(|elem| bson_eq(elem, item)))
+            .collect(),
+        false => items,
+    };
+
+    let len = array.len() as i64;
+    let at = match position {
+        Some(p) if p < 0 => (len + p).max(0),
+        Some(p) => p.min(len),
+        None => len,
+    } as usize;
+    array.splice(at..at, items);
+
+    match slice {
+        Some(n) if n >= 0 => array.truncate(n as usize),
+        Some(n) => {
+            let keep = n.unsigned_abs() as usize;
+            if array.len() > keep {
+                array.drain(..array.len() - keep);
+            }
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+fn pull_matches(elem: &Bson, cond: &Bson) -> QueryResult<bool> {
+    match (elem, cond) {
+        (_, Bson::Document(ops)) if is_operator_doc(ops) => match_field(&[elem], cond),
+        (Bson::Document(elem), Bson::Document(cond)) => matches(elem, cond),
+        (elem, cond) => Ok(bson_eq(elem, cond)),
+    }
+}
+
+// endregion: --- Update
+
+// region:    --- Projection
+
+fn project(doc: &Document, projection: &Document) -> QueryResult<Document> {
+    if projection.is_empty() {
+        return Ok(doc.clone());
+    }
+
+    // {_id: 1}만 있는 경우도 _id만 남기는 inclusion
+    let only_id = projection.len() == 1 && projection.get("_id").is_some_and(truthy);
+    let is_inclusion = only_id
+        || projection.iter().any(|(key, spec)| match spec {
+            Bson::Document(spec) => spec.contains_key("$elemMatch"),
+            spec => key != "_id" && truthy(spec),
+        });
+
+    if !is_inclusion {
+        let mut projected = doc.clone();
+        for (path, spec) in projection {
+            match spec {
+                Bson::Document(spec) if spec.contains_key("$slice") => {
+                    slice_field(&mut projected, path, spec)?
+                }
+                _ => remove_path(&mut projected, path),
+            }
+        }
+        return Ok(projected);
+    }
+
+    let mut projected = Document::new();
+    if projection.get("_id").is_none_or(truthy) {
+        if let Some(id) = doc.get("_id") {
+            projected.insert("_id", id.clone());
+        }
+    }
+
+    for (path, spec) in projection {
+        match spec {
+            _ if path == "_id" => {}
+            Bson::Document(spec) if spec.contains_key("$elemMatch") => {
+                let Ok(sub) = spec.get_document("$elemMatch") else {
+                    return Err(unsupported("$elemMatch"));
+                };
+                let Ok(array) = doc.get_array(path) else {
+                    continue;
+                };
+                for elem in array {
+                    if let Some(elem_doc) = elem.as_document() {
+                        if matches(elem_doc, sub)? {
+                            projected.insert(path, vec![elem.clone()]);
+                            break;
+                        }
+                    }
+                }
+            }
+            Bson::Document(spec) if spec.contains_key("$slice") => {
+                if let Some(value) = doc.get(path) {
+                    projected.insert(path, value.clone());
+                    slice_field(&mut projected, path, spec)?;
+                }
+            }
+            spec if truthy(spec) => include_path(doc, &mut projected, path),
+            _ => {}
+        }
+    }
+    Ok(projected)
+}
+
+fn include_path(doc: &Document, projected: &mut Document, path: &str) {
+    match path.split_once('.') {
+        None => {
+            if let Some(value) = doc.get(path) {
+                projected.insert(path, value.clone());
+            }
+        }
+        Some((head, rest)) => match doc.get(head) {
+            Some(Bson::Document(child)) => {
+                if !matches!(projected.get(head), Some(Bson::Document(_))) {
+                    projected.insert(head, Document::new());
+                }
+                if let Ok(sub) = projected.get_document_mut(head) {
+                    include_path(child, sub, rest);
+                }
+            }
+            Some(value) => {
+                projected.insert(head, value.clone());
+            }
+            None => {}
+        },
+    }
+}
+
+fn remove_path(doc: &mut Document, path: &str) {
+    match path.split_once('.') {
+        None => {
+            doc.remove(path);
+        }
+        Some((head, rest)) => {
+            if let Ok(child) = doc.get_document_mut(head) {
+                remove_path(child, rest);
+            }
+        }
+    }
+}
+
+// { $slice: n } 혹은 { $slice: [skip, n] }
+fn slice_field(doc: &mut Document, path: &str, spec: &Document) -> QueryResult<()> {
+    let Ok(array) = doc.get_array_mut(path) else {
+        return Ok(());
+    };
+
+    let (skip, n) = match spec.get("$slice") {
+        Some(Bson::Array(args)) if args.len() == 2 => match (as_i64(&args[0]), as_i64(&args[1])) {
+            (Some(skip), Some(n)) => (skip, n),
+            _ => return Err(unsupported("$slice")),
+        },
+        Some(n) => match as_i64(n) {
+            Some(n) if n < 0 => (n, -n),
+            Some(n) => (0, n),
+            None => return Err(unsupported("$slice")),
+        },
+        None => return Ok(()),
+    };
+
+    let len = array.len() as i64;
+    let start = match skip {
+        s if s < 0 => (len + s).max(0),
+        s => s.min(len),
+    } as usize;
+    let end = (start + n.max(0) as usize).min(array.len());
+    *array = array[start..end].to_vec();
+    Ok(())
+}
+
+// endregion: --- Projection
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeded(docs: Vec<Document>) -> MemoryStorage {
+        let storage = MemoryStorage::default();
+        storage.colls().insert("items".to_string(), docs);
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_filter_operators() {
+        let storage = seeded(vec![
+            doc! { "_id": 1, "n": 1, "tags": ["a", "b"], "deletedAt": 1 },
+            doc! { "_id": 2, "n": 2, "tags": ["b"] },
+            doc! { "_id": 3, "n": 3, "sub": { "x": Bson::Null } },
+        ]);
+
+        let count = |filter: Document| storage.count("items", filter, None);
+
+        assert_eq!(
+            count(doc! { "deletedAt": { "$exists": false } })
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(count(doc! { "tags": "b" }).await.unwrap(), 2);
+        assert_eq!(
+            count(doc! { "n": { "$gte": 2, "$lt": 3 } }).await.unwrap(),
+            1
+        );
+        assert_eq!(count(doc! { "n": { "$in": [1, 3] } }).await.unwrap(), 2);
+        assert_eq!(
+            count(doc! { "version": { "$in": [0, Bson::Null] } })
+                .await
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            count(doc! { "$or": [{ "n": 1 }, { "sub.x": Bson::Null, "n": 3 }] })
+                .await
+                .unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_projection() {
+        let doc = doc! { "_id": 1, "n": 1, "tags": ["a"] };
+
+        assert_eq!(project(&doc, &doc! { "_id": 1 }).unwrap(), doc! { "_id": 1 });
+        assert_eq!(
+            project(&doc, &doc! { "n": 1 }).unwrap(),
+            doc! { "_id": 1, "n": 1 }
+        );
+        assert_eq!(
+            project(&doc, &doc! { "tags": 0 }).unwrap(),
+            doc! { "_id": 1, "n": 1 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_array_updates() {
+        let storage = seeded(vec![doc! {
+            "_id": 1,
+            "arr": [{ "_id": 1, "v": 1 }, { "_id": 2, "v": 1 }],
+        }]);
+
+        let updated = storage
+            .find_one_and_update(
+                "items",
+                doc! { "_id": 1, "arr": { "$elemMatch": { "_id": 2 } } },
+                doc! {
+                    "$set": { "arr.$[elem].name": "two" },
+                    "$inc": { "arr.$[elem].v": 1, "version": 1 },
+                },
+                Some(vec![doc! { "elem._id": 2 }]),
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            updated.get_array("arr").unwrap()[1],
+            Bson::Document(doc! { "_id": 2, "v": 2, "name": "two" })
+        );
+        assert_eq!(updated.get_i32("version").unwrap(), 1);
+
+        storage
+            .update_one(
+                "items",
+                doc! { "_id": 1 },
+                doc! {
+                    "$pull": { "arr": { "_id": 1 } },
+                    "$push": { "arr": { "$each": [{ "_id": 3 }], "$position": 0, "$slice": 2 } },
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let found = storage
+            .find_one(
+                "items",
+                doc! { "_id": 1 },
+                Some(doc! { "arr": { "$elemMatch": { "_id": 2 } } }),
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            found,
+            doc! { "_id": 1, "arr": [{ "_id": 2, "v": 2, "name": "two" }] }
+        );
+
+        let ids: Vec<i32> = storage
+            .find_one("items", doc! {}, None, None)
+            .await
+            .unwrap()
+            .unwrap()
+            .get_array("arr")
+            .unwrap()
+            .iter()
+            .filter_map(|elem| elem.as_document()?.get_i32("_id").ok())
+            .collect();
+        assert_eq!(ids, vec![3, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_graph_lookup() {
+        // 1 <- 2 <- 3 <- 4(삭제됨), 1 <- 5
+        let storage = seeded(vec![
+            doc! { "_id": 1, "name": "a" },
+            doc! { "_id": 2, "parent": 1, "name": "b" },
+            doc! { "_id": 3, "parent": 2, "name": "c" },
+            doc! { "_id": 4, "parent": 3, "deletedAt": 1 },
+            doc! { "_id": 5, "parent": 1, "name": "e" },
+        ]);
+        let lookup = |start: Document, connect_from, connect_to| GraphLookup {
+            start,
+            connect_from,
+            connect_to,
+            restrict: doc! { "deletedAt": { "$exists": false } },
+            projection: Some(doc! { "_id": 1 }),
+        };
+
+        let found = storage
+            .graph_lookup("items", lookup(doc! { "_id": 1 }, "_id", "parent"))
+            .await
+            .unwrap();
+        let found: Vec<(i32, i64)> = found
+            .iter()
+            .map(|doc| (doc.get_i32("_id").unwrap(), doc.get_i64("depth").unwrap()))
+            .collect();
+        assert_eq!(found, vec![(2, 0), (5, 0), (3, 1)]);
+
+        // 여러 시작 문서에서 찾은 같은 문서는 한 번만
+        let found = storage
+            .graph_lookup(
+                "items",
+                lookup(doc! { "_id": { "$in": [3, 5] } }, "parent", "_id"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            found,
+            vec![
+                doc! { "_id": 1, "depth": 0_i64 },
+                doc! { "_id": 2, "depth": 0_i64 },
+            ]
+        );
+    }
+}
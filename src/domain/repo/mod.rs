@@ -1,12 +1,22 @@
 use serde::{de::DeserializeOwned, Serialize};
 
+use index::IndexDef;
+
 pub mod base;
 pub mod base_array;
+pub mod history;
+pub mod index;
+pub mod memory;
+pub mod query;
+pub mod storage;
+pub mod transaction;
 pub mod utils;
 pub mod base_postgre;
 
 pub trait ElemInfo {
     const ARR_NAME: &'static str;
+    // 배열 원소 기준 경로로 선언(ARR_NAME이 앞에 붙음)
+    const ELEM_INDEXES: &'static [IndexDef] = &[];
     type UpdateReq: Serialize;
     type CreateReq: Serialize;
     type Res: DeserializeOwned + Serialize + Unpin + Send + Sync;
@@ -0,0 +1,454 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::infra::db::error::Error as DBError;
+use crate::infra::types::QueryOptions;
+
+type Result<T> = core::result::Result<T, DBError>;
+
+/// ?filter=에 쓸 수 있는 필드의 저장 타입.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    Str,
+    Int,
+    Float,
+    Bool,
+    // ISO 8601 문자열로 저장된 날짜(NaiveDate, DateTime<Local>). 문자열로 비교함.
+    Date,
+    // bson datetime(postgres는 timestamptz)으로 저장된 시각
+    DateTime,
+    ObjectId,
+    Uuid,
+}
+
+/// repo마다 filter/sort를 허용하는 필드 목록.
+pub type QueryFields = &'static [(&'static str, FieldKind)];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl Op {
+    // 두 글자 연산자를 먼저 찾아야 함
+    const ALL: [(&'static str, Op); 6] = [
+        (">=", Op::Gte),
+        ("<=", Op::Lte),
+        ("!=", Op::Ne),
+        (">", Op::Gt),
+        ("<", Op::Lt),
+        ("=", Op::Eq),
+    ];
+
+    fn mongo(self) -> &'static str {
+        match self {
+            Op::Eq => "$eq",
+            Op::Ne => "$ne",
+            Op::Gt => "$gt",
+            Op::Gte => "$gte",
+            Op::Lt => "$lt",
+            Op::Lte => "$lte",
+        }
+    }
+
+    pub fn sql(self) -> &'static str {
+        match self {
+            Op::Eq => "=",
+            Op::Ne => "!=",
+            Op::Gt => ">",
+            Op::Gte => ">=",
+            Op::Lt => "<",
+            Op::Lte => "<=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Date(String),
+    DateTime(DateTime<Utc>),
+    ObjectId(ObjectId),
+    Uuid(Uuid),
+}
+
+impl Value {
+    pub fn parse(kind: FieldKind, raw: &str) -> Option<Self> {
+        if raw == "null" {
+            return Some(Value::Null);
+        }
+
+        match kind {
+            FieldKind::Str => Some(Value::Str(raw.to_string())),
+            FieldKind::Int => raw.parse().ok().map(Value::Int),
+            FieldKind::Float => raw.parse().ok().map(Value::Float),
+            FieldKind::Bool => raw.parse().ok().map(Value::Bool),
+            FieldKind::Date => (NaiveDate::from_str(raw).is_ok()
+                || DateTime::parse_from_rfc3339(raw).is_ok())
+            .then(|| Value::Date(raw.to_string())),
+            FieldKind::DateTime => parse_datetime(raw).map(Value::DateTime),
+            FieldKind::ObjectId => ObjectId::from_str(raw).ok().map(Value::ObjectId),
+            FieldKind::Uuid => Uuid::parse_str(raw).ok().map(Value::Uuid),
+        }
+    }
+
+    fn to_bson(&self) -> Bson {
+        match self {
+            Value::Null => Bson::Null,
+            Value::Str(s) | Value::Date(s) => Bson::String(s.clone()),
+            Value::Int(n) => Bson::Int64(*n),
+            Value::Float(n) => Bson::Double(*n),
+            Value::Bool(b) => Bson::Boolean(*b),
+            Value::DateTime(dt) => Bson::DateTime((*dt).into()),
+            Value::ObjectId(oid) => Bson::ObjectId(*oid),
+            Value::Uuid(uuid) => Bson::from(*uuid),
+        }
+    }
+}
+
+// 날짜만 주어지면 그날 0시(UTC)
+fn parse_datetime(raw: &str) -> Option<DateTime<Utc>> {
+    match DateTime::parse_from_rfc3339(raw) {
+        Ok(dt) => Some(dt.with_timezone(&Utc)),
+        Err(_) => NaiveDate::from_str(raw)
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|dt| dt.and_utc()),
+    }
+}
+
+/// `field op value` 하나. `=`/`!=`는 `a|b`로 여러 값을 줄 수 있음(in/nin).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub field: &'static str,
+    pub op: Op,
+    pub values: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SortKey {
+    pub field: &'static str,
+    pub desc: bool,
+}
+
+/// list endpoint의 `?filter=progress_rate>=50,milestone=true&sort=-due_at,title`을
+/// repo의 QueryFields로 검사한 결과. 허용되지 않은 필드나 타입이 맞지 않는 값은 InvalidQuery.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListQuery {
+    pub conditions: Vec<Condition>,
+    pub sort: Vec<SortKey>,
+}
+
+impl ListQuery {
+    pub fn parse(opts: &QueryOptions, fields: QueryFields) -> Result<Self> {
+        let mut query = ListQuery::default();
+
+        for clause in split(opts.filter.as_deref()) {
+            query.conditions.push(parse_condition(clause, fields)?);
+        }
+
+        for item in split(opts.sort.as_deref()) {
+            let (name, desc) = match item.strip_prefix('-') {
+                Some(name) => (name, true),
+                None => (item.strip_prefix('+').unwrap_or(item), false),
+            };
+            let (field, _) = find_field(name, fields)?;
+            if query.sort.iter().any(|key| key.field == field) {
+                return Err(DBError::InvalidQuery(format!(
+                    "duplicated sort field: {}",
+                    name
+                )));
+            }
+            query.sort.push(SortKey { field, desc });
+        }
+
+        Ok(query)
+    }
+
+    /// 조건들을 $and로 묶은 mongodb filter. 조건이 없으면 None.
+    pub fn to_filter(&self) -> Option<Document> {
+        if self.conditions.is_empty() {
+            return None;
+        }
+
+        let conditions: Vec<Document> = self
+            .conditions
+            .iter()
+            .map(|cond| {
+                let values: Vec<Bson> = cond.values.iter().map(Value::to_bson).collect();
+                let expr = match (cond.op, values.len()) {
+                    (Op::Eq, n) if n > 1 => doc! { "$in": values },
+                    (Op::Ne, n) if n > 1 => doc! { "$nin": values },
+                    (op, _) => doc! { op.mongo(): values[0].clone() },
+                };
+                doc! { cond.field: expr }
+            })
+            .collect();
+
+        Some(doc! { "$and": conditions })
+    }
+
+    /// mongodb sort. 정렬 조건이 없으면 None.
+    pub fn to_sort(&self) -> Option<Document> {
+        if self.sort.is_empty() {
+            return None;
+        }
+
+        let mut sort = Document::new();
+        for key in &self.sort {
+            sort.insert(key.field, if key.desc { -1 } else { 1 });
+        }
+        Some(sort)
+    }
+}
+
+fn split(raw: Option<&str>) -> impl Iterator<Item = &str> {
+    raw.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn find_field(name: &str, fields: QueryFields) -> Result<(&'static str, FieldKind)> {
+    fields
+        .iter()
+        .find(|(field, _)| *field == name)
+        .copied()
+        .ok_or(DBError::InvalidQuery(format!("unknown field: {}", name)))
+}
+
+fn parse_condition(clause: &str, fields: QueryFields) -> Result<Condition> {
+    let invalid = || DBError::InvalidQuery(format!("invalid filter: {}", clause));
+
+    let pos = clause.find(['=', '!', '<', '>']).ok_or_else(invalid)?;
+    let (name, rest) = clause.split_at(pos);
+    let (token, op) = Op::ALL
+        .iter()
+        .find(|(token, _)| rest.starts_with(token))
+        .copied()
+        .ok_or_else(invalid)?;
+    let (field, kind) = find_field(name.trim(), fields)?;
+
+    let raw = rest[token.len()..].trim();
+    let raws: Vec<&str> = match op {
+        Op::Eq | Op::Ne => raw.split('|').collect(),
+        _ => vec![raw],
+    };
+
+    let mut values = Vec::new();
+    for raw in raws {
+        match Value::parse(kind, raw) {
+            // 크기 비교에는 null을 쓸 수 없음
+            Some(Value::Null) if !matches!(op, Op::Eq | Op::Ne) => return Err(invalid()),
+            Some(value) => values.push(value),
+            None => {
+                return Err(DBError::InvalidQuery(format!(
+                    "invalid value for {}: {}",
+                    field, raw
+                )))
+            }
+        }
+    }
+
+    Ok(Condition { field, op, values })
+}
+
+/// ?fields=로 고를 수 있는 필드를 db에서 읽는 방식.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Load {
+    // Model에 필수인 필드. 요청되지 않아도 읽고 응답에서만 뺌.
+    Always,
+    // Model에서 Option이거나 #[serde(default)]인 필드. 요청되지 않으면 db에서 읽지 않음.
+    Lazy,
+    // db에 없고 응답을 만든 뒤 따로 채우는 필드(tags). projection에 넣지 않음.
+    Attached,
+}
+
+/// repo마다 ?fields=로 고를 수 있는 응답 필드 목록(id는 항상 포함).
+pub type SelectFields = &'static [(&'static str, Load)];
+
+/// `?fields=title,due_at`를 repo의 SelectFields로 검사한 결과.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSelection {
+    pub fields: Vec<&'static str>,
+}
+
+impl FieldSelection {
+    /// fields가 없거나 비어 있으면 None(전체 필드).
+    pub fn parse(raw: Option<&str>, allowed: SelectFields) -> Result<Option<Self>> {
+        if split(raw).next().is_none() {
+            return Ok(None);
+        }
+
+        let mut fields = Vec::new();
+        for name in split(raw) {
+            if name == "id" {
+                continue;
+            }
+            let (field, _) = allowed
+                .iter()
+                .find(|(field, _)| *field == name)
+                .copied()
+                .ok_or(DBError::InvalidQuery(format!("unknown field: {}", name)))?;
+            if !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+
+        Ok(Some(Self { fields }))
+    }
+
+    /// fields가 없거나(전체 필드) field를 포함하면 true.
+    /// Attached 필드를 채울지 정할 때 사용함.
+    pub fn requests(raw: Option<&str>, field: &str) -> bool {
+        split(raw).next().is_none() || split(raw).any(|name| name == field)
+    }
+
+    /// 요청되지 않은 Lazy 필드를 읽지 않도록 projection을 고침.
+    /// 포함(1) 방식이면 요청된 필드를 더하고 Lazy 필드를 빼며, 아니면 Lazy 필드를 제외(0)함.
+    pub fn projection(&self, allowed: SelectFields, mut proj_opts: Document) -> Document {
+        let inclusion = proj_opts.iter().any(|(_, v)| is_truthy(v));
+        for (field, load) in allowed {
+            let requested = self.fields.contains(field);
+            match (inclusion, requested, load) {
+                (_, _, Load::Attached) => {}
+                (true, true, _) => {
+                    proj_opts.insert(*field, 1);
+                }
+                (true, false, Load::Lazy) => {
+                    proj_opts.remove(*field);
+                }
+                (false, false, Load::Lazy) => {
+                    proj_opts.insert(*field, 0);
+                }
+                (false, true, _) => {
+                    proj_opts.remove(*field);
+                }
+                _ => {}
+            }
+        }
+        proj_opts
+    }
+}
+
+pub(crate) fn is_truthy(value: &Bson) -> bool {
+    match value {
+        Bson::Int32(n) => *n != 0,
+        Bson::Int64(n) => *n != 0,
+        Bson::Boolean(b) => *b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELDS: QueryFields = &[
+        ("title", FieldKind::Str),
+        ("progress_rate", FieldKind::Int),
+        ("milestone", FieldKind::Bool),
+        ("due_at", FieldKind::Date),
+    ];
+
+    fn opts(filter: &str, sort: &str) -> QueryOptions {
+        QueryOptions {
+            filter: Some(filter.to_string()),
+            sort: Some(sort.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query = ListQuery::parse(
+            &opts(
+                "progress_rate>=50,milestone=true,title=a|b",
+                "-due_at,title",
+            ),
+            FIELDS,
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.to_filter(),
+            Some(doc! { "$and": [
+                { "progress_rate": { "$gte": 50_i64 } },
+                { "milestone": { "$eq": true } },
+                { "title": { "$in": ["a", "b"] } },
+            ] })
+        );
+        assert_eq!(query.to_sort(), Some(doc! { "due_at": -1, "title": 1 }));
+    }
+
+    #[test]
+    fn test_reject_query() {
+        // whitelist에 없는 필드
+        let res = ListQuery::parse(&opts("user=x", ""), FIELDS);
+        assert!(matches!(res, Err(DBError::InvalidQuery(_))));
+
+        // 타입이 맞지 않는 값
+        let res = ListQuery::parse(&opts("progress_rate>=many", ""), FIELDS);
+        assert!(matches!(res, Err(DBError::InvalidQuery(_))));
+
+        // 크기 비교에 null
+        let res = ListQuery::parse(&opts("due_at<null", ""), FIELDS);
+        assert!(matches!(res, Err(DBError::InvalidQuery(_))));
+
+        let res = ListQuery::parse(&opts("", "-title,title"), FIELDS);
+        assert!(matches!(res, Err(DBError::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn test_field_selection() {
+        const SELECT: SelectFields = &[
+            ("title", Load::Always),
+            ("due_at", Load::Lazy),
+            ("chat_msgs", Load::Lazy),
+            ("tags", Load::Attached),
+        ];
+
+        assert_eq!(FieldSelection::parse(None, SELECT).unwrap(), None);
+        let res = FieldSelection::parse(Some("title,secret"), SELECT);
+        assert!(matches!(res, Err(DBError::InvalidQuery(_))));
+
+        let selection = FieldSelection::parse(Some("id,due_at"), SELECT)
+            .unwrap()
+            .unwrap();
+        assert_eq!(selection.fields, vec!["due_at"]);
+        assert!(FieldSelection::requests(None, "tags"));
+        assert!(!FieldSelection::requests(Some("id,due_at"), "tags"));
+
+        // 제외 방식: 요청되지 않은 Lazy 필드만 제외
+        assert_eq!(
+            selection.projection(SELECT, Document::new()),
+            doc! { "chat_msgs": 0 }
+        );
+        // 포함 방식: 요청된 필드를 더하고 Lazy 필드는 뺌
+        assert_eq!(
+            selection.projection(SELECT, doc! { "title": 1, "chat_msgs": 1 }),
+            doc! { "title": 1, "due_at": 1 }
+        );
+
+        // Attached 필드는 요청되어도 db에서 읽지 않음
+        let selection = FieldSelection::parse(Some("title,tags"), SELECT)
+            .unwrap()
+            .unwrap();
+        assert_eq!(selection.fields, vec!["title", "tags"]);
+        assert_eq!(
+            selection.projection(SELECT, doc! { "title": 1 }),
+            doc! { "title": 1 }
+        );
+    }
+}
@@ -0,0 +1,552 @@
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::error::{BulkWriteFailure, ErrorKind};
+use mongodb::options::{
+    FindOneAndUpdateOptions, FindOneOptions, FindOptions, InsertManyOptions, ReturnDocument,
+    UpdateOptions,
+};
+use mongodb::{ClientSession, Database};
+use std::sync::{Arc, OnceLock};
+
+use crate::domain::error::{Error::*, Result};
+use crate::infra::db::error::Error as DBError;
+
+#[derive(Debug, Default, Clone)]
+pub struct FindOpts {
+    pub projection: Option<Document>,
+    pub sort: Option<Document>,
+    pub skip: Option<u64>,
+    // 0이면 제한 없음
+    pub limit: Option<i64>,
+}
+
+/// $graphLookup. start에 맞는 문서들에서 시작하여, connect_from의 값과 connect_to가 같은 문서를
+/// 재귀적으로 찾음(시작 문서는 제외). restrict는 찾는 문서에만 적용됨.
+/// 결과 문서에는 시작 문서로부터의 거리(0부터)가 depth로 들어있고, depth 순으로 정렬됨.
+/// projection은 inclusion만 지원하며 _id, depth는 항상 포함됨.
+#[derive(Debug, Clone)]
+pub struct GraphLookup {
+    pub start: Document,
+    pub connect_from: &'static str,
+    pub connect_to: &'static str,
+    pub restrict: Document,
+    pub projection: Option<Document>,
+}
+
+/// 문서 저장소. base/base_array의 generic 함수들은 이 trait만 사용함.
+/// mongodb::Database와, 테스트용 MemoryStorage(memory.rs)가 구현함.
+///
+/// session이 주어지면 해당 transaction 안에서 실행됨(지원하지 않는 저장소는 무시).
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn find(
+        &self,
+        coll: &str,
+        filter: Document,
+        opts: FindOpts,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Vec<Document>>;
+
+    async fn find_one(
+        &self,
+        coll: &str,
+        filter: Document,
+        projection: Option<Document>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<Document>>;
+
+    async fn count(
+        &self,
+        coll: &str,
+        filter: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64>;
+
+    async fn graph_lookup(&self, coll: &str, lookup: GraphLookup) -> Result<Vec<Document>>;
+
+    /// 삽입된 문서의 _id를 반환. 같은 _id(혹은 unique key)가 있으면 MongoDuplicateError.
+    async fn insert_one(
+        &self,
+        coll: &str,
+        doc: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Bson>;
+
+    /// ordered: false로 삽입하며, 실패한 문서의 (docs 기준 index, message)를 반환.
+    async fn insert_many(
+        &self,
+        coll: &str,
+        docs: Vec<Document>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Vec<(usize, String)>>;
+
+    /// 업데이트 후의 문서를 반환. unique 인덱스를 위반하면 MongoDuplicateError.
+    async fn find_one_and_update(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        array_filters: Option<Vec<Document>>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<Document>>;
+
+    /// matched count를 반환.
+    async fn update_one(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64>;
+
+    /// filter에 맞는 문서가 없으면 filter의 값과 $setOnInsert로 만든 문서에 update를 적용함.
+    /// unique 인덱스를 위반하면 MongoDuplicateError.
+    async fn upsert_one(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<()>;
+
+    /// modified count를 반환.
+    async fn update_many(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64>;
+
+    /// 영구 삭제. deleted count를 반환.
+    async fn delete_many(&self, coll: &str, filter: Document) -> Result<u64>;
+
+    /// transaction이 시작된 session. transaction을 지원하지 않으면 None.
+    async fn start_transaction(&self) -> Result<Option<ClientSession>>;
+}
+
+#[async_trait]
+impl Storage for Database {
+    async fn find(
+        &self,
+        coll: &str,
+        filter: Document,
+        opts: FindOpts,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Vec<Document>> {
+        let find_options = FindOptions::builder()
+            .projection(opts.projection)
+            .sort(opts.sort)
+            .skip(opts.skip)
+            .limit(opts.limit)
+            .build();
+
+        let coll = self.collection::<Document>(coll);
+        let docs = match session {
+            Some(session) => {
+                let mut cursor = coll
+                    .find_with_session(filter, find_options, session)
+                    .await
+                    .map_err(DBError::MongoQueryError)?;
+                cursor.stream(session).try_collect().await
+            }
+            None => {
+                let cursor = coll
+                    .find(filter, find_options)
+                    .await
+                    .map_err(DBError::MongoQueryError)?;
+                cursor.try_collect().await
+            }
+        };
+        Ok(docs.map_err(DBError::MongoQueryError)?)
+    }
+
+    async fn find_one(
+        &self,
+        coll: &str,
+        filter: Document,
+        projection: Option<Document>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<Document>> {
+        let coll = self.collection::<Document>(coll);
+        let find_options = FindOneOptions::builder().projection(projection).build();
+
+        let result = match session {
+            Some(session) => {
+                coll.find_one_with_session(filter, find_options, session)
+                    .await
+            }
+            None => coll.find_one(filter, find_options).await,
+        };
+        Ok(result.map_err(DBError::MongoQueryError)?)
+    }
+
+    async fn count(
+        &self,
+        coll: &str,
+        filter: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64> {
+        let coll = self.collection::<Document>(coll);
+        let result = match session {
+            Some(session) => {
+                coll.count_documents_with_session(filter, None, session)
+                    .await
+            }
+            None => coll.count_documents(filter, None).await,
+        };
+        Ok(result.map_err(DBError::MongoQueryError)?)
+    }
+
+    async fn graph_lookup(&self, coll: &str, lookup: GraphLookup) -> Result<Vec<Document>> {
+        let mut pipeline = vec![
+            doc! { "$match": lookup.start },
+            doc! { "$graphLookup": {
+                "from": coll,
+                "startWith": format!("${}", lookup.connect_from),
+                "connectFromField": lookup.connect_from,
+                "connectToField": lookup.connect_to,
+                "as": "found",
+                "depthField": "depth",
+                "restrictSearchWithMatch": lookup.restrict,
+            } },
+            doc! { "$unwind": "$found" },
+            doc! { "$replaceRoot": { "newRoot": "$found" } },
+            // 여러 시작 문서에서 찾은 같은 문서는 가장 가까운 것만 남김
+            doc! { "$sort": { "depth": 1 } },
+            doc! { "$group": { "_id": "$_id", "doc": { "$first": "$$ROOT" } } },
+            doc! { "$replaceRoot": { "newRoot": "$doc" } },
+            doc! { "$sort": { "depth": 1, "_id": 1 } },
+        ];
+        if let Some(mut projection) = lookup.projection {
+            projection.insert("depth", 1);
+            pipeline.push(doc! { "$project": projection });
+        }
+
+        let cursor = self
+            .collection::<Document>(coll)
+            .aggregate(pipeline, None)
+            .await
+            .map_err(DBError::MongoQueryError)?;
+
+        Ok(cursor
+            .try_collect()
+            .await
+            .map_err(DBError::MongoQueryError)?)
+    }
+
+    async fn insert_one(
+        &self,
+        coll: &str,
+        doc: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Bson> {
+        let coll = self.collection::<Document>(coll);
+        let result = match session {
+            Some(session) => coll.insert_one_with_session(doc, None, session).await,
+            None => coll.insert_one(doc, None).await,
+        };
+        match result {
+            Ok(result) => Ok(result.inserted_id),
+            Err(e)
+                if e.to_string()
+                    .contains("E11000 duplicate key error collection") =>
+            {
+                Err(MongoDuplicateError(e))
+            }
+            Err(e) => Err(DB(DBError::MongoQueryError(e))),
+        }
+    }
+
+    async fn insert_many(
+        &self,
+        coll: &str,
+        docs: Vec<Document>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Vec<(usize, String)>> {
+        if docs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let coll = self.collection::<Document>(coll);
+        let options = InsertManyOptions::builder().ordered(false).build();
+        let result = match session {
+            Some(session) => coll.insert_many_with_session(docs, options, session).await,
+            None => coll.insert_many(docs, options).await,
+        };
+        let Err(e) = result else {
+            return Ok(Vec::new());
+        };
+
+        match e.kind.as_ref() {
+            ErrorKind::BulkWrite(BulkWriteFailure {
+                write_errors: Some(write_errors),
+                ..
+            }) => Ok(write_errors
+                .iter()
+                .map(|write_error| (write_error.index, write_error.message.clone()))
+                .collect()),
+            _ => Err(DB(DBError::MongoQueryError(e))),
+        }
+    }
+
+    async fn find_one_and_update(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        array_filters: Option<Vec<Document>>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<Document>> {
+        let options = FindOneAndUpdateOptions::builder()
+            .array_filters(array_filters)
+            .return_document(ReturnDocument::After)
+            .build();
+
+        let coll = self.collection::<Document>(coll);
+        let result = match session {
+            Some(session) => {
+                coll.find_one_and_update_with_session(filter, update, options, session)
+                    .await
+            }
+            None => coll.find_one_and_update(filter, update, options).await,
+        };
+        match result {
+            Ok(doc) => Ok(doc),
+            Err(e)
+                if e.to_string()
+                    .contains("E11000 duplicate key error collection") =>
+            {
+                Err(MongoDuplicateError(e))
+            }
+            Err(e) => Err(DB(DBError::MongoQueryError(e))),
+        }
+    }
+
+    async fn update_one(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64> {
+        let coll = self.collection::<Document>(coll);
+        let result = match session {
+            Some(session) => {
+                coll.update_one_with_session(filter, update, None, session)
+                    .await
+            }
+            None => coll.update_one(filter, update, None).await,
+        }
+        .map_err(DBError::MongoQueryError)?;
+
+        Ok(result.matched_count)
+    }
+
+    async fn upsert_one(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<()> {
+        let options = UpdateOptions::builder().upsert(true).build();
+
+        let coll = self.collection::<Document>(coll);
+        let result = match session {
+            Some(session) => {
+                coll.update_one_with_session(filter, update, options, session)
+                    .await
+            }
+            None => coll.update_one(filter, update, options).await,
+        };
+        match result {
+            Ok(_) => Ok(()),
+            Err(e)
+                if e.to_string()
+                    .contains("E11000 duplicate key error collection") =>
+            {
+                Err(MongoDuplicateError(e))
+            }
+            Err(e) => Err(DB(DBError::MongoQueryError(e))),
+        }
+    }
+
+    async fn update_many(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64> {
+        let coll = self.collection::<Document>(coll);
+        let result = match session {
+            Some(session) => {
+                coll.update_many_with_session(filter, update, None::<UpdateOptions>, session)
+                    .await
+            }
+            None => coll.update_many(filter, update, None).await,
+        }
+        .map_err(DBError::MongoQueryError)?;
+
+        Ok(result.modified_count)
+    }
+
+    async fn delete_many(&self, coll: &str, filter: Document) -> Result<u64> {
+        let result = self
+            .collection::<Document>(coll)
+            .delete_many(filter, None)
+            .await
+            .map_err(DBError::MongoQueryError)?;
+
+        Ok(result.deleted_count)
+    }
+
+    async fn start_transaction(&self) -> Result<Option<ClientSession>> {
+        // standalone 서버에서는 transaction을 쓸 수 없으므로 session 없이 각 작업을 바로 반영함.
+        if !supports_transactions(self).await? {
+            return Ok(None);
+        }
+
+        // Database::client()는 공개되어 있지 않으므로 collection을 통해 client를 얻음.
+        let client = self.collection::<Document>("_").client().clone();
+
+        let mut session = client
+            .start_session(None)
+            .await
+            .map_err(DBError::MongoError)?;
+        session
+            .start_transaction(None)
+            .await
+            .map_err(DBError::MongoError)?;
+
+        Ok(Some(session))
+    }
+}
+
+// 서버 구성은 실행 중에 바뀌지 않으므로 처음 한번만 확인함.
+static TRANSACTIONS: OnceLock<bool> = OnceLock::new();
+
+/// replica set 멤버(setName)나 mongos(msg: isdbgrid)일 때만 transaction을 지원함.
+async fn supports_transactions(db: &Database) -> Result<bool> {
+    if let Some(supported) = TRANSACTIONS.get() {
+        return Ok(*supported);
+    }
+
+    let hello = db
+        .run_command(doc! { "hello": 1 }, None)
+        .await
+        .map_err(DBError::MongoQueryError)?;
+    let supported = hello.contains_key("setName") || hello.get_str("msg") == Ok("isdbgrid");
+    if !supported {
+        tracing::warn!("mongodb is not a replica set; running without transactions");
+    }
+    Ok(*TRANSACTIONS.get_or_init(|| supported))
+}
+
+// AppState에는 Arc<dyn Storage>로 들어있으므로, &app_state.mongodb.db를 그대로 넘길 수 있게 함.
+#[async_trait]
+impl<S: Storage + ?Sized> Storage for Arc<S> {
+    async fn find(
+        &self,
+        coll: &str,
+        filter: Document,
+        opts: FindOpts,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Vec<Document>> {
+        (**self).find(coll, filter, opts, session).await
+    }
+
+    async fn find_one(
+        &self,
+        coll: &str,
+        filter: Document,
+        projection: Option<Document>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<Document>> {
+        (**self).find_one(coll, filter, projection, session).await
+    }
+
+    async fn count(
+        &self,
+        coll: &str,
+        filter: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64> {
+        (**self).count(coll, filter, session).await
+    }
+
+    async fn graph_lookup(&self, coll: &str, lookup: GraphLookup) -> Result<Vec<Document>> {
+        (**self).graph_lookup(coll, lookup).await
+    }
+
+    async fn insert_one(
+        &self,
+        coll: &str,
+        doc: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Bson> {
+        (**self).insert_one(coll, doc, session).await
+    }
+
+    async fn insert_many(
+        &self,
+        coll: &str,
+        docs: Vec<Document>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Vec<(usize, String)>> {
+        (**self).insert_many(coll, docs, session).await
+    }
+
+    async fn find_one_and_update(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        array_filters: Option<Vec<Document>>,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<Document>> {
+        (**self)
+            .find_one_and_update(coll, filter, update, array_filters, session)
+            .await
+    }
+
+    async fn update_one(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64> {
+        (**self).update_one(coll, filter, update, session).await
+    }
+
+    async fn upsert_one(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<()> {
+        (**self).upsert_one(coll, filter, update, session).await
+    }
+
+    async fn update_many(
+        &self,
+        coll: &str,
+        filter: Document,
+        update: Document,
+        session: Option<&mut ClientSession>,
+    ) -> Result<u64> {
+        (**self).update_many(coll, filter, update, session).await
+    }
+
+    async fn delete_many(&self, coll: &str, filter: Document) -> Result<u64> {
+        (**self).delete_many(coll, filter).await
+    }
+
+    async fn start_transaction(&self) -> Result<Option<ClientSession>> {
+        (**self).start_transaction().await
+    }
+}
@@ -0,0 +1,68 @@
+use mongodb::error::UNKNOWN_TRANSACTION_COMMIT_RESULT;
+use mongodb::ClientSession;
+
+use crate::domain::error::{Error::*, Result};
+use crate::infra::db::error::Error as DBError;
+
+use super::storage::Storage;
+
+/// 여러 문서에 걸친 작업을 하나의 transaction으로 묶음.
+/// (transaction은 replica set 혹은 mongos에서만 사용 가능)
+/// transaction을 지원하지 않는 저장소(MemoryStorage, standalone mongo)에서는 각 작업이 바로 반영됨.
+///
+/// ```ignore
+/// let mut tx = Transaction::start(db).await?;
+/// let result = async {
+///     base::delete_many::<S>(db, filter, None, tx.session(), user).await?;
+///     ...
+///     Ok(())
+/// }
+/// .await;
+/// tx.finish(result).await
+/// ```
+pub struct Transaction {
+    session: Option<ClientSession>,
+}
+
+impl Transaction {
+    pub async fn start(db: &dyn Storage) -> Result<Self> {
+        Ok(Self {
+            session: db.start_transaction().await?,
+        })
+    }
+
+    pub fn session(&mut self) -> Option<&mut ClientSession> {
+        self.session.as_mut()
+    }
+
+    /// result가 Ok면 commit, Err면 abort 후 result를 그대로 반환.
+    pub async fn finish<T>(mut self, result: Result<T>) -> Result<T> {
+        let Some(session) = self.session.as_mut() else {
+            return result;
+        };
+
+        match result {
+            Ok(value) => {
+                Self::commit(session).await?;
+                Ok(value)
+            }
+            Err(e) => {
+                if let Err(abort_err) = session.abort_transaction().await {
+                    tracing::warn!("failed to abort transaction: {}", abort_err);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    async fn commit(session: &mut ClientSession) -> Result<()> {
+        loop {
+            match session.commit_transaction().await {
+                Ok(()) => return Ok(()),
+                // commit 결과를 알 수 없는 경우는 재시도해도 안전함.
+                Err(e) if e.contains_label(UNKNOWN_TRANSACTION_COMMIT_RESULT) => continue,
+                Err(e) => return Err(DB(DBError::MongoError(e))),
+            }
+        }
+    }
+}
@@ -1,101 +1,214 @@
-use crate::domain::error::{Error::*, Result};
+use crate::domain::error::{Error, Error::*, Result};
 use crate::infra::db::error::Error as DBError;
 
-use mongodb::bson::{self};
-use mongodb::options::ReturnDocument;
-use mongodb::{
-    bson::{oid::ObjectId, Document},
-    options::FindOneAndUpdateOptions,
-    Collection,
-};
+use base64::{engine::general_purpose, Engine as _};
+use mongodb::bson::{self, doc, Bson};
+use mongodb::bson::{oid::ObjectId, Document};
+use mongodb::ClientSession;
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use uuid::Uuid;
+
+use super::history;
+use super::storage::Storage;
+use super::transaction::Transaction;
 
 pub async fn find_mdoc_by_id<Model>(
-    coll: &Collection<Model>,
+    db: &dyn Storage,
+    coll: &str,
     oid: &ObjectId,
     filter: Document,
 ) -> Result<Model>
 where
     Model: DeserializeOwned + Unpin + Send + Sync,
 {
-    let doc = match coll.find_one(filter, None).await {
-        Ok(Some(doc)) => doc,
-        Ok(None) => return Err(NotFoundError(oid.to_string())),
-        Err(e) => return Err(DB(DBError::MongoQueryError(e))),
+    let doc = match db.find_one(coll, filter, None, None).await? {
+        Some(doc) => doc,
+        None => return Err(NotFoundError(oid.to_string())),
     };
 
-    Ok(doc)
+    Ok(bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?)
 }
 
-pub async fn update_doc_ret_doc<Model>(
-    coll: &Collection<Model>,
+/// 업데이트 후의 문서를 반환하고, 바뀐 필드를 history에 기록함.
+/// find_filter에 version 조건(If-Match)이 있으면 그 version일 때만 업데이트하고,
+/// 없으면 조건 없이 바로 업데이트함. 어느 쪽이든 조건에 맞는 문서가 없으면 NotFoundError.
+/// 업데이트 전 문서 읽기, 업데이트, history 기록은 하나의 transaction으로 묶음.
+pub async fn update_doc_ret_doc(
+    db: &dyn Storage,
+    coll: &str,
     oid: &ObjectId,
     array_filters: Option<Document>,
     update_doc: Document,
     find_filter: Document,
-) -> Result<Document>
-where
-    Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
-{
-    let options;
-
-    if let Some(filters) = array_filters {
-        options = FindOneAndUpdateOptions::builder()
-            .array_filters(Some(vec![filters]))
-            .return_document(ReturnDocument::After)
-            .build();
-    } else {
-        options = FindOneAndUpdateOptions::builder()
-            .return_document(ReturnDocument::After)
-            .build();
-    }
+    user: &Uuid,
+) -> Result<Document> {
+    let array_filters = array_filters.map(|filter| vec![filter]);
 
-    match coll
-        .find_one_and_update(find_filter, update_doc, options)
-        .await
+    let mut tx = Transaction::start(db).await?;
+    let result = match update_doc_in_session(
+        db,
+        coll,
+        find_filter,
+        update_doc,
+        array_filters,
+        user,
+        tx.session(),
+    )
+    .await
     {
-        Ok(Some(doc)) => {
-            let serialized_data =
-                bson::to_bson(&doc).map_err(|e| DB(DBError::MongoSerializeBsonError(e)))?;
-            return Ok(serialized_data.as_document().unwrap().clone());
-        }
+        Ok(Some(doc)) => Ok(doc),
         Ok(None) => Err(NotFoundError(oid.to_string())),
-        Err(e) => Err(DB(DBError::MongoQueryError(e))),
-    }
+        Err(e) => Err(e),
+    };
+    tx.finish(result).await
+}
+
+/// update_doc_ret_doc과 같지만 호출한 쪽의 session(transaction) 안에서 실행함.
+/// 조건에 맞는 문서가 없으면 None.
+pub async fn update_doc_in_session(
+    db: &dyn Storage,
+    coll: &str,
+    find_filter: Document,
+    update_doc: Document,
+    array_filters: Option<Vec<Document>>,
+    user: &Uuid,
+    mut session: Option<&mut ClientSession>,
+) -> Result<Option<Document>> {
+    let before = db
+        .find_one(coll, find_filter.clone(), None, session.as_deref_mut())
+        .await?;
+    let Some(before) = before else {
+        return Ok(None);
+    };
+    let after = db
+        .find_one_and_update(
+            coll,
+            find_filter,
+            update_doc,
+            array_filters,
+            session.as_deref_mut(),
+        )
+        .await?;
+    let Some(after) = after else {
+        return Ok(None);
+    };
+
+    history::record_update(db, coll, &before, &after, user, session).await?;
+    Ok(Some(after))
 }
 
 pub async fn update_doc_ret_model<Model>(
-    coll: &Collection<Model>,
+    db: &dyn Storage,
+    coll: &str,
     oid: &ObjectId,
     array_filters: Option<Document>,
     update_doc: Document,
     find_filter: Document,
+    user: &Uuid,
 ) -> Result<Model>
 where
-    Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
+    Model: DeserializeOwned + Unpin + Send + Sync,
 {
-    let options;
-
-    if let Some(filters) = array_filters {
-        options = FindOneAndUpdateOptions::builder()
-            .array_filters(Some(vec![filters]))
-            .return_document(ReturnDocument::After)
-            .build();
-    } else {
-        options = FindOneAndUpdateOptions::builder()
-            .return_document(ReturnDocument::After)
-            .build();
+    let doc =
+        update_doc_ret_doc(db, coll, oid, array_filters, update_doc, find_filter, user).await?;
+
+    Ok(bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?)
+}
+
+// region:    --- Version
+
+/// If-Match로 받은 version과 일치하는 조건.
+/// version 필드가 추가되기 전의 문서는 version 0으로 취급함.
+pub fn version_filter(expected: i64) -> Bson {
+    match expected {
+        0 => Bson::Document(doc! { "$in": [0, Bson::Null] }),
+        v => Bson::Int64(v),
     }
+}
 
-    match coll
-        .find_one_and_update(find_filter, update_doc, options)
-        .await
-    {
-        Ok(Some(doc)) => {
-            Ok(doc)
+/// 문서의 version. version 필드가 추가되기 전의 문서는 0.
+pub fn doc_version(doc: &Document) -> i64 {
+    match doc.get("version") {
+        Some(Bson::Int32(v)) => *v as i64,
+        Some(Bson::Int64(v)) => *v,
+        _ => 0,
+    }
+}
+
+/// version 조건을 포함한 update가 실패했을 때,
+/// 문서가 없는 것(404)인지 version이 달라진 것(412)인지 구분.
+pub async fn not_found_or_stale(
+    db: &dyn Storage,
+    coll: &str,
+    id: &str,
+    filter_without_version: Document,
+) -> Error {
+    match db.count(coll, filter_without_version, None).await {
+        Ok(0) => NotFoundError(id.to_string()),
+        Ok(_) => PreconditionFailed(id.to_string()),
+        Err(e) => e,
+    }
+}
+
+// endregion: --- Version
+
+// region:    --- Cursor
+
+/// keyset pagination의 마지막 위치를 불투명한 문자열로 변환.
+pub fn encode_cursor(key: Bson, id: Bson) -> String {
+    let cursor = doc! { "k": key, "id": id };
+    let bytes = bson::to_vec(&cursor).unwrap_or_default();
+    general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// encode_cursor로 만든 문자열을 (sort_key 값, id 값)으로 복원.
+/// 잘못된 cursor면 None.
+pub fn decode_cursor(cursor: &str) -> Option<(Bson, Bson)> {
+    let bytes = general_purpose::URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    let doc = bson::from_slice::<Document>(&bytes).ok()?;
+
+    Some((doc.get("k")?.clone(), doc.get("id")?.clone()))
+}
+
+/// (sort의 정렬 키들, _id 오름차순) 기준으로 cursor 이후의 문서만 찾는 필터.
+/// keys는 cursor 위치에서의 정렬 키 값들(sort와 같은 순서).
+/// mongodb는 null/누락 필드를 오름차순에서는 가장 앞, 내림차순에서는 가장 뒤에 정렬하므로 따로 처리함.
+pub fn keyset_filter(sort: &Document, keys: &[Bson], id: Bson) -> Document {
+    let mut branches: Vec<Document> = Vec::new();
+    let mut equal: Vec<Document> = Vec::new();
+
+    for ((sort_key, direction), key) in sort.iter().zip(keys) {
+        let desc = direction.as_i32() == Some(-1);
+        let after = match (key, desc) {
+            (Bson::Null, false) => Some(doc! { sort_key: { "$ne": Bson::Null } }),
+            (Bson::Null, true) => None,
+            (key, false) => Some(doc! { sort_key: { "$gt": key.clone() } }),
+            (key, true) => Some(doc! {
+                "$or": [
+                    { sort_key: { "$lt": key.clone() } },
+                    { sort_key: Bson::Null },
+                ]
+            }),
+        };
+        if let Some(after) = after {
+            branches.push(and_all(equal.clone(), after));
         }
-        Ok(None) => Err(NotFoundError(oid.to_string())),
-        Err(e) => Err(DB(DBError::MongoQueryError(e))),
+        equal.push(doc! { sort_key: key.clone() });
+    }
+    branches.push(and_all(equal, doc! { "_id": { "$gt": id } }));
+
+    match branches.len() {
+        1 => branches.remove(0),
+        _ => doc! { "$or": branches },
     }
 }
+
+fn and_all(mut conds: Vec<Document>, last: Document) -> Document {
+    if conds.is_empty() {
+        return last;
+    }
+    conds.push(last);
+    doc! { "$and": conds }
+}
+
+// endregion: --- Cursor
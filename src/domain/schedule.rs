@@ -36,6 +36,8 @@ pub struct ScheduleModel {
     pub habits: Vec<ScheduledHabit>,
     pub scheduled_times: Vec<ScheduledAt>,
 
+    #[serde(default)]
+    pub version: i64,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub createdAt: DateTime<Utc>,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
@@ -0,0 +1,195 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::doc;
+use mongodb::bson::Document;
+use mongodb::bson::{self, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::interface::dto::settings::{
+    req::UpdateSettingsReq,
+    res::{SettingsData, SettingsRes, SingleSettingsRes},
+};
+
+use crate::{
+    domain::error::{Error::*, Result},
+    domain::repo::base::{self, MongoRepo},
+    domain::repo::index::{IndexDef, IndexKind::*},
+    domain::repo::query::{FieldKind, Load::*, QueryFields, SelectFields},
+    domain::repo::storage::Storage,
+    infra::db::error::Error as DBError,
+};
+
+// 설정하지 않은 user의 하루 작업 가능 시간(분)
+pub const DEFAULT_DAILY_CAPACITY: u32 = 8 * 60;
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SettingsModel {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    #[serde(with = "bson::serde_helpers::uuid_1_as_binary")]
+    pub user: Uuid,
+    pub daily_capacity_minutes: u32,
+    #[serde(default)]
+    pub version: i64,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub createdAt: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub updatedAt: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct NewSettings {
+    daily_capacity_minutes: u32,
+}
+
+/// user마다 하나씩 있는 설정. 처음 조회할 때 기본값으로 생성됨.
+pub struct SettingsService;
+
+impl MongoRepo for SettingsService {
+    const COLL_NAME: &'static str = "user_settings";
+    const QUERY_FIELDS: QueryFields = &[("daily_capacity_minutes", FieldKind::Int)];
+    const SELECT_FIELDS: SelectFields = &[
+        ("user", Always),
+        ("daily_capacity_minutes", Always),
+        ("createdAt", Always),
+        ("updatedAt", Always),
+        ("version", Always),
+    ];
+    const INDEXES: &'static [IndexDef] = &[IndexDef {
+        keys: &[("user", Asc)],
+        unique: true,
+        ..IndexDef::DEFAULT
+    }];
+    type Model = SettingsModel;
+    type ModelResponse = SettingsRes;
+    fn convert_doc_to_response(settings: &SettingsModel) -> SettingsRes {
+        SettingsRes::from_model(settings)
+    }
+
+    fn create_doc<Schema: Serialize>(user: &Uuid, body: &Schema) -> Result<Document> {
+        let serialized_data = bson::to_bson(body).map_err(DBError::from)?;
+        let document = serialized_data.as_document().unwrap();
+        let datetime = Utc::now();
+        let mut doc_with_dates = doc! {
+            "user": user,
+            "version": 1,
+            "createdAt": datetime,
+            "updatedAt": datetime,
+        };
+        doc_with_dates.extend(document.clone());
+        Ok(doc_with_dates)
+    }
+}
+
+impl SettingsService {
+    pub async fn get_settings(db: &dyn Storage, user: &Uuid) -> Result<SingleSettingsRes> {
+        let settings_result = Self::find_or_create(db, user).await?;
+
+        Ok(SingleSettingsRes {
+            status: "success",
+            data: SettingsData {
+                settings: settings_result,
+            },
+        })
+    }
+
+    pub async fn update_settings(
+        db: &dyn Storage,
+        body: &UpdateSettingsReq,
+        if_match: Option<i64>,
+        user: &Uuid,
+    ) -> Result<SingleSettingsRes> {
+        if body
+            .daily_capacity_minutes
+            .is_some_and(|minutes| minutes > 24 * 60)
+        {
+            return Err(DB(DBError::InvalidQuery(
+                "daily_capacity_minutes must be at most 1440".to_string(),
+            )));
+        }
+        let settings = Self::find_or_create(db, user).await?;
+        let settings_result =
+            base::update::<Self, UpdateSettingsReq>(db, &settings.id, body, if_match, user).await?;
+
+        Ok(SingleSettingsRes {
+            status: "success",
+            data: SettingsData {
+                settings: settings_result,
+            },
+        })
+    }
+
+    /// 하루에 일정을 잡을 수 있는 시간(분)
+    pub async fn daily_capacity(db: &dyn Storage, user: &Uuid) -> Result<u32> {
+        Ok(Self::find(db, user)
+            .await?
+            .map_or(DEFAULT_DAILY_CAPACITY, |settings| {
+                settings.daily_capacity_minutes
+            }))
+    }
+
+    async fn find(db: &dyn Storage, user: &Uuid) -> Result<Option<SettingsModel>> {
+        let filter = doc! { "user": user, "deletedAt": { "$exists": false } };
+        match db.find_one(Self::COLL_NAME, filter, None, None).await? {
+            Some(doc) => Ok(Some(bson::from_document(doc).map_err(DBError::from)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_or_create(db: &dyn Storage, user: &Uuid) -> Result<SettingsRes> {
+        if let Some(settings) = Self::find(db, user).await? {
+            return Ok(SettingsRes::from_model(&settings));
+        }
+        let settings = NewSettings {
+            daily_capacity_minutes: DEFAULT_DAILY_CAPACITY,
+        };
+        base::create::<Self, _>(db, &settings, user).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::db::MongoDB;
+
+    const USER: Uuid = Uuid::from_bytes([0; 16]);
+
+    #[tokio::test]
+    async fn test_settings() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+
+        assert_eq!(
+            SettingsService::daily_capacity(db, &USER).await.unwrap(),
+            DEFAULT_DAILY_CAPACITY
+        );
+        let res = SettingsService::get_settings(db, &USER).await.unwrap();
+        assert_eq!(
+            res.data.settings.daily_capacity_minutes,
+            DEFAULT_DAILY_CAPACITY
+        );
+        let id = res.data.settings.id.clone();
+
+        let body = UpdateSettingsReq {
+            daily_capacity_minutes: Some(300),
+        };
+        let res = SettingsService::update_settings(db, &body, Some(1), &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.data.settings.id, id);
+        assert_eq!(res.data.settings.daily_capacity_minutes, 300);
+        assert_eq!(res.data.settings.version, 2);
+        assert_eq!(
+            SettingsService::daily_capacity(db, &USER).await.unwrap(),
+            300
+        );
+
+        // 하루보다 긴 시간은 설정할 수 없음
+        let body = UpdateSettingsReq {
+            daily_capacity_minutes: Some(24 * 60 + 1),
+        };
+        let res = SettingsService::update_settings(db, &body, None, &USER).await;
+        claim::assert_matches!(res, Err(DB(DBError::InvalidQuery(_))));
+    }
+}
@@ -2,7 +2,7 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use chrono::{DateTime, Utc};
 use mongodb::bson::{self, doc, oid::ObjectId};
-use mongodb::Database;
+use uuid::Uuid;
 
 use crate::domain::repo::CollInfo;
 use crate::interface::dto::sub::chat::req::{CreateMsgReq, UpdateMsgReq};
@@ -11,12 +11,14 @@ use crate::interface::dto::sub::chat::res::*;
 use crate::{
     domain::error::{Result},
     domain::repo::base_array::{self, MongoArrayRepo},
+    domain::repo::index::{IndexDef, IndexKind::*},
+    domain::repo::storage::Storage,
 };
 
+use crate::domain::note::NoteModel;
 use crate::domain::task::TaskModel;
-//use crate::domain::note::NoteModel;
 
-use crate::infra::types::{ChatType, MsgType};
+use crate::infra::types::{ChatType, MsgType, PageOptions};
 
 #[allow(non_snake_case)]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -30,15 +32,16 @@ pub struct MsgModel {
     pub chat_type: Option<ChatType>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chat_msgs: Option<Vec<MsgModel>>,
+    #[serde(default)]
+    pub version: i64,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub createdAt: DateTime<Utc>,
 }
 
-
-// impl CollInfo for NoteModel {
-//     const COLL_NAME: &'static str = "notes";
-//     const ARR_NAME: &'static str = "chat_msgs";
-// }
+impl CollInfo for NoteModel {
+    const COLL_NAME: &'static str = "notes";
+    const ARR_NAME: &'static str = "chat_msgs";
+}
 
 
 
@@ -63,6 +66,10 @@ where
     type ElemRes = MsgRes;
     const COLL_NAME: &'static str = Model::COLL_NAME;
     const ARR_NAME: &'static str = Model::ARR_NAME;
+    const ELEM_INDEXES: &'static [IndexDef] = &[IndexDef {
+        keys: &[("booked", Asc)],
+        ..IndexDef::DEFAULT
+    }];
     fn convert_doc_to_response(doc: &MsgModel) -> Result<Self::ElemRes> {
         Ok(MsgRes::from_model(doc))
     }
@@ -72,8 +79,13 @@ impl<Model> ChatMsgService<Model>
 where
     Model: DeserializeOwned + Serialize + Unpin + Send + Sync + CollInfo,
 {
-    pub async fn get_msg(db: &Database, src_id: &str, msg_id: &str) -> Result<SingleMsgRes> {
-        let result = base_array::get_elem::<Self>(db, src_id, msg_id).await?;
+    pub async fn get_msg(
+        db: &dyn Storage,
+        src_id: &str,
+        msg_id: &str,
+        user: &Uuid,
+    ) -> Result<SingleMsgRes> {
+        let result = base_array::get_elem::<Self>(db, src_id, msg_id, user).await?;
         Ok(SingleMsgRes {
             status: "success",
             data: MsgData { msg: result },
@@ -81,11 +93,12 @@ where
     }
 
     pub async fn add_msg(
-        db: &Database,
+        db: &dyn Storage,
         src_id: &str,
         new_msg: &CreateMsgReq,
+        user: &Uuid,
     ) -> Result<SingleMsgRes> {
-        let result = base_array::add_elem::<Self>(db, src_id, new_msg, Some("booked")).await?;
+        let result = base_array::add_elem::<Self>(db, src_id, new_msg, user).await?;
         Ok(SingleMsgRes {
             status: "success",
             data: MsgData { msg: result },
@@ -93,30 +106,42 @@ where
     }
 
     pub async fn fetch_msgs(
-        db: &Database,
+        db: &dyn Storage,
         src_id: &str,
-        limit: i64,
-        page: i64,
+        page_opts: PageOptions,
+        user: &Uuid,
     ) -> Result<MsgListRes> {
-        let results = base_array::fetch_elems::<Self>(db, src_id, Some(limit), Some(page)).await?;
+        let results = base_array::fetch_elems::<Self>(db, src_id, &page_opts, user).await?;
         Ok(MsgListRes {
             status: "success",
-            results: results.len(),
-            msgs: results,
+            results: results.items.len(),
+            msgs: results.items,
+            next_cursor: results.next_cursor,
+            has_more: results.has_more,
+            total: results.total,
         })
     }
 
-    pub async fn remove_msg(db: &Database, src_id: &str, msg_id: &str) -> Result<()> {
-        base_array::remove_elem::<Self>(db, src_id, msg_id).await
+    pub async fn remove_msg(
+        db: &dyn Storage,
+        src_id: &str,
+        msg_id: &str,
+        user: &Uuid,
+    ) -> Result<()> {
+        base_array::remove_elem::<Self>(db, src_id, msg_id, user).await
     }
 
     pub async fn update_msg(
-        db: &Database,
+        db: &dyn Storage,
         src_id: &str,
         msg_id: &str,
         update_msg: &UpdateMsgReq,
+        if_match: Option<i64>,
+        user: &Uuid,
     ) -> Result<SingleMsgRes> {
-        let result = base_array::update_elem::<Self>(db, src_id, msg_id, update_msg).await?;
+        let result =
+            base_array::update_elem::<Self>(db, src_id, msg_id, update_msg, if_match, user)
+                .await?;
         Ok(SingleMsgRes {
             status: "success",
             data: MsgData { msg: result },
@@ -125,7 +150,7 @@ where
 
     // TODO: 그냥 update_msg함수로 될 것 같은데?
     // pub async fn add_chat_to_msg(
-    //     db: &Database,
+    //     db: &dyn Storage,
     //     src_id: &str,
     //     msg_id: &str,
     // ) -> Result<SingleMsgRes> {
@@ -161,144 +186,116 @@ where
     // }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//     use crate::{
-//         domain::types::{ChatType, MsgType},
-//         infra::db::MongoDB,
-//     };
-//     use dotenv::dotenv;
-//     use uuid::Uuid;
-
-//     async fn get_test_db() -> Database {
-//         dotenv().ok();
-//         std::env::set_var("RUST_BACKTRACE", "0");
-//         let mongodb = MongoDB::init_test().await.unwrap();
-
-//         mongodb.db
-//     }
-
-//     async fn get_fake_src_id() -> String {
-//         let db = get_test_db().await;
-//         let coll = db.collection::<EventModel>("events");
-
-//         let new_event = EventModel {
-//             id: ObjectId::new(),
-//             user: Uuid::from_bytes([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
-//             title: "test chatting".to_string(),
-//             complete: false,
-//             start_date: None,
-//             due_at: None,
-//             location: None,
-//             chat_type: ChatType::Event,
-//             chat_msgs: None,
-//             createdAt: Utc::now(),
-//             updatedAt: Utc::now(),
-//         };
-
-//         let result = coll.insert_one(new_event, None).await.unwrap();
-//         result.inserted_id.as_object_id().unwrap().to_hex()
-//     }
-
-//     async fn get_fake_msg_id(db: &Database, src_id: &str) -> String {
-//         let coll = db.collection::<EventModel>("events");
-
-//         let oid = ObjectId::from_str(src_id).unwrap();
-
-//         let new_msg = MsgModel {
-//             id: ObjectId::new(),
-//             msg_type: MsgType::Text,
-//             content: "fake 새로운 메세지".to_string(),
-//             created_at: Utc::now(),
-//             booked: false,
-//             chat_msgs: None,
-//             chat_type: None,
-//         };
-
-//         let update_doc = doc! {
-//             "$push": { "chat_msgs": bson::to_bson(&new_msg).unwrap() },
-//             "$set": { "updatedAt": Bson::DateTime(Utc::now().into()) }
-//         };
-
-//         let options = FindOneAndUpdateOptions::builder()
-//             .return_document(ReturnDocument::After)
-//             .build();
-
-//         let doc = coll
-//             .find_one_and_update(doc! {"_id": oid}, update_doc, options)
-//             .await
-//             .unwrap()
-//             .unwrap();
-
-//         doc.msgs().unwrap().last().unwrap().id.to_hex()
-//     }
-
-//     #[tokio::test]
-//     async fn test_add_msg() -> Result<()> {
-//         let db = get_test_db().await;
-//         let src_id = get_fake_src_id().await;
-//         let new_msg = MsgModel {
-//             id: ObjectId::new(),
-//             msg_type: MsgType::Text,
-//             content: "배고파요".to_string(),
-//             created_at: Utc::now(),
-//             booked: false,
-//             chat_msgs: None,
-//             chat_type: None,
-//         };
-
-//         let result = ChatMsgService::<EventModel>::add_msg(&db, &src_id, &new_msg).await?;
-//         assert!(result.contains(&new_msg));
-//         Ok(())
-//     }
-
-//     #[tokio::test]
-//     async fn test_fetch_msgs() -> Result<()> {
-//         let db = get_test_db().await;
-//         let src_id = get_fake_src_id().await;
-//         let result = ChatMsgService::<EventModel>::fetch_msgs(&db, &src_id).await?;
-//         assert!(!result.is_empty());
-//         Ok(())
-//     }
-
-//     #[tokio::test]
-//     async fn test_remove_msg() -> Result<()> {
-//         let db = get_test_db().await;
-//         let src_id = get_fake_src_id().await;
-//         let msg_id = get_fake_msg_id(&db, &src_id).await;
-//         let result = ChatMsgService::<EventModel>::remove_msg(&db, &src_id, &msg_id).await?;
-
-//         assert!(!result.iter().any(|msg| msg.id.to_hex() == msg_id));
-//         Ok(())
-//     }
-
-//     #[tokio::test]
-//     async fn test_update_msg() -> Result<()> {
-//         let db = get_test_db().await;
-//         let src_id = get_fake_src_id().await;
-//         let msg_id = get_fake_msg_id(&db, &src_id).await;
-//         let update_msg = UpdateMsgReq {
-//             msg_type: todo!(),
-//             content: todo!(),
-//             booked: todo!(),
-//         };
-
-//         let result =
-//             ChatMsgService::<EventModel>::update_msg(&db, &src_id, &msg_id, &update_msg).await?;
-//         // assert based on the expected outcome of the update
-//         Ok(())
-//     }
-
-//     #[tokio::test]
-//     async fn test_add_chat_to_msg() -> Result<()> {
-//         let db = get_test_db().await;
-//         let src_id = get_fake_src_id().await;
-//         let msg_id = get_fake_msg_id(&db, &src_id).await;
-//         let result =
-//             ChatMsgService::<EventModel>::add_chat_to_msg(&db, "some_src_id", "some_msg_id")
-//                 .await?;
-//         // assert based on the expected outcome of adding chat to message
-//         Ok(())
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::error::Error::*;
+    use crate::infra::db::MongoDB;
+
+    const USER: Uuid = Uuid::from_bytes([0; 16]);
+
+    // chat_msgs를 가질 task 문서를 만들고 id를 반환
+    async fn setup() -> (MongoDB, String) {
+        let mongodb = MongoDB::init_memory();
+        let src_id = mongodb
+            .db
+            .insert_one(
+                "tasks",
+                doc! { "user": USER, "title": "test chatting", "version": 1 },
+                None,
+            )
+            .await
+            .unwrap();
+
+        (mongodb, src_id.as_object_id().unwrap().to_hex())
+    }
+
+    fn new_msg(content: &str) -> CreateMsgReq {
+        CreateMsgReq {
+            msg_type: MsgType::Text,
+            content: content.to_string(),
+            booked: false,
+        }
+    }
+
+    fn page_opts() -> PageOptions {
+        PageOptions {
+            limit: 10,
+            page: 1,
+            cursor: None,
+            with_total: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_and_fetch_msgs() -> Result<()> {
+        let (mongodb, src_id) = setup().await;
+        for content in ["첫 메세지", "배고파요"] {
+            ChatMsgService::<TaskModel>::add_msg(&mongodb.db, &src_id, &new_msg(content), &USER)
+                .await?;
+        }
+
+        let result =
+            ChatMsgService::<TaskModel>::fetch_msgs(&mongodb.db, &src_id, page_opts(), &USER)
+                .await?;
+        // 최신 메세지가 앞에 옴
+        assert_eq!(result.total, Some(2));
+        assert_eq!(result.msgs[0].content, "배고파요");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_msg() -> Result<()> {
+        let (mongodb, src_id) = setup().await;
+        let added =
+            ChatMsgService::<TaskModel>::add_msg(&mongodb.db, &src_id, &new_msg("배고파요"), &USER)
+                .await?;
+        let msg_id = added.data.msg.id;
+        let update_msg = UpdateMsgReq {
+            msg_type: None,
+            content: None,
+            booked: Some(true),
+        };
+
+        let result = ChatMsgService::<TaskModel>::update_msg(
+            &mongodb.db,
+            &src_id,
+            &msg_id,
+            &update_msg,
+            Some(1),
+            &USER,
+        )
+        .await?;
+        assert!(result.data.msg.booked);
+        assert_eq!(result.data.msg.version, 2);
+
+        // 이전 version으로는 업데이트할 수 없음
+        let result = ChatMsgService::<TaskModel>::update_msg(
+            &mongodb.db,
+            &src_id,
+            &msg_id,
+            &update_msg,
+            Some(1),
+            &USER,
+        )
+        .await;
+        claim::assert_matches!(result, Err(PreconditionFailed(_)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_remove_msg() -> Result<()> {
+        let (mongodb, src_id) = setup().await;
+        let added =
+            ChatMsgService::<TaskModel>::add_msg(&mongodb.db, &src_id, &new_msg("배고파요"), &USER)
+                .await?;
+        let msg_id = added.data.msg.id;
+
+        ChatMsgService::<TaskModel>::remove_msg(&mongodb.db, &src_id, &msg_id, &USER).await?;
+
+        let result =
+            ChatMsgService::<TaskModel>::get_msg(&mongodb.db, &src_id, &msg_id, &USER).await;
+        claim::assert_matches!(result, Err(NotFoundError(_)));
+        Ok(())
+    }
+}
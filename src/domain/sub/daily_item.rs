@@ -1,25 +1,33 @@
 use crate::interface::dto::sub::daily_item::req::*;
 use crate::interface::dto::sub::daily_item::res::*;
 use crate::{
-    domain::daily::DailyModel,
+    domain::daily::{self, DailyModel},
     domain::error::{Result},
     domain::repo::base_array::{self, MongoArrayRepo},
+    domain::repo::index::{IndexDef, IndexKind::*},
+    domain::repo::storage::Storage,
     domain::repo::ElemInfo,
+    infra::types::PageOptions,
 };
 use chrono::{DateTime, Utc};
 use mongodb::bson::oid::ObjectId;
 use mongodb::bson::{self, doc};
-use mongodb::Database;
+use uuid::Uuid;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 #[allow(non_snake_case)]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct DailyTaskModel {
+    // 이전 버전에서는 "id"로 저장되었음
+    #[serde(rename = "_id", alias = "id")]
+    pub id: ObjectId,
     pub task_id: ObjectId,
     pub title: String,
     pub done: bool,
     pub doneAt: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub version: i64,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub createdAt: DateTime<Utc>,
 }
@@ -27,10 +35,15 @@ pub struct DailyTaskModel {
 #[allow(non_snake_case)]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct DailyEventModel {
+    // 이전 버전에서는 "id"로 저장되었음
+    #[serde(rename = "_id", alias = "id")]
+    pub id: ObjectId,
     pub event_id: ObjectId,
     pub title: String,
     pub done: bool,
     pub doneAt: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub version: i64,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub createdAt: DateTime<Utc>,
 }
@@ -38,11 +51,16 @@ pub struct DailyEventModel {
 #[allow(non_snake_case)]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct DailyHabitModel {
+    // 이전 버전에서는 "id"로 저장되었음
+    #[serde(rename = "_id", alias = "id")]
+    pub id: ObjectId,
     pub habit_id: ObjectId,
     pub icon: String,
     pub name: String,
     pub done: bool,
     pub doneAt: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub version: i64,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub createdAt: DateTime<Utc>,
 }
@@ -50,11 +68,21 @@ pub struct DailyHabitModel {
 #[allow(non_snake_case)]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct TimerResultModel {
+    // 이전 버전에서는 "id"로 저장되었음
+    #[serde(rename = "_id", alias = "id")]
+    pub id: ObjectId,
     pub category_id: ObjectId,
     pub category_color: String,
     pub startAt: DateTime<Utc>,
     pub endAt: DateTime<Utc>,
     pub focus_time: String,
+    // task timer로 기록된 경우의 task와, 일시정지를 뺀 시간(초)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<ObjectId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_secs: Option<i64>,
+    #[serde(default)]
+    pub version: i64,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub createdAt: DateTime<Utc>,
 }
@@ -119,9 +147,13 @@ where
     type CreateElemReq = Elem::CreateReq;
     type ElemRes = Elem::Res;
 
-    const COLL_NAME: &'static str = "daily";
+    const COLL_NAME: &'static str = daily::COLL_NAME;
 
     const ARR_NAME: &'static str = Elem::ARR_NAME;
+    const ELEM_INDEXES: &'static [IndexDef] = &[IndexDef {
+        keys: &[("done", Asc)],
+        ..IndexDef::DEFAULT
+    }];
 
     fn convert_doc_to_response(doc: &Self::ElemModel) -> Result<Self::ElemRes> {
         Ok(Elem::convert_to_res(doc))
@@ -133,11 +165,12 @@ where
     Elem: DeserializeOwned + Serialize + Unpin + Send + Sync + ElemInfo,
 {
     pub async fn get_elem(
-        db: &Database,
+        db: &dyn Storage,
         src_id: &str,
         elem_id: &str,
+        user: &Uuid,
     ) -> Result<SingleDailyItemRes<Elem::Res>> {
-        let result = base_array::get_elem::<Self>(db, src_id, elem_id).await?;
+        let result = base_array::get_elem::<Self>(db, src_id, elem_id, user).await?;
         Ok(SingleDailyItemRes {
             status: "success",
             data: DailyItemData { item: result },
@@ -145,11 +178,12 @@ where
     }
 
     pub async fn add_elem(
-        db: &Database,
+        db: &dyn Storage,
         src_id: &str,
         new_elem: &Elem::CreateReq,
+        user: &Uuid,
     ) -> Result<SingleDailyItemRes<Elem::Res>> {
-        let result = base_array::add_elem::<Self>(db, src_id, new_elem, Some("done")).await?;
+        let result = base_array::add_elem::<Self>(db, src_id, new_elem, user).await?;
         Ok(SingleDailyItemRes {
             status: "success",
             data: DailyItemData { item: result },
@@ -157,34 +191,75 @@ where
     }
 
     pub async fn fetch_elems(
-        db: &Database,
+        db: &dyn Storage,
         src_id: &str,
-        limit: i64,
-        page: i64,
+        page_opts: PageOptions,
+        user: &Uuid,
     ) -> Result<DailyItemListRes<Elem::Res>> {
-        let results = base_array::fetch_elems::<Self>(db, src_id, Some(limit), Some(page)).await?;
+        let results = base_array::fetch_elems::<Self>(db, src_id, &page_opts, user).await?;
         Ok(DailyItemListRes {
             status: "success",
-            results: results.len(),
-            items: results,
+            results: results.items.len(),
+            items: results.items,
+            next_cursor: results.next_cursor,
+            has_more: results.has_more,
+            total: results.total,
         })
     }
 
     pub async fn update_elem(
-        db: &Database,
+        db: &dyn Storage,
         src_id: &str,
         elem_id: &str,
         new_elem: &Elem::UpdateReq,
+        if_match: Option<i64>,
+        user: &Uuid,
     ) -> Result<SingleDailyItemRes<Elem::Res>> {
-        let result = base_array::update_elem::<Self>(db, src_id, elem_id, new_elem).await?;
+        let result =
+            base_array::update_elem::<Self>(db, src_id, elem_id, new_elem, if_match, user)
+                .await?;
         Ok(SingleDailyItemRes {
             status: "success",
             data: DailyItemData { item: result },
         })
     }
 
-    pub async fn remove_elem(db: &Database, src_id: &str, elem_id: &str) -> Result<()> {
-        base_array::remove_elem::<Self>(db, src_id, elem_id).await?;
+    pub async fn move_elem(
+        db: &dyn Storage,
+        src_id: &str,
+        elem_id: &str,
+        new_index: usize,
+        user: &Uuid,
+    ) -> Result<SingleDailyItemRes<Elem::Res>> {
+        let result = base_array::move_elem::<Self>(db, src_id, elem_id, new_index, user).await?;
+        Ok(SingleDailyItemRes {
+            status: "success",
+            data: DailyItemData { item: result },
+        })
+    }
+
+    // 다른 날짜의 daily 문서로 원소를 옮김.
+    pub async fn transfer_elem(
+        db: &dyn Storage,
+        src_id: &str,
+        dst_id: &str,
+        elem_id: &str,
+        user: &Uuid,
+    ) -> Result<SingleDailyItemRes<Elem::Res>> {
+        let result = base_array::transfer_elem::<Self>(db, src_id, dst_id, elem_id, user).await?;
+        Ok(SingleDailyItemRes {
+            status: "success",
+            data: DailyItemData { item: result },
+        })
+    }
+
+    pub async fn remove_elem(
+        db: &dyn Storage,
+        src_id: &str,
+        elem_id: &str,
+        user: &Uuid,
+    ) -> Result<()> {
+        base_array::remove_elem::<Self>(db, src_id, elem_id, user).await?;
         Ok(())
     }
 }
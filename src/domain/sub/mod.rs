@@ -1,10 +1,13 @@
 pub mod chat;
 pub mod daily_item;
-//pub mod property;
-// pub mod note_block;
-// pub mod note_propV;
-// pub mod note_page;
+pub mod property;
+pub mod note_block;
+#[allow(non_snake_case)]
+pub mod note_propV;
+pub mod note_page;
 pub mod schedule_item;
 
 pub mod habit_record;
+pub mod recurrence;
 pub mod tag;
+pub mod timer;
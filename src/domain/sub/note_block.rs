@@ -1,12 +1,13 @@
 use mongodb::bson::oid::ObjectId;
-use mongodb::Database;
+use uuid::Uuid;
 use serde::{Deserialize, Serialize};
 
 use crate::domain::error::Result;
 
 use crate::domain::repo::base_array::{self, MongoArrayRepo};
+use crate::domain::repo::storage::Storage;
 use crate::domain::note::NoteModel;
-use crate::infra::types::BlockType;
+use crate::infra::types::{BlockType, PageOptions};
 use crate::interface::dto::sub::note_block::req::{CreateBlockReq, UpdateBlockReq};
 use crate::interface::dto::sub::note_block::res::*;
 
@@ -16,6 +17,7 @@ pub struct BlockModel {
     pub id: ObjectId,
     pub src_task_id: ObjectId,
     pub block_type: BlockType,
+    #[serde(default)]
     pub body: String,
 }
 
@@ -39,7 +41,7 @@ impl MongoArrayRepo for BlockService {
     type CreateElemReq = CreateBlockReq;
     type ElemRes = BlockRes;
 
-    const COLL_NAME: &'static str = "tasks";
+    const COLL_NAME: &'static str = "notes";
     const ARR_NAME: &'static str = "blocks";
 
     fn convert_doc_to_response(doc: &BlockModel) -> Result<Self::ElemRes> {
@@ -49,11 +51,12 @@ impl MongoArrayRepo for BlockService {
 
 impl BlockService {
     pub async fn get_block(
-        db: &Database,
+        db: &dyn Storage,
         category_id: &str,
         prop_id: &str,
+        user: &Uuid,
     ) -> Result<SingleBlockRes> {
-        let result = base_array::get_elem::<Self>(db, category_id, prop_id).await?;
+        let result = base_array::get_elem::<Self>(db, category_id, prop_id, user).await?;
         Ok(SingleBlockRes {
             status: "success",
             data: BlockData { block: result },
@@ -61,19 +64,26 @@ impl BlockService {
     }
 
     pub async fn add_block(
-        db: &Database,
+        db: &dyn Storage,
         category_id: &str,
         new_prop: &CreateBlockReq,
+        user: &Uuid,
     ) -> Result<SingleBlockRes> {
-        let result = base_array::add_elem::<Self>(db, category_id, new_prop, None).await?;
+        let result = base_array::add_elem::<Self>(db, category_id, new_prop, user).await?;
         Ok(SingleBlockRes {
             status: "success",
             data: BlockData { block: result },
         })
     }
 
-    pub async fn fetch_blocks(db: &Database, category_id: &str) -> Result<BlockListRes> {
-        let results = base_array::fetch_elems::<Self>(db, category_id, None, None).await?;
+    pub async fn fetch_blocks(
+        db: &dyn Storage,
+        category_id: &str,
+        user: &Uuid,
+    ) -> Result<BlockListRes> {
+        let results = base_array::fetch_elems::<Self>(db, category_id, &PageOptions::default(), user)
+            .await?
+            .items;
         Ok(BlockListRes {
             status: "success",
             results: results.len(),
@@ -82,20 +92,164 @@ impl BlockService {
     }
 
     pub async fn update_block(
-        db: &Database,
+        db: &dyn Storage,
         category_id: &str,
         prop_id: &str,
         new_prop: &UpdateBlockReq,
+        user: &Uuid,
     ) -> Result<SingleBlockRes> {
-        let result = base_array::update_elem::<Self>(db, category_id, prop_id, new_prop).await?;
+        let result =
+            base_array::update_elem::<Self>(db, category_id, prop_id, new_prop, None, user).await?;
         Ok(SingleBlockRes {
             status: "success",
             data: BlockData { block: result },
         })
     }
 
-    pub async fn remove_block(db: &Database, category_id: &str, prop_id: &str) -> Result<()> {
-        base_array::remove_elem::<Self>(db, category_id, prop_id).await?;
+    pub async fn move_block(
+        db: &dyn Storage,
+        note_id: &str,
+        block_id: &str,
+        new_index: usize,
+        user: &Uuid,
+    ) -> Result<SingleBlockRes> {
+        let result = base_array::move_elem::<Self>(db, note_id, block_id, new_index, user).await?;
+        Ok(SingleBlockRes {
+            status: "success",
+            data: BlockData { block: result },
+        })
+    }
+
+    // 다른 note로 block을 옮김.
+    pub async fn transfer_block(
+        db: &dyn Storage,
+        note_id: &str,
+        dst_note_id: &str,
+        block_id: &str,
+        user: &Uuid,
+    ) -> Result<SingleBlockRes> {
+        let result =
+            base_array::transfer_elem::<Self>(db, note_id, dst_note_id, block_id, user).await?;
+        Ok(SingleBlockRes {
+            status: "success",
+            data: BlockData { block: result },
+        })
+    }
+
+    pub async fn remove_block(
+        db: &dyn Storage,
+        category_id: &str,
+        prop_id: &str,
+        user: &Uuid,
+    ) -> Result<()> {
+        base_array::remove_elem::<Self>(db, category_id, prop_id, user).await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::category::CategoryService;
+    use crate::domain::error::Error::*;
+    use crate::domain::note::NoteService;
+    use crate::infra::db::MongoDB;
+    use crate::interface::dto::{category::req::CreateCategoryReq, note::req::CreateNoteReq};
+
+    const USER: Uuid = Uuid::from_bytes([0; 16]);
+    const OTHER_USER: Uuid = Uuid::from_bytes([1; 16]);
+
+    async fn create_note(db: &dyn Storage, user: &Uuid) -> String {
+        let body = CreateCategoryReq {
+            name: "Work".to_string(),
+            color: "Red".to_string(),
+        };
+        let category = CategoryService::create_category(db, &body, user)
+            .await
+            .unwrap()
+            .data
+            .category;
+        let mut body = CreateNoteReq {
+            title: "meeting".to_string(),
+            category_id: category.id,
+            category_color: category.color,
+            category_name: category.name,
+            prop_values: None,
+            pages: None,
+            parent_id: None,
+            chat_type: None,
+        };
+        NoteService::create_note(db, &mut body, user)
+            .await
+            .unwrap()
+            .data
+            .note
+            .id
+    }
+
+    fn new_block() -> CreateBlockReq {
+        CreateBlockReq {
+            src_task_id: ObjectId::new().to_hex(),
+            block_type: BlockType::Editor,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_block_of_other_user() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let note_id = create_note(db, &USER).await;
+        let block_id = BlockService::add_block(db, &note_id, &new_block(), &USER)
+            .await
+            .unwrap()
+            .data
+            .block
+            .id;
+
+        let res = BlockService::get_block(db, &note_id, &block_id, &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+        let res = BlockService::fetch_blocks(db, &note_id, &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+        let res = BlockService::add_block(db, &note_id, &new_block(), &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+
+        let body = UpdateBlockReq {
+            src_task_id: None,
+            block_type: Some(BlockType::Code),
+            body: None,
+        };
+        let res = BlockService::update_block(db, &note_id, &block_id, &body, &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+        let res = BlockService::move_block(db, &note_id, &block_id, 0, &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+        let res = BlockService::remove_block(db, &note_id, &block_id, &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+
+        let res = BlockService::fetch_blocks(db, &note_id, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.results, 1);
+        claim::assert_matches!(&res.blocks[0].block_type, BlockType::Editor);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_block_to_other_user() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let note_id = create_note(db, &USER).await;
+        let other_note_id = create_note(db, &OTHER_USER).await;
+        let block_id = BlockService::add_block(db, &note_id, &new_block(), &USER)
+            .await
+            .unwrap()
+            .data
+            .block
+            .id;
+
+        // 다른 user의 note로 옮길 수 없고, 원래 note에 그대로 남음
+        let res =
+            BlockService::transfer_block(db, &note_id, &other_note_id, &block_id, &USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+        let res = BlockService::get_block(db, &note_id, &block_id, &USER).await;
+        claim::assert_ok!(res);
+    }
+}
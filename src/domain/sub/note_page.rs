@@ -35,7 +35,7 @@ impl PageModel {
             id: ObjectId::new(),
             user: original_note.user,
             title: "New Subnote".to_string(),
-            connected_note: original_note.id.clone(),
+            connected_note: original_note.id,
             contents: Vec::new(),
             createdAt: Utc::now(),
             updatedAt: Utc::now(),
@@ -1,18 +1,22 @@
-use mongodb::bson::oid::ObjectId;
-use mongodb::bson::Document;
-use mongodb::Database;
+use mongodb::bson::{self, oid::ObjectId, Document};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use uuid::Uuid;
 
 use crate::domain::error::{Error::*, Result};
+use crate::infra::db::error::Error as DBError;
 
 use crate::domain::repo::base_array::{self, MongoArrayRepo};
+use crate::domain::repo::storage::Storage;
 use crate::domain::note::NoteModel;
-use crate::infra::types::{PropValueType, PropertyType};
+use crate::infra::types::{PageOptions, PropValueType, PropertyType};
 use crate::interface::dto::sub::note_propV::req::*;
 use crate::interface::dto::sub::note_propV::res::*;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PropValueModel {
+    // category의 property _id를 그대로 사용하므로, 배열 원소의 _id가 됨.
+    #[serde(rename = "_id")]
     pub prop_id: ObjectId,
     pub prop_name: String,
     pub prop_type: PropertyType,
@@ -63,7 +67,7 @@ impl MongoArrayRepo for PropValueService {
     type CreateElemReq = CreatePropValueReq;
     type ElemRes = PropValueRes;
 
-    const COLL_NAME: &'static str = "categories";
+    const COLL_NAME: &'static str = "notes";
     const ARR_NAME: &'static str = "prop_values";
 
     fn convert_doc_to_response(doc: &PropValueModel) -> Result<Self::ElemRes> {
@@ -71,17 +75,25 @@ impl MongoArrayRepo for PropValueService {
     }
 
     fn create_doc(body: &CreatePropValueReq) -> Result<Document> {
-        todo!()
+        let prop_id = ObjectId::from_str(&body.prop_id).map_err(DBError::MongoGetOidError)?;
+        let prop_value = PropValueModel::new(
+            prop_id,
+            body.prop_name.clone(),
+            body.prop_type.clone(),
+            body.value.clone(),
+        )?;
+        bson::to_document(&prop_value).map_err(|e| DB(DBError::MongoSerializeBsonError(e)))
     }
 }
 
 impl PropValueService {
     pub async fn get_propV(
-        db: &Database,
-        category_id: &str,
+        db: &dyn Storage,
+        note_id: &str,
         prop_id: &str,
+        user: &Uuid,
     ) -> Result<SinglePropValueRes> {
-        let result = base_array::get_elem::<Self>(db, category_id, prop_id).await?;
+        let result = base_array::get_elem::<Self>(db, note_id, prop_id, user).await?;
         Ok(SinglePropValueRes {
             status: "success",
             data: PropValueData { propV: result },
@@ -89,21 +101,28 @@ impl PropValueService {
     }
 
     pub async fn add_propV(
-        db: &Database,
-        category_id: &str,
+        db: &dyn Storage,
+        note_id: &str,
         mut new_propV: CreatePropValueReq,
+        user: &Uuid,
     ) -> Result<SinglePropValueRes> {
         new_propV.value = validate_value(&new_propV.prop_type, &new_propV.value)?;
 
-        let result = base_array::add_elem::<Self>(db, category_id, &new_propV, None).await?;
+        let result = base_array::add_elem::<Self>(db, note_id, &new_propV, user).await?;
         Ok(SinglePropValueRes {
             status: "success",
             data: PropValueData { propV: result },
         })
     }
 
-    pub async fn fetch_propVs(db: &Database, category_id: &str) -> Result<PropValueListRes> {
-        let results = base_array::fetch_elems::<Self>(db, category_id, None, None).await?;
+    pub async fn fetch_propVs(
+        db: &dyn Storage,
+        note_id: &str,
+        user: &Uuid,
+    ) -> Result<PropValueListRes> {
+        let results = base_array::fetch_elems::<Self>(db, note_id, &PageOptions::default(), user)
+            .await?
+            .items;
         Ok(PropValueListRes {
             status: "success",
             results: results.len(),
@@ -112,20 +131,39 @@ impl PropValueService {
     }
 
     pub async fn update_propV(
-        db: &Database,
-        category_id: &str,
+        db: &dyn Storage,
+        note_id: &str,
         prop_id: &str,
         new_prop: &UpdatePropValueReq,
+        user: &Uuid,
     ) -> Result<SinglePropValueRes> {
-        let result = base_array::update_elem::<Self>(db, category_id, prop_id, new_prop).await?;
+        // 값만 바꾸는 경우에도 저장된 prop_type에 맞는지 확인함.
+        if let Some(values) = &new_prop.values {
+            let prop_type = match &new_prop.prop_type {
+                Some(prop_type) => prop_type.clone(),
+                None => {
+                    let stored = Self::get_propV(db, note_id, prop_id, user).await?;
+                    stored.data.propV.prop_type
+                }
+            };
+            validate_value(&prop_type, values)?;
+        }
+
+        let result =
+            base_array::update_elem::<Self>(db, note_id, prop_id, new_prop, None, user).await?;
         Ok(SinglePropValueRes {
             status: "success",
             data: PropValueData { propV: result },
         })
     }
 
-    pub async fn remove_propV(db: &Database, category_id: &str, prop_id: &str) -> Result<()> {
-        base_array::remove_elem::<Self>(db, category_id, prop_id).await?;
+    pub async fn remove_propV(
+        db: &dyn Storage,
+        note_id: &str,
+        prop_id: &str,
+        user: &Uuid,
+    ) -> Result<()> {
+        base_array::remove_elem::<Self>(db, note_id, prop_id, user).await?;
         Ok(())
     }
 }
@@ -1,14 +1,16 @@
 use mongodb::bson::oid::ObjectId;
-use mongodb::Database;
+use uuid::Uuid;
 use serde::{Deserialize, Serialize};
 
-use crate::infra::types::PropertyType;
+use crate::infra::types::{PageOptions, PropertyType};
 use crate::interface::dto::sub::property::{req::*, res::*};
 
 use crate::domain::{
     category::CategoryModel,
     error::Result,
     repo::base_array::{self, MongoArrayRepo},
+    repo::index::{IndexDef, IndexKind::*},
+    repo::storage::Storage,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -52,6 +54,10 @@ impl MongoArrayRepo for PropertyService {
 
     const COLL_NAME: &'static str = "categories";
     const ARR_NAME: &'static str = "props";
+    const ELEM_INDEXES: &'static [IndexDef] = &[IndexDef {
+        keys: &[("prop_type", Asc)],
+        ..IndexDef::DEFAULT
+    }];
 
     fn convert_doc_to_response(doc: &PropertyModel) -> Result<Self::ElemRes> {
         Ok(PropertyRes::from_model(doc))
@@ -60,11 +66,12 @@ impl MongoArrayRepo for PropertyService {
 
 impl PropertyService {
     pub async fn get_property(
-        db: &Database,
+        db: &dyn Storage,
         category_id: &str,
         prop_id: &str,
+        user: &Uuid,
     ) -> Result<SinglePropertyRes> {
-        let result = base_array::get_elem::<Self>(db, category_id, prop_id).await?;
+        let result = base_array::get_elem::<Self>(db, category_id, prop_id, user).await?;
         Ok(SinglePropertyRes {
             status: "success",
             data: PropertyData { prop: result },
@@ -72,20 +79,26 @@ impl PropertyService {
     }
 
     pub async fn add_property(
-        db: &Database,
+        db: &dyn Storage,
         category_id: &str,
         new_prop: &CreatePropertyReq,
+        user: &Uuid,
     ) -> Result<SinglePropertyRes> {
-        let result =
-            base_array::add_elem::<Self>(db, category_id, new_prop, Some("prop_type")).await?;
+        let result = base_array::add_elem::<Self>(db, category_id, new_prop, user).await?;
         Ok(SinglePropertyRes {
             status: "success",
             data: PropertyData { prop: result },
         })
     }
 
-    pub async fn fetch_properties(db: &Database, category_id: &str) -> Result<PropertyListRes> {
-        let results = base_array::fetch_elems::<Self>(db, category_id, None, None).await?;
+    pub async fn fetch_properties(
+        db: &dyn Storage,
+        category_id: &str,
+        user: &Uuid,
+    ) -> Result<PropertyListRes> {
+        let results = base_array::fetch_elems::<Self>(db, category_id, &PageOptions::default(), user)
+            .await?
+            .items;
         Ok(PropertyListRes {
             status: "success",
             results: results.len(),
@@ -94,20 +107,175 @@ impl PropertyService {
     }
 
     pub async fn update_property(
-        db: &Database,
+        db: &dyn Storage,
         category_id: &str,
         prop_id: &str,
         new_prop: &UpdatePropertyReq,
+        user: &Uuid,
+    ) -> Result<SinglePropertyRes> {
+        let result =
+            base_array::update_elem::<Self>(db, category_id, prop_id, new_prop, None, user).await?;
+        Ok(SinglePropertyRes {
+            status: "success",
+            data: PropertyData { prop: result },
+        })
+    }
+
+    pub async fn move_property(
+        db: &dyn Storage,
+        category_id: &str,
+        prop_id: &str,
+        new_index: usize,
+        user: &Uuid,
     ) -> Result<SinglePropertyRes> {
-        let result = base_array::update_elem::<Self>(db, category_id, prop_id, new_prop).await?;
+        let result =
+            base_array::move_elem::<Self>(db, category_id, prop_id, new_index, user).await?;
+        Ok(SinglePropertyRes {
+            status: "success",
+            data: PropertyData { prop: result },
+        })
+    }
+
+    // 다른 category로 property를 옮김.
+    pub async fn transfer_property(
+        db: &dyn Storage,
+        category_id: &str,
+        dst_category_id: &str,
+        prop_id: &str,
+        user: &Uuid,
+    ) -> Result<SinglePropertyRes> {
+        let result =
+            base_array::transfer_elem::<Self>(db, category_id, dst_category_id, prop_id, user)
+                .await?;
         Ok(SinglePropertyRes {
             status: "success",
             data: PropertyData { prop: result },
         })
     }
 
-    pub async fn remove_property(db: &Database, category_id: &str, prop_id: &str) -> Result<()> {
-        base_array::remove_elem::<Self>(db, category_id, prop_id).await?;
+    pub async fn remove_property(
+        db: &dyn Storage,
+        category_id: &str,
+        prop_id: &str,
+        user: &Uuid,
+    ) -> Result<()> {
+        base_array::remove_elem::<Self>(db, category_id, prop_id, user).await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::category::CategoryService;
+    use crate::domain::error::Error::*;
+    use crate::infra::db::MongoDB;
+    use crate::interface::dto::category::req::CreateCategoryReq;
+
+    const USER: Uuid = Uuid::from_bytes([0; 16]);
+    const OTHER_USER: Uuid = Uuid::from_bytes([1; 16]);
+
+    async fn create_category(db: &dyn Storage, user: &Uuid) -> String {
+        let body = CreateCategoryReq {
+            name: "Work".to_string(),
+            color: "Red".to_string(),
+        };
+        CategoryService::create_category(db, &body, user)
+            .await
+            .unwrap()
+            .data
+            .category
+            .id
+    }
+
+    async fn add_property(db: &dyn Storage, category_id: &str) -> String {
+        let body = CreatePropertyReq {
+            name: "Deadline".to_string(),
+            prop_type: PropertyType::DateTime,
+        };
+        PropertyService::add_property(db, category_id, &body, &USER)
+            .await
+            .unwrap()
+            .data
+            .prop
+            .id
+    }
+
+    #[tokio::test]
+    async fn test_property_of_other_user() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let category_id = create_category(db, &USER).await;
+        let prop_id = add_property(db, &category_id).await;
+
+        let res = PropertyService::get_property(db, &category_id, &prop_id, &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+        let res = PropertyService::fetch_properties(db, &category_id, &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+
+        let body = CreatePropertyReq {
+            name: "Owner".to_string(),
+            prop_type: PropertyType::Text,
+        };
+        let res = PropertyService::add_property(db, &category_id, &body, &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+
+        let body = UpdatePropertyReq {
+            name: Some("Due".to_string()),
+            prop_type: None,
+            options: None,
+        };
+        let res =
+            PropertyService::update_property(db, &category_id, &prop_id, &body, &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+        let res = PropertyService::move_property(db, &category_id, &prop_id, 1, &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+        let res = PropertyService::remove_property(db, &category_id, &prop_id, &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+
+        let res = PropertyService::fetch_properties(db, &category_id, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.results, 2);
+        assert_eq!(res.props[0].name, "Deadline");
+    }
+
+    #[tokio::test]
+    async fn test_transfer_property_to_other_user() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let category_id = create_category(db, &USER).await;
+        let other_category_id = create_category(db, &OTHER_USER).await;
+        let prop_id = add_property(db, &category_id).await;
+
+        // 다른 user의 category로 옮길 수 없고, 원래 category에 그대로 남음
+        let res = PropertyService::transfer_property(
+            db,
+            &category_id,
+            &other_category_id,
+            &prop_id,
+            &USER,
+        )
+        .await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+        let res = PropertyService::get_property(db, &category_id, &prop_id, &USER).await;
+        claim::assert_ok!(res);
+
+        // 다른 user의 category에서 가져올 수도 없음
+        let other_prop_id = PropertyService::fetch_properties(db, &other_category_id, &OTHER_USER)
+            .await
+            .unwrap()
+            .props[0]
+            .id
+            .clone();
+        let res = PropertyService::transfer_property(
+            db,
+            &other_category_id,
+            &category_id,
+            &other_prop_id,
+            &USER,
+        )
+        .await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+    }
+}
@@ -0,0 +1,549 @@
+use std::fmt;
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::error::{Error::*, Result};
+
+// 한 번의 expand에서 만들 수 있는 최대 occurrence 수
+const MAX_OCCURRENCES: usize = 5000;
+
+/// task에 저장되는 반복 규칙(RFC 5545 RRULE)과 예외.
+/// 규칙의 기준일(DTSTART)은 task의 start_date.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecurrenceModel {
+    pub rrule: String,
+    // 건너뛰는 occurrence(EXDATE)
+    #[serde(default)]
+    pub exdates: Vec<NaiveDate>,
+    // occurrence 하나만 바꾼 내용
+    #[serde(default)]
+    pub overrides: Vec<OccurrenceOverride>,
+}
+
+/// date는 규칙에 따른 원래 날짜(RECURRENCE-ID)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OccurrenceOverride {
+    pub date: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress_rate: Option<u8>,
+}
+
+impl OccurrenceOverride {
+    pub fn new(date: NaiveDate) -> Self {
+        Self {
+            date,
+            title: None,
+            start_date: None,
+            end_date: None,
+            progress_rate: None,
+        }
+    }
+
+    /// 나중에 바꾼 값이 우선함
+    pub fn merge(&mut self, other: OccurrenceOverride) {
+        if other.title.is_some() {
+            self.title = other.title;
+        }
+        if other.start_date.is_some() {
+            self.start_date = other.start_date;
+        }
+        if other.end_date.is_some() {
+            self.end_date = other.end_date;
+        }
+        if other.progress_rate.is_some() {
+            self.progress_rate = other.progress_rate;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// 지원하는 RRULE: FREQ=DAILY|WEEKLY|MONTHLY, INTERVAL, BYDAY(MONTHLY에서는 1MO, -1FR 같은 n번째 요일),
+/// BYMONTHDAY, UNTIL, COUNT. WKST는 MO만 지원함.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RRule {
+    pub freq: Freq,
+    pub interval: u32,
+    // (n번째, 요일). n이 0이면 모든 해당 요일
+    pub by_day: Vec<(i8, Weekday)>,
+    pub by_month_day: Vec<i8>,
+    pub until: Option<NaiveDate>,
+    pub count: Option<u32>,
+}
+
+impl RRule {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let raw = raw.trim();
+        let raw = raw.strip_prefix("RRULE:").unwrap_or(raw);
+
+        let mut freq = None;
+        let mut rule = RRule {
+            freq: Freq::Daily,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            until: None,
+            count: None,
+        };
+
+        for part in raw.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("'{}' is not KEY=VALUE", part)))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Freq::Daily,
+                        "WEEKLY" => Freq::Weekly,
+                        "MONTHLY" => Freq::Monthly,
+                        _ => return Err(invalid(format!("unsupported FREQ '{}'", value))),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(|| invalid(format!("invalid INTERVAL '{}'", value)))?
+                }
+                "COUNT" => {
+                    rule.count = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|count| *count > 0)
+                            .ok_or_else(|| invalid(format!("invalid COUNT '{}'", value)))?,
+                    )
+                }
+                "UNTIL" => rule.until = Some(parse_date(value)?),
+                "BYDAY" => {
+                    rule.by_day = value.split(',').map(parse_by_day).collect::<Result<_>>()?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = value
+                        .split(',')
+                        .map(parse_month_day)
+                        .collect::<Result<_>>()?
+                }
+                "WKST" if value.eq_ignore_ascii_case("MO") => {}
+                _ => return Err(invalid(format!("unsupported rule part '{}'", part))),
+            }
+        }
+
+        rule.freq = freq.ok_or_else(|| invalid("FREQ is required".to_string()))?;
+        if rule.until.is_some() && rule.count.is_some() {
+            return Err(invalid(
+                "UNTIL and COUNT can not be used together".to_string(),
+            ));
+        }
+        if rule.freq != Freq::Monthly && rule.by_day.iter().any(|(n, _)| *n != 0) {
+            return Err(invalid(
+                "BYDAY with ordinal is only allowed in MONTHLY".to_string(),
+            ));
+        }
+        if rule.freq == Freq::Weekly && !rule.by_month_day.is_empty() {
+            return Err(invalid("BYMONTHDAY is not allowed in WEEKLY".to_string()));
+        }
+        Ok(rule)
+    }
+
+    /// dtstart부터 규칙에 따른 날짜를 순서대로 만들어, to 이하인 것만 반환.
+    /// COUNT는 dtstart부터 센 횟수이므로 앞의 occurrence도 모두 계산함.
+    pub fn dates_until(&self, dtstart: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        let last = match self.until {
+            Some(until) => until.min(to),
+            None => to,
+        };
+        let mut dates = Vec::new();
+
+        for period in 0.. {
+            let Some(candidates) = self.period_dates(dtstart, period) else {
+                break;
+            };
+            // 기간의 시작이 last를 넘었으면 이후 기간에는 날짜가 없음
+            if candidates.period_start > last {
+                break;
+            }
+            for date in candidates.dates {
+                if date < dtstart || date > last {
+                    continue;
+                }
+                if self
+                    .count
+                    .is_some_and(|count| dates.len() >= count as usize)
+                {
+                    return dates;
+                }
+                dates.push(date);
+            }
+            if self
+                .count
+                .is_some_and(|count| dates.len() >= count as usize)
+                || dates.len() >= MAX_OCCURRENCES
+            {
+                break;
+            }
+        }
+        dates
+    }
+
+    /// period번째 기간(일/주/월)에 해당하는 후보 날짜(정렬됨)
+    fn period_dates(&self, dtstart: NaiveDate, period: u64) -> Option<PeriodDates> {
+        let step = period.checked_mul(self.interval as u64)?;
+        match self.freq {
+            Freq::Daily => {
+                let date = dtstart.checked_add_days(Days::new(step))?;
+                let dates = if self.matches_by_day(date) && self.matches_by_month_day(date) {
+                    vec![date]
+                } else {
+                    vec![]
+                };
+                Some(PeriodDates {
+                    period_start: date,
+                    dates,
+                })
+            }
+            Freq::Weekly => {
+                let week_start = dtstart
+                    .checked_sub_days(Days::new(dtstart.weekday().num_days_from_monday() as u64))?
+                    .checked_add_days(Days::new(step.checked_mul(7)?))?;
+                let mut dates: Vec<NaiveDate> = if self.by_day.is_empty() {
+                    vec![week_start + Days::new(dtstart.weekday().num_days_from_monday() as u64)]
+                } else {
+                    self.by_day
+                        .iter()
+                        .map(|(_, weekday)| {
+                            week_start + Days::new(weekday.num_days_from_monday() as u64)
+                        })
+                        .collect()
+                };
+                dates.sort();
+                dates.dedup();
+                Some(PeriodDates {
+                    period_start: week_start,
+                    dates,
+                })
+            }
+            Freq::Monthly => {
+                let month_start = dtstart
+                    .with_day(1)?
+                    .checked_add_months(Months::new(u32::try_from(step).ok()?))?;
+                let days_in_month = days_in_month(month_start);
+                let mut dates: Vec<NaiveDate> = if !self.by_month_day.is_empty() {
+                    self.by_month_day
+                        .iter()
+                        .filter_map(|day| {
+                            let day = match *day {
+                                d if d > 0 => d as u32,
+                                d => (days_in_month as i32 + d as i32 + 1).try_into().ok()?,
+                            };
+                            month_start.with_day(day)
+                        })
+                        .filter(|date| self.matches_by_day(*date))
+                        .collect()
+                } else if !self.by_day.is_empty() {
+                    (1..=days_in_month)
+                        .filter_map(|day| month_start.with_day(day))
+                        .filter(|date| self.matches_nth_weekday(*date, days_in_month))
+                        .collect()
+                } else {
+                    // 해당 일이 없는 달(예: 31일)은 건너뜀
+                    month_start.with_day(dtstart.day()).into_iter().collect()
+                };
+                dates.sort();
+                dates.dedup();
+                Some(PeriodDates {
+                    period_start: month_start,
+                    dates,
+                })
+            }
+        }
+    }
+
+    fn matches_by_day(&self, date: NaiveDate) -> bool {
+        self.by_day.is_empty() || self.by_day.iter().any(|(_, wd)| *wd == date.weekday())
+    }
+
+    fn matches_by_month_day(&self, date: NaiveDate) -> bool {
+        if self.by_month_day.is_empty() {
+            return true;
+        }
+        let days_in_month = days_in_month(date) as i32;
+        self.by_month_day.iter().any(|day| match *day as i32 {
+            d if d > 0 => d == date.day() as i32,
+            d => days_in_month + d + 1 == date.day() as i32,
+        })
+    }
+
+    // MONTHLY의 BYDAY. 1MO는 첫째 월요일, -1FR은 마지막 금요일
+    fn matches_nth_weekday(&self, date: NaiveDate, days_in_month: u32) -> bool {
+        let from_start = ((date.day() - 1) / 7 + 1) as i8;
+        let from_end = -(((days_in_month - date.day()) / 7 + 1) as i8);
+        self.by_day.iter().any(|(n, weekday)| {
+            *weekday == date.weekday() && (*n == 0 || *n == from_start || *n == from_end)
+        })
+    }
+}
+
+impl fmt::Display for RRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let freq = match self.freq {
+            Freq::Daily => "DAILY",
+            Freq::Weekly => "WEEKLY",
+            Freq::Monthly => "MONTHLY",
+        };
+        write!(f, "FREQ={}", freq)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|(n, weekday)| match n {
+                    0 => weekday_code(*weekday).to_string(),
+                    n => format!("{}{}", n, weekday_code(*weekday)),
+                })
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(|d| d.to_string()).collect();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        Ok(())
+    }
+}
+
+struct PeriodDates {
+    period_start: NaiveDate,
+    dates: Vec<NaiveDate>,
+}
+
+impl RecurrenceModel {
+    pub fn rule(&self) -> Result<RRule> {
+        RRule::parse(&self.rrule)
+    }
+
+    /// from ~ to(미포함) 사이에 걸치는 occurrence의 원래 날짜. exdates는 제외함.
+    /// span은 occurrence 하나의 기간(end_date - start_date, 일).
+    pub fn occurrences(
+        &self,
+        dtstart: NaiveDate,
+        span: u64,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<NaiveDate>> {
+        let rule = self.rule()?;
+        Ok(rule
+            .dates_until(dtstart, to)
+            .into_iter()
+            .filter(|date| *date < to && !self.exdates.contains(date))
+            .filter(|date| *date + Days::new(span) >= from)
+            .collect())
+    }
+
+    pub fn override_of(&self, date: NaiveDate) -> Option<&OccurrenceOverride> {
+        self.overrides.iter().find(|o| o.date == date)
+    }
+
+    /// date 전까지만 남기고, date부터의 규칙과 예외를 반환함(this and following).
+    /// COUNT가 있는 규칙은 남은 횟수를 나눠 가짐.
+    pub fn split_at(&mut self, dtstart: NaiveDate, date: NaiveDate) -> Result<RecurrenceModel> {
+        let mut head = self.rule()?;
+        let mut tail = head.clone();
+
+        match head.count {
+            Some(count) => {
+                let before = head
+                    .dates_until(dtstart, date.pred_opt().unwrap_or(date))
+                    .into_iter()
+                    .filter(|d| *d < date)
+                    .count() as u32;
+                head.count = Some(before.max(1));
+                tail.count = Some(count.saturating_sub(before).max(1));
+            }
+            None => head.until = date.pred_opt(),
+        }
+
+        let (exdates_tail, exdates_head): (Vec<_>, Vec<_>) =
+            self.exdates.drain(..).partition(|d| *d >= date);
+        let (overrides_tail, overrides_head): (Vec<_>, Vec<_>) =
+            self.overrides.drain(..).partition(|o| o.date >= date);
+
+        self.rrule = head.to_string();
+        self.exdates = exdates_head;
+        self.overrides = overrides_head;
+
+        Ok(RecurrenceModel {
+            rrule: tail.to_string(),
+            exdates: exdates_tail,
+            overrides: overrides_tail,
+        })
+    }
+}
+
+fn invalid(msg: String) -> crate::domain::error::Error {
+    InvalidRecurrence(msg)
+}
+
+// UNTIL은 날짜(YYYYMMDD) 혹은 날짜-시간(YYYYMMDDTHHMMSSZ). 날짜 부분만 사용함.
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    let date = value.get(..8).unwrap_or(value);
+    NaiveDate::parse_from_str(date, "%Y%m%d")
+        .map_err(|_| invalid(format!("invalid UNTIL '{}'", value)))
+}
+
+fn parse_month_day(value: &str) -> Result<i8> {
+    value
+        .trim()
+        .parse::<i8>()
+        .ok()
+        .filter(|day| *day != 0 && (-31..=31).contains(day))
+        .ok_or_else(|| invalid(format!("invalid BYMONTHDAY '{}'", value)))
+}
+
+fn parse_by_day(value: &str) -> Result<(i8, Weekday)> {
+    let value = value.trim();
+    let split = value.len().saturating_sub(2);
+    let (n, code) = value.split_at(split);
+    let weekday = match code.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return Err(invalid(format!("invalid BYDAY '{}'", value))),
+    };
+    let n = match n {
+        "" => 0,
+        n => n
+            .parse::<i8>()
+            .ok()
+            .filter(|n| *n != 0 && (-5..=5).contains(n))
+            .ok_or_else(|| invalid(format!("invalid BYDAY '{}'", value)))?,
+    };
+    Ok((n, weekday))
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let first = date.with_day(1).unwrap();
+    let next = first + Months::new(1);
+    (next - first).num_days() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn dates(rrule: &str, dtstart: &str, to: &str) -> Vec<String> {
+        RRule::parse(rrule)
+            .unwrap()
+            .dates_until(date(dtstart), date(to))
+            .iter()
+            .map(|d| d.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_rrule_expansion() {
+        assert_eq!(
+            dates("FREQ=DAILY;INTERVAL=2;COUNT=3", "2024-01-30", "2024-12-31"),
+            ["2024-01-30", "2024-02-01", "2024-02-03"]
+        );
+        // 2024-01-01은 월요일
+        assert_eq!(
+            dates("FREQ=WEEKLY;BYDAY=MO,WE", "2024-01-03", "2024-01-15"),
+            ["2024-01-03", "2024-01-08", "2024-01-10", "2024-01-15"]
+        );
+        assert_eq!(
+            dates(
+                "RRULE:FREQ=MONTHLY;UNTIL=20240501",
+                "2024-01-31",
+                "2024-12-31"
+            ),
+            ["2024-01-31", "2024-03-31"]
+        );
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=-1FR,2MO", "2024-01-01", "2024-02-29"),
+            ["2024-01-08", "2024-01-26", "2024-02-12", "2024-02-23"]
+        );
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYMONTHDAY=-1", "2024-01-15", "2024-03-31"),
+            ["2024-01-31", "2024-02-29", "2024-03-31"]
+        );
+
+        assert!(RRule::parse("FREQ=YEARLY").is_err());
+        assert!(RRule::parse("FREQ=DAILY;COUNT=2;UNTIL=20240101").is_err());
+        assert!(RRule::parse("FREQ=WEEKLY;BYDAY=1MO").is_err());
+        assert_eq!(
+            RRule::parse("FREQ=MONTHLY;BYDAY=-1FR;COUNT=3")
+                .unwrap()
+                .to_string(),
+            "FREQ=MONTHLY;BYDAY=-1FR;COUNT=3"
+        );
+    }
+
+    #[test]
+    fn test_recurrence_split() {
+        let mut recurrence = RecurrenceModel {
+            rrule: "FREQ=DAILY;COUNT=5".to_string(),
+            exdates: vec![date("2024-01-02"), date("2024-01-04")],
+            overrides: vec![],
+        };
+        let tail = recurrence
+            .split_at(date("2024-01-01"), date("2024-01-03"))
+            .unwrap();
+
+        assert_eq!(recurrence.rrule, "FREQ=DAILY;COUNT=2");
+        assert_eq!(recurrence.exdates, [date("2024-01-02")]);
+        assert_eq!(tail.rrule, "FREQ=DAILY;COUNT=3");
+        assert_eq!(tail.exdates, [date("2024-01-04")]);
+        assert_eq!(
+            tail.occurrences(
+                date("2024-01-03"),
+                0,
+                date("2024-01-01"),
+                date("2024-01-31")
+            )
+            .unwrap(),
+            [date("2024-01-03"), date("2024-01-05")]
+        );
+    }
+}
@@ -1,12 +1,14 @@
 use crate::domain::error::Result;
 use crate::domain::repo::base_array::{self, MongoArrayRepo};
+use crate::domain::repo::index::{IndexDef, IndexKind::*};
+use crate::domain::repo::storage::Storage;
 use crate::domain::repo::ElemInfo;
 use crate::domain::schedule::ScheduleModel;
-use crate::infra::types::ScheduleType;
+use crate::infra::types::{PageOptions, ScheduleType};
 use crate::interface::dto::sub::schedule_item::{req::*, res::*};
 use chrono::{DateTime, NaiveDate, NaiveTime, Utc, Weekday};
 use mongodb::bson::oid::ObjectId;
-use mongodb::Database;
+use uuid::Uuid;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
@@ -16,6 +18,8 @@ pub struct ScheduledTask {
     pub title: String,
     pub category_id: ObjectId,
     pub category_color: String,
+    #[serde(default)]
+    pub version: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -24,6 +28,8 @@ pub struct ScheduledEvent {
     pub title: String,
     pub start_date: Option<NaiveDate>,
     pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub version: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -31,6 +37,8 @@ pub struct ScheduledHabit {
     pub habit_id: ObjectId,
     pub name: String,
     pub icon: String,
+    #[serde(default)]
+    pub version: i64,
 }
 
 #[allow(non_snake_case)]
@@ -41,6 +49,8 @@ pub struct ScheduledAt {
     pub weekday: Weekday, //Mon=0
     pub startAt: Option<NaiveTime>,
     pub endAt: Option<NaiveTime>,
+    #[serde(default)]
+    pub version: i64,
 }
 
 impl ElemInfo for ScheduledTask {
@@ -78,6 +88,10 @@ impl ElemInfo for ScheduledHabit {
 
 impl ElemInfo for ScheduledAt {
     const ARR_NAME: &'static str = "scheduled_times";
+    const ELEM_INDEXES: &'static [IndexDef] = &[IndexDef {
+        keys: &[("item_type", Asc)],
+        ..IndexDef::DEFAULT
+    }];
     type UpdateReq = UpdateScheduledAtReq;
     type CreateReq = CreateScheduledAtReq;
     type Res = ScheduledAtRes;
@@ -105,6 +119,7 @@ where
 
     const COLL_NAME: &'static str = "schedules";
     const ARR_NAME: &'static str = Elem::ARR_NAME;
+    const ELEM_INDEXES: &'static [IndexDef] = Elem::ELEM_INDEXES;
 
     fn convert_doc_to_response(doc: &Self::ElemModel) -> Result<Self::ElemRes> {
         Ok(Elem::convert_to_res(doc))
@@ -116,11 +131,12 @@ where
     Elem: DeserializeOwned + Serialize + Unpin + Send + Sync + ElemInfo,
 {
     pub async fn get_elem(
-        db: &Database,
+        db: &dyn Storage,
         src_id: &str,
         elem_id: &str,
+        user: &Uuid,
     ) -> Result<SingleScheduleItemRes<Elem::Res>> {
-        let result = base_array::get_elem::<Self>(db, src_id, elem_id).await?;
+        let result = base_array::get_elem::<Self>(db, src_id, elem_id, user).await?;
         Ok(SingleScheduleItemRes {
             status: "success",
             data: ScheduleItemData { item: result },
@@ -128,11 +144,12 @@ where
     }
 
     pub async fn add_elem(
-        db: &Database,
+        db: &dyn Storage,
         src_id: &str,
         new_elem: &Elem::CreateReq,
+        user: &Uuid,
     ) -> Result<SingleScheduleItemRes<Elem::Res>> {
-        let result = base_array::add_elem::<Self>(db, src_id, new_elem, Some("item_type")).await?;
+        let result = base_array::add_elem::<Self>(db, src_id, new_elem, user).await?;
         Ok(SingleScheduleItemRes {
             status: "success",
             data: ScheduleItemData { item: result },
@@ -140,34 +157,46 @@ where
     }
 
     pub async fn fetch_elems(
-        db: &Database,
+        db: &dyn Storage,
         src_id: &str,
-        limit: i64,
-        page: i64,
+        page_opts: PageOptions,
+        user: &Uuid,
     ) -> Result<ScheduleItemListRes<Elem::Res>> {
-        let results = base_array::fetch_elems::<Self>(db, src_id, Some(limit), Some(page)).await?;
+        let results = base_array::fetch_elems::<Self>(db, src_id, &page_opts, user).await?;
         Ok(ScheduleItemListRes {
             status: "success",
-            results: results.len(),
-            items: results,
+            results: results.items.len(),
+            items: results.items,
+            next_cursor: results.next_cursor,
+            has_more: results.has_more,
+            total: results.total,
         })
     }
 
     pub async fn update_elem(
-        db: &Database,
+        db: &dyn Storage,
         src_id: &str,
         elem_id: &str,
         new_elem: &Elem::UpdateReq,
+        if_match: Option<i64>,
+        user: &Uuid,
     ) -> Result<SingleScheduleItemRes<Elem::Res>> {
-        let result = base_array::update_elem::<Self>(db, src_id, elem_id, new_elem).await?;
+        let result =
+            base_array::update_elem::<Self>(db, src_id, elem_id, new_elem, if_match, user)
+                .await?;
         Ok(SingleScheduleItemRes {
             status: "success",
             data: ScheduleItemData { item: result },
         })
     }
 
-    pub async fn remove_elem(db: &Database, src_id: &str, elem_id: &str) -> Result<()> {
-        base_array::remove_elem::<Self>(db, src_id, elem_id).await?;
+    pub async fn remove_elem(
+        db: &dyn Storage,
+        src_id: &str,
+        elem_id: &str,
+        user: &Uuid,
+    ) -> Result<()> {
+        base_array::remove_elem::<Self>(db, src_id, elem_id, user).await?;
         Ok(())
     }
 }
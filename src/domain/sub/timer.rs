@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::infra::types::TimerState;
+
+/// task에 저장되는 실행/일시정지 중인 timer.
+/// 멈추면 daily의 timer_results에 TimerResultModel로 남음.
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TaskTimerModel {
+    pub state: TimerState,
+    pub category_id: ObjectId,
+    pub category_color: String,
+    // 처음 시작한 시각
+    pub startAt: DateTime<Utc>,
+    // Running일 때 마지막으로 시작/재개한 시각
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resumedAt: Option<DateTime<Utc>>,
+    // 마지막 일시정지까지 쌓인 시간(초)
+    pub elapsed_secs: i64,
+}
+
+impl TaskTimerModel {
+    pub fn new(category_id: ObjectId, category_color: String, now: DateTime<Utc>) -> Self {
+        Self {
+            state: TimerState::Running,
+            category_id,
+            category_color,
+            startAt: now,
+            resumedAt: Some(now),
+            elapsed_secs: 0,
+        }
+    }
+
+    /// 일시정지한 시간을 뺀, now까지 실행된 시간(초)
+    pub fn focus_secs(&self, now: DateTime<Utc>) -> i64 {
+        let running = self
+            .resumedAt
+            .map_or(0, |resumed_at| (now - resumed_at).num_seconds().max(0));
+        self.elapsed_secs + running
+    }
+
+    pub fn pause(&mut self, now: DateTime<Utc>) {
+        self.elapsed_secs = self.focus_secs(now);
+        self.resumedAt = None;
+        self.state = TimerState::Paused;
+    }
+
+    pub fn resume(&mut self, now: DateTime<Utc>) {
+        self.resumedAt = Some(now);
+        self.state = TimerState::Running;
+    }
+}
@@ -0,0 +1,1103 @@
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use mongodb::bson::{self, doc, oid::ObjectId, Bson, Document};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::domain::daily::DailyModel;
+use crate::domain::habit::HabitService;
+use crate::domain::memo::MemoService;
+use crate::domain::schedule::ScheduleModel;
+use crate::domain::sub::daily_item::{DailyItemService, DailyTaskModel};
+use crate::domain::sub::schedule_item::{ScheduleItemService, ScheduledAt};
+use crate::domain::task::TaskService;
+use crate::infra::types::{ConflictResolution, MergeStrategy, SyncChange, SyncKind, SyncOp};
+use crate::interface::dto::{
+    daily::res::DailyRes,
+    habit::{
+        req::{CreateHabitReq, UpdateHabitReq},
+        res::HabitRes,
+    },
+    memo::{
+        req::{CreateMemoReq, UpdateMemoReq},
+        res::MemoRes,
+    },
+    schedule::res::ScheduleRes,
+    sync::req::{SyncMutationReq, SyncPushReq},
+    sync::res::{SyncChangeRes, SyncData, SyncMutationRes, SyncPullRes, SyncPushRes},
+    task::{
+        req::{CreateTaskReq, DeleteTaskOptionReq, UpdateTaskReq},
+        res::TaskRes,
+    },
+};
+use crate::{
+    domain::error::{Error::*, Result},
+    domain::repo::base::{self, MongoRepo, BATCH_MAX},
+    domain::repo::base_array::MongoArrayRepo,
+    domain::repo::storage::{FindOpts, Storage},
+    domain::repo::utils::{doc_version, keyset_filter},
+    infra::db::error::Error as DBError,
+};
+
+/// pull에서 한번에 보낼 수 있는 최대 문서 수.
+pub const PULL_LIMIT_MAX: usize = 1000;
+// 다음 since를 조회 시각보다 이만큼 앞당김
+const TOKEN_LAG_SECS: i64 = 60;
+
+/// 오프라인 client를 위한 delta sync.
+/// pull: since token 이후에 생성/수정/삭제된 문서를 updatedAt과 tombstone(deletedAt)으로 찾음.
+/// push: client의 변경을 순서대로 반영하고, 그 사이 서버에서 바뀐 문서는 충돌로 보고함.
+pub struct SyncService;
+
+/// push에서 문서를 생성/수정/삭제하는 경로.
+/// 각 service의 검증(상태 전이, 부모 관계, 반복 규칙 등)을 그대로 거치도록 함.
+#[async_trait]
+pub trait SyncRepo: MongoRepo {
+    type CreateReq: DeserializeOwned + Sync;
+    type UpdateReq: DeserializeOwned + Serialize + Sync;
+
+    /// client가 만든 id로 생성함.
+    async fn sync_create(
+        db: &dyn Storage,
+        id: ObjectId,
+        body: &Self::CreateReq,
+        user: &Uuid,
+    ) -> Result<Self::ModelResponse>;
+
+    /// version이 바뀌었으면 PreconditionFailed.
+    async fn sync_update(
+        db: &dyn Storage,
+        id: &str,
+        body: &Self::UpdateReq,
+        version: i64,
+        user: &Uuid,
+    ) -> Result<Self::ModelResponse>;
+
+    async fn sync_delete(db: &dyn Storage, id: &str, user: &Uuid) -> Result<()>;
+}
+
+#[async_trait]
+impl SyncRepo for TaskService {
+    type CreateReq = CreateTaskReq;
+    type UpdateReq = UpdateTaskReq;
+
+    async fn sync_create(
+        db: &dyn Storage,
+        id: ObjectId,
+        body: &CreateTaskReq,
+        user: &Uuid,
+    ) -> Result<TaskRes> {
+        Self::insert_task(db, Some(id), body, user).await
+    }
+
+    async fn sync_update(
+        db: &dyn Storage,
+        id: &str,
+        body: &UpdateTaskReq,
+        version: i64,
+        user: &Uuid,
+    ) -> Result<TaskRes> {
+        let res = Self::update_task(db, id, body, Some(version), user).await?;
+        Ok(res.data.task.into_inner())
+    }
+
+    // subtask 삭제는 client가 따로 보내므로, 남은 subtask는 최상위 task가 됨.
+    async fn sync_delete(db: &dyn Storage, id: &str, user: &Uuid) -> Result<()> {
+        Self::delete_task(db, id, DeleteTaskOptionReq::ConvertSubtaskToTask, user).await
+    }
+}
+
+#[async_trait]
+impl SyncRepo for MemoService {
+    type CreateReq = CreateMemoReq;
+    type UpdateReq = UpdateMemoReq;
+
+    async fn sync_create(
+        db: &dyn Storage,
+        id: ObjectId,
+        body: &CreateMemoReq,
+        user: &Uuid,
+    ) -> Result<MemoRes> {
+        base::create_with_id::<Self, CreateMemoReq>(db, Some(id), body, user).await
+    }
+
+    async fn sync_update(
+        db: &dyn Storage,
+        id: &str,
+        body: &UpdateMemoReq,
+        version: i64,
+        user: &Uuid,
+    ) -> Result<MemoRes> {
+        let res = Self::update_memo(db, id, body, Some(version), user).await?;
+        Ok(res.data.memo.into_inner())
+    }
+
+    async fn sync_delete(db: &dyn Storage, id: &str, user: &Uuid) -> Result<()> {
+        Self::delete_memo(db, id, user).await
+    }
+}
+
+#[async_trait]
+impl SyncRepo for HabitService {
+    type CreateReq = CreateHabitReq;
+    type UpdateReq = UpdateHabitReq;
+
+    async fn sync_create(
+        db: &dyn Storage,
+        id: ObjectId,
+        body: &CreateHabitReq,
+        user: &Uuid,
+    ) -> Result<HabitRes> {
+        base::create_with_id::<Self, CreateHabitReq>(db, Some(id), body, user).await
+    }
+
+    async fn sync_update(
+        db: &dyn Storage,
+        id: &str,
+        body: &UpdateHabitReq,
+        version: i64,
+        user: &Uuid,
+    ) -> Result<HabitRes> {
+        let res = Self::update_habit(db, id, body, Some(version), user).await?;
+        Ok(res.data.habit.into_inner())
+    }
+
+    async fn sync_delete(db: &dyn Storage, id: &str, user: &Uuid) -> Result<()> {
+        Self::delete_habit(db, id, user).await
+    }
+}
+
+impl SyncKind {
+    pub const ALL: [SyncKind; 5] = [
+        SyncKind::Task,
+        SyncKind::Memo,
+        SyncKind::Habit,
+        SyncKind::Daily,
+        SyncKind::Schedule,
+    ];
+
+    pub fn coll_name(&self) -> &'static str {
+        match self {
+            SyncKind::Task => TaskService::COLL_NAME,
+            SyncKind::Memo => MemoService::COLL_NAME,
+            SyncKind::Habit => HabitService::COLL_NAME,
+            SyncKind::Daily => DailyItemService::<DailyTaskModel>::COLL_NAME,
+            SyncKind::Schedule => ScheduleItemService::<ScheduledAt>::COLL_NAME,
+        }
+    }
+}
+
+impl SyncService {
+    /// since가 없으면 삭제되지 않은 모든 문서를 created로 반환함.
+    /// 한번에 limit개까지 (kind, updatedAt, _id) 순서로 보내며, 남은 문서가 있으면 has_more가 true이고
+    /// next_token으로 이어서 받음. 마지막 page의 next_token이 다음 pull의 since가 됨.
+    pub async fn pull(
+        db: &dyn Storage,
+        since: Option<&str>,
+        limit: usize,
+        retention_days: i64,
+        user: &Uuid,
+    ) -> Result<SyncPullRes> {
+        if limit == 0 || limit > PULL_LIMIT_MAX {
+            return Err(DB(DBError::ListLimitOverMax {
+                max: PULL_LIMIT_MAX as i64,
+                actual: limit as i64,
+            }));
+        }
+
+        let now = Utc::now();
+        let token = match since {
+            Some(token) => {
+                Some(decode_token(token).ok_or(DBError::InvalidSyncToken(token.to_string()))?)
+            }
+            None => None,
+        };
+        let (since, after, full_resync) = match token {
+            Some(SyncToken {
+                since,
+                page: Some(page),
+            }) => (since, Some(page), false),
+            Some(SyncToken { since, page: None }) => {
+                // 보관 기간이 지나 영구 삭제된 문서는 tombstone이 남지 않으므로 처음부터 다시 받게 함.
+                let full_resync =
+                    since.is_some_and(|since| since < now - Duration::days(retention_days));
+                (since.filter(|_| !full_resync), None, full_resync)
+            }
+            None => (None, None, false),
+        };
+        // 조회 전에 정한 시각보다 TOKEN_LAG_SECS만큼 앞선 시각을 다음 since로 사용.
+        // updatedAt을 정한 뒤 늦게 commit된 문서도 다음 pull에서 받을 수 있도록 함.
+        // 그 사이에 바뀐 문서는 다음 pull에 한번 더 포함될 수 있음.
+        let next_since = match &after {
+            Some(page) => page.next_since,
+            None => now - Duration::seconds(TOKEN_LAG_SECS),
+        };
+
+        let mut changes: Vec<SyncChangeRes> = Vec::new();
+        let mut last: Option<(usize, Bson, ObjectId)> = None;
+        let mut has_more = false;
+        let first_kind = after.as_ref().map_or(0, |page| page.kind);
+        for (kind_index, kind) in SyncKind::ALL.into_iter().enumerate().skip(first_kind) {
+            if changes.len() == limit {
+                has_more = true;
+                break;
+            }
+
+            let mut filter = doc! { "user": user };
+            match since {
+                Some(since) => {
+                    filter.insert("updatedAt", doc! { "$gte": Bson::DateTime(since.into()) })
+                }
+                None => filter.insert("deletedAt", doc! { "$exists": false }),
+            };
+            let sort = doc! { "updatedAt": 1 };
+            if let Some(page) = after.as_ref().filter(|page| page.kind == kind_index) {
+                let keyset = keyset_filter(
+                    &sort,
+                    std::slice::from_ref(&page.updated_at),
+                    page.id.into(),
+                );
+                filter = doc! { "$and": [filter, keyset] };
+            }
+            let remaining = limit - changes.len();
+            let find_options = FindOpts {
+                sort: Some(doc! { "updatedAt": 1, "_id": 1 }),
+                limit: Some(remaining as i64 + 1),
+                ..FindOpts::default()
+            };
+
+            let docs = db
+                .find(kind.coll_name(), filter, find_options, None)
+                .await?;
+            has_more = docs.len() > remaining;
+            for doc in docs.into_iter().take(remaining) {
+                let id = doc.get_object_id("_id").map_err(DBError::MongoDataError)?;
+                let updated_at = doc.get("updatedAt").cloned().unwrap_or(Bson::Null);
+                last = Some((kind_index, updated_at, id));
+                changes.push(Self::convert_doc_to_change(kind, doc, since)?);
+            }
+            if has_more {
+                break;
+            }
+        }
+
+        let next_token = match last.filter(|_| has_more) {
+            Some((kind, updated_at, id)) => encode_token(&SyncToken {
+                since,
+                page: Some(SyncPage {
+                    next_since,
+                    kind,
+                    updated_at,
+                    id,
+                }),
+            }),
+            None => encode_token(&SyncToken {
+                since: Some(next_since),
+                page: None,
+            }),
+        };
+
+        Ok(SyncPullRes {
+            status: "success",
+            results: changes.len(),
+            changes,
+            next_token,
+            has_more,
+            full_resync,
+        })
+    }
+
+    /// mutation은 요청 순서대로 하나씩 반영함(같은 문서의 create 후 update 등).
+    /// daily, schedule은 원소 단위로 수정되므로 push에서는 받지 않음.
+    pub async fn push(db: &dyn Storage, body: &SyncPushReq, user: &Uuid) -> Result<SyncPushRes> {
+        if body.mutations.len() > BATCH_MAX {
+            return Err(DB(DBError::ListLimitOverMax {
+                max: BATCH_MAX as i64,
+                actual: body.mutations.len() as i64,
+            }));
+        }
+
+        let mut items: Vec<SyncMutationRes> = Vec::new();
+        for (index, mutation) in body.mutations.iter().enumerate() {
+            let result = match mutation.kind {
+                SyncKind::Task => {
+                    Self::apply::<TaskService>(db, mutation, body.strategy, user).await
+                }
+                SyncKind::Memo => {
+                    Self::apply::<MemoService>(db, mutation, body.strategy, user).await
+                }
+                SyncKind::Habit => {
+                    Self::apply::<HabitService>(db, mutation, body.strategy, user).await
+                }
+                SyncKind::Daily | SyncKind::Schedule => Ok(failed(
+                    &mutation.id,
+                    format!("{:?} can not be changed by sync", mutation.kind),
+                )),
+            };
+
+            let mut item = result.unwrap_or_else(|e| failed(&mutation.id, e.to_string()));
+            item.index = index;
+            items.push(item);
+        }
+
+        let count = |status: &str| items.iter().filter(|item| item.status == status).count();
+        Ok(SyncPushRes {
+            status: "success",
+            results: items.len(),
+            applied: count("applied"),
+            conflicts: count("conflict"),
+            failed: count("fail"),
+            items,
+        })
+    }
+
+    async fn apply<S>(
+        db: &dyn Storage,
+        mutation: &SyncMutationReq,
+        strategy: MergeStrategy,
+        user: &Uuid,
+    ) -> Result<SyncMutationRes>
+    where
+        S: SyncRepo,
+        S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
+        S::ModelResponse: Into<SyncData>,
+    {
+        let id = &mutation.id;
+        let Ok(oid) = ObjectId::from_str(id) else {
+            return Ok(failed(id, format!("ID: {} is not a valid id", id)));
+        };
+
+        // 삭제된 문서도 포함해서 조회
+        let current = db
+            .find_one(S::COLL_NAME, doc! { "_id": oid, "user": user }, None, None)
+            .await?;
+
+        match (mutation.op, current) {
+            // 재전송된 create는 이미 반영된 것으로 봄
+            (SyncOp::Create, Some(current)) => Ok(applied(id, alive_data::<S>(current)?)),
+            (SyncOp::Create, None) => {
+                let body: S::CreateReq = match parse(mutation.data.as_ref()) {
+                    Ok(body) => body,
+                    Err(message) => return Ok(failed(id, message)),
+                };
+
+                let res = S::sync_create(db, oid, &body, user).await?;
+                Ok(applied(id, Some(res.into())))
+            }
+            (_, None) => Ok(failed(id, format!("ID: {} not found", id))),
+            (SyncOp::Update, Some(current)) => {
+                let body: S::UpdateReq = match parse(mutation.data.as_ref()) {
+                    Ok(body) => body,
+                    Err(message) => return Ok(failed(id, message)),
+                };
+                let patch = bson::to_document(&body).map_err(DBError::MongoSerializeBsonError)?;
+
+                Self::apply_update::<S>(db, mutation, strategy, current, patch, user).await
+            }
+            (SyncOp::Delete, Some(current)) => {
+                if current.contains_key("deletedAt") {
+                    return Ok(applied(id, None));
+                }
+
+                // merge에서는 서버에서 바뀐 내용이 있으면 삭제하지 않음.
+                let client_wins = !is_stale(mutation, &current)
+                    || (strategy == MergeStrategy::Lww && is_client_newer(mutation, &current));
+                if !client_wins {
+                    return Ok(conflict(
+                        id,
+                        ConflictResolution::Server,
+                        Vec::new(),
+                        alive_data::<S>(current)?,
+                    ));
+                }
+
+                S::sync_delete(db, id, user).await?;
+                match is_stale(mutation, &current) {
+                    true => Ok(conflict(id, ConflictResolution::Client, Vec::new(), None)),
+                    false => Ok(applied(id, None)),
+                }
+            }
+        }
+    }
+
+    async fn apply_update<S>(
+        db: &dyn Storage,
+        mutation: &SyncMutationReq,
+        strategy: MergeStrategy,
+        current: Document,
+        patch: Document,
+        user: &Uuid,
+    ) -> Result<SyncMutationRes>
+    where
+        S: SyncRepo,
+        S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
+        S::ModelResponse: Into<SyncData>,
+    {
+        let id = &mutation.id;
+
+        // 서버에서 삭제된 문서는 수정하지 않음(복구는 휴지통에서).
+        if current.contains_key("deletedAt") {
+            return Ok(conflict(id, ConflictResolution::Server, Vec::new(), None));
+        }
+
+        let version = doc_version(&current);
+        if !is_stale(mutation, &current) {
+            let updated =
+                Self::update_if_unchanged::<S>(db, mutation, &patch, version, user).await?;
+            return match updated {
+                Some(data) => Ok(applied(id, Some(data))),
+                None => Self::server_wins::<S>(db, id, Vec::new(), user).await,
+            };
+        }
+
+        let (patch, resolution, conflict_fields) = match strategy {
+            MergeStrategy::Lww if is_client_newer(mutation, &current) => {
+                (patch, ConflictResolution::Client, Vec::new())
+            }
+            MergeStrategy::Lww => {
+                return Ok(conflict(
+                    id,
+                    ConflictResolution::Server,
+                    Vec::new(),
+                    alive_data::<S>(current)?,
+                ))
+            }
+            MergeStrategy::Merge => {
+                let base: Document = match mutation.base.as_ref() {
+                    Some(_) => match parse::<S::UpdateReq>(mutation.base.as_ref()) {
+                        Ok(base) => {
+                            bson::to_document(&base).map_err(DBError::MongoSerializeBsonError)?
+                        }
+                        Err(message) => return Ok(failed(id, message)),
+                    },
+                    None => Document::new(),
+                };
+
+                // 서버에서 바뀌지 않은 필드(base와 같은 값)만 반영하고,
+                // 양쪽에서 다르게 바뀐 필드는 서버 값을 유지함.
+                // base가 없는 필드는 서버에 값이 있으면 충돌로 봄.
+                let mut merged = Document::new();
+                let mut conflict_fields = Vec::new();
+                for (field, value) in patch {
+                    let server_value = current.get(&field);
+                    if server_value == Some(&value) {
+                        continue;
+                    }
+                    if base.get(&field) == server_value {
+                        merged.insert(field, value);
+                    } else {
+                        conflict_fields.push(field);
+                    }
+                }
+                (merged, ConflictResolution::Merged, conflict_fields)
+            }
+        };
+
+        if patch.is_empty() {
+            return Ok(conflict(
+                id,
+                resolution,
+                conflict_fields,
+                alive_data::<S>(current)?,
+            ));
+        }
+
+        match Self::update_if_unchanged::<S>(db, mutation, &patch, version, user).await? {
+            Some(data)
+                if resolution == ConflictResolution::Merged && conflict_fields.is_empty() =>
+            {
+                Ok(applied(id, Some(data)))
+            }
+            Some(data) => Ok(conflict(id, resolution, conflict_fields, Some(data))),
+            None => Self::server_wins::<S>(db, id, conflict_fields, user).await,
+        }
+    }
+
+    // 조회한 뒤에 다른 요청이 문서를 바꿨으면 None.
+    // patch에 남은 필드만 client의 data에서 다시 읽어 service의 수정 경로로 반영함.
+    async fn update_if_unchanged<S>(
+        db: &dyn Storage,
+        mutation: &SyncMutationReq,
+        patch: &Document,
+        version: i64,
+        user: &Uuid,
+    ) -> Result<Option<SyncData>>
+    where
+        S: SyncRepo,
+        S::ModelResponse: Into<SyncData>,
+    {
+        let mut data = mutation.data.clone();
+        if let Some(Value::Object(fields)) = data.as_mut() {
+            fields.retain(|field, _| patch.contains_key(field));
+        }
+        let body: S::UpdateReq = parse(data.as_ref()).map_err(DBError::InvalidQuery)?;
+
+        match S::sync_update(db, &mutation.id, &body, version, user).await {
+            Ok(res) => Ok(Some(res.into())),
+            Err(PreconditionFailed(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn server_wins<S>(
+        db: &dyn Storage,
+        id: &str,
+        conflict_fields: Vec<String>,
+        user: &Uuid,
+    ) -> Result<SyncMutationRes>
+    where
+        S: MongoRepo,
+        S::Model: DeserializeOwned + Serialize + Unpin + Send + Sync,
+        S::ModelResponse: Into<SyncData>,
+    {
+        let data = base::get::<S>(db, id, user).await.ok().map(Into::into);
+        Ok(conflict(
+            id,
+            ConflictResolution::Server,
+            conflict_fields,
+            data,
+        ))
+    }
+
+    fn convert_doc_to_change(
+        kind: SyncKind,
+        doc: Document,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<SyncChangeRes> {
+        let id = doc.get_object_id("_id").map_err(DBError::MongoDataError)?;
+        let version = doc_version(&doc);
+        let updated_at = doc
+            .get_datetime("updatedAt")
+            .map_err(DBError::MongoDataError)?
+            .to_chrono();
+
+        if doc.contains_key("deletedAt") {
+            return Ok(SyncChangeRes {
+                kind,
+                op: SyncChange::Deleted,
+                id: id.to_hex(),
+                version,
+                updatedAt: updated_at,
+                data: None,
+            });
+        }
+
+        let created_at = doc
+            .get_datetime("createdAt")
+            .map_err(DBError::MongoDataError)?
+            .to_chrono();
+        let op = match since {
+            Some(since) if created_at < since => SyncChange::Updated,
+            _ => SyncChange::Created,
+        };
+
+        let data = match kind {
+            SyncKind::Task => model_data::<TaskService>(doc)?,
+            SyncKind::Memo => model_data::<MemoService>(doc)?,
+            SyncKind::Habit => model_data::<HabitService>(doc)?,
+            SyncKind::Daily => {
+                let daily: DailyModel =
+                    bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?;
+                SyncData::Daily(DailyRes::from_model(&daily))
+            }
+            SyncKind::Schedule => {
+                let schedule: ScheduleModel =
+                    bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?;
+                SyncData::Schedule(ScheduleRes::from_model(&schedule))
+            }
+        };
+
+        Ok(SyncChangeRes {
+            kind,
+            op,
+            id: id.to_hex(),
+            version,
+            updatedAt: updated_at,
+            data: Some(data),
+        })
+    }
+}
+
+fn model_data<S>(doc: Document) -> Result<SyncData>
+where
+    S: MongoRepo,
+    S::Model: DeserializeOwned,
+    S::ModelResponse: Into<SyncData>,
+{
+    let model: S::Model = bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?;
+    Ok(S::convert_doc_to_response(&model).into())
+}
+
+// 삭제된 문서면 None
+fn alive_data<S>(doc: Document) -> Result<Option<SyncData>>
+where
+    S: MongoRepo,
+    S::Model: DeserializeOwned,
+    S::ModelResponse: Into<SyncData>,
+{
+    match doc.contains_key("deletedAt") {
+        true => Ok(None),
+        false => model_data::<S>(doc).map(Some),
+    }
+}
+
+// client가 받은 이후에 서버에서 문서가 바뀌었는지.
+// base_version이 없으면 client가 서버의 현재 상태를 보고 바꾼 것으로 봄.
+fn is_stale(mutation: &SyncMutationReq, current: &Document) -> bool {
+    mutation
+        .base_version
+        .is_some_and(|version| version != doc_version(current))
+}
+
+fn is_client_newer(mutation: &SyncMutationReq, current: &Document) -> bool {
+    match (mutation.updatedAt, current.get_datetime("updatedAt")) {
+        (Some(client), Ok(server)) => client > server.to_chrono(),
+        (Some(_), Err(_)) => true,
+        (None, _) => false,
+    }
+}
+
+fn parse<T: DeserializeOwned>(data: Option<&Value>) -> std::result::Result<T, String> {
+    match data {
+        Some(data) => serde_json::from_value(data.clone()).map_err(|e| e.to_string()),
+        None => Err("data is required".to_string()),
+    }
+}
+
+fn applied(id: &str, data: Option<SyncData>) -> SyncMutationRes {
+    SyncMutationRes {
+        index: 0,
+        id: id.to_string(),
+        status: "applied",
+        resolution: None,
+        conflict_fields: Vec::new(),
+        data,
+        message: None,
+    }
+}
+
+fn conflict(
+    id: &str,
+    resolution: ConflictResolution,
+    conflict_fields: Vec<String>,
+    data: Option<SyncData>,
+) -> SyncMutationRes {
+    SyncMutationRes {
+        index: 0,
+        id: id.to_string(),
+        status: "conflict",
+        resolution: Some(resolution),
+        conflict_fields,
+        data,
+        message: None,
+    }
+}
+
+fn failed(id: &str, message: String) -> SyncMutationRes {
+    SyncMutationRes {
+        index: 0,
+        id: id.to_string(),
+        status: "fail",
+        resolution: None,
+        conflict_fields: Vec::new(),
+        data: None,
+        message: Some(message),
+    }
+}
+
+// region:    --- Token
+
+// token은 since와, page를 이어서 받는 중이면 마지막으로 보낸 문서의 위치를 감싼 불투명한 문자열.
+struct SyncToken {
+    // None이면 처음부터 받는 중
+    since: Option<DateTime<Utc>>,
+    page: Option<SyncPage>,
+}
+
+struct SyncPage {
+    // 모든 page를 받은 뒤 사용할 since
+    next_since: DateTime<Utc>,
+    // SyncKind::ALL에서의 위치
+    kind: usize,
+    updated_at: Bson,
+    id: ObjectId,
+}
+
+fn encode_token(token: &SyncToken) -> String {
+    let since = token
+        .since
+        .map_or(Bson::Null, |since| Bson::DateTime(since.into()));
+    let mut doc = doc! { "t": since };
+    if let Some(page) = &token.page {
+        doc.insert("n", Bson::DateTime(page.next_since.into()));
+        doc.insert("k", page.kind as i64);
+        doc.insert("u", page.updated_at.clone());
+        doc.insert("id", page.id);
+    }
+    let bytes = bson::to_vec(&doc).unwrap_or_default();
+    general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn decode_token(token: &str) -> Option<SyncToken> {
+    let bytes = general_purpose::URL_SAFE_NO_PAD.decode(token).ok()?;
+    let doc = bson::from_slice::<Document>(&bytes).ok()?;
+
+    let since = match doc.get("t")? {
+        Bson::Null => None,
+        since => Some(since.as_datetime()?.to_chrono()),
+    };
+    let page = match doc.get_datetime("n") {
+        Ok(next_since) => Some(SyncPage {
+            next_since: next_since.to_chrono(),
+            kind: usize::try_from(doc.get_i64("k").ok()?).ok()?,
+            updated_at: doc.get("u")?.clone(),
+            id: doc.get_object_id("id").ok()?,
+        }),
+        Err(_) => None,
+    };
+    // 처음부터 받는 중이 아니면 since가 있어야 함
+    if since.is_none() && page.is_none() {
+        return None;
+    }
+    Some(SyncToken { since, page })
+}
+
+// endregion: --- Token
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::infra::db::MongoDB;
+    use crate::infra::types::TaskStatus;
+
+    const USER: Uuid = Uuid::from_bytes([0; 16]);
+
+    async fn create_memo(mongodb: &MongoDB, title: &str) -> String {
+        let body = CreateMemoReq {
+            title: title.to_string(),
+            color: "#f97316".to_string(),
+        };
+        let res = MemoService::create_memo(&mongodb.db, &body, &USER)
+            .await
+            .unwrap();
+        res.data.memo.into_inner().id
+    }
+
+    fn update_mutation(id: &str, data: Value) -> SyncMutationReq {
+        SyncMutationReq {
+            kind: SyncKind::Memo,
+            op: SyncOp::Update,
+            id: id.to_string(),
+            base_version: Some(1),
+            updatedAt: None,
+            data: Some(data),
+            base: None,
+        }
+    }
+
+    fn memo_title(data: &Option<SyncData>) -> String {
+        match data {
+            Some(SyncData::Memo(memo)) => memo.title.clone(),
+            _ => panic!("not a memo"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pull_since_token() {
+        let mongodb = MongoDB::init_memory();
+        let first = create_memo(&mongodb, "첫 번째 메모").await;
+        let second = create_memo(&mongodb, "두 번째 메모").await;
+        // 다음 since보다 먼저 생성된 문서여야 다음 pull에서 updated로 옴
+        let created_at = Bson::DateTime((Utc::now() - Duration::days(1)).into());
+        let update = doc! { "$set": { "createdAt": created_at } };
+        mongodb
+            .db
+            .update_many(MemoService::COLL_NAME, doc! {}, update, None)
+            .await
+            .unwrap();
+
+        let res = SyncService::pull(&mongodb.db, None, PULL_LIMIT_MAX, 30, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.results, 2);
+        assert!(res.changes.iter().all(|c| c.op == SyncChange::Created));
+        assert!(!res.full_resync);
+
+        let body = UpdateMemoReq {
+            title: Some("수정된 메모".to_string()),
+            content: None,
+            color: None,
+        };
+        MemoService::update_memo(&mongodb.db, &first, &body, None, &USER)
+            .await
+            .unwrap();
+        MemoService::delete_memo(&mongodb.db, &second, &USER)
+            .await
+            .unwrap();
+
+        let res = SyncService::pull(
+            &mongodb.db,
+            Some(&res.next_token),
+            PULL_LIMIT_MAX,
+            30,
+            &USER,
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.results, 2);
+        let updated = res.changes.iter().find(|c| c.id == first).unwrap();
+        assert_eq!(updated.op, SyncChange::Updated);
+        assert_eq!(memo_title(&updated.data), "수정된 메모");
+        // 삭제된 문서는 tombstone으로 옴
+        let deleted = res.changes.iter().find(|c| c.id == second).unwrap();
+        assert_eq!(deleted.op, SyncChange::Deleted);
+        assert!(deleted.data.is_none());
+
+        let res = SyncService::pull(&mongodb.db, Some("invalid"), PULL_LIMIT_MAX, 30, &USER).await;
+        claim::assert_matches!(res, Err(DB(DBError::InvalidSyncToken(_))));
+    }
+
+    #[tokio::test]
+    async fn test_pull_pages() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let body = CreateTaskReq {
+            title: "task".to_string(),
+            parent_id: None,
+            start_date: None,
+            end_date: None,
+            due_at: None,
+            estimate_minutes: None,
+            auto_progress: None,
+            priority: None,
+            recurrence: None,
+        };
+        TaskService::create_task(db, &body, &USER).await.unwrap();
+        let first = create_memo(&mongodb, "첫 번째 메모").await;
+        let second = create_memo(&mongodb, "두 번째 메모").await;
+
+        let res = SyncService::pull(db, None, 2, 30, &USER).await.unwrap();
+        let kinds: Vec<SyncKind> = res.changes.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, [SyncKind::Task, SyncKind::Memo]);
+        assert_eq!(res.changes[1].id, first);
+        assert!(res.has_more);
+
+        let res = SyncService::pull(db, Some(&res.next_token), 2, 30, &USER)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = res.changes.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, [second.as_str()]);
+        assert!(!res.has_more);
+
+        // 다음 since는 조회 시각보다 앞서므로, 방금 바뀐 문서는 한번 더 옴
+        let res = SyncService::pull(db, Some(&res.next_token), 10, 30, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.results, 3);
+
+        let res = SyncService::pull(db, None, PULL_LIMIT_MAX + 1, 30, &USER).await;
+        claim::assert_matches!(res, Err(DB(DBError::ListLimitOverMax { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_push_create_is_idempotent() {
+        let mongodb = MongoDB::init_memory();
+        let id = ObjectId::new().to_hex();
+        let body = SyncPushReq {
+            strategy: MergeStrategy::Lww,
+            mutations: vec![SyncMutationReq {
+                kind: SyncKind::Memo,
+                op: SyncOp::Create,
+                id: id.clone(),
+                base_version: None,
+                updatedAt: None,
+                data: Some(json!({ "title": "오프라인 메모", "color": "#06b6d4" })),
+                base: None,
+            }],
+        };
+
+        for _ in 0..2 {
+            let res = SyncService::push(&mongodb.db, &body, &USER).await.unwrap();
+            assert_eq!(res.applied, 1);
+            assert_eq!(res.items[0].id, id);
+        }
+
+        let res = MemoService::get_memo(&mongodb.db, &id, None, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.data.memo.title, "오프라인 메모");
+        let count = mongodb
+            .db
+            .count(MemoService::COLL_NAME, doc! {}, None)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_push_lww_conflict() {
+        let mongodb = MongoDB::init_memory();
+        let id = create_memo(&mongodb, "원래 제목").await;
+        let body = UpdateMemoReq {
+            title: Some("서버 제목".to_string()),
+            content: None,
+            color: None,
+        };
+        MemoService::update_memo(&mongodb.db, &id, &body, None, &USER)
+            .await
+            .unwrap();
+
+        // 서버보다 먼저 바뀐 client 변경은 버려짐
+        let mut mutation = update_mutation(&id, json!({ "title": "client 제목" }));
+        mutation.updatedAt = Some(Utc::now() - Duration::hours(1));
+        let body = SyncPushReq {
+            strategy: MergeStrategy::Lww,
+            mutations: vec![mutation],
+        };
+        let res = SyncService::push(&mongodb.db, &body, &USER).await.unwrap();
+        assert_eq!(res.conflicts, 1);
+        assert_eq!(res.items[0].resolution, Some(ConflictResolution::Server));
+        assert_eq!(memo_title(&res.items[0].data), "서버 제목");
+
+        // 서버보다 늦게 바뀐 client 변경은 반영됨
+        let mut mutation = update_mutation(&id, json!({ "title": "client 제목" }));
+        mutation.updatedAt = Some(Utc::now() + Duration::hours(1));
+        let body = SyncPushReq {
+            strategy: MergeStrategy::Lww,
+            mutations: vec![mutation],
+        };
+        let res = SyncService::push(&mongodb.db, &body, &USER).await.unwrap();
+        assert_eq!(res.conflicts, 1);
+        assert_eq!(res.items[0].resolution, Some(ConflictResolution::Client));
+        assert_eq!(memo_title(&res.items[0].data), "client 제목");
+    }
+
+    #[tokio::test]
+    async fn test_push_field_merge() {
+        let mongodb = MongoDB::init_memory();
+        let id = create_memo(&mongodb, "원래 제목").await;
+        let body = UpdateMemoReq {
+            title: Some("서버 제목".to_string()),
+            content: None,
+            color: None,
+        };
+        MemoService::update_memo(&mongodb.db, &id, &body, None, &USER)
+            .await
+            .unwrap();
+
+        // title은 양쪽에서 바뀌었으므로 충돌, color는 client만 바꿨으므로 반영
+        let mut mutation =
+            update_mutation(&id, json!({ "title": "client 제목", "color": "#84cc16" }));
+        mutation.base = Some(json!({ "title": "원래 제목", "color": "#f97316" }));
+        let body = SyncPushReq {
+            strategy: MergeStrategy::Merge,
+            mutations: vec![mutation],
+        };
+        let res = SyncService::push(&mongodb.db, &body, &USER).await.unwrap();
+        let item = &res.items[0];
+        assert_eq!(item.status, "conflict");
+        assert_eq!(item.resolution, Some(ConflictResolution::Merged));
+        assert_eq!(item.conflict_fields, vec!["title".to_string()]);
+
+        let res = MemoService::get_memo(&mongodb.db, &id, None, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.data.memo.title, "서버 제목");
+        assert_eq!(res.data.memo.color, "#84cc16");
+    }
+
+    fn task_mutation(op: SyncOp, id: &str, data: Option<Value>) -> SyncMutationReq {
+        SyncMutationReq {
+            kind: SyncKind::Task,
+            op,
+            id: id.to_string(),
+            base_version: None,
+            updatedAt: None,
+            data,
+            base: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_push_task_validates_status() {
+        let mongodb = MongoDB::init_memory();
+        let id = ObjectId::new().to_hex();
+        let data = json!({ "title": "오프라인 task" });
+        let body = SyncPushReq {
+            strategy: MergeStrategy::Lww,
+            mutations: vec![
+                task_mutation(SyncOp::Create, &id, Some(data)),
+                task_mutation(SyncOp::Update, &id, Some(json!({ "status": "Done" }))),
+                // 완료된 task는 바로 취소할 수 없음
+                task_mutation(SyncOp::Update, &id, Some(json!({ "status": "Cancelled" }))),
+            ],
+        };
+        let res = SyncService::push(&mongodb.db, &body, &USER).await.unwrap();
+        assert_eq!(res.applied, 2);
+        assert_eq!(res.failed, 1);
+        assert_eq!(res.items[2].status, "fail");
+
+        let res = TaskService::get_task(&mongodb.db, &id, None, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.data.task.status, TaskStatus::Done);
+        assert!(res.data.task.doneAt.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_push_task_validates_recurrence() {
+        let mongodb = MongoDB::init_memory();
+        let id = ObjectId::new().to_hex();
+        let data = json!({
+            "title": "잘못된 반복",
+            "start_date": "2024-01-01",
+            "recurrence": { "rrule": "FREQ=HOURLY" },
+        });
+        let body = SyncPushReq {
+            strategy: MergeStrategy::Lww,
+            mutations: vec![task_mutation(SyncOp::Create, &id, Some(data))],
+        };
+        let res = SyncService::push(&mongodb.db, &body, &USER).await.unwrap();
+        assert_eq!(res.failed, 1);
+
+        let res = TaskService::get_task(&mongodb.db, &id, None, &USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_push_task_delete_removes_dependency() {
+        let mongodb = MongoDB::init_memory();
+        let (a, b) = (ObjectId::new().to_hex(), ObjectId::new().to_hex());
+        let body = SyncPushReq {
+            strategy: MergeStrategy::Lww,
+            mutations: vec![
+                task_mutation(SyncOp::Create, &a, Some(json!({ "title": "A" }))),
+                task_mutation(SyncOp::Create, &b, Some(json!({ "title": "B" }))),
+            ],
+        };
+        SyncService::push(&mongodb.db, &body, &USER).await.unwrap();
+        TaskService::add_dependency(&mongodb.db, &a, &b, &USER)
+            .await
+            .unwrap();
+
+        let body = SyncPushReq {
+            strategy: MergeStrategy::Lww,
+            mutations: vec![task_mutation(SyncOp::Delete, &b, None)],
+        };
+        let res = SyncService::push(&mongodb.db, &body, &USER).await.unwrap();
+        assert_eq!(res.applied, 1);
+
+        let res = TaskService::get_task(&mongodb.db, &a, None, &USER)
+            .await
+            .unwrap();
+        let depends_on = res.data.task.depends_on.clone().unwrap_or_default();
+        assert!(depends_on.is_empty());
+    }
+}
@@ -1,9 +1,12 @@
-use super::repo::base_postgre as base;
+use super::repo::base_postgre::{self as base, PostgreRepo};
 use crate::{
-    domain::error::Result,
+    domain::error::{Error::*, Result},
+    domain::repo::query::{FieldKind, QueryFields},
+    infra::db::error::Error as DBError,
+    infra::types::{PageOptions, QueryOptions},
     interface::dto::tag::{
         req::{CreateTagReq, UpdateTagReq},
-        res::{SingleTagRes, TagData, TagListRes, TagRes},
+        res::{SingleTagRes, TagData, TagListRes, TagRes, TagStatListRes, TagStatRes},
     },
 };
 use chrono::{DateTime, Utc};
@@ -24,22 +27,42 @@ pub struct TagModel {
 
 pub struct TagService;
 
-impl base::PostgreRepo for TagService {
+// 자동완성 점수에서 사용 기록의 가중치가 1/e로 줄어드는 기간(일)
+const SUGGESTION_DECAY_DAYS: f64 = 30.0;
+const SUGGESTION_LIMIT: i64 = 10;
+
+impl PostgreRepo for TagService {
     const TABLE: &'static str = "tags";
+    const QUERY_FIELDS: QueryFields = &[
+        ("name", FieldKind::Str),
+        ("createdAt", FieldKind::DateTime),
+        ("updatedAt", FieldKind::DateTime),
+    ];
     type Entity = TagModel;
     type Res = TagRes;
     fn convert_entity_to_response(entity: &Self::Entity) -> Self::Res {
         TagRes::from_entity(entity)
     }
+    fn entity_id(entity: &Self::Entity) -> Uuid {
+        entity.id
+    }
 }
 
 impl TagService {
-    pub async fn fetch_tags(db: &Pool<Postgres>, user: &Uuid) -> Result<TagListRes> {
-        let res = base::fetch::<Self>(db, user).await?;
+    pub async fn fetch_tags(
+        db: &Pool<Postgres>,
+        user: &Uuid,
+        page_opts: PageOptions,
+        query_opts: QueryOptions,
+    ) -> Result<TagListRes> {
+        let res = base::fetch::<Self>(db, user, &page_opts, &query_opts).await?;
         Ok(TagListRes {
             status: "success",
-            results: res.len(),
-            tags: res,
+            results: res.items.len(),
+            tags: res.items,
+            next_cursor: res.next_cursor,
+            has_more: res.has_more,
+            total: res.total,
         })
     }
 
@@ -73,9 +96,164 @@ impl TagService {
         Ok(base::update::<Self, UpdateTagReq>(db, user, id, body).await?)
     }
 
-    pub async fn delete_tag(db: &Pool<Postgres>, id: Uuid) -> Result<()> {
-        // TODO: Relation table에서 tag와 관련된 데이터 삭제
-        Ok(base::delete::<Self>(db, id).await?)
+    pub async fn delete_tag(db: &Pool<Postgres>, user: &Uuid, id: Uuid) -> Result<()> {
+        // tag_relations의 row는 ON DELETE CASCADE로 함께 삭제됨
+        Ok(base::delete::<Self>(db, user, id).await?)
+    }
+
+    /// source의 item_tags, tag_relations를 모두 target으로 옮기고 source를 삭제함.
+    /// target에 이미 있는 relation은 그대로 둠.
+    pub async fn merge_tag(
+        db: &Pool<Postgres>,
+        user: &Uuid,
+        source_id: Uuid,
+        target_id: Uuid,
+    ) -> Result<SingleTagRes> {
+        if source_id == target_id {
+            return Err(DB(DBError::InvalidRelation(format!(
+                "{} can not be merged into itself",
+                source_id
+            ))));
+        }
+        base::get::<Self>(db, user, source_id).await?;
+        base::get::<Self>(db, user, target_id).await?;
+
+        let mut tx = db.begin().await.map_err(DBError::from)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO item_tags ("user", tag_id, item_type, item_oid, "createdAt")
+            SELECT "user", $2, item_type, item_oid, "createdAt"
+            FROM item_tags
+            WHERE tag_id = $1 AND "user" = $3
+            ON CONFLICT DO NOTHING
+        "#,
+        )
+        .bind(source_id)
+        .bind(target_id)
+        .bind(user)
+        .execute(&mut *tx)
+        .await
+        .map_err(DBError::from)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO tag_relations ("user", tag_id, group_id)
+            SELECT "user", $2, group_id
+            FROM tag_relations
+            WHERE tag_id = $1 AND "user" = $3
+            ON CONFLICT DO NOTHING
+        "#,
+        )
+        .bind(source_id)
+        .bind(target_id)
+        .bind(user)
+        .execute(&mut *tx)
+        .await
+        .map_err(DBError::from)?;
+
+        // source의 relation은 ON DELETE CASCADE로 함께 삭제됨
+        sqlx::query(r#"DELETE FROM tags WHERE id = $1 AND "user" = $2"#)
+            .bind(source_id)
+            .bind(user)
+            .execute(&mut *tx)
+            .await
+            .map_err(DBError::from)?;
+
+        tx.commit().await.map_err(DBError::from)?;
+        Self::get_tag(db, user, target_id).await
+    }
+
+    /// tag별 사용 횟수와 마지막 사용 시각. 오래 쓰지 않은 tag가 먼저 나옴.
+    pub async fn fetch_tag_stats(db: &Pool<Postgres>, user: &Uuid) -> Result<TagStatListRes> {
+        let tags = sqlx::query_as::<_, TagStatRes>(
+            r#"
+            SELECT t.id::text AS id, t.name,
+                COUNT(it.tag_id) AS usage_count,
+                MAX(it."createdAt") AS last_used_at
+            FROM tags t
+            LEFT JOIN item_tags it ON it.tag_id = t.id
+            WHERE t."user" = $1
+            GROUP BY t.id
+            ORDER BY last_used_at ASC NULLS FIRST, t.name
+        "#,
+        )
+        .bind(user)
+        .fetch_all(db)
+        .await
+        .map_err(DBError::from)?;
+
+        Ok(TagStatListRes {
+            status: "success",
+            results: tags.len(),
+            tags,
+        })
+    }
+
+    /// q로 시작하는(대소문자 무시) tag를 사용 빈도와 최근 사용 순으로 정렬.
+    /// 각 사용 기록은 오래될수록 가중치가 줄어듦.
+    pub async fn suggest_tags(
+        db: &Pool<Postgres>,
+        user: &Uuid,
+        q: Option<&str>,
+        limit: Option<i64>,
+    ) -> Result<TagStatListRes> {
+        let pattern = format!("{}%", escape_like(q.unwrap_or_default().trim()));
+        let limit = limit.unwrap_or(SUGGESTION_LIMIT).clamp(1, 100);
+
+        let tags = sqlx::query_as::<_, TagStatRes>(
+            r#"
+            SELECT t.id::text AS id, t.name,
+                COUNT(it.tag_id) AS usage_count,
+                MAX(it."createdAt") AS last_used_at
+            FROM tags t
+            LEFT JOIN item_tags it ON it.tag_id = t.id
+            WHERE t."user" = $1
+                AND t.name ILIKE $2
+            GROUP BY t.id
+            ORDER BY COALESCE(SUM(EXP(
+                    -EXTRACT(EPOCH FROM NOW() - it."createdAt")::float8 / 86400.0 / $3
+                )), 0) DESC,
+                t.name
+            LIMIT $4
+        "#,
+        )
+        .bind(user)
+        .bind(pattern)
+        .bind(SUGGESTION_DECAY_DAYS)
+        .bind(limit)
+        .fetch_all(db)
+        .await
+        .map_err(DBError::from)?;
+
+        Ok(TagStatListRes {
+            status: "success",
+            results: tags.len(),
+            tags,
+        })
     }
 }
 // endregion: --- TaskBmc
+
+// LIKE pattern에서 특수문자(\, %, _)를 문자 그대로 비교하도록 escape
+fn escape_like(raw: &str) -> String {
+    let mut escaped = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("work"), "work");
+        assert_eq!(escape_like("100%_done\\"), "100\\%\\_done\\\\");
+    }
+}
@@ -1,6 +1,9 @@
-use super::repo::base_postgre as base;
+use super::repo::base_postgre::{self as base, PostgreRepo};
 use crate::{
-    domain::error::Result,
+    domain::error::{Error::*, Result},
+    infra::db::error::Error as DBError,
+    domain::repo::query::{FieldKind, QueryFields},
+    infra::types::{PageOptions, QueryOptions},
     interface::dto::tag_group::{
         req::{CreateTagGroupReq, UpdateTagGroupReq},
         res::{SingleTagGroupRes, TagGroupData, TagGroupListRes, TagGroupRes},
@@ -19,28 +22,46 @@ pub struct TagGroupModel {
     pub user: Uuid,
     pub name: String,
     pub color: String,
+    pub parent_id: Option<Uuid>,
     pub createdAt: DateTime<Utc>,
     pub updatedAt: DateTime<Utc>,
 }
 
 pub struct TagGroupService;
 
-impl base::PostgreRepo for TagGroupService {
+impl PostgreRepo for TagGroupService {
     const TABLE: &'static str = "tag_groups";
+    const QUERY_FIELDS: QueryFields = &[
+        ("name", FieldKind::Str),
+        ("color", FieldKind::Str),
+        ("createdAt", FieldKind::DateTime),
+        ("updatedAt", FieldKind::DateTime),
+    ];
     type Entity = TagGroupModel;
     type Res = TagGroupRes;
     fn convert_entity_to_response(entity: &Self::Entity) -> Self::Res {
         TagGroupRes::from_entity(entity)
     }
+    fn entity_id(entity: &Self::Entity) -> Uuid {
+        entity.id
+    }
 }
 
 impl TagGroupService {
-    pub async fn fetch_groups(db: &Pool<Postgres>, user: &Uuid) -> Result<TagGroupListRes> {
-        let res = base::fetch::<Self>(db, user).await?;
+    pub async fn fetch_groups(
+        db: &Pool<Postgres>,
+        user: &Uuid,
+        page_opts: PageOptions,
+        query_opts: QueryOptions,
+    ) -> Result<TagGroupListRes> {
+        let res = base::fetch::<Self>(db, user, &page_opts, &query_opts).await?;
         Ok(TagGroupListRes {
             status: "success",
-            results: res.len(),
-            tag_groups: res,
+            results: res.items.len(),
+            tag_groups: res.items,
+            next_cursor: res.next_cursor,
+            has_more: res.has_more,
+            total: res.total,
         })
     }
 
@@ -49,6 +70,9 @@ impl TagGroupService {
         user: &Uuid,
         body: CreateTagGroupReq,
     ) -> Result<SingleTagGroupRes> {
+        if let Some(parent_id) = body.parent_id {
+            base::get::<Self>(db, user, parent_id).await?;
+        }
         let res = base::create::<Self, CreateTagGroupReq>(db, user, body).await?;
 
         Ok(SingleTagGroupRes {
@@ -74,8 +98,76 @@ impl TagGroupService {
         Ok(base::update::<Self, UpdateTagGroupReq>(db, user, id, body).await?)
     }
 
-    pub async fn delete_group(db: &Pool<Postgres>, id: Uuid) -> Result<()> {
-        Ok(base::delete::<Self>(db, id).await?)
+    /// group을 다른 group 아래로 옮김. 자기 자신이나 하위 group 아래로는 옮길 수 없음.
+    pub async fn move_group(
+        db: &Pool<Postgres>,
+        user: &Uuid,
+        id: Uuid,
+        parent_id: Option<Uuid>,
+    ) -> Result<SingleTagGroupRes> {
+        let mut tx = db.begin().await.map_err(DBError::from)?;
+
+        if let Some(parent_id) = parent_id {
+            let (exists, cycle) = sqlx::query_as::<_, (bool, bool)>(
+                r#"
+                WITH RECURSIVE descendants AS (
+                    SELECT id FROM tag_groups WHERE id = $1 AND "user" = $2
+                    UNION
+                    SELECT tg.id FROM tag_groups tg
+                    JOIN descendants d ON tg.parent_id = d.id
+                )
+                SELECT
+                    EXISTS(SELECT 1 FROM tag_groups WHERE id = $3 AND "user" = $2),
+                    EXISTS(SELECT 1 FROM descendants WHERE id = $3)
+            "#,
+            )
+            .bind(id)
+            .bind(user)
+            .bind(parent_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DBError::from)?;
+
+            if !exists {
+                return Err(DB(DBError::EntityNotFound {
+                    entity: Self::TABLE,
+                    id: parent_id,
+                }));
+            }
+            if cycle {
+                return Err(DB(DBError::InvalidRelation(format!(
+                    "{} can not be moved under its own subgroup",
+                    id
+                ))));
+            }
+        }
+
+        let res = sqlx::query(
+            r#"
+            UPDATE tag_groups SET parent_id = $3, "updatedAt" = NOW()
+            WHERE id = $1 AND "user" = $2
+        "#,
+        )
+        .bind(id)
+        .bind(user)
+        .bind(parent_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(DBError::from)?;
+        if res.rows_affected() == 0 {
+            return Err(DB(DBError::EntityNotFound {
+                entity: Self::TABLE,
+                id,
+            }));
+        }
+
+        tx.commit().await.map_err(DBError::from)?;
+        Self::get_group(db, user, id).await
+    }
+
+    pub async fn delete_group(db: &Pool<Postgres>, user: &Uuid, id: Uuid) -> Result<()> {
+        // tag_relations의 row는 ON DELETE CASCADE로 함께 삭제됨
+        Ok(base::delete::<Self>(db, user, id).await?)
     }
 
     
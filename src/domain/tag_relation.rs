@@ -1,8 +1,12 @@
 use crate::{
-    domain::error::{Error::*, Result},
+    domain::error::Result,
+    domain::repo::base_postgre as base,
+    domain::{tag::TagService, tag_group::TagGroupService},
     infra::db::error::Error as DBError,
     interface::dto::{
-        relation::CreateTagRelationReq, tag::res::{SingleTagRes, TagData, TagListRes, TagRes}, tag_group::res::{SingleTagGroupRes, TagGroupData, TagGroupListRes, TagGroupRes}
+        relation::CreateTagRelationReq,
+        tag::res::{SingleTagRes, TagData, TagListRes, TagRes},
+        tag_group::res::{SingleTagGroupRes, TagGroupData, TagGroupListRes, TagGroupRes},
     },
 };
 use serde::{Deserialize, Serialize};
@@ -35,12 +39,13 @@ impl TagRelationService {
         user: &Uuid,
     ) -> Result<TagGroupListRes> {
         let query = r#"
-            SELECT tg.id, tg.user, tg.name, tg.color, 
-                ARRAY_AGG(t.name) AS tags
+            SELECT tg.id::text AS id, tg."user", tg.name, tg.color,
+                tg.parent_id::text AS parent_id,
+                ARRAY_AGG(t.name) FILTER (WHERE t.id IS NOT NULL) AS tags
             FROM tag_groups tg
-            LEFT JOIN tag_relation tr ON tg.id = tr.group_id
-            LEFT JOIN tags t ON tr.tag_id = t.id  
-            WHERE tg.user = $1
+            LEFT JOIN tag_relations tr ON tg.id = tr.group_id
+            LEFT JOIN tags t ON tr.tag_id = t.id
+            WHERE tg."user" = $1
             GROUP BY tg.id
         "#;
 
@@ -57,6 +62,9 @@ impl TagRelationService {
             status: "success",
             results: res.len(),
             tag_groups: res,
+            next_cursor: None,
+            has_more: false,
+            total: None,
         })
     }
 
@@ -66,13 +74,15 @@ impl TagRelationService {
         id: Uuid,
     ) -> Result<SingleTagGroupRes> {
         let query = r#"
-            SELECT tg.id, tg.user, tg.name, tg.color, 
-                ARRAY_AGG(t.name) AS tags
+            SELECT tg.id::text AS id, tg."user", tg.name, tg.color,
+                tg.parent_id::text AS parent_id,
+                ARRAY_AGG(t.name) FILTER (WHERE t.id IS NOT NULL) AS tags
             FROM tag_groups tg
-            LEFT JOIN tag_relation tr ON tg.id = tr.group_id
+            LEFT JOIN tag_relations tr ON tg.id = tr.group_id
             LEFT JOIN tags t ON tr.tag_id = t.id
-            WHERE tg.user = $1
+            WHERE tg."user" = $1
                 AND tg.id = $2
+            GROUP BY tg.id
         "#;
 
         let res = sqlx::query_as::<Postgres, TagGroupRes>(query)
@@ -93,6 +103,7 @@ impl TagRelationService {
         user: &Uuid,
         body: CreateTagRelationReq,
     ) -> Result<SingleTagGroupRes> {
+        Self::check_owner(db, user, &body).await?;
         let mut fields = body.not_none_fields();
         fields.push(Field::from(("user", user)));
 
@@ -110,13 +121,13 @@ impl TagRelationService {
     // tag
     pub async fn fetch_tags_with_groups(db: &Pool<Postgres>, user: &Uuid) -> Result<TagListRes> {
         let query = r#"
-            SELECT t.id, t.name,
-                ARRAY_AGG(ROW(tg.name, tg.color)) AS groups
+            SELECT t.id::text AS id, t."user", t.name,
+                ARRAY_AGG(ROW(tg.name, tg.color)) FILTER (WHERE tg.id IS NOT NULL) AS groups
             FROM tags t
-            LEFT JOIN tag_relation tr ON tg.id = tr.group_id
-            LEFT JOIN tag_groups tg ON tr.tag_id = tg.id
-            WHERE t.user = $1
-            GROUP BY t.id, tg.user, tg.name, tg.color
+            LEFT JOIN tag_relations tr ON t.id = tr.tag_id
+            LEFT JOIN tag_groups tg ON tr.group_id = tg.id
+            WHERE t."user" = $1
+            GROUP BY t.id
         "#;
 
         let res = sqlx::query_as::<_, TagRes>(query)
@@ -132,23 +143,26 @@ impl TagRelationService {
             status: "success",
             results: res.len(),
             tags: res,
+            next_cursor: None,
+            has_more: false,
+            total: None,
         })
     }
 
-    
     pub async fn get_tag_with_groups(
         db: &Pool<Postgres>,
         user: &Uuid,
         id: Uuid,
     ) -> Result<SingleTagRes> {
         let query = r#"
-            SELECT t.id, t.name,
-                ARRAY_AGG(ROW(tg.name, tg.color)) AS groups
+            SELECT t.id::text AS id, t."user", t.name,
+                ARRAY_AGG(ROW(tg.name, tg.color)) FILTER (WHERE tg.id IS NOT NULL) AS groups
             FROM tags t
-            LEFT JOIN tag_relation tr ON tg.id = tr.group_id
-            LEFT JOIN tag_groups tg ON tr.tag_id = tg.id
-            WHERE t.user = $1
+            LEFT JOIN tag_relations tr ON t.id = tr.tag_id
+            LEFT JOIN tag_groups tg ON tr.group_id = tg.id
+            WHERE t."user" = $1
                 AND t.id = $2
+            GROUP BY t.id
         "#;
 
         let res = sqlx::query_as::<Postgres, TagRes>(query)
@@ -162,13 +176,14 @@ impl TagRelationService {
             status: "success",
             data: TagData { tag: res },
         })
-    }   
+    }
 
     pub async fn assign_group(
         db: &Pool<Postgres>,
         user: &Uuid,
         body: CreateTagRelationReq,
     ) -> Result<SingleTagRes> {
+        Self::check_owner(db, user, &body).await?;
         let mut fields = body.not_none_fields();
         fields.push(Field::from(("user", user)));
 
@@ -183,22 +198,14 @@ impl TagRelationService {
         Ok(Self::get_tag_with_groups(db, user, id).await?)
     }
 
-    /// 입력받은 tag_id혹은 group_id와 관련된 row 제거
-    pub async fn delete_relation(db: &Pool<Postgres>, id: Uuid) -> Result<()> {
-        let count = sqlb::delete()
-            .table(Self::TABLE)
-            .and_where("id", "=", id)
-            .exec(db)
-            .await
-            .map_err(DBError::from)?;
-
-        if count == 0 {
-            Err(DB(DBError::EntityNotFound {
-                entity: Self::TABLE,
-                id,
-            }))
-        } else {
-            Ok(())
-        }
+    // tag와 group이 모두 user의 것일 때만 연결함
+    async fn check_owner(
+        db: &Pool<Postgres>,
+        user: &Uuid,
+        body: &CreateTagRelationReq,
+    ) -> Result<()> {
+        base::get::<TagService>(db, user, body.tag_id).await?;
+        base::get::<TagGroupService>(db, user, body.group_id).await?;
+        Ok(())
     }
 }
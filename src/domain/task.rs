@@ -1,25 +1,47 @@
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
 
 use crate::domain::sub::chat::MsgModel;
-use crate::infra::types::{ChatType, QueryFilterOptions};
-use crate::interface::dto::task::req::DeleteTaskOptionReq;
+use crate::domain::sub::recurrence::{OccurrenceOverride, RRule, RecurrenceModel};
+use crate::domain::settings::SettingsService;
+use crate::domain::sub::timer::TaskTimerModel;
+use crate::infra::types::{
+    BatchResult, ChatType, PageOptions, Partial, Priority, QueryFilterOptions, QueryOptions,
+    TaskStatus, TaskTreeItem,
+};
+use crate::interface::dto::task::req::{
+    CloneTaskReq, DeleteTaskOptionReq, OccurrenceScope, RecurrenceReq, UpdateOccurrenceReq,
+};
+use mongodb::ClientSession;
 
 use chrono::prelude::*;
+use chrono::{Days, Duration};
 use mongodb::bson::doc;
+use mongodb::bson::Document;
 use mongodb::bson::{self, oid::ObjectId};
-use mongodb::{bson::Document, Database};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::interface::dto::batch::{req::BatchUpdateReq, res::BatchRes};
+use crate::interface::dto::history::res::ChangeListRes;
 use crate::interface::dto::task::{
     req::{CreateTaskReq, TaskFetchOptions, UpdateTaskReq},
-    res::{SingleTaskRes, TaskData, TaskListRes, TaskRes},
+    res::{
+        CapacityDay, CapacityRes, CriticalPathRes, SingleTaskRes, SingleTaskTreeRes, TaskData,
+        TaskListRes, TaskPathItem, TaskPathRes, TaskRes,
+    },
 };
 
 use crate::{
     domain::error::{Error::*, Result},
     domain::repo::base::{self, MongoRepo},
+    domain::repo::index::{IndexDef, IndexKind::*},
+    domain::repo::query::{FieldKind, FieldSelection, Load::*, QueryFields, SelectFields},
+    domain::repo::storage::{FindOpts, GraphLookup, Storage},
+    domain::repo::transaction::Transaction,
+    domain::repo::utils::{find_mdoc_by_id, update_doc_ret_model},
     infra::db::error::Error as DBError,
 };
 
@@ -35,17 +57,51 @@ pub struct TaskModel {
     pub end_date: Option<NaiveDate>,
 
     pub due_at: Option<DateTime<Local>>,
+    // 예상 소요 시간(분). critical path 계산에 사용
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate_minutes: Option<u32>,
 
     pub progress_rate: u8,
     pub milestone: bool,
+    // true면 progress_rate를 subtask들로부터 계산함(roll_up)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_progress: Option<bool>,
+    // milestone의 모든 subtask가 Done이 된 시각(취소된 subtask는 제외)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub milestone_reached_at: Option<DateTime<Utc>>,
+
+    // status가 없는 예전 task는 Todo
+    #[serde(default)]
+    pub status: TaskStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    // Done/Cancelled가 된 시각. 다른 status가 되면 null
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doneAt: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancelledAt: Option<DateTime<Utc>>,
+
+    // 실행/일시정지 중인 timer. 멈추면 지워지고 tracked_minutes에 더해짐
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timer: Option<TaskTimerModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracked_minutes: Option<u32>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_id: Option<ObjectId>,
+    // 먼저 끝나야 하는 task들
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depends_on: Option<Vec<ObjectId>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chat_type: Option<ChatType>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chat_msgs: Option<Vec<MsgModel>>,
+    // 반복 규칙. start_date가 기준일(DTSTART)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<RecurrenceModel>,
 
+    #[serde(default)]
+    pub version: i64,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
     pub createdAt: DateTime<Utc>,
     #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
@@ -55,8 +111,171 @@ pub struct TaskModel {
 #[derive(Clone, Debug)]
 pub struct TaskService;
 
+// 반복 규칙만 바꿈(occurrence 수정/삭제)
+#[derive(Serialize)]
+struct RecurrenceUpdate<'a> {
+    recurrence: &'a RecurrenceModel,
+}
+
+// 수정 요청과, status 전이에 따라 함께 바뀌는 값들
+#[allow(non_snake_case)]
+#[derive(Serialize)]
+struct TaskUpdate<'a> {
+    #[serde(flatten)]
+    body: &'a UpdateTaskReq,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    progress_rate: Option<u8>,
+    // Some(None)이면 null로 지움
+    #[serde(skip_serializing_if = "Option::is_none")]
+    doneAt: Option<Option<DateTime<Utc>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cancelledAt: Option<Option<DateTime<Utc>>>,
+}
+
+impl<'a> TaskUpdate<'a> {
+    // current: 수정 전 status(body.status가 없으면 사용하지 않음)
+    fn new(body: &'a UpdateTaskReq, current: Option<TaskStatus>) -> Result<Self> {
+        let mut update = TaskUpdate {
+            body,
+            progress_rate: None,
+            doneAt: None,
+            cancelledAt: None,
+        };
+        let (Some(current), Some(to)) = (current, body.status) else {
+            return Ok(update);
+        };
+        if current == to {
+            return Ok(update);
+        }
+        check_transition(current, to)?;
+
+        let now = Utc::now();
+        match to {
+            TaskStatus::Done => {
+                update.doneAt = Some(Some(now));
+                update.cancelledAt = Some(None);
+                if body.progress_rate.is_none() {
+                    update.progress_rate = Some(100);
+                }
+            }
+            TaskStatus::Cancelled => {
+                update.doneAt = Some(None);
+                update.cancelledAt = Some(Some(now));
+            }
+            TaskStatus::Todo | TaskStatus::Doing => {
+                update.doneAt = Some(None);
+                update.cancelledAt = Some(None);
+            }
+        }
+        Ok(update)
+    }
+}
+
+// roll-up 계산에 필요한 값들
+#[derive(Deserialize)]
+struct ProgressNode {
+    progress_rate: u8,
+    estimate_minutes: Option<u32>,
+    #[serde(default)]
+    status: TaskStatus,
+    auto_progress: Option<bool>,
+    #[serde(default)]
+    milestone: bool,
+    milestone_reached_at: Option<DateTime<Utc>>,
+}
+
+// roll-up으로 바뀌는 값들
+#[derive(Serialize)]
+struct RollUpUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    progress_rate: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    milestone_reached_at: Option<DateTime<Utc>>,
+}
+
+// parent만 바꿈(move)
+#[derive(Serialize)]
+struct ParentUpdate {
+    parent_id: ObjectId,
+}
+
 impl MongoRepo for TaskService {
     const COLL_NAME: &'static str = "tasks";
+    // parent_id가 없는 task가 먼저 나오도록 함
+    const SORT_KEY: &'static str = "parent_id";
+    const QUERY_FIELDS: QueryFields = &[
+        ("title", FieldKind::Str),
+        ("parent_id", FieldKind::ObjectId),
+        ("start_date", FieldKind::Date),
+        ("end_date", FieldKind::Date),
+        ("due_at", FieldKind::Date),
+        ("progress_rate", FieldKind::Int),
+        ("estimate_minutes", FieldKind::Int),
+        ("depends_on", FieldKind::ObjectId),
+        ("milestone", FieldKind::Bool),
+        ("auto_progress", FieldKind::Bool),
+        ("status", FieldKind::Str),
+        ("priority", FieldKind::Int),
+        ("doneAt", FieldKind::Date),
+        ("cancelledAt", FieldKind::Date),
+        ("tracked_minutes", FieldKind::Int),
+        ("createdAt", FieldKind::DateTime),
+        ("updatedAt", FieldKind::DateTime),
+    ];
+    // tree를 만들 때 parent_id와 날짜를 사용하므로 항상 읽음
+    const SELECT_FIELDS: SelectFields = &[
+        ("user", Always),
+        ("title", Always),
+        ("parent_id", Always),
+        ("progress_rate", Always),
+        ("milestone", Always),
+        ("chat_type", Lazy),
+        ("chat_msgs", Lazy),
+        ("start_date", Always),
+        ("end_date", Always),
+        ("due_at", Lazy),
+        ("estimate_minutes", Always),
+        // blocked를 계산하는 데 필요함
+        ("depends_on", Always),
+        ("auto_progress", Always),
+        ("milestone_reached_at", Always),
+        ("status", Always),
+        ("priority", Always),
+        ("doneAt", Always),
+        ("cancelledAt", Always),
+        ("timer", Always),
+        ("tracked_minutes", Always),
+        // 기간 조회 시 occurrence를 펼치는 데 필요함
+        ("recurrence", Always),
+        ("createdAt", Always),
+        ("updatedAt", Always),
+        ("version", Always),
+        // postgres의 item_tags에서 채움
+        ("tags", Attached),
+    ];
+    const INDEXES: &'static [IndexDef] = &[
+        IndexDef {
+            keys: &[("user", Asc), ("parent_id", Asc), ("_id", Asc)],
+            ..IndexDef::DEFAULT
+        },
+        IndexDef {
+            keys: &[("user", Asc), ("start_date", Asc), ("end_date", Asc)],
+            ..IndexDef::DEFAULT
+        },
+        // sync pull
+        IndexDef {
+            keys: &[("user", Asc), ("updatedAt", Asc)],
+            ..IndexDef::DEFAULT
+        },
+        // 사용자마다 실행 중인 timer는 하나뿐
+        IndexDef {
+            name: Some("user_running_timer"),
+            keys: &[("user", Asc)],
+            unique: true,
+            partial_filter: &[("timer.state", "Running")],
+            ..IndexDef::DEFAULT
+        },
+    ];
     type Model = TaskModel;
     type ModelResponse = TaskRes;
     fn convert_doc_to_response(task: &TaskModel) -> TaskRes {
@@ -73,7 +292,9 @@ impl MongoRepo for TaskService {
             "user": user,
             "milestone":false,
             "chat_type": "Task",
+            "status": "Todo",
             "progress_rate": 0,
+            "version": 1,
             "createdAt": datetime,
             "updatedAt": datetime,
         };
@@ -84,9 +305,9 @@ impl MongoRepo for TaskService {
 
 impl TaskService {
     pub async fn fetch_tasks(
-        db: &Database,
-        limit: i64,
-        page: i64,
+        db: &dyn Storage,
+        page_opts: PageOptions,
+        query_opts: QueryOptions,
         start_date: &str,
         end_date: &str,
         user: &Uuid,
@@ -96,11 +317,18 @@ impl TaskService {
         };
 
         if !start_date.is_empty() && !end_date.is_empty() {
+            // 반복 task는 기준일이 기간 이전이면 occurrence가 기간에 걸칠 수 있음
             find_filter.insert(
-                "$and",
+                "$or",
                 vec![
-                    doc! { "start_date": { "$lt": end_date } },
-                    doc! { "end_date": { "$gt": start_date } },
+                    doc! { "$and": [
+                        { "start_date": { "$lt": end_date } },
+                        { "end_date": { "$gt": start_date } },
+                    ] },
+                    doc! {
+                        "recurrence": { "$exists": true },
+                        "start_date": { "$lt": end_date },
+                    },
                 ],
             );
         }
@@ -108,47 +336,70 @@ impl TaskService {
         let filter_opts = QueryFilterOptions {
             find_filter: Some(find_filter),
             proj_opts: Some(TaskFetchOptions::build_projection()),
-            limit,
-            page,
+            page_opts,
+            query_opts,
         };
 
         tracing::info!("filter_opts: {:?}", filter_opts.find_filter);
-        let tasks_results = base::fetch::<Self>(db, filter_opts, user)
-            .await
-            .expect("task 응답을 받아오지 못했습니다.");
+        let tasks_results = base::fetch::<Self>(db, filter_opts, user).await?;
+
+        let mut tasks = match (
+            NaiveDate::from_str(start_date),
+            NaiveDate::from_str(end_date),
+        ) {
+            (Ok(from), Ok(to)) => expand_occurrences(tasks_results.items, from, to),
+            _ => tasks_results.items,
+        };
+        attach_blocked(db, user, tasks.iter_mut().map(|task| &mut **task).collect()).await?;
 
         Ok(TaskListRes {
             status: "success",
-            results: tasks_results.len(),
-            tasks: tasks_results,
+            results: tasks.len(),
+            tasks,
+            next_cursor: tasks_results.next_cursor,
+            has_more: tasks_results.has_more,
+            total: tasks_results.total,
         })
     }
 
     pub async fn create_task(
-        db: &Database,
+        db: &dyn Storage,
         body: &CreateTaskReq,
         user: &Uuid,
     ) -> Result<SingleTaskRes> {
-        tracing::info!("body: {:?}", body);
-        let task_result = base::create::<Self, CreateTaskReq>(
-            db,
-            body,
-            user,
-            Some(vec!["start_date", "end_date"]),
-        )
-        .await
-        .expect("task 생성에 실패했습니다.");
+        let task_result = Self::insert_task(db, None, body, user).await?;
 
         Ok(SingleTaskRes {
             status: "success",
-            data: TaskData { task: task_result },
+            data: TaskData { task: task_result.into() },
         })
     }
 
-    pub async fn get_task(db: &Database, id: &str, user: &Uuid) -> Result<SingleTaskRes> {
-        let task_result = base::get::<Self>(db, id, user)
-            .await
-            .expect("task를 가져오는데 실패했습니다.");
+    /// id가 주어지면 그 id로 생성함(sync에서 client가 만든 id).
+    pub async fn insert_task(
+        db: &dyn Storage,
+        id: Option<ObjectId>,
+        body: &CreateTaskReq,
+        user: &Uuid,
+    ) -> Result<TaskRes> {
+        tracing::info!("body: {:?}", body);
+        validate_recurrence(body.recurrence.as_ref(), body.start_date)?;
+        if let Some(parent_id) = body.parent_id.as_deref().filter(|id| !id.is_empty()) {
+            check_parent_owner(db, parent_id, user).await?;
+        }
+        let task_result = base::create_with_id::<Self, CreateTaskReq>(db, id, body, user).await?;
+        roll_up(db, &parent_oids([&task_result]), user).await?;
+        Ok(task_result)
+    }
+
+    pub async fn get_task(
+        db: &dyn Storage,
+        id: &str,
+        fields: Option<&str>,
+        user: &Uuid,
+    ) -> Result<SingleTaskRes> {
+        let mut task_result = base::get_fields::<Self>(db, id, fields, user).await?;
+        attach_blocked(db, user, vec![&mut *task_result]).await?;
 
         Ok(SingleTaskRes {
             status: "success",
@@ -157,77 +408,3045 @@ impl TaskService {
     }
 
     pub async fn update_task(
-        db: &Database,
+        db: &dyn Storage,
         id: &str,
         body: &UpdateTaskReq,
+        if_match: Option<i64>,
         user: &Uuid,
     ) -> Result<SingleTaskRes> {
-        let task_result = base::update::<Self, UpdateTaskReq>(db, id, body, user)
-            .await
-            .expect("task 업데이트에 실패했습니다.");
+        validate_recurrence(body.recurrence.as_ref(), None)?;
+        if let Some(parent_id) = body.parent_id.as_deref().filter(|id| !id.is_empty()) {
+            check_parent(db, id, parent_id, user).await?;
+        }
+        let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+        let old_parents = match body.parent_id {
+            Some(_) => parents_of(db, &[oid], user).await?,
+            None => vec![],
+        };
+        let current = match body.status {
+            Some(_) => statuses_of(db, &[oid], user).await?.remove(&oid),
+            None => None,
+        };
+        let update = TaskUpdate::new(body, current)?;
+        let mut task_result = base::update::<Self, _>(db, id, &update, if_match, user).await?;
+        if affects_progress(body) {
+            let mut ids = vec![oid];
+            ids.extend(old_parents);
+            ids.extend(parent_oids([&task_result]));
+            roll_up(db, &ids, user).await?;
+            // auto_progress면 task 자신도 다시 계산되었을 수 있음
+            task_result = base::get::<Self>(db, id, user).await?;
+        }
 
         Ok(SingleTaskRes {
             status: "success",
-            data: TaskData { task: task_result },
+            data: TaskData { task: task_result.into() },
+        })
+    }
+
+    pub async fn fetch_task_history(
+        db: &dyn Storage,
+        id: &str,
+        page_opts: PageOptions,
+        user: &Uuid,
+    ) -> Result<ChangeListRes> {
+        let changes = base::fetch_history::<Self>(db, id, &page_opts, user).await?;
+        Ok(ChangeListRes::from_page(changes))
+    }
+
+    pub async fn revert_task(
+        db: &dyn Storage,
+        id: &str,
+        revision: &str,
+        user: &Uuid,
+    ) -> Result<SingleTaskRes> {
+        let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+        let (current, target) = base::revert_target::<Self>(db, id, revision, user).await?;
+        // 되돌린 parent_id, depends_on도 update와 같은 검증을 거침
+        validate_revert(db, &oid, &current, &target, user).await?;
+
+        let old_parents = parents_of(db, &[oid], user).await?;
+        let task_result = base::apply_revert::<Self>(db, id, &current, &target, user).await?;
+
+        let mut ids = vec![oid];
+        ids.extend(old_parents);
+        ids.extend(parent_oids([&task_result]));
+        roll_up(db, &ids, user).await?;
+        let task_result = base::get::<Self>(db, id, user).await?;
+
+        Ok(SingleTaskRes {
+            status: "success",
+            data: TaskData { task: task_result.into() },
         })
     }
 
+    pub async fn bulk_create_tasks(
+        db: &dyn Storage,
+        bodies: &[CreateTaskReq],
+        user: &Uuid,
+    ) -> Result<BatchRes<TaskRes>> {
+        if let Some(e) = base::check_batch_size(bodies.len()) {
+            return Err(DB(e));
+        }
+        // 검증에 실패한 item은 생성하지 않고 그 item만 실패로 표시함
+        let mut invalid: Vec<BatchResult<TaskRes>> = Vec::new();
+        let mut valid: Vec<(usize, &CreateTaskReq)> = Vec::new();
+        for (index, body) in bodies.iter().enumerate() {
+            match validate_recurrence(body.recurrence.as_ref(), body.start_date) {
+                Ok(()) => valid.push((index, body)),
+                Err(e) => invalid.push(BatchResult {
+                    index,
+                    id: None,
+                    outcome: Err(e.to_string()),
+                }),
+            }
+        }
+
+        let valid_bodies: Vec<&CreateTaskReq> = valid.iter().map(|(_, body)| *body).collect();
+        let mut results = base::bulk_create::<Self, _>(db, &valid_bodies, user).await?;
+        let created = results
+            .iter()
+            .filter_map(|result| result.outcome.as_ref().ok());
+        roll_up(db, &parent_oids(created), user).await?;
+
+        // index를 요청 기준으로 되돌림
+        for result in results.iter_mut() {
+            result.index = valid[result.index].0;
+        }
+        results.extend(invalid);
+        results.sort_by_key(|result| result.index);
+        Ok(BatchRes::from_results(results))
+    }
+
+    /// item마다 update_task와 같은 검증, version 확인, history 기록, 진행률 갱신을 거침.
+    pub async fn bulk_update_tasks(
+        db: &dyn Storage,
+        items: &[BatchUpdateReq<UpdateTaskReq>],
+        user: &Uuid,
+    ) -> Result<BatchRes<TaskRes>> {
+        if let Some(e) = base::check_batch_size(items.len()) {
+            return Err(DB(e));
+        }
+
+        let mut results = Vec::with_capacity(items.len());
+        for (index, item) in items.iter().enumerate() {
+            let outcome = Self::update_task(db, &item.id, &item.body, item.version, user).await;
+            results.push(BatchResult {
+                index,
+                id: Some(item.id.to_owned()),
+                outcome: outcome
+                    .map(|res| res.data.task.into_inner())
+                    .map_err(|e| e.to_string()),
+            });
+        }
+        Ok(BatchRes::from_results(results))
+    }
+
+    pub async fn bulk_delete_tasks(
+        db: &dyn Storage,
+        ids: &[String],
+        user: &Uuid,
+    ) -> Result<BatchRes<()>> {
+        let oids: Vec<ObjectId> = ids
+            .iter()
+            .filter_map(|id| ObjectId::from_str(id).ok())
+            .collect();
+        let parents = parents_of(db, &oids, user).await?;
+        let results = base::bulk_delete::<Self>(db, ids, user).await?;
+        let deleted: Vec<ObjectId> = results
+            .iter()
+            .filter(|result| result.outcome.is_ok())
+            .filter_map(|result| result.id.as_deref())
+            .filter_map(|id| ObjectId::from_str(id).ok())
+            .collect();
+        remove_dependency_edges(db, &deleted, None, user).await?;
+        roll_up(db, &parents, user).await?;
+        Ok(BatchRes::without_data(results))
+    }
+
     pub async fn delete_task(
-        db: &Database,
+        db: &dyn Storage,
         id: &str,
         option: DeleteTaskOptionReq,
         user: &Uuid,
     ) -> Result<()> {
         tracing::info!("id {}: {:?}", &id, &option);
 
-        match option {
-            DeleteTaskOptionReq::DeleteOnlyTask => base::delete::<Self>(db, id).await,
+        let root = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+
+        // 함께 삭제되는 subtask(모든 깊이)의 dependency도 정리해야 하므로 미리 찾아둠
+        let subtasks = match option {
             DeleteTaskOptionReq::DeleteAllSubtasks => {
-                let subtasks = base::fetch::<Self>(
-                    db,
-                    QueryFilterOptions {
-                        find_filter: Some(doc! {
-                            "parent_id": id,
-                            "user": user,
-                        }),
-                        proj_opts: None,
-                        limit: 0,
-                        page: 0,
-                    },
-                    user,
+                let docs = descendants(db, &[root], Some(doc! { "_id": 1 }), user).await?;
+                docs.iter()
+                    .filter_map(|doc| doc.get_object_id("_id").ok())
+                    .collect()
+            }
+            _ => vec![],
+        };
+        let mut deleted = vec![root];
+        deleted.extend(subtasks.iter().copied());
+        let parents = parents_of(db, &[root], user).await?;
+
+        // subtask 처리와 root 삭제는 하나의 transaction으로 실행함.
+        let mut tx = Transaction::start(db).await?;
+        let result = async {
+            match option {
+                DeleteTaskOptionReq::DeleteOnlyTask => {}
+                DeleteTaskOptionReq::DeleteAllSubtasks => {
+                    // subtask들은 root task와 함께 삭제되었음을 기록하여, 복구시 같이 복구되도록 함.
+                    let filter = doc! { "_id": { "$in": &subtasks } };
+                    base::delete_many::<Self>(db, filter, Some(&root), tx.session(), user).await?;
+                }
+                DeleteTaskOptionReq::ConvertSubtaskToTask => {
+                    let filter = doc! { "parent_id": root };
+                    base::unset_fields_many::<Self>(db, filter, &["parent_id"], tx.session(), user)
+                        .await?;
+                }
+            }
+
+            let filter = doc! { "_id": root };
+            if base::delete_many::<Self>(db, filter, None, tx.session(), user).await? == 0 {
+                return Err(NotFoundError(id.to_string()));
+            }
+            remove_dependency_edges(db, &deleted, tx.session(), user).await
+        }
+        .await;
+
+        tx.finish(result).await?;
+        roll_up(db, &parents, user).await
+    }
+
+    /// id의 task가 depends_on의 task에 의존하도록 함. cycle이 생기면 InvalidRelation.
+    pub async fn add_dependency(
+        db: &dyn Storage,
+        id: &str,
+        depends_on: &str,
+        user: &Uuid,
+    ) -> Result<SingleTaskRes> {
+        let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+        let dep_oid = ObjectId::from_str(depends_on).map_err(DBError::MongoGetOidError)?;
+        if oid == dep_oid {
+            return Err(DB(DBError::InvalidRelation(format!(
+                "task {} can not depend on itself",
+                id
+            ))));
+        }
+        base::get::<Self>(db, depends_on, user).await?;
+
+        let graph = load_dependency_graph(db, user).await?;
+        if reaches(&graph, dep_oid, oid) {
+            return Err(DB(DBError::InvalidRelation(format!(
+                "task {} depending on {} creates a cycle",
+                id, depends_on
+            ))));
+        }
+
+        let task = update_doc_ret_model::<TaskModel>(
+            db,
+            Self::COLL_NAME,
+            &oid,
+            None,
+            doc! {
+                "$addToSet": { "depends_on": dep_oid },
+                "$set": { "updatedAt": Utc::now() },
+                "$inc": { "version": 1 },
+            },
+            doc! { "_id": oid, "user": user, "deletedAt": { "$exists": false } },
+            user,
+        )
+        .await?;
+
+        let mut task = TaskRes::from_model(&task);
+        attach_blocked(db, user, vec![&mut task]).await?;
+        Ok(SingleTaskRes {
+            status: "success",
+            data: TaskData { task: task.into() },
+        })
+    }
+
+    pub async fn remove_dependency(
+        db: &dyn Storage,
+        id: &str,
+        depends_on: &str,
+        user: &Uuid,
+    ) -> Result<SingleTaskRes> {
+        let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+        let dep_oid = ObjectId::from_str(depends_on).map_err(DBError::MongoGetOidError)?;
+
+        let task = update_doc_ret_model::<TaskModel>(
+            db,
+            Self::COLL_NAME,
+            &oid,
+            None,
+            doc! {
+                "$pull": { "depends_on": dep_oid },
+                "$set": { "updatedAt": Utc::now() },
+                "$inc": { "version": 1 },
+            },
+            doc! {
+                "_id": oid,
+                "user": user,
+                "depends_on": dep_oid,
+                "deletedAt": { "$exists": false },
+            },
+            user,
+        )
+        .await?;
+
+        let mut task = TaskRes::from_model(&task);
+        attach_blocked(db, user, vec![&mut task]).await?;
+        Ok(SingleTaskRes {
+            status: "success",
+            data: TaskData { task: task.into() },
+        })
+    }
+
+    /// root가 의존하는 task들(root가 없으면 전체 task) 중 가장 오래 걸리는 dependency chain.
+    /// 각 task의 소요 시간은 estimate_minutes, 없으면 start_date ~ end_date의 일수로 계산함.
+    pub async fn critical_path(
+        db: &dyn Storage,
+        root: Option<&str>,
+        user: &Uuid,
+    ) -> Result<CriticalPathRes> {
+        let root = match root.filter(|root| !root.is_empty()) {
+            Some(root) => {
+                base::get::<Self>(db, root, user).await?;
+                Some(ObjectId::from_str(root).map_err(DBError::MongoGetOidError)?)
+            }
+            None => None,
+        };
+
+        let docs = db
+            .find(
+                Self::COLL_NAME,
+                doc! { "user": user, "deletedAt": { "$exists": false } },
+                FindOpts {
+                    projection: Some(TaskFetchOptions::build_projection()),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        let mut tasks = HashMap::new();
+        for doc in docs {
+            let task: TaskModel =
+                bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?;
+            tasks.insert(task.id, task);
+        }
+
+        let nodes: HashMap<ObjectId, (u64, Vec<ObjectId>)> = tasks
+            .values()
+            .map(|task| {
+                (
+                    task.id,
+                    (
+                        task_minutes(task),
+                        task.depends_on.clone().unwrap_or_default(),
+                    ),
                 )
-                .await
-                .expect("fetch task 실패");
+            })
+            .collect();
+        let (total_minutes, chain) = longest_chain(&nodes, root);
+
+        let mut chain: Vec<TaskRes> = chain
+            .iter()
+            .filter_map(|id| tasks.get(id))
+            .map(TaskRes::from_model)
+            .collect();
+        attach_blocked(db, user, chain.iter_mut().collect()).await?;
+
+        Ok(CriticalPathRes {
+            status: "success",
+            results: chain.len(),
+            total_minutes,
+            tasks: chain,
+        })
+    }
+
+    /// start_date ~ end_date(미포함)의 날짜별 작업량. 각 task의 estimate_minutes를
+    /// start_date ~ end_date에 고르게 나누고, 하루 capacity를 넘는 날을 표시함.
+    /// 반복 task는 occurrence마다, 끝났거나 취소된 task는 빼고 계산함.
+    pub async fn capacity(
+        db: &dyn Storage,
+        mut query_opts: QueryOptions,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+        user: &Uuid,
+    ) -> Result<CapacityRes> {
+        let (Some(from), Some(to)) = (start_date, end_date) else {
+            return Err(DB(DBError::InvalidQuery(
+                "start_date and end_date are required".to_string(),
+            )));
+        };
+        if from >= to {
+            return Err(DB(DBError::InvalidQuery(format!(
+                "start_date {} must be before end_date {}",
+                from, to
+            ))));
+        }
+        let daily_capacity = SettingsService::daily_capacity(db, user).await?;
+
+        // 계산에 필요한 필드만 읽도록 ?fields=는 무시함.
+        // fetch_tasks는 end_date가 기간 시작보다 뒤인 task만 가져오므로 하루 앞부터 조회함
+        query_opts.fields = None;
+        let page_opts = PageOptions {
+            limit: 0,
+            page: 1,
+            ..Default::default()
+        };
+        let fetch_from = from.pred_opt().unwrap_or(from);
+        let tasks = Self::fetch_tasks(
+            db,
+            page_opts,
+            query_opts,
+            &fetch_from.to_string(),
+            &to.to_string(),
+            user,
+        )
+        .await?
+        .tasks;
+
+        let mut days: Vec<CapacityDay> = from
+            .iter_days()
+            .take_while(|date| *date < to)
+            .map(|date| CapacityDay {
+                date,
+                minutes: 0,
+                capacity: daily_capacity,
+                over: false,
+                task_ids: vec![],
+            })
+            .collect();
+        for task in &tasks {
+            if matches!(task.status, TaskStatus::Done | TaskStatus::Cancelled) {
+                continue;
+            }
+            let (Some(estimate), Some(start)) = (task.estimate_minutes, task.start_date) else {
+                continue;
+            };
+            let end = task.end_date.unwrap_or(start);
+            for (date, minutes) in spread_estimate(estimate, start, end, from, to) {
+                let day = &mut days[(date - from).num_days() as usize];
+                day.minutes += minutes;
+                if !day.task_ids.contains(&task.id) {
+                    day.task_ids.push(task.id.clone());
+                }
+            }
+        }
+        for day in days.iter_mut() {
+            day.over = day.minutes > daily_capacity;
+        }
+
+        Ok(CapacityRes {
+            status: "success",
+            start_date: from,
+            end_date: to,
+            daily_capacity,
+            total_minutes: days.iter().map(|day| day.minutes).sum(),
+            over_days: days.iter().filter(|day| day.over).count(),
+            days,
+        })
+    }
+
+    /// 목록 조회 결과로 tree를 만듦. 깊이 제한 없이 subtask를 붙이며,
+    /// 목록에 없는 조상 task들은 한 번의 $graphLookup으로 가져옴.
+    pub async fn build_tree(
+        db: &dyn Storage,
+        user: &Uuid,
+        tasks: Vec<Partial<TaskRes>>,
+        fields: Option<&str>,
+    ) -> Result<Vec<TaskTreeItem>> {
+        if tasks.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let present: HashSet<&str> = tasks.iter().map(|task| task.id.as_str()).collect();
+        let orphans: HashSet<ObjectId> = tasks
+            .iter()
+            .filter(|task| {
+                task.parent_id
+                    .as_deref()
+                    .is_some_and(|parent_id| !present.contains(parent_id))
+            })
+            .filter_map(|task| ObjectId::from_str(&task.id).ok())
+            .collect();
+
+        let mut tree_tasks = Vec::new();
+        if !orphans.is_empty() {
+            let orphans: Vec<ObjectId> = orphans.into_iter().collect();
+            let (projection, selected) = tree_projection(fields)?;
+            let docs = ancestors(db, &orphans, Some(projection), user).await?;
+            let mut parents: Vec<Partial<TaskRes>> = to_partials(docs, selected)?
+                .into_iter()
+                .filter(|task| !present.contains(task.id.as_str()))
+                .collect();
+            attach_blocked(
+                db,
+                user,
+                parents.iter_mut().map(|task| &mut **task).collect(),
+            )
+            .await?;
+            tree_tasks.extend(parents);
+        }
+        tree_tasks.splice(0..0, tasks);
+
+        Ok(assemble_tree(tree_tasks))
+    }
+
+    /// id의 task와 그 아래의 모든 subtask
+    pub async fn fetch_subtree(
+        db: &dyn Storage,
+        id: &str,
+        fields: Option<&str>,
+        user: &Uuid,
+    ) -> Result<SingleTaskTreeRes> {
+        let root = base::get_fields::<Self>(db, id, fields, user).await?;
+        let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+
+        let (projection, selected) = tree_projection(fields)?;
+        let docs = descendants(db, &[oid], Some(projection), user).await?;
+        let mut tasks = vec![root];
+        tasks.extend(to_partials(docs, selected)?);
+        attach_blocked(db, user, tasks.iter_mut().map(|task| &mut **task).collect()).await?;
+
+        let task = assemble_tree(tasks)
+            .into_iter()
+            .find(|item| item.task.id == id)
+            .ok_or(NotFoundError(id.to_string()))?;
+        Ok(SingleTaskTreeRes {
+            status: "success",
+            task,
+        })
+    }
+
+    /// id의 task를 parent_id의 subtask로 옮김(subtask들도 함께). parent_id가 없으면 최상위로.
+    /// 자기 자신이나 자신의 subtask 아래로 옮기면 InvalidRelation.
+    pub async fn move_task(
+        db: &dyn Storage,
+        id: &str,
+        parent_id: Option<&str>,
+        user: &Uuid,
+    ) -> Result<SingleTaskRes> {
+        let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+        let mut parents = parents_of(db, &[oid], user).await?;
+        let mut task = match parent_id.filter(|parent_id| !parent_id.is_empty()) {
+            Some(parent_id) => {
+                let parent_id = check_parent(db, id, parent_id, user).await?;
+                base::update::<Self, _>(db, id, &ParentUpdate { parent_id }, None, user).await?
+            }
+            None => base::update_unset_fields::<Self>(db, id, &["parent_id"], user).await?,
+        };
+        parents.extend(parent_oids([&task]));
+        roll_up(db, &parents, user).await?;
+
+        // roll-up으로 task 자신은 바뀌지 않음
+        attach_blocked(db, user, vec![&mut task]).await?;
+        Ok(SingleTaskRes {
+            status: "success",
+            data: TaskData { task: task.into() },
+        })
+    }
+
+    /// 최상위 task부터 id의 task까지의 경로(breadcrumb)
+    pub async fn task_path(db: &dyn Storage, id: &str, user: &Uuid) -> Result<TaskPathRes> {
+        let task = base::get_fields::<Self>(db, id, Some("title"), user).await?;
+        let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+
+        let docs = ancestors(db, &[oid], Some(doc! { "_id": 1, "title": 1 }), user).await?;
+        let mut path: Vec<TaskPathItem> = docs
+            .iter()
+            .rev()
+            .filter_map(|doc| {
+                Some(TaskPathItem {
+                    id: doc.get_object_id("_id").ok()?.to_hex(),
+                    title: doc.get_str("title").ok()?.to_string(),
+                })
+            })
+            .collect();
+        path.push(TaskPathItem {
+            id: task.id.clone(),
+            title: task.title.clone(),
+        });
 
-                for subtask in subtasks {
-                    base::delete::<Self>(db, &subtask.id).await?;
+        Ok(TaskPathRes {
+            status: "success",
+            results: path.len(),
+            path,
+        })
+    }
+
+    /// id의 task와 그 아래의 모든 subtask를 새 _id로 복사하고, 복사된 tree를 반환함.
+    /// subtree 안의 parent_id와 depends_on은 복사본끼리 이어지도록 바꿈.
+    pub async fn clone_task(
+        db: &dyn Storage,
+        id: &str,
+        body: &CloneTaskReq,
+        user: &Uuid,
+    ) -> Result<SingleTaskTreeRes> {
+        let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+        let filter = doc! { "_id": oid, "user": user, "deletedAt": { "$exists": false } };
+        let root = db
+            .find_one(Self::COLL_NAME, filter, None, None)
+            .await?
+            .ok_or(NotFoundError(id.to_string()))?;
+        let parent = root.get_object_id("parent_id").ok();
+
+        let mut docs = vec![root];
+        docs.extend(descendants(db, &[oid], None, user).await?);
+        let oids: HashMap<ObjectId, ObjectId> = docs
+            .iter()
+            .filter_map(|doc| doc.get_object_id("_id").ok())
+            .map(|old| (old, ObjectId::new()))
+            .collect();
+
+        let now = Utc::now();
+        let mut clones = Vec::with_capacity(docs.len());
+        for mut doc in docs {
+            let task: TaskModel = bson::from_document(doc.clone()).map_err(DBError::from)?;
+            doc.remove("depth");
+            // 기록된 시간과 실행 중인 timer는 복사하지 않음
+            doc.remove("timer");
+            doc.remove("tracked_minutes");
+            doc.insert("_id", oids[&task.id]);
+            if let Some(parent_id) = task.parent_id.and_then(|parent_id| oids.get(&parent_id)) {
+                doc.insert("parent_id", *parent_id);
+            }
+            // subtree 밖의 task에 대한 dependency는 그대로 둠
+            if let Some(depends_on) = &task.depends_on {
+                let depends_on: Vec<ObjectId> = depends_on
+                    .iter()
+                    .map(|dep| *oids.get(dep).unwrap_or(dep))
+                    .collect();
+                doc.insert("depends_on", depends_on);
+            }
+            match &task.chat_msgs {
+                Some(msgs) if body.include_chat_msgs => {
+                    let msgs: Vec<MsgModel> = msgs
+                        .iter()
+                        .map(|msg| MsgModel {
+                            id: ObjectId::new(),
+                            ..msg.clone()
+                        })
+                        .collect();
+                    doc.insert("chat_msgs", bson::to_bson(&msgs).map_err(DBError::from)?);
                 }
-                base::delete::<Self>(db, id).await
+                _ => {
+                    doc.remove("chat_msgs");
+                }
+            }
+            if let Some(days) = body.shift_days.filter(|days| *days != 0) {
+                shift_task_dates(&mut doc, &task, days)?;
             }
-            DeleteTaskOptionReq::ConvertSubtaskToTask => {
-                let subtasks = base::fetch::<Self>(
+            doc.insert("version", 1);
+            doc.insert("createdAt", now);
+            doc.insert("updatedAt", now);
+            clones.push(doc);
+        }
+
+        insert_tree(db, clones, user).await?;
+        if let Some(parent) = parent {
+            roll_up(db, &[parent], user).await?;
+        }
+        Self::fetch_subtree(db, &oids[&oid].to_hex(), None, user).await
+    }
+
+    /// 반복 task의 occurrence 하나(this) 혹은 그 이후 전체(this and following)를 바꿈.
+    /// this는 override로 저장하고, this and following은 date부터 새 task로 나눔.
+    pub async fn update_occurrence(
+        db: &dyn Storage,
+        id: &str,
+        date: NaiveDate,
+        body: &UpdateOccurrenceReq,
+        if_match: Option<i64>,
+        user: &Uuid,
+    ) -> Result<SingleTaskRes> {
+        // 뒤쪽 series를 만들 때 원래 task의 모든 값이 필요하므로 model로 읽음
+        let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+        let filter = doc! { "_id": oid, "user": user, "deletedAt": { "$exists": false } };
+        let model: TaskModel = find_mdoc_by_id(db, Self::COLL_NAME, &oid, filter).await?;
+        let task = Self::convert_doc_to_response(&model);
+        let (mut recurrence, dtstart, span) = occurrence_of(&task, id, date)?;
+
+        let changes = OccurrenceOverride {
+            date,
+            title: body.title.clone(),
+            start_date: body.start_date,
+            end_date: body.end_date,
+            progress_rate: body.progress_rate,
+        };
+
+        let task = match body.scope {
+            OccurrenceScope::This => {
+                match recurrence.overrides.iter_mut().find(|o| o.date == date) {
+                    Some(existing) => existing.merge(changes),
+                    None => recurrence.overrides.push(changes),
+                }
+                let task = base::update::<Self, _>(
                     db,
-                    QueryFilterOptions {
-                        find_filter: Some(doc! {
-                            "parent_id": id,
-                            "user": user,
-                        }),
-                        proj_opts: None,
-                        limit: 0,
-                        page: 0,
+                    id,
+                    &RecurrenceUpdate {
+                        recurrence: &recurrence,
                     },
+                    if_match,
                     user,
                 )
-                .await
-                .expect("fetch task 실패");
+                .await?;
+                return Ok(SingleTaskRes {
+                    status: "success",
+                    data: TaskData {
+                        task: expand_one(&task, &recurrence, date, span).into(),
+                    },
+                });
+            }
+            // 첫 occurrence부터 바꾸면 series 전체를 바꿈
+            OccurrenceScope::ThisAndFollowing if date <= dtstart => {
+                let start_date = body.start_date.unwrap_or(dtstart);
+                let update = UpdateTaskReq {
+                    title: body.title.clone(),
+                    milestone: None,
+                    parent_id: None,
+                    start_date: Some(start_date),
+                    end_date: Some(body.end_date.unwrap_or(start_date + Days::new(span))),
+                    due_at: None,
+                    progress_rate: body.progress_rate,
+                    estimate_minutes: None,
+                    auto_progress: None,
+                    status: None,
+                    priority: None,
+                    chat_type: None,
+                    recurrence: None,
+                };
+                base::update::<Self, UpdateTaskReq>(db, id, &update, if_match, user).await?
+            }
+            OccurrenceScope::ThisAndFollowing => {
+                let tail = recurrence.split_at(dtstart, date)?;
 
-                for subtask in subtasks {
-                    base::update_unset_fields::<Self>(db, &subtask.id, &["parent_id"], user)
-                        .await
-                        .expect("update task 실패");
+                // 뒤쪽 series는 원래 task의 값을 그대로 이어받고, 요청한 값만 바꿈.
+                // timer, chat 등의 기록과 roll_up 결과는 원래 task에 남김
+                let start_date = body.start_date.unwrap_or(date);
+                let now = Utc::now();
+                let split = TaskModel {
+                    id: ObjectId::new(),
+                    title: body.title.clone().unwrap_or_else(|| model.title.clone()),
+                    start_date: Some(start_date),
+                    end_date: Some(body.end_date.unwrap_or(start_date + Days::new(span))),
+                    progress_rate: body.progress_rate.unwrap_or(model.progress_rate),
+                    milestone_reached_at: None,
+                    timer: None,
+                    tracked_minutes: None,
+                    chat_msgs: None,
+                    recurrence: Some(tail),
+                    version: 1,
+                    createdAt: now,
+                    updatedAt: now,
+                    ..model
+                };
+                let split_doc = bson::to_document(&split).map_err(DBError::from)?;
+
+                // 뒤쪽 series 생성과 원래 series 자르기가 함께 반영되도록 transaction으로 묶음
+                let mut tx = Transaction::start(db).await?;
+                let result = async {
+                    let update = RecurrenceUpdate {
+                        recurrence: &recurrence,
+                    };
+                    base::update_with_session::<Self, _>(
+                        db,
+                        id,
+                        &update,
+                        if_match,
+                        user,
+                        tx.session(),
+                    )
+                    .await?;
+                    db.insert_one(Self::COLL_NAME, split_doc, tx.session())
+                        .await?;
+                    Ok(())
                 }
-                base::delete::<Self>(db, id).await
+                .await;
+                tx.finish(result).await?;
+
+                let created = Self::convert_doc_to_response(&split);
+                roll_up(db, &parent_oids([&created]), user).await?;
+                created
+            }
+        };
+
+        Ok(SingleTaskRes {
+            status: "success",
+            data: TaskData { task: task.into() },
+        })
+    }
+
+    /// occurrence 하나를 건너뛰거나(this), date 이후의 occurrence를 모두 없앰(this and following).
+    pub async fn delete_occurrence(
+        db: &dyn Storage,
+        id: &str,
+        date: NaiveDate,
+        scope: OccurrenceScope,
+        user: &Uuid,
+    ) -> Result<()> {
+        let task = base::get::<Self>(db, id, user).await?;
+        let (mut recurrence, dtstart, _) = occurrence_of(&task, id, date)?;
+
+        match scope {
+            OccurrenceScope::This => {
+                recurrence.exdates.push(date);
+                recurrence.exdates.sort();
+                recurrence.overrides.retain(|o| o.date != date);
+            }
+            OccurrenceScope::ThisAndFollowing if date <= dtstart => {
+                return Self::delete_task(db, id, DeleteTaskOptionReq::DeleteOnlyTask, user).await;
+            }
+            OccurrenceScope::ThisAndFollowing => {
+                recurrence.split_at(dtstart, date)?;
             }
         }
+
+        base::update::<Self, _>(
+            db,
+            id,
+            &RecurrenceUpdate {
+                recurrence: &recurrence,
+            },
+            None,
+            user,
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// 삭제된 task들을 가리키는 depends_on을 제거함.
+/// 휴지통에서 복구할 때 되돌릴 수 있도록, 빠진 dependency는 삭제된 task의 deletedDependents에 기록함.
+async fn remove_dependency_edges(
+    db: &dyn Storage,
+    ids: &[ObjectId],
+    mut session: Option<&mut ClientSession>,
+    user: &Uuid,
+) -> Result<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let filter = doc! { "user": user, "depends_on": { "$in": ids } };
+    let docs = db
+        .find(
+            TaskService::COLL_NAME,
+            filter.clone(),
+            FindOpts {
+                projection: Some(doc! { "_id": 1, "depends_on": 1 }),
+                ..Default::default()
+            },
+            session.as_deref_mut(),
+        )
+        .await?;
+
+    // 삭제된 task -> 그 task에 의존하던 task들
+    let mut dependents: HashMap<ObjectId, Vec<ObjectId>> = HashMap::new();
+    for doc in docs {
+        let Ok(dependent) = doc.get_object_id("_id") else {
+            continue;
+        };
+        for dep in doc.get_array("depends_on").into_iter().flatten() {
+            match dep.as_object_id() {
+                Some(dep) if ids.contains(&dep) => {
+                    dependents.entry(dep).or_default().push(dependent)
+                }
+                _ => {}
+            }
+        }
+    }
+    for (id, dependents) in dependents {
+        db.update_one(
+            TaskService::COLL_NAME,
+            doc! { "_id": id, "user": user },
+            doc! { "$set": { "deletedDependents": dependents } },
+            session.as_deref_mut(),
+        )
+        .await?;
+    }
+
+    db.update_many(
+        TaskService::COLL_NAME,
+        filter,
+        doc! {
+            "$pull": { "depends_on": { "$in": ids } },
+            "$set": { "updatedAt": Utc::now() },
+            "$inc": { "version": 1 },
+        },
+        session,
+    )
+    .await?;
+    Ok(())
+}
+
+/// 휴지통에서 함께 복구되는 task들(filter)이 삭제될 때 빠졌던 dependency 중 되돌릴 수 있는 것들.
+/// (복구되는 task, 다시 그 task에 의존하게 될 task들). 삭제된 task나 cycle을 만드는 것은 제외함.
+pub(crate) async fn restorable_dependencies(
+    db: &dyn Storage,
+    filter: Document,
+    user: &Uuid,
+) -> Result<Vec<(ObjectId, Vec<ObjectId>)>> {
+    let deps_of = |doc: &Document, field: &str| -> Vec<ObjectId> {
+        doc.get_array(field)
+            .map(|deps| deps.iter().filter_map(|dep| dep.as_object_id()).collect())
+            .unwrap_or_default()
+    };
+    let docs = db
+        .find(
+            TaskService::COLL_NAME,
+            filter,
+            FindOpts {
+                projection: Some(doc! { "_id": 1, "depends_on": 1, "deletedDependents": 1 }),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+
+    // 복구되는 task들도 살아있는 것으로 봄
+    let mut graph = load_dependency_graph(db, user).await?;
+    let mut alive = HashSet::new();
+    let mut dependents = Vec::new();
+    for doc in &docs {
+        let Ok(id) = doc.get_object_id("_id") else {
+            continue;
+        };
+        graph.insert(id, deps_of(doc, "depends_on"));
+        alive.insert(id);
+        dependents.extend(deps_of(doc, "deletedDependents"));
+    }
+    alive.extend(
+        find_ids(
+            db,
+            doc! {
+                "_id": { "$in": dependents },
+                "user": user,
+                "deletedAt": { "$exists": false },
+            },
+        )
+        .await?,
+    );
+
+    let mut edges = Vec::new();
+    for doc in &docs {
+        let Ok(id) = doc.get_object_id("_id") else {
+            continue;
+        };
+        let mut restorable = Vec::new();
+        for dependent in deps_of(doc, "deletedDependents") {
+            if !alive.contains(&dependent) || reaches(&graph, id, dependent) {
+                continue;
+            }
+            graph.entry(dependent).or_default().push(id);
+            restorable.push(dependent);
+        }
+        if !restorable.is_empty() {
+            edges.push((id, restorable));
+        }
+    }
+    Ok(edges)
+}
+
+/// restorable_dependencies로 찾은 dependency를 되돌림
+pub(crate) async fn restore_dependencies(
+    db: &dyn Storage,
+    edges: &[(ObjectId, Vec<ObjectId>)],
+    mut session: Option<&mut ClientSession>,
+    user: &Uuid,
+) -> Result<()> {
+    for (id, dependents) in edges {
+        db.update_many(
+            TaskService::COLL_NAME,
+            doc! { "_id": { "$in": dependents }, "user": user },
+            doc! {
+                "$addToSet": { "depends_on": id },
+                "$set": { "updatedAt": Utc::now() },
+                "$inc": { "version": 1 },
+            },
+            session.as_deref_mut(),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn find_ids(db: &dyn Storage, filter: Document) -> Result<Vec<ObjectId>> {
+    let docs = db
+        .find(
+            TaskService::COLL_NAME,
+            filter,
+            FindOpts {
+                projection: Some(doc! { "_id": 1 }),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+    Ok(docs
+        .iter()
+        .filter_map(|doc| doc.get_object_id("_id").ok())
+        .collect())
+}
+
+// ids의 task들에서 parent_id를 따라 올라가며 찾은 조상 task들. 가까운 것부터.
+async fn ancestors(
+    db: &dyn Storage,
+    ids: &[ObjectId],
+    projection: Option<Document>,
+    user: &Uuid,
+) -> Result<Vec<Document>> {
+    tree_lookup(db, ids, ("parent_id", "_id"), projection, user).await
+}
+
+// ids의 task들 아래의 모든 subtask. 가까운 것부터.
+pub(crate) async fn descendants(
+    db: &dyn Storage,
+    ids: &[ObjectId],
+    projection: Option<Document>,
+    user: &Uuid,
+) -> Result<Vec<Document>> {
+    tree_lookup(db, ids, ("_id", "parent_id"), projection, user).await
+}
+
+async fn tree_lookup(
+    db: &dyn Storage,
+    ids: &[ObjectId],
+    (connect_from, connect_to): (&'static str, &'static str),
+    projection: Option<Document>,
+    user: &Uuid,
+) -> Result<Vec<Document>> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let lookup = GraphLookup {
+        start: doc! { "_id": { "$in": ids }, "user": user },
+        connect_from,
+        connect_to,
+        restrict: doc! { "user": user, "deletedAt": { "$exists": false } },
+        projection,
+    };
+    db.graph_lookup(TaskService::COLL_NAME, lookup).await
+}
+
+/// _id를 미리 정한 task들을 한 번에 생성함.
+/// 하나라도 실패하면 일부만 생성된 tree를 남기지 않도록 생성된 것들을 지움.
+pub(crate) async fn insert_tree(db: &dyn Storage, docs: Vec<Document>, user: &Uuid) -> Result<()> {
+    let ids: Vec<ObjectId> = docs
+        .iter()
+        .filter_map(|doc| doc.get_object_id("_id").ok())
+        .collect();
+    let errors = db.insert_many(TaskService::COLL_NAME, docs, None).await?;
+    if let Some((_, message)) = errors.into_iter().next() {
+        let filter = doc! { "_id": { "$in": ids }, "user": user };
+        db.delete_many(TaskService::COLL_NAME, filter).await?;
+        return Err(TypedError(message));
+    }
+    Ok(())
+}
+
+pub(crate) fn shift_date(date: NaiveDate, days: i64) -> Result<NaiveDate> {
+    Duration::try_days(days)
+        .and_then(|delta| date.checked_add_signed(delta))
+        .ok_or(TypedError(format!(
+            "date out of range: {} + {} days",
+            date, days
+        )))
+}
+
+// 복사본의 start_date, end_date, due_at과 반복 규칙의 날짜들을 days만큼 옮김
+fn shift_task_dates(doc: &mut Document, task: &TaskModel, days: i64) -> Result<()> {
+    if let Some(start_date) = task.start_date {
+        let start_date = shift_date(start_date, days)?;
+        doc.insert(
+            "start_date",
+            bson::to_bson(&start_date).map_err(DBError::from)?,
+        );
+    }
+    if let Some(end_date) = task.end_date {
+        let end_date = shift_date(end_date, days)?;
+        doc.insert("end_date", bson::to_bson(&end_date).map_err(DBError::from)?);
+    }
+    if let Some(due_at) = task.due_at {
+        let due_at = Duration::try_days(days)
+            .and_then(|delta| due_at.checked_add_signed(delta))
+            .ok_or(TypedError(format!(
+                "date out of range: {} + {} days",
+                due_at, days
+            )))?;
+        doc.insert("due_at", bson::to_bson(&due_at).map_err(DBError::from)?);
+    }
+    if let Some(mut recurrence) = task.recurrence.clone() {
+        for exdate in recurrence.exdates.iter_mut() {
+            *exdate = shift_date(*exdate, days)?;
+        }
+        for occurrence in recurrence.overrides.iter_mut() {
+            occurrence.date = shift_date(occurrence.date, days)?;
+            if let Some(start_date) = occurrence.start_date {
+                occurrence.start_date = Some(shift_date(start_date, days)?);
+            }
+            if let Some(end_date) = occurrence.end_date {
+                occurrence.end_date = Some(shift_date(end_date, days)?);
+            }
+        }
+        doc.insert(
+            "recurrence",
+            bson::to_bson(&recurrence).map_err(DBError::from)?,
+        );
+    }
+    Ok(())
+}
+
+/// parent가 user의 삭제되지 않은 task인지 확인함.
+async fn check_parent_owner(db: &dyn Storage, parent_id: &str, user: &Uuid) -> Result<ObjectId> {
+    let parent_oid = ObjectId::from_str(parent_id).map_err(DBError::MongoGetOidError)?;
+    let filter = doc! { "_id": parent_oid, "user": user, "deletedAt": { "$exists": false } };
+    if db.count(TaskService::COLL_NAME, filter, None).await? == 0 {
+        return Err(NotFoundError(parent_id.to_string()));
+    }
+    Ok(parent_oid)
+}
+
+/// id의 task를 parent_id 아래로 옮길 수 있는지 확인함.
+/// parent가 자기 자신이거나 자신의 subtask이면(parent의 조상 중에 자신이 있으면) cycle.
+async fn check_parent(
+    db: &dyn Storage,
+    id: &str,
+    parent_id: &str,
+    user: &Uuid,
+) -> Result<ObjectId> {
+    let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+    let parent_oid = check_parent_owner(db, parent_id, user).await?;
+
+    let cycle = oid == parent_oid
+        || ancestors(db, &[parent_oid], Some(doc! { "_id": 1 }), user)
+            .await?
+            .iter()
+            .any(|doc| {
+                doc.get_object_id("_id")
+                    .is_ok_and(|ancestor| ancestor == oid)
+            });
+    if cycle {
+        return Err(DB(DBError::InvalidRelation(format!(
+            "moving task {} under {} creates a cycle",
+            id, parent_id
+        ))));
+    }
+    Ok(parent_oid)
+}
+
+/// 허용되는 status 전이. Done은 다시 열 수 있고(Todo, Doing), Cancelled는 Todo로만 되돌림.
+fn check_transition(from: TaskStatus, to: TaskStatus) -> Result<()> {
+    use TaskStatus::*;
+    match (from, to) {
+        (Todo, Doing | Done | Cancelled)
+        | (Doing, Todo | Done | Cancelled)
+        | (Done, Todo | Doing)
+        | (Cancelled, Todo) => Ok(()),
+        _ => Err(InvalidTransition(format!("{:?} -> {:?}", from, to))),
+    }
+}
+
+// ids의 task들의 현재 status
+async fn statuses_of(
+    db: &dyn Storage,
+    ids: &[ObjectId],
+    user: &Uuid,
+) -> Result<HashMap<ObjectId, TaskStatus>> {
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let docs = db
+        .find(
+            TaskService::COLL_NAME,
+            doc! { "_id": { "$in": ids }, "user": user, "deletedAt": { "$exists": false } },
+            FindOpts {
+                projection: Some(doc! { "status": 1 }),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+
+    let mut statuses = HashMap::new();
+    for doc in docs {
+        let Ok(id) = doc.get_object_id("_id") else {
+            continue;
+        };
+        let status = match doc.get("status") {
+            Some(status) => {
+                bson::from_bson(status.clone()).map_err(DBError::MongoDeserializeBsonError)?
+            }
+            None => TaskStatus::default(),
+        };
+        statuses.insert(id, status);
+    }
+    Ok(statuses)
+}
+
+// progress roll-up에 영향을 주는 수정인지
+fn affects_progress(body: &UpdateTaskReq) -> bool {
+    body.progress_rate.is_some()
+        || body.status.is_some()
+        || body.estimate_minutes.is_some()
+        || body.parent_id.is_some()
+        || body.auto_progress.is_some()
+        || body.milestone.is_some()
+}
+
+fn parent_oids<'a>(tasks: impl IntoIterator<Item = &'a TaskRes>) -> Vec<ObjectId> {
+    tasks
+        .into_iter()
+        .filter_map(|task| task.parent_id.as_deref())
+        .filter_map(|id| ObjectId::from_str(id).ok())
+        .collect()
+}
+
+// ids의 task들의 parent들(삭제된 task 포함)
+pub(crate) async fn parents_of(
+    db: &dyn Storage,
+    ids: &[ObjectId],
+    user: &Uuid,
+) -> Result<Vec<ObjectId>> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let docs = db
+        .find(
+            TaskService::COLL_NAME,
+            doc! { "_id": { "$in": ids }, "user": user },
+            FindOpts {
+                projection: Some(doc! { "parent_id": 1 }),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+    Ok(docs
+        .iter()
+        .filter_map(|doc| doc.get_object_id("parent_id").ok())
+        .collect())
+}
+
+/// ids의 task들을 subtask들로부터 다시 계산하고, progress_rate가 바뀌면 parent로 올라가며 반복함.
+/// (subtask가 추가/삭제되거나 progress_rate, estimate_minutes가 바뀐 task들을 넘김)
+pub(crate) async fn roll_up(db: &dyn Storage, ids: &[ObjectId], user: &Uuid) -> Result<()> {
+    let ids: HashSet<ObjectId> = ids.iter().copied().collect();
+    for id in ids {
+        let mut chain = vec![id];
+        let docs = ancestors(db, &[id], Some(doc! { "_id": 1 }), user).await?;
+        chain.extend(docs.iter().filter_map(|doc| doc.get_object_id("_id").ok()));
+        for id in chain {
+            if !recompute_progress(db, id, user).await? {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// auto_progress인 task의 progress_rate와, milestone의 달성 시각을 subtask들로 계산함.
+/// subtask가 없으면 그대로 둠. progress_rate가 바뀌었으면 true.
+async fn recompute_progress(db: &dyn Storage, id: ObjectId, user: &Uuid) -> Result<bool> {
+    let filter = doc! { "_id": id, "user": user, "deletedAt": { "$exists": false } };
+    let projection = doc! {
+        "progress_rate": 1,
+        "estimate_minutes": 1,
+        "auto_progress": 1,
+        "milestone": 1,
+        "milestone_reached_at": 1,
+    };
+    let Some(doc) = db
+        .find_one(
+            TaskService::COLL_NAME,
+            filter.clone(),
+            Some(projection),
+            None,
+        )
+        .await?
+    else {
+        return Ok(false);
+    };
+    let task: ProgressNode =
+        bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?;
+    let auto_progress = task.auto_progress.unwrap_or(false);
+    if !auto_progress && !task.milestone && task.milestone_reached_at.is_none() {
+        return Ok(false);
+    }
+
+    let docs = db
+        .find(
+            TaskService::COLL_NAME,
+            doc! { "user": user, "parent_id": id, "deletedAt": { "$exists": false } },
+            FindOpts {
+                projection: Some(doc! { "progress_rate": 1, "estimate_minutes": 1, "status": 1 }),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+    // 취소된 subtask는 계산에서 뺌
+    let mut children = Vec::with_capacity(docs.len());
+    for doc in docs {
+        let child: ProgressNode =
+            bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?;
+        if child.status != TaskStatus::Cancelled {
+            children.push(child);
+        }
+    }
+    if children.is_empty() {
+        return Ok(false);
+    }
+
+    let progress_rate = auto_progress
+        .then(|| rolled_up_progress(&children))
+        .filter(|rate| *rate != task.progress_rate);
+    // 진행률이 100이어도 Done이 아니면 끝난 것으로 보지 않음(다시 열린 task 등)
+    let reached = task.milestone
+        && children
+            .iter()
+            .all(|child| child.status == TaskStatus::Done);
+    let (milestone_reached_at, unreached) = match (reached, task.milestone_reached_at) {
+        (true, None) => (Some(Utc::now()), false),
+        (false, Some(_)) => (None, true),
+        _ => (None, false),
+    };
+    if progress_rate.is_none() && milestone_reached_at.is_none() && !unreached {
+        return Ok(false);
+    }
+
+    let changes = RollUpUpdate {
+        progress_rate,
+        milestone_reached_at,
+    };
+    let mut set = bson::to_document(&changes).map_err(DBError::MongoSerializeBsonError)?;
+    set.insert("updatedAt", Utc::now());
+    let mut update = doc! { "$set": set, "$inc": { "version": 1 } };
+    if unreached {
+        update.insert("$unset", doc! { "milestone_reached_at": "" });
+    }
+    update_doc_ret_model::<TaskModel>(db, TaskService::COLL_NAME, &id, None, update, filter, user)
+        .await?;
+    Ok(progress_rate.is_some())
+}
+
+/// subtask들의 progress_rate 평균(반올림). 모든 subtask에 estimate_minutes가 있으면 그 값으로 가중함.
+fn rolled_up_progress(children: &[ProgressNode]) -> u8 {
+    let weighted = children
+        .iter()
+        .all(|child| child.estimate_minutes.is_some_and(|minutes| minutes > 0));
+    let (sum, total) = children.iter().fold((0u64, 0u64), |(sum, total), child| {
+        let weight = match child.estimate_minutes {
+            Some(minutes) if weighted => minutes as u64,
+            _ => 1,
+        };
+        (
+            sum + child.progress_rate.min(100) as u64 * weight,
+            total + weight,
+        )
+    });
+    match total {
+        0 => 0,
+        total => ((sum + total / 2) / total) as u8,
+    }
+}
+
+// 목록 조회와 같은 projection에 ?fields=를 적용함
+fn tree_projection(fields: Option<&str>) -> Result<(Document, Option<Arc<[&'static str]>>)> {
+    let selection = FieldSelection::parse(fields, TaskService::SELECT_FIELDS)?;
+    let projection = match &selection {
+        Some(selection) => selection.projection(
+            TaskService::SELECT_FIELDS,
+            TaskFetchOptions::build_projection(),
+        ),
+        None => TaskFetchOptions::build_projection(),
+    };
+    Ok((
+        projection,
+        selection.map(|selection| selection.fields.into()),
+    ))
+}
+
+fn to_partials(
+    docs: Vec<Document>,
+    fields: Option<Arc<[&'static str]>>,
+) -> Result<Vec<Partial<TaskRes>>> {
+    let mut tasks = Vec::with_capacity(docs.len());
+    for doc in docs {
+        let task: TaskModel =
+            bson::from_document(doc).map_err(DBError::MongoDeserializeBsonError)?;
+        tasks.push(Partial::new(TaskRes::from_model(&task), fields.clone()));
+    }
+    Ok(tasks)
+}
+
+/// parent_id로 tasks를 tree로 묶음. parent가 tasks에 없으면 최상위가 됨.
+/// 반복 task의 occurrence는 같은 id가 여러 번 나오므로 subtask를 붙이지 않음.
+fn assemble_tree(tasks: Vec<Partial<TaskRes>>) -> Vec<TaskTreeItem> {
+    let parents: HashSet<String> = tasks
+        .iter()
+        .filter(|task| task.occurrence_date.is_none())
+        .map(|task| task.id.clone())
+        .collect();
+
+    let mut children: HashMap<String, Vec<Partial<TaskRes>>> = HashMap::new();
+    let mut roots = Vec::new();
+    for task in tasks {
+        match task
+            .parent_id
+            .clone()
+            .filter(|id| parents.contains(id) && *id != task.id)
+        {
+            Some(parent_id) => children.entry(parent_id).or_default().push(task),
+            None => roots.push(task),
+        }
+    }
+
+    // 꺼낸 subtask는 map에서 지우므로, 잘못된 데이터에 cycle이 있어도 끝남
+    fn attach(
+        task: Partial<TaskRes>,
+        children: &mut HashMap<String, Vec<Partial<TaskRes>>>,
+    ) -> TaskTreeItem {
+        let subtasks = match task.occurrence_date {
+            Some(_) => vec![],
+            None => children.remove(&task.id).unwrap_or_default(),
+        };
+        let mut subtasks: Vec<TaskTreeItem> = subtasks
+            .into_iter()
+            .map(|task| attach(task, children))
+            .collect();
+        sort_tree(&mut subtasks);
+        TaskTreeItem { task, subtasks }
+    }
+
+    let mut tree: Vec<TaskTreeItem> = roots
+        .into_iter()
+        .map(|task| attach(task, &mut children))
+        .collect();
+    // cycle에 걸려 root에서 닿지 않는 task들은 최상위로 둠
+    while let Some(parent_id) = children.keys().next().cloned() {
+        for task in children.remove(&parent_id).unwrap_or_default() {
+            let item = attach(task, &mut children);
+            tree.push(item);
+        }
+    }
+    sort_tree(&mut tree);
+    tree
+}
+
+fn sort_tree(items: &mut [TaskTreeItem]) {
+    items.sort_by(|a, b| {
+        a.task
+            .start_date
+            .cmp(&b.task.start_date)
+            .then_with(|| b.task.end_date.cmp(&a.task.end_date))
+    });
+}
+
+// task id -> depends_on
+async fn load_dependency_graph(
+    db: &dyn Storage,
+    user: &Uuid,
+) -> Result<HashMap<ObjectId, Vec<ObjectId>>> {
+    let docs = db
+        .find(
+            TaskService::COLL_NAME,
+            doc! {
+                "user": user,
+                "depends_on": { "$exists": true },
+                "deletedAt": { "$exists": false },
+            },
+            FindOpts {
+                projection: Some(doc! { "_id": 1, "depends_on": 1 }),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+
+    let mut graph = HashMap::new();
+    for doc in docs {
+        let Ok(id) = doc.get_object_id("_id") else {
+            continue;
+        };
+        let deps = doc
+            .get_array("depends_on")
+            .map(|deps| deps.iter().filter_map(|dep| dep.as_object_id()).collect())
+            .unwrap_or_default();
+        graph.insert(id, deps);
+    }
+    Ok(graph)
+}
+
+// revert로 바뀌는 parent는 있어야 하고, 새로 생기는 dependency는 cycle을 만들면 안 됨
+async fn validate_revert(
+    db: &dyn Storage,
+    oid: &ObjectId,
+    current: &Document,
+    target: &Document,
+    user: &Uuid,
+) -> Result<()> {
+    if let Ok(parent_oid) = target.get_object_id("parent_id") {
+        if current.get_object_id("parent_id") != Ok(parent_oid) {
+            check_parent(db, &oid.to_hex(), &parent_oid.to_hex(), user).await?;
+        }
+    }
+
+    let deps_of = |doc: &Document| -> Vec<ObjectId> {
+        doc.get_array("depends_on")
+            .map(|deps| deps.iter().filter_map(|dep| dep.as_object_id()).collect())
+            .unwrap_or_default()
+    };
+    let current_deps = deps_of(current);
+    let target_deps = deps_of(target);
+    let added: Vec<ObjectId> = target_deps
+        .iter()
+        .filter(|dep| !current_deps.contains(dep))
+        .copied()
+        .collect();
+    if added.is_empty() {
+        return Ok(());
+    }
+
+    for dep in &added {
+        base::get::<TaskService>(db, &dep.to_hex(), user).await?;
+    }
+    let mut graph = load_dependency_graph(db, user).await?;
+    graph.insert(*oid, target_deps);
+    if let Some(dep) = added.iter().find(|dep| reaches(&graph, **dep, *oid)) {
+        return Err(DB(DBError::InvalidRelation(format!(
+            "task {} depending on {} creates a cycle",
+            oid, dep
+        ))));
+    }
+    Ok(())
+}
+
+// depends_on을 따라 from에서 to에 닿을 수 있는지
+fn reaches(graph: &HashMap<ObjectId, Vec<ObjectId>>, from: ObjectId, to: ObjectId) -> bool {
+    let mut visited = HashSet::new();
+    let mut stack = vec![from];
+    while let Some(id) = stack.pop() {
+        if id == to {
+            return true;
+        }
+        if !visited.insert(id) {
+            continue;
+        }
+        if let Some(deps) = graph.get(&id) {
+            stack.extend(deps.iter().copied());
+        }
+    }
+    false
+}
+
+/// depends_on 중 Done도 Cancelled도 아닌 task가 있으면 blocked
+async fn attach_blocked(db: &dyn Storage, user: &Uuid, tasks: Vec<&mut TaskRes>) -> Result<()> {
+    let deps: HashSet<ObjectId> = tasks
+        .iter()
+        .flat_map(|task| task.depends_on.iter().flatten())
+        .filter_map(|id| ObjectId::from_str(id).ok())
+        .collect();
+
+    let unfinished: HashSet<String> = if deps.is_empty() {
+        HashSet::new()
+    } else {
+        let deps: Vec<ObjectId> = deps.into_iter().collect();
+        let filter = doc! {
+            "_id": { "$in": deps },
+            "user": user,
+            "status": { "$nin": ["Done", "Cancelled"] },
+            "deletedAt": { "$exists": false },
+        };
+        find_ids(db, filter)
+            .await?
+            .iter()
+            .map(|id| id.to_hex())
+            .collect()
+    };
+
+    for task in tasks {
+        let blocked = task
+            .depends_on
+            .iter()
+            .flatten()
+            .any(|id| unfinished.contains(id));
+        task.blocked = Some(blocked);
+    }
+    Ok(())
+}
+
+fn task_minutes(task: &TaskModel) -> u64 {
+    if let Some(minutes) = task.estimate_minutes {
+        return minutes as u64;
+    }
+    match (task.start_date, task.end_date) {
+        (Some(start), Some(end)) => ((end - start).num_days().max(0) as u64 + 1) * 24 * 60,
+        _ => 0,
+    }
+}
+
+/// estimate를 start ~ end(포함)의 날짜에 고르게 나눔. 나누어떨어지지 않는 분은 앞의 날짜부터 1분씩 더함.
+/// from ~ to(미포함)에 들어가는 날짜만 반환함.
+fn spread_estimate(
+    estimate: u32,
+    start: NaiveDate,
+    end: NaiveDate,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<(NaiveDate, u32)> {
+    let span = (end - start).num_days().max(0) + 1;
+    let (per_day, rest) = (estimate / span as u32, estimate % span as u32);
+    let first = (from - start).num_days().max(0);
+    let last = (to - start).num_days().min(span);
+    (first..last)
+        .map(|i| {
+            let minutes = per_day + u32::from((i as u32) < rest);
+            (start + Days::new(i as u64), minutes)
+        })
+        .filter(|(_, minutes)| *minutes > 0)
+        .collect()
+}
+
+/// nodes: id -> (소요 시간, depends_on). 각 task가 끝나기까지의 가장 긴 chain을 계산하여,
+/// root(없으면 가장 늦게 끝나는 task)에서 끝나는 chain을 앞의 task부터 반환함.
+fn longest_chain(
+    nodes: &HashMap<ObjectId, (u64, Vec<ObjectId>)>,
+    root: Option<ObjectId>,
+) -> (u64, Vec<ObjectId>) {
+    // id -> (chain의 총 시간, chain에서 바로 앞의 task)
+    let mut finish: HashMap<ObjectId, (u64, Option<ObjectId>)> = HashMap::new();
+
+    fn visit(
+        id: ObjectId,
+        nodes: &HashMap<ObjectId, (u64, Vec<ObjectId>)>,
+        finish: &mut HashMap<ObjectId, (u64, Option<ObjectId>)>,
+        visiting: &mut HashSet<ObjectId>,
+    ) -> u64 {
+        if let Some((total, _)) = finish.get(&id) {
+            return *total;
+        }
+        let Some((minutes, deps)) = nodes.get(&id) else {
+            return 0;
+        };
+        // cycle은 추가 시 막지만, 혹시 있으면 그 edge는 무시함
+        if !visiting.insert(id) {
+            return 0;
+        }
+        let mut longest: Option<(u64, ObjectId)> = None;
+        for dep in deps.iter().filter(|dep| nodes.contains_key(dep)) {
+            let total = visit(*dep, nodes, finish, visiting);
+            if longest.is_none_or(|(best, _)| total > best) {
+                longest = Some((total, *dep));
+            }
+        }
+        visiting.remove(&id);
+
+        let total = minutes + longest.map_or(0, |(total, _)| total);
+        finish.insert(id, (total, longest.map(|(_, dep)| dep)));
+        total
+    }
+
+    let mut visiting = HashSet::new();
+    let end = match root {
+        Some(root) => {
+            visit(root, nodes, &mut finish, &mut visiting);
+            Some(root)
+        }
+        None => {
+            let mut ids: Vec<&ObjectId> = nodes.keys().collect();
+            ids.sort();
+            for id in &ids {
+                visit(**id, nodes, &mut finish, &mut visiting);
+            }
+            ids.into_iter()
+                .max_by_key(|id| {
+                    (
+                        finish.get(id).map_or(0, |(total, _)| *total),
+                        std::cmp::Reverse(**id),
+                    )
+                })
+                .copied()
+        }
+    };
+
+    let Some(end) = end.filter(|end| nodes.contains_key(end)) else {
+        return (0, vec![]);
+    };
+    let total = finish.get(&end).map_or(0, |(total, _)| *total);
+    let mut chain = vec![end];
+    let mut current = end;
+    while let Some((_, Some(prev))) = finish.get(&current) {
+        chain.push(*prev);
+        current = *prev;
+    }
+    chain.reverse();
+    (total, chain)
+}
+
+fn validate_recurrence(
+    recurrence: Option<&RecurrenceReq>,
+    start_date: Option<NaiveDate>,
+) -> Result<()> {
+    let Some(recurrence) = recurrence else {
+        return Ok(());
+    };
+    RRule::parse(&recurrence.rrule)?;
+    if start_date.is_none() {
+        return Err(InvalidRecurrence(
+            "start_date is required for recurring task".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// task의 반복 규칙, 기준일, occurrence 하나의 기간(일). date가 실제 occurrence인지 확인함.
+fn occurrence_of(
+    task: &TaskRes,
+    id: &str,
+    date: NaiveDate,
+) -> Result<(RecurrenceModel, NaiveDate, u64)> {
+    let (Some(recurrence), Some(dtstart)) = (task.recurrence.clone(), task.start_date) else {
+        return Err(InvalidRecurrence(format!("{} is not a recurring task", id)));
+    };
+    let span = occurrence_span(task, dtstart);
+    let occurrences = recurrence.occurrences(dtstart, span, date, date + Days::new(1))?;
+    if !occurrences.contains(&date) {
+        return Err(NotFoundError(format!("{}@{}", id, date)));
+    }
+    Ok((recurrence, dtstart, span))
+}
+
+fn occurrence_span(task: &TaskRes, dtstart: NaiveDate) -> u64 {
+    task.end_date
+        .map(|end| (end - dtstart).num_days().max(0) as u64)
+        .unwrap_or(0)
+}
+
+/// 기간이 주어진 조회에서 반복 task를 from ~ to의 occurrence들로 바꿈.
+/// 반복 규칙이 잘못된 task는 펼치지 않고 그대로 둠.
+fn expand_occurrences(
+    tasks: Vec<Partial<TaskRes>>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<Partial<TaskRes>> {
+    let mut expanded = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        let (Some(recurrence), Some(dtstart)) = (task.recurrence.as_ref(), task.start_date) else {
+            expanded.push(task);
+            continue;
+        };
+        let span = occurrence_span(&task, dtstart);
+        match recurrence.occurrences(dtstart, span, from, to) {
+            Ok(dates) => {
+                for date in dates {
+                    let mut occurrence = task.clone();
+                    *occurrence = expand_one(&task, recurrence, date, span);
+                    expanded.push(occurrence);
+                }
+            }
+            Err(e) => {
+                tracing::warn!("failed to expand recurrence of {}: {:?}", task.id, e);
+                expanded.push(task);
+            }
+        }
+    }
+    expanded
+}
+
+// date의 occurrence. override가 있으면 적용함
+fn expand_one(task: &TaskRes, recurrence: &RecurrenceModel, date: NaiveDate, span: u64) -> TaskRes {
+    let mut occurrence = task.clone();
+    occurrence.start_date = Some(date);
+    occurrence.end_date = Some(date + Days::new(span));
+    occurrence.occurrence_date = Some(date);
+    if let Some(changes) = recurrence.override_of(date) {
+        if let Some(title) = &changes.title {
+            occurrence.title = title.clone();
+        }
+        if changes.start_date.is_some() {
+            occurrence.start_date = changes.start_date;
+        }
+        if changes.end_date.is_some() {
+            occurrence.end_date = changes.end_date;
+        }
+        if let Some(progress_rate) = changes.progress_rate {
+            occurrence.progress_rate = progress_rate;
+        }
+    }
+    occurrence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::sub::chat::ChatMsgService;
+    use crate::domain::test_utils::{create_task, date, page_opts, task_req, OTHER_USER, USER};
+    use crate::infra::db::MongoDB;
+    use crate::infra::types::MsgType;
+    use crate::interface::dto::sub::chat::req::CreateMsgReq;
+
+    async fn occurrences(db: &dyn Storage, from: &str, to: &str) -> Vec<(String, String, u8)> {
+        let res =
+            TaskService::fetch_tasks(db, page_opts(), QueryOptions::default(), from, to, &USER)
+                .await
+                .unwrap();
+        let mut tasks: Vec<_> = res
+            .tasks
+            .iter()
+            .map(|task| {
+                (
+                    task.title.clone(),
+                    task.start_date.unwrap().to_string(),
+                    task.progress_rate,
+                )
+            })
+            .collect();
+        tasks.sort_by(|a, b| a.1.cmp(&b.1));
+        tasks
+    }
+
+    async fn create_weekly(db: &dyn Storage) -> String {
+        // 2024-01-01은 월요일
+        let body = CreateTaskReq {
+            title: "운동".to_string(),
+            parent_id: None,
+            start_date: Some(date("2024-01-01")),
+            end_date: Some(date("2024-01-01")),
+            due_at: None,
+            estimate_minutes: None,
+            auto_progress: None,
+            priority: None,
+            recurrence: Some(RecurrenceReq {
+                rrule: "FREQ=WEEKLY;BYDAY=MO,TH".to_string(),
+                exdates: vec![date("2024-01-04")],
+            }),
+        };
+        let res = TaskService::create_task(db, &body, &USER).await.unwrap();
+        res.data.task.id.clone()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_recurring_tasks() {
+        let mongodb = MongoDB::init_memory();
+        create_weekly(&mongodb.db).await;
+
+        let tasks = occurrences(&mongodb.db, "2024-01-01", "2024-01-15").await;
+        let dates: Vec<&str> = tasks.iter().map(|(_, date, _)| date.as_str()).collect();
+        assert_eq!(dates, ["2024-01-01", "2024-01-08", "2024-01-11"]);
+
+        // 기간이 없으면 펼치지 않음
+        let res = TaskService::fetch_tasks(
+            &mongodb.db,
+            page_opts(),
+            QueryOptions::default(),
+            "",
+            "",
+            &USER,
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.results, 1);
+        assert_eq!(res.tasks[0].occurrence_date, None);
+
+        let body = CreateTaskReq {
+            title: "잘못된 반복".to_string(),
+            parent_id: None,
+            start_date: Some(date("2024-01-01")),
+            end_date: None,
+            due_at: None,
+            estimate_minutes: None,
+            auto_progress: None,
+            priority: None,
+            recurrence: Some(RecurrenceReq {
+                rrule: "FREQ=HOURLY".to_string(),
+                exdates: vec![],
+            }),
+        };
+        let res = TaskService::create_task(&mongodb.db, &body, &USER).await;
+        assert!(matches!(res, Err(InvalidRecurrence(_))));
+    }
+
+    #[tokio::test]
+    async fn test_update_occurrence() {
+        let mongodb = MongoDB::init_memory();
+        let id = create_weekly(&mongodb.db).await;
+
+        // occurrence 하나만 완료
+        let body = UpdateOccurrenceReq {
+            scope: OccurrenceScope::This,
+            title: None,
+            start_date: None,
+            end_date: None,
+            progress_rate: Some(100),
+        };
+        let res = TaskService::update_occurrence(
+            &mongodb.db,
+            &id,
+            date("2024-01-08"),
+            &body,
+            None,
+            &USER,
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.data.task.occurrence_date, Some(date("2024-01-08")));
+        assert_eq!(res.data.task.progress_rate, 100);
+
+        // 제외된 날짜는 occurrence가 아님
+        let res = TaskService::update_occurrence(
+            &mongodb.db,
+            &id,
+            date("2024-01-04"),
+            &body,
+            None,
+            &USER,
+        )
+        .await;
+        assert!(matches!(res, Err(NotFoundError(_))));
+
+        // 2024-01-11부터는 제목을 바꿈
+        let body = UpdateOccurrenceReq {
+            scope: OccurrenceScope::ThisAndFollowing,
+            title: Some("수영".to_string()),
+            start_date: None,
+            end_date: None,
+            progress_rate: None,
+        };
+        TaskService::update_occurrence(&mongodb.db, &id, date("2024-01-11"), &body, None, &USER)
+            .await
+            .unwrap();
+
+        let tasks = occurrences(&mongodb.db, "2024-01-01", "2024-01-16").await;
+        assert_eq!(
+            tasks,
+            [
+                ("운동".to_string(), "2024-01-01".to_string(), 0),
+                ("운동".to_string(), "2024-01-08".to_string(), 100),
+                ("수영".to_string(), "2024-01-11".to_string(), 0),
+                ("수영".to_string(), "2024-01-15".to_string(), 0),
+            ]
+        );
+
+        // 2024-01-08 이후 삭제
+        TaskService::delete_occurrence(
+            &mongodb.db,
+            &id,
+            date("2024-01-08"),
+            OccurrenceScope::ThisAndFollowing,
+            &USER,
+        )
+        .await
+        .unwrap();
+        let tasks = occurrences(&mongodb.db, "2024-01-01", "2024-01-16").await;
+        let titles: Vec<&str> = tasks.iter().map(|(title, _, _)| title.as_str()).collect();
+        assert_eq!(titles, ["운동", "수영", "수영"]);
+    }
+
+    #[tokio::test]
+    async fn test_update_following_occurrences_keeps_task_fields() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let id = create_weekly(db).await;
+        let dep = create_task(db, &task_req("준비"), &USER).await;
+        TaskService::add_dependency(db, &id, &dep, &USER)
+            .await
+            .unwrap();
+        let update = UpdateTaskReq {
+            estimate_minutes: Some(45),
+            priority: Some(Priority::High),
+            status: Some(TaskStatus::Doing),
+            chat_type: Some(ChatType::Task),
+            ..no_changes()
+        };
+        let res = TaskService::update_task(db, &id, &update, None, &USER)
+            .await
+            .unwrap();
+        let version = res.data.task.version;
+
+        let body = UpdateOccurrenceReq {
+            scope: OccurrenceScope::ThisAndFollowing,
+            title: Some("수영".to_string()),
+            start_date: None,
+            end_date: None,
+            progress_rate: None,
+        };
+        // version이 다르면 뒤쪽 series를 만들지 않음
+        let on = date("2024-01-11");
+        let res =
+            TaskService::update_occurrence(db, &id, on, &body, Some(version + 1), &USER).await;
+        assert!(matches!(res, Err(PreconditionFailed(_))));
+        assert_eq!(titles_of(db, "", "", &USER).await, ["운동", "준비"]);
+
+        let res = TaskService::update_occurrence(db, &id, on, &body, Some(version), &USER)
+            .await
+            .unwrap();
+        let split = res.data.task.into_inner();
+        assert_eq!(split.title, "수영");
+        assert_eq!(split.start_date, Some(on));
+        assert_eq!(split.estimate_minutes, Some(45));
+        assert_eq!(split.priority, Some(Priority::High));
+        assert_eq!(split.status, TaskStatus::Doing);
+        assert_eq!(split.chat_type, Some(ChatType::Task));
+        assert_eq!(split.depends_on, Some(vec![dep]));
+        assert_eq!(split.version, 1);
+
+        let res = TaskService::get_task(db, &id, None, &USER).await.unwrap();
+        assert_eq!(res.data.task.version, version + 1);
+    }
+
+    // 2024-01-01 ~ 2024-01-02의 task
+    fn dated(title: &str) -> CreateTaskReq {
+        CreateTaskReq {
+            start_date: Some(date("2024-01-01")),
+            end_date: Some(date("2024-01-02")),
+            ..task_req(title)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bulk_create_tasks() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let body = |title: &str, recurrence: Option<RecurrenceReq>| CreateTaskReq {
+            recurrence,
+            ..task_req(title)
+        };
+        // start_date가 없는 반복 task는 그 item만 실패함
+        let weekly = RecurrenceReq {
+            rrule: "FREQ=WEEKLY".to_string(),
+            exdates: vec![],
+        };
+        let bodies = vec![body("a", None), body("b", Some(weekly)), body("c", None)];
+
+        let res = TaskService::bulk_create_tasks(db, &bodies, &USER)
+            .await
+            .unwrap();
+        assert_eq!((res.succeeded, res.failed), (2, 1));
+        let indexes: Vec<usize> = res.items.iter().map(|item| item.index).collect();
+        assert_eq!(indexes, [0, 1, 2]);
+        assert_eq!(res.items[0].data.as_ref().unwrap().title, "a");
+        assert_eq!(res.items[1].status, "fail");
+        assert_eq!(res.items[1].id, None);
+        assert_eq!(res.items[2].data.as_ref().unwrap().title, "c");
+    }
+
+    #[tokio::test]
+    async fn test_bulk_update_tasks() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_task(db, &dated("a"), &USER).await;
+        let b = create_task(db, &dated("b"), &USER).await;
+        let item = |id: &str, version: Option<i64>, body: UpdateTaskReq| BatchUpdateReq {
+            id: id.to_string(),
+            version,
+            body,
+        };
+
+        let items = vec![
+            item(
+                &a,
+                Some(1),
+                UpdateTaskReq {
+                    title: Some("a2".to_string()),
+                    ..no_changes()
+                },
+            ),
+            // version이 다르면 그 item만 실패함
+            item(
+                &b,
+                Some(5),
+                UpdateTaskReq {
+                    title: Some("b2".to_string()),
+                    ..no_changes()
+                },
+            ),
+            // 자기 자신을 parent로 둘 수 없음
+            item(
+                &b,
+                None,
+                UpdateTaskReq {
+                    parent_id: Some(b.clone()),
+                    ..no_changes()
+                },
+            ),
+        ];
+        let res = TaskService::bulk_update_tasks(db, &items, &USER)
+            .await
+            .unwrap();
+        assert_eq!((res.succeeded, res.failed), (1, 2));
+        let task = res.items[0].data.as_ref().unwrap();
+        assert_eq!((task.title.as_str(), task.version), ("a2", 2));
+
+        let res = TaskService::get_task(db, &b, None, &USER).await.unwrap();
+        let task = res.data.task.into_inner();
+        assert_eq!((task.title.as_str(), task.version), ("b", 1));
+        assert_eq!(task.parent_id, None);
+
+        // 하나씩 업데이트할 때와 같이 history가 남음
+        let res = TaskService::fetch_task_history(db, &a, page_opts(), &USER)
+            .await
+            .unwrap();
+        assert!(res.changes.iter().any(|change| change.version == 2));
+
+        // item마다 왕복하므로 BATCH_MAX를 넘으면 아무것도 바꾸지 않음
+        let items: Vec<_> = (0..=base::BATCH_MAX)
+            .map(|_| item(&a, None, no_changes()))
+            .collect();
+        let res = TaskService::bulk_update_tasks(db, &items, &USER).await;
+        assert!(matches!(res, Err(DB(DBError::ListLimitOverMax { .. }))));
+    }
+
+    async fn revision_of(db: &dyn Storage, id: &str, version: i64) -> String {
+        let res = TaskService::fetch_task_history(db, id, page_opts(), &USER)
+            .await
+            .unwrap();
+        let change = res.changes.iter().find(|change| change.version == version);
+        change.unwrap().id.clone()
+    }
+
+    #[tokio::test]
+    async fn test_revert_task_parent_cycle() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_task(db, &dated("a"), &USER).await;
+        let b = create_task(db, &dated("b"), &USER).await;
+        let move_under = |parent: &str| UpdateTaskReq {
+            parent_id: Some(parent.to_string()),
+            ..no_changes()
+        };
+
+        // b를 a 아래로 옮긴 뒤, 다시 꺼내고 a를 b 아래로 옮김
+        TaskService::update_task(db, &b, &move_under(&a), None, &USER)
+            .await
+            .unwrap();
+        let revision = revision_of(db, &b, 2).await;
+        TaskService::update_task(db, &b, &move_under(""), None, &USER)
+            .await
+            .unwrap();
+        TaskService::update_task(db, &a, &move_under(&b), None, &USER)
+            .await
+            .unwrap();
+
+        let res = TaskService::revert_task(db, &b, &revision, &USER).await;
+        assert!(matches!(res, Err(DB(DBError::InvalidRelation(_)))));
+        let res = TaskService::get_task(db, &b, None, &USER).await.unwrap();
+        assert_eq!(res.data.task.into_inner().parent_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_revert_task_dependency_cycle() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_task(db, &dated("a"), &USER).await;
+        let b = create_task(db, &dated("b"), &USER).await;
+
+        // a가 b에 의존하던 revision으로 되돌리면, 지금 b가 a에 의존하므로 cycle
+        TaskService::add_dependency(db, &a, &b, &USER)
+            .await
+            .unwrap();
+        let revision = revision_of(db, &a, 2).await;
+        TaskService::remove_dependency(db, &a, &b, &USER)
+            .await
+            .unwrap();
+        TaskService::add_dependency(db, &b, &a, &USER)
+            .await
+            .unwrap();
+
+        let res = TaskService::revert_task(db, &a, &revision, &USER).await;
+        assert!(matches!(res, Err(DB(DBError::InvalidRelation(_)))));
+
+        // cycle이 없어지면 되돌릴 수 있음
+        TaskService::remove_dependency(db, &b, &a, &USER)
+            .await
+            .unwrap();
+        let res = TaskService::revert_task(db, &a, &revision, &USER)
+            .await
+            .unwrap();
+        let task = res.data.task.into_inner();
+        assert_eq!(task.depends_on, Some(vec![b.clone()]));
+    }
+
+    // a(120분) <- b(2일) <- c(30분) 순서로 의존하는 task들
+    async fn create_chain(db: &dyn Storage) -> (String, String, String) {
+        let estimated = |title: &str, minutes: u32| CreateTaskReq {
+            estimate_minutes: Some(minutes),
+            ..dated(title)
+        };
+        let a = create_task(db, &estimated("설계", 120), &USER).await;
+        let b = create_task(db, &dated("구현"), &USER).await;
+        let c = create_task(db, &estimated("배포", 30), &USER).await;
+        TaskService::add_dependency(db, &b, &a, &USER)
+            .await
+            .unwrap();
+        TaskService::add_dependency(db, &c, &b, &USER)
+            .await
+            .unwrap();
+        (a, b, c)
+    }
+
+    #[tokio::test]
+    async fn test_add_dependency() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_task(db, &dated("a"), &USER).await;
+        let b = create_task(db, &dated("b"), &USER).await;
+
+        let res = TaskService::add_dependency(db, &b, &a, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.data.task.depends_on, Some(vec![a.clone()]));
+        assert_eq!(res.data.task.blocked, Some(true));
+
+        let res = TaskService::remove_dependency(db, &b, &a, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.data.task.depends_on, Some(vec![]));
+        assert_eq!(res.data.task.blocked, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_dependency_cycle() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let (a, _, c) = create_chain(db).await;
+
+        // a -> b -> c -> a
+        let res = TaskService::add_dependency(db, &a, &c, &USER).await;
+        assert!(matches!(res, Err(DB(DBError::InvalidRelation(_)))));
+        let res = TaskService::add_dependency(db, &a, &a, &USER).await;
+        assert!(matches!(res, Err(DB(DBError::InvalidRelation(_)))));
+    }
+
+    #[tokio::test]
+    async fn test_critical_path() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let (a, b, c) = create_chain(db).await;
+
+        // b는 날짜로 계산(2일)
+        let res = TaskService::critical_path(db, Some(&c), &USER)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = res.tasks.iter().map(|task| task.id.as_str()).collect();
+        assert_eq!(ids, [a.as_str(), b.as_str(), c.as_str()]);
+        assert_eq!(res.total_minutes, 120 + 2 * 24 * 60 + 30);
+    }
+
+    #[tokio::test]
+    async fn test_delete_dependency_target() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let (_, b, c) = create_chain(db).await;
+
+        // b가 삭제되면 c의 dependency도 사라짐
+        TaskService::delete_task(db, &b, DeleteTaskOptionReq::DeleteOnlyTask, &USER)
+            .await
+            .unwrap();
+        let res = TaskService::get_task(db, &c, None, &USER).await.unwrap();
+        assert_eq!(res.data.task.depends_on, Some(vec![]));
+        assert_eq!(res.data.task.blocked, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_dependency_on_other_users_task() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let (a, _, c) = create_chain(db).await;
+        let other = create_task(db, &dated("other"), &OTHER_USER).await;
+
+        // 다른 user의 task에는 의존할 수 없고, 그 user의 graph에도 섞이지 않음
+        let res = TaskService::add_dependency(db, &a, &other, &USER).await;
+        assert!(matches!(res, Err(NotFoundError(_))));
+        let res = TaskService::add_dependency(db, &other, &c, &OTHER_USER).await;
+        assert!(matches!(res, Err(NotFoundError(_))));
+        let res = TaskService::critical_path(db, None, &OTHER_USER)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = res.tasks.iter().map(|task| task.id.as_str()).collect();
+        assert_eq!(ids, [other.as_str()]);
+    }
+
+    async fn create_subtask(db: &dyn Storage, title: &str, parent_id: Option<&str>) -> String {
+        let body = CreateTaskReq {
+            parent_id: parent_id.map(str::to_string),
+            ..dated(title)
+        };
+        create_task(db, &body, &USER).await
+    }
+
+    // a > b > c > d, e
+    async fn create_tree(db: &dyn Storage) -> [String; 5] {
+        let a = create_subtask(db, "a", None).await;
+        let b = create_subtask(db, "b", Some(&a)).await;
+        let c = create_subtask(db, "c", Some(&b)).await;
+        let d = create_subtask(db, "d", Some(&c)).await;
+        let e = create_subtask(db, "e", None).await;
+        [a, b, c, d, e]
+    }
+
+    async fn path_titles(db: &dyn Storage, id: &str) -> Vec<String> {
+        let res = TaskService::task_path(db, id, &USER).await.unwrap();
+        res.path.into_iter().map(|item| item.title).collect()
+    }
+
+    fn tree_titles(items: &[TaskTreeItem]) -> Vec<String> {
+        items
+            .iter()
+            .map(|item| match item.subtasks.as_slice() {
+                [] => item.task.title.clone(),
+                subtasks => format!("{}({})", item.task.title, tree_titles(subtasks).join(",")),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_build_tree() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [_, _, c, d, _] = create_tree(db).await;
+
+        let res = TaskService::fetch_tasks(db, page_opts(), QueryOptions::default(), "", "", &USER)
+            .await
+            .unwrap();
+        let tree = TaskService::build_tree(db, &USER, res.tasks, None)
+            .await
+            .unwrap();
+        let mut titles = tree_titles(&tree);
+        titles.sort();
+        assert_eq!(titles, ["a(b(c(d)))", "e"]);
+
+        // 목록에 없는 조상들도 붙음
+        let res = TaskService::get_task(db, &d, None, &USER).await.unwrap();
+        assert_eq!(res.data.task.parent_id.as_deref(), Some(c.as_str()));
+        let tree = TaskService::build_tree(db, &USER, vec![res.data.task], None)
+            .await
+            .unwrap();
+        assert_eq!(tree_titles(&tree), ["a(b(c(d)))"]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_subtree() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [_, b, _, d, _] = create_tree(db).await;
+
+        let res = TaskService::fetch_subtree(db, &b, None, &USER)
+            .await
+            .unwrap();
+        assert_eq!(tree_titles(&[res.task]), ["b(c(d))"]);
+        assert_eq!(path_titles(db, &d).await, ["a", "b", "c", "d"]);
+    }
+
+    #[tokio::test]
+    async fn test_move_task() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [_, _, c, d, e] = create_tree(db).await;
+
+        let res = TaskService::move_task(db, &c, None, &USER).await.unwrap();
+        assert_eq!(res.data.task.parent_id, None);
+        assert_eq!(path_titles(db, &d).await, ["c", "d"]);
+        TaskService::move_task(db, &c, Some(&e), &USER)
+            .await
+            .unwrap();
+        assert_eq!(path_titles(db, &d).await, ["e", "c", "d"]);
+    }
+
+    #[tokio::test]
+    async fn test_move_task_cycle() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [a, _, _, d, _] = create_tree(db).await;
+
+        // 자기 자신이나 subtask 아래로는 옮길 수 없음
+        let res = TaskService::move_task(db, &a, Some(&d), &USER).await;
+        assert!(matches!(res, Err(DB(DBError::InvalidRelation(_)))));
+        let res = TaskService::move_task(db, &a, Some(&a), &USER).await;
+        assert!(matches!(res, Err(DB(DBError::InvalidRelation(_)))));
+
+        let update = UpdateTaskReq {
+            parent_id: Some(d.clone()),
+            ..no_changes()
+        };
+        let res = TaskService::update_task(db, &a, &update, None, &USER).await;
+        assert!(matches!(res, Err(DB(DBError::InvalidRelation(_)))));
+    }
+
+    #[tokio::test]
+    async fn test_delete_subtree() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [a, b, _, d, _] = create_tree(db).await;
+
+        // 모든 깊이의 subtask가 함께 삭제됨
+        TaskService::delete_task(db, &b, DeleteTaskOptionReq::DeleteAllSubtasks, &USER)
+            .await
+            .unwrap();
+        let res = TaskService::get_task(db, &d, None, &USER).await;
+        assert!(matches!(res, Err(NotFoundError(_))));
+        let res = TaskService::fetch_subtree(db, &a, None, &USER)
+            .await
+            .unwrap();
+        assert_eq!(tree_titles(&[res.task]), ["a"]);
+    }
+
+    #[tokio::test]
+    async fn test_tree_of_other_users_task() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [a, b, _, d, _] = create_tree(db).await;
+
+        // 다른 user의 task 아래로는 옮길 수 없음
+        let other = create_task(db, &dated("other"), &OTHER_USER).await;
+        let res = TaskService::move_task(db, &other, Some(&b), &OTHER_USER).await;
+        assert!(matches!(res, Err(NotFoundError(_))));
+        let body = CreateTaskReq {
+            parent_id: Some(b.clone()),
+            ..dated("other child")
+        };
+        let res = TaskService::create_task(db, &body, &OTHER_USER).await;
+        assert!(matches!(res, Err(NotFoundError(_))));
+
+        // parent_id가 이어져 있어도 $graphLookup은 같은 user의 task만 따라감
+        let oid = ObjectId::from_str(&other).unwrap();
+        let parent_oid = ObjectId::from_str(&b).unwrap();
+        db.update_one(
+            TaskService::COLL_NAME,
+            doc! { "_id": oid },
+            doc! { "$set": { "parent_id": parent_oid } },
+            None,
+        )
+        .await
+        .unwrap();
+        let res = TaskService::fetch_subtree(db, &a, None, &USER)
+            .await
+            .unwrap();
+        assert_eq!(tree_titles(&[res.task]), ["a(b(c(d)))"]);
+        let res = TaskService::task_path(db, &other, &OTHER_USER)
+            .await
+            .unwrap();
+        let titles: Vec<String> = res.path.into_iter().map(|item| item.title).collect();
+        assert_eq!(titles, ["other"]);
+        let res = TaskService::task_path(db, &d, &OTHER_USER).await;
+        assert!(matches!(res, Err(NotFoundError(_))));
+    }
+
+    fn no_changes() -> UpdateTaskReq {
+        UpdateTaskReq {
+            title: None,
+            milestone: None,
+            parent_id: None,
+            start_date: None,
+            end_date: None,
+            due_at: None,
+            progress_rate: None,
+            estimate_minutes: None,
+            auto_progress: None,
+            status: None,
+            priority: None,
+            chat_type: None,
+            recurrence: None,
+        }
+    }
+
+    async fn create_rolled_up(
+        db: &dyn Storage,
+        title: &str,
+        parent_id: Option<&str>,
+        estimate_minutes: Option<u32>,
+    ) -> String {
+        let body = CreateTaskReq {
+            parent_id: parent_id.map(str::to_string),
+            estimate_minutes,
+            auto_progress: Some(true),
+            ..task_req(title)
+        };
+        create_task(db, &body, &USER).await
+    }
+
+    // g > (p > (a, b), s). g, p는 auto_progress, p는 milestone. [g, p, a, b, s]를 반환
+    async fn create_rolled_up_tree(db: &dyn Storage) -> [String; 5] {
+        let g = create_rolled_up(db, "g", None, None).await;
+        let p = create_rolled_up(db, "p", Some(&g), None).await;
+        let a = create_rolled_up(db, "a", Some(&p), Some(60)).await;
+        let b = create_rolled_up(db, "b", Some(&p), Some(180)).await;
+        let s = create_subtask(db, "s", Some(&g)).await;
+        let update = UpdateTaskReq {
+            milestone: Some(true),
+            ..no_changes()
+        };
+        TaskService::update_task(db, &p, &update, None, &USER)
+            .await
+            .unwrap();
+        [g, p, a, b, s]
+    }
+
+    async fn set_progress(db: &dyn Storage, id: &str, progress_rate: u8) {
+        let update = UpdateTaskReq {
+            progress_rate: Some(progress_rate),
+            ..no_changes()
+        };
+        TaskService::update_task(db, id, &update, None, &USER)
+            .await
+            .unwrap();
+    }
+
+    async fn progress(db: &dyn Storage, id: &str) -> (u8, bool) {
+        let task = base::get::<TaskService>(db, id, &USER).await.unwrap();
+        (task.progress_rate, task.milestone_reached_at.is_some())
+    }
+
+    #[tokio::test]
+    async fn test_progress_roll_up() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [g, p, a, b, _] = create_rolled_up_tree(db).await;
+
+        // p는 estimate로 가중, g는 p에 estimate가 없으므로 단순 평균
+        set_progress(db, &a, 100).await;
+        assert_eq!(progress(db, &p).await, (25, false));
+        assert_eq!(progress(db, &g).await, (13, false));
+
+        // 진행률이 100이어도 Done이 아니면 milestone에 도달하지 않음
+        set_progress(db, &b, 100).await;
+        assert_eq!(progress(db, &p).await, (100, false));
+        assert_eq!(progress(db, &g).await, (50, false));
+
+        set_status(db, &a, TaskStatus::Done).await.unwrap();
+        set_status(db, &b, TaskStatus::Done).await.unwrap();
+        assert_eq!(progress(db, &p).await, (100, true));
+    }
+
+    #[tokio::test]
+    async fn test_progress_roll_up_overrides_manual() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [_, p, a, _, _] = create_rolled_up_tree(db).await;
+        set_progress(db, &a, 100).await;
+
+        // 수동으로 바꿔도 auto_progress면 다시 계산됨
+        let update = UpdateTaskReq {
+            progress_rate: Some(10),
+            ..no_changes()
+        };
+        let res = TaskService::update_task(db, &p, &update, None, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.data.task.progress_rate, 25);
+    }
+
+    #[tokio::test]
+    async fn test_progress_roll_up_subtask_changes() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [g, p, a, b, s] = create_rolled_up_tree(db).await;
+        set_status(db, &a, TaskStatus::Done).await.unwrap();
+        set_status(db, &b, TaskStatus::Done).await.unwrap();
+
+        let c = create_subtask(db, "c", Some(&p)).await;
+        assert_eq!(progress(db, &p).await, (67, false));
+        assert_eq!(progress(db, &g).await, (34, false));
+
+        TaskService::move_task(db, &c, Some(&g), &USER)
+            .await
+            .unwrap();
+        assert_eq!(progress(db, &p).await, (100, true));
+        assert_eq!(progress(db, &g).await, (33, false));
+
+        TaskService::delete_task(db, &s, DeleteTaskOptionReq::DeleteOnlyTask, &USER)
+            .await
+            .unwrap();
+        assert_eq!(progress(db, &g).await, (50, false));
+        set_progress(db, &c, 100).await;
+        assert_eq!(progress(db, &g).await, (100, false));
+    }
+
+    #[tokio::test]
+    async fn test_progress_without_auto_progress() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [g, p, a, b, _] = create_rolled_up_tree(db).await;
+        set_status(db, &a, TaskStatus::Done).await.unwrap();
+        set_status(db, &b, TaskStatus::Done).await.unwrap();
+
+        // auto_progress가 아닌 task는 그대로
+        let update = UpdateTaskReq {
+            auto_progress: Some(false),
+            progress_rate: Some(0),
+            ..no_changes()
+        };
+        TaskService::update_task(db, &p, &update, None, &USER)
+            .await
+            .unwrap();
+        assert_eq!(progress(db, &p).await, (0, true));
+        assert_eq!(progress(db, &g).await, (0, false));
+    }
+
+    #[tokio::test]
+    async fn test_progress_roll_up_ignores_other_users_tasks() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [_, p, a, _, _] = create_rolled_up_tree(db).await;
+
+        // parent_id가 p를 가리키는 다른 user의 완료된 task는 p의 subtask로 세지 않음
+        let other = create_task(db, &task_req("other"), &OTHER_USER).await;
+        let oid = ObjectId::from_str(&other).unwrap();
+        let parent_oid = ObjectId::from_str(&p).unwrap();
+        let update = doc! { "$set": { "parent_id": parent_oid, "progress_rate": 100 } };
+        db.update_one(TaskService::COLL_NAME, doc! { "_id": oid }, update, None)
+            .await
+            .unwrap();
+        set_progress(db, &a, 100).await;
+        assert_eq!(progress(db, &p).await, (25, false));
+
+        // 다른 user는 USER의 subtask를 바꿔 roll-up을 일으킬 수 없음
+        let update = UpdateTaskReq {
+            progress_rate: Some(0),
+            ..no_changes()
+        };
+        let res = TaskService::update_task(db, &a, &update, None, &OTHER_USER).await;
+        assert!(matches!(res, Err(NotFoundError(_))));
+        assert_eq!(progress(db, &p).await, (25, false));
+    }
+
+    async fn create_prioritized(db: &dyn Storage, title: &str, priority: Priority) -> String {
+        let body = CreateTaskReq {
+            priority: Some(priority),
+            ..task_req(title)
+        };
+        create_task(db, &body, &USER).await
+    }
+
+    async fn set_status(db: &dyn Storage, id: &str, status: TaskStatus) -> Result<TaskRes> {
+        let update = UpdateTaskReq {
+            status: Some(status),
+            ..no_changes()
+        };
+        let res = TaskService::update_task(db, id, &update, None, &USER).await?;
+        Ok(res.data.task.into_inner())
+    }
+
+    async fn titles_of(db: &dyn Storage, filter: &str, sort: &str, user: &Uuid) -> Vec<String> {
+        let query_opts = QueryOptions {
+            filter: Some(filter.to_string()),
+            sort: Some(sort.to_string()),
+            ..Default::default()
+        };
+        let res = TaskService::fetch_tasks(db, page_opts(), query_opts, "", "", user)
+            .await
+            .unwrap();
+        res.tasks.iter().map(|task| task.title.clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_status_done() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_prioritized(db, "a", Priority::Low).await;
+
+        let task = set_status(db, &a, TaskStatus::Doing).await.unwrap();
+        assert_eq!(task.status, TaskStatus::Doing);
+        assert_eq!(task.doneAt, None);
+
+        // Done이 되면 완료 시각과 progress_rate 100
+        let task = set_status(db, &a, TaskStatus::Done).await.unwrap();
+        assert!(task.doneAt.is_some());
+        assert_eq!(task.progress_rate, 100);
+
+        let res = set_status(db, &a, TaskStatus::Cancelled).await;
+        assert!(matches!(res, Err(InvalidTransition(_))));
+    }
+
+    #[tokio::test]
+    async fn test_status_reopen() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_prioritized(db, "a", Priority::Low).await;
+        set_status(db, &a, TaskStatus::Done).await.unwrap();
+
+        // 다시 열면 완료 시각은 지워짐
+        let task = set_status(db, &a, TaskStatus::Todo).await.unwrap();
+        assert_eq!(task.status, TaskStatus::Todo);
+        assert_eq!(task.doneAt, None);
+    }
+
+    #[tokio::test]
+    async fn test_status_reopen_blocks_again() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [_, p, a, b, _] = create_rolled_up_tree(db).await;
+        TaskService::add_dependency(db, &b, &a, &USER)
+            .await
+            .unwrap();
+        set_status(db, &a, TaskStatus::Done).await.unwrap();
+        set_status(db, &b, TaskStatus::Done).await.unwrap();
+        let res = TaskService::get_task(db, &b, None, &USER).await.unwrap();
+        assert_eq!(res.data.task.blocked, Some(false));
+        assert_eq!(progress(db, &p).await, (100, true));
+
+        // 다시 열린 task는 진행률이 100으로 남아도 끝나지 않은 것으로 봄
+        let task = set_status(db, &a, TaskStatus::Doing).await.unwrap();
+        assert_eq!(task.progress_rate, 100);
+        let res = TaskService::get_task(db, &b, None, &USER).await.unwrap();
+        assert_eq!(res.data.task.blocked, Some(true));
+        assert_eq!(progress(db, &p).await, (100, false));
+    }
+
+    #[tokio::test]
+    async fn test_status_cancelled() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let b = create_prioritized(db, "b", Priority::Urgent).await;
+
+        let task = set_status(db, &b, TaskStatus::Cancelled).await.unwrap();
+        assert!(task.cancelledAt.is_some());
+        let res = set_status(db, &b, TaskStatus::Doing).await;
+        assert!(matches!(res, Err(InvalidTransition(_))));
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_status_and_priority() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        create_prioritized(db, "a", Priority::Low).await;
+        let b = create_prioritized(db, "b", Priority::Urgent).await;
+        create_prioritized(db, "c", Priority::Medium).await;
+        set_status(db, &b, TaskStatus::Cancelled).await.unwrap();
+
+        assert_eq!(
+            titles_of(db, "status=Todo", "-priority", &USER).await,
+            ["c", "a"]
+        );
+        assert_eq!(
+            titles_of(db, "priority>=2", "priority", &USER).await,
+            ["c", "b"]
+        );
+        assert_eq!(
+            titles_of(db, "status=Cancelled", "title", &USER).await,
+            ["b"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_of_other_users_task() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_prioritized(db, "a", Priority::Low).await;
+        let body = CreateTaskReq {
+            priority: Some(Priority::Urgent),
+            ..task_req("other")
+        };
+        create_task(db, &body, &OTHER_USER).await;
+
+        // 다른 user는 상태를 바꿀 수 없고, 상태/우선순위 filter에도 그 user의 task만 나옴
+        let update = UpdateTaskReq {
+            status: Some(TaskStatus::Done),
+            ..no_changes()
+        };
+        let res = TaskService::update_task(db, &a, &update, None, &OTHER_USER).await;
+        assert!(matches!(res, Err(NotFoundError(_))));
+        assert_eq!(
+            titles_of(db, "status=Todo", "title", &OTHER_USER).await,
+            ["other"]
+        );
+        assert_eq!(titles_of(db, "priority>=1", "title", &USER).await, ["a"]);
+    }
+
+    // p > a > (b, c), other. c는 b에, b는 other에 의존하고 a에는 chat msg가 하나 있음.
+    // [p, a, b, other]를 반환
+    async fn create_clone_source(db: &dyn Storage) -> [String; 4] {
+        let p = create_subtask(db, "p", None).await;
+        let a = create_subtask(db, "a", Some(&p)).await;
+        let b = create_subtask(db, "b", Some(&a)).await;
+        let c = create_subtask(db, "c", Some(&a)).await;
+        let other = create_subtask(db, "other", None).await;
+        TaskService::add_dependency(db, &c, &b, &USER)
+            .await
+            .unwrap();
+        TaskService::add_dependency(db, &b, &other, &USER)
+            .await
+            .unwrap();
+        let msg = CreateMsgReq {
+            msg_type: MsgType::Text,
+            content: "메모".to_string(),
+            booked: false,
+        };
+        ChatMsgService::<TaskModel>::add_msg(db, &a, &msg, &USER)
+            .await
+            .unwrap();
+        [p, a, b, other]
+    }
+
+    // 7일 뒤로, chat msg와 함께 복사
+    async fn clone_shifted(db: &dyn Storage, id: &str) -> TaskTreeItem {
+        let body = CloneTaskReq {
+            include_chat_msgs: true,
+            shift_days: Some(7),
+        };
+        TaskService::clone_task(db, id, &body, &USER)
+            .await
+            .unwrap()
+            .task
+    }
+
+    fn subtask<'a>(tree: &'a TaskTreeItem, title: &str) -> &'a TaskTreeItem {
+        let subtask = tree.subtasks.iter().find(|item| item.task.title == title);
+        subtask.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_clone_task() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [p, a, _, _] = create_clone_source(db).await;
+
+        let tree = clone_shifted(db, &a).await;
+        assert_eq!(tree_titles(std::slice::from_ref(&tree)), ["a(b,c)"]);
+        // 복사본은 원래 task와 같은 parent 아래
+        assert_ne!(tree.task.id, a);
+        assert_eq!(tree.task.parent_id.as_deref(), Some(p.as_str()));
+        assert_eq!(
+            subtask(&tree, "b").task.parent_id.as_deref(),
+            Some(tree.task.id.as_str())
+        );
+        let tree_p = TaskService::fetch_subtree(db, &p, None, &USER)
+            .await
+            .unwrap();
+        assert_eq!(
+            tree_titles(std::slice::from_ref(&tree_p.task)),
+            ["p(a(b,c),a(b,c))"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clone_task_shift_days() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [_, a, b, _] = create_clone_source(db).await;
+
+        let tree = clone_shifted(db, &a).await;
+        let copy_b = subtask(&tree, "b");
+        assert_eq!(copy_b.task.start_date, Some(date("2024-01-08")));
+        assert_eq!(copy_b.task.end_date, Some(date("2024-01-09")));
+
+        // 원래 task는 그대로
+        let original = TaskService::get_task(db, &b, None, &USER).await.unwrap();
+        assert_eq!(original.data.task.start_date, Some(date("2024-01-01")));
+    }
+
+    #[tokio::test]
+    async fn test_clone_task_dependencies() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [_, a, _, other] = create_clone_source(db).await;
+
+        // subtree 안의 dependency는 복사본으로, 밖의 것은 그대로
+        let tree = clone_shifted(db, &a).await;
+        let (copy_b, copy_c) = (subtask(&tree, "b"), subtask(&tree, "c"));
+        assert_eq!(copy_c.task.depends_on, Some(vec![copy_b.task.id.clone()]));
+        assert_eq!(copy_b.task.depends_on, Some(vec![other.clone()]));
+    }
+
+    #[tokio::test]
+    async fn test_clone_task_chat_msgs() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [_, a, _, _] = create_clone_source(db).await;
+        let original = base::get::<TaskService>(db, &a, &USER).await.unwrap();
+        let msg_id = original.chat_msgs.unwrap()[0].id;
+
+        let tree = clone_shifted(db, &a).await;
+        let copy_a = TaskService::get_task(db, &tree.task.id, None, &USER)
+            .await
+            .unwrap();
+        let msgs = copy_a.data.task.into_inner().chat_msgs.unwrap();
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].content, "메모");
+        assert_ne!(msgs[0].id, msg_id);
+    }
+
+    #[tokio::test]
+    async fn test_clone_task_defaults() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [_, a, _, _] = create_clone_source(db).await;
+
+        // 기본값은 날짜를 옮기지 않고 chat msg도 복사하지 않음
+        let tree = TaskService::clone_task(db, &a, &CloneTaskReq::default(), &USER)
+            .await
+            .unwrap()
+            .task;
+        assert_eq!(tree.task.start_date, Some(date("2024-01-01")));
+        let copy_a = TaskService::get_task(db, &tree.task.id, None, &USER)
+            .await
+            .unwrap();
+        assert_eq!(copy_a.data.task.into_inner().chat_msgs, None);
+    }
+
+    #[tokio::test]
+    async fn test_clone_missing_task() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+
+        let body = CloneTaskReq::default();
+        let res = TaskService::clone_task(db, &ObjectId::new().to_hex(), &body, &USER).await;
+        assert!(matches!(res, Err(NotFoundError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_clone_other_users_task() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let [_, a, _, _] = create_clone_source(db).await;
+
+        let res = TaskService::clone_task(db, &a, &CloneTaskReq::default(), &OTHER_USER).await;
+        assert!(matches!(res, Err(NotFoundError(_))));
+
+        // a를 parent로 가리키는 다른 user의 task는 복사되지 않음
+        let other = create_task(db, &dated("다른 user"), &OTHER_USER).await;
+        let oid = ObjectId::from_str(&other).unwrap();
+        let parent_oid = ObjectId::from_str(&a).unwrap();
+        let update = doc! { "$set": { "parent_id": parent_oid } };
+        db.update_one(TaskService::COLL_NAME, doc! { "_id": oid }, update, None)
+            .await
+            .unwrap();
+        let tree = clone_shifted(db, &a).await;
+        assert_eq!(tree_titles(std::slice::from_ref(&tree)), ["a(b,c)"]);
+    }
+
+    async fn create_estimated(
+        db: &dyn Storage,
+        title: &str,
+        (start, end): (&str, &str),
+        estimate_minutes: Option<u32>,
+        recurrence: Option<RecurrenceReq>,
+    ) -> String {
+        let body = CreateTaskReq {
+            start_date: Some(date(start)),
+            end_date: Some(date(end)),
+            estimate_minutes,
+            recurrence,
+            ..task_req(title)
+        };
+        create_task(db, &body, &USER).await
+    }
+
+    // 2024-01-01 ~ 2024-01-08의 작업량
+    async fn capacity_of(db: &dyn Storage, user: &Uuid) -> CapacityRes {
+        TaskService::capacity(
+            db,
+            QueryOptions::default(),
+            Some(date("2024-01-01")),
+            Some(date("2024-01-08")),
+            user,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_capacity() {
+        use crate::interface::dto::settings::req::UpdateSettingsReq;
+
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_estimated(db, "a", ("2024-01-01", "2024-01-03"), Some(400), None).await;
+        // 기간 시작일에 끝나는 task도 그 날의 몫이 들어감
+        let b = create_estimated(db, "b", ("2023-12-30", "2024-01-01"), Some(300), None).await;
+        let daily = RecurrenceReq {
+            rrule: "FREQ=DAILY;COUNT=3".to_string(),
+            exdates: vec![],
+        };
+        let c =
+            create_estimated(db, "c", ("2024-01-05", "2024-01-05"), Some(30), Some(daily)).await;
+        let done =
+            create_estimated(db, "done", ("2024-01-02", "2024-01-02"), Some(500), None).await;
+        set_status(db, &done, TaskStatus::Done).await.unwrap();
+        create_estimated(db, "none", ("2024-01-02", "2024-01-04"), None, None).await;
+        create_estimated(db, "later", ("2024-01-08", "2024-01-09"), Some(100), None).await;
+
+        let body = UpdateSettingsReq {
+            daily_capacity_minutes: Some(200),
+        };
+        SettingsService::update_settings(db, &body, None, &USER)
+            .await
+            .unwrap();
+
+        let res = capacity_of(db, &USER).await;
+        let days: Vec<(String, u32, bool)> = res
+            .days
+            .iter()
+            .map(|day| (day.date.to_string(), day.minutes, day.over))
+            .collect();
+        assert_eq!(
+            days,
+            [
+                ("2024-01-01".to_string(), 234, true),
+                ("2024-01-02".to_string(), 133, false),
+                ("2024-01-03".to_string(), 133, false),
+                ("2024-01-04".to_string(), 0, false),
+                ("2024-01-05".to_string(), 30, false),
+                ("2024-01-06".to_string(), 30, false),
+                ("2024-01-07".to_string(), 30, false),
+            ]
+        );
+        assert_eq!(res.days[0].task_ids, [a, b]);
+        assert_eq!(res.days[4].task_ids, [c]);
+        assert_eq!(res.daily_capacity, 200);
+        assert_eq!(res.total_minutes, 590);
+        assert_eq!(res.over_days, 1);
+    }
+
+    #[tokio::test]
+    async fn test_capacity_requires_range() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+
+        let res = TaskService::capacity(
+            db,
+            QueryOptions::default(),
+            Some(date("2024-01-01")),
+            None,
+            &USER,
+        )
+        .await;
+        assert!(matches!(res, Err(DB(DBError::InvalidQuery(_)))));
+    }
+
+    #[tokio::test]
+    async fn test_capacity_of_other_user() {
+        use crate::domain::settings::DEFAULT_DAILY_CAPACITY;
+        use crate::interface::dto::settings::req::UpdateSettingsReq;
+
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_estimated(db, "a", ("2024-01-01", "2024-01-03"), Some(400), None).await;
+        let body = UpdateSettingsReq {
+            daily_capacity_minutes: Some(200),
+        };
+        SettingsService::update_settings(db, &body, None, &USER)
+            .await
+            .unwrap();
+        let body = CreateTaskReq {
+            start_date: Some(date("2024-01-02")),
+            end_date: Some(date("2024-01-02")),
+            estimate_minutes: Some(300),
+            ..task_req("other")
+        };
+        let other = create_task(db, &body, &OTHER_USER).await;
+
+        // 각 user는 자기 task와 자기 설정으로만 계산
+        let res = capacity_of(db, &USER).await;
+        assert_eq!(res.daily_capacity, 200);
+        assert_eq!(res.total_minutes, 400);
+        assert_eq!(res.days[1].task_ids, [a]);
+        assert_eq!(res.over_days, 0);
+
+        let res = capacity_of(db, &OTHER_USER).await;
+        assert_eq!(res.daily_capacity, DEFAULT_DAILY_CAPACITY);
+        assert_eq!(res.total_minutes, 300);
+        assert_eq!(res.days[1].task_ids, [other]);
+        assert_eq!(res.over_days, 0);
     }
 }
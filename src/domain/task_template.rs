@@ -0,0 +1,641 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use mongodb::bson::doc;
+use mongodb::bson::Document;
+use mongodb::bson::{self, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::sub::chat::MsgModel;
+use crate::domain::task::{self, TaskModel, TaskService};
+use crate::infra::types::{MsgType, PageOptions, Priority, QueryFilterOptions, QueryOptions};
+use crate::interface::dto::task::res::SingleTaskTreeRes;
+use crate::interface::dto::task_template::{
+    req::{CreateTaskTemplateReq, InstantiateTemplateReq, UpdateTaskTemplateReq},
+    res::{SingleTaskTemplateRes, TaskTemplateData, TaskTemplateListRes, TaskTemplateRes},
+};
+
+use crate::{
+    domain::error::{Error::*, Result},
+    domain::repo::base::{self, MongoRepo},
+    domain::repo::index::{IndexDef, IndexKind::*},
+    domain::repo::query::{FieldKind, Load::*, QueryFields, SelectFields},
+    domain::repo::storage::Storage,
+    infra::db::error::Error as DBError,
+};
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TaskTemplateModel {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    #[serde(with = "bson::serde_helpers::uuid_1_as_binary")]
+    pub user: Uuid,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    // parent가 앞에 오도록 depth 순서로 저장
+    pub nodes: Vec<TemplateNode>,
+    #[serde(default)]
+    pub version: i64,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub createdAt: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub updatedAt: DateTime<Utc>,
+}
+
+/// template에 저장된 task 하나. key는 template 안에서만 쓰이며 parent는 parent node의 key.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TemplateNode {
+    pub key: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<u32>,
+    pub title: String,
+    // root task의 start_date로부터의 일 수
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_offset: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_offset: Option<i64>,
+    pub milestone: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate_minutes: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_progress: Option<bool>,
+    // 생성되는 task의 chat_msgs로 복사됨
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chat_msgs: Vec<TemplateMsg>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TemplateMsg {
+    pub msg_type: MsgType,
+    pub content: String,
+    pub booked: bool,
+}
+
+pub struct TaskTemplateService;
+
+// task subtree를 snapshot한 template
+#[derive(Serialize)]
+struct TemplateSnapshot<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    nodes: Vec<TemplateNode>,
+}
+
+// template node로부터 생성되는 task. _id는 parent_id를 잇기 위해 미리 정함
+#[derive(Serialize)]
+struct InstantiatedTask<'a> {
+    _id: ObjectId,
+    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_id: Option<ObjectId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_date: Option<NaiveDate>,
+    milestone: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    estimate_minutes: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    priority: Option<Priority>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    auto_progress: Option<bool>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    chat_msgs: Vec<MsgModel>,
+}
+
+impl MongoRepo for TaskTemplateService {
+    const COLL_NAME: &'static str = "task_templates";
+    const QUERY_FIELDS: QueryFields = &[
+        ("name", FieldKind::Str),
+        ("createdAt", FieldKind::DateTime),
+        ("updatedAt", FieldKind::DateTime),
+    ];
+    const SELECT_FIELDS: SelectFields = &[
+        ("user", Always),
+        ("name", Always),
+        ("description", Lazy),
+        ("nodes", Always),
+        ("createdAt", Always),
+        ("updatedAt", Always),
+        ("version", Always),
+    ];
+    const INDEXES: &'static [IndexDef] = &[IndexDef {
+        keys: &[("user", Asc), ("name", Asc)],
+        ..IndexDef::DEFAULT
+    }];
+    type Model = TaskTemplateModel;
+    type ModelResponse = TaskTemplateRes;
+    fn convert_doc_to_response(template: &TaskTemplateModel) -> TaskTemplateRes {
+        TaskTemplateRes::from_model(template)
+    }
+
+    fn create_doc<Schema: Serialize>(user: &Uuid, body: &Schema) -> Result<Document> {
+        let serialized_data = bson::to_bson(body).map_err(DBError::from)?;
+        let document = serialized_data.as_document().unwrap();
+        let datetime = Utc::now();
+        let mut doc_with_dates = doc! {
+            "user": user,
+            "version": 1,
+            "createdAt": datetime,
+            "updatedAt": datetime,
+        };
+        doc_with_dates.extend(document.clone());
+        Ok(doc_with_dates)
+    }
+}
+
+impl TaskTemplateService {
+    pub async fn fetch_templates(
+        db: &dyn Storage,
+        page_opts: PageOptions,
+        query_opts: QueryOptions,
+        user: &Uuid,
+    ) -> Result<TaskTemplateListRes> {
+        let filter_opts = QueryFilterOptions {
+            find_filter: None,
+            proj_opts: None,
+            page_opts,
+            query_opts,
+        };
+        let templates_result = base::fetch::<Self>(db, filter_opts, user).await?;
+
+        Ok(TaskTemplateListRes {
+            status: "success",
+            results: templates_result.items.len(),
+            templates: templates_result.items,
+            next_cursor: templates_result.next_cursor,
+            has_more: templates_result.has_more,
+            total: templates_result.total,
+        })
+    }
+
+    /// task_id의 task와 그 아래의 모든 subtask를 template으로 저장함.
+    /// 날짜는 root task의 start_date(없으면 가장 이른 start_date)로부터의 일 수로 저장됨.
+    pub async fn create_template(
+        db: &dyn Storage,
+        body: &CreateTaskTemplateReq,
+        user: &Uuid,
+    ) -> Result<SingleTaskTemplateRes> {
+        let oid = ObjectId::from_str(&body.task_id).map_err(DBError::MongoGetOidError)?;
+        let filter = doc! { "_id": oid, "user": user, "deletedAt": { "$exists": false } };
+        let root = db
+            .find_one(TaskService::COLL_NAME, filter, None, None)
+            .await?
+            .ok_or(NotFoundError(body.task_id.to_string()))?;
+
+        let mut docs = vec![root];
+        docs.extend(task::descendants(db, &[oid], None, user).await?);
+        let mut tasks = Vec::with_capacity(docs.len());
+        for doc in docs {
+            let task: TaskModel = bson::from_document(doc).map_err(DBError::from)?;
+            tasks.push(task);
+        }
+
+        let snapshot = TemplateSnapshot {
+            name: &body.name,
+            description: body.description.as_deref(),
+            nodes: snapshot_nodes(&tasks),
+        };
+        let template_result = base::create::<Self, _>(db, &snapshot, user).await?;
+
+        Ok(SingleTaskTemplateRes {
+            status: "success",
+            data: TaskTemplateData {
+                template: template_result.into(),
+            },
+        })
+    }
+
+    pub async fn get_template(
+        db: &dyn Storage,
+        id: &str,
+        fields: Option<&str>,
+        user: &Uuid,
+    ) -> Result<SingleTaskTemplateRes> {
+        let template_result = base::get_fields::<Self>(db, id, fields, user).await?;
+
+        Ok(SingleTaskTemplateRes {
+            status: "success",
+            data: TaskTemplateData {
+                template: template_result,
+            },
+        })
+    }
+
+    pub async fn update_template(
+        db: &dyn Storage,
+        id: &str,
+        body: &UpdateTaskTemplateReq,
+        if_match: Option<i64>,
+        user: &Uuid,
+    ) -> Result<SingleTaskTemplateRes> {
+        let template_result =
+            base::update::<Self, UpdateTaskTemplateReq>(db, id, body, if_match, user).await?;
+
+        Ok(SingleTaskTemplateRes {
+            status: "success",
+            data: TaskTemplateData {
+                template: template_result.into(),
+            },
+        })
+    }
+
+    pub async fn delete_template(db: &dyn Storage, id: &str, user: &Uuid) -> Result<()> {
+        base::delete::<Self>(db, id, user).await
+    }
+
+    /// template의 task들을 start_date 기준으로 한 번에 생성하고, 생성된 tree를 반환함.
+    /// parent_id가 주어지면 root task를 그 task의 subtask로 만듦.
+    pub async fn instantiate_template(
+        db: &dyn Storage,
+        id: &str,
+        body: &InstantiateTemplateReq,
+        user: &Uuid,
+    ) -> Result<SingleTaskTreeRes> {
+        let template = base::get::<Self>(db, id, user).await?;
+        if template.nodes.is_empty() {
+            return Err(TypedError(format!("template {} has no tasks", id)));
+        }
+
+        let parent_oid = match body.parent_id.as_deref().filter(|id| !id.is_empty()) {
+            Some(parent_id) => {
+                let parent_oid =
+                    ObjectId::from_str(parent_id).map_err(DBError::MongoGetOidError)?;
+                let filter =
+                    doc! { "_id": parent_oid, "user": user, "deletedAt": { "$exists": false } };
+                if db.count(TaskService::COLL_NAME, filter, None).await? == 0 {
+                    return Err(NotFoundError(parent_id.to_string()));
+                }
+                Some(parent_oid)
+            }
+            None => None,
+        };
+
+        // key마다 새 _id를 정해두고 parent 링크를 바꿈
+        let oids: HashMap<u32, ObjectId> = template
+            .nodes
+            .iter()
+            .map(|node| (node.key, ObjectId::new()))
+            .collect();
+        let now = Utc::now();
+        let mut docs = Vec::with_capacity(template.nodes.len());
+        for node in &template.nodes {
+            let parent_id = match node.parent {
+                Some(key) => Some(*oids.get(&key).ok_or(TypedError(format!(
+                    "template {} has no parent node {}",
+                    id, key
+                )))?),
+                None => parent_oid,
+            };
+            let chat_msgs = node
+                .chat_msgs
+                .iter()
+                .map(|msg| MsgModel {
+                    id: ObjectId::new(),
+                    msg_type: msg.msg_type.clone(),
+                    content: msg.content.to_owned(),
+                    booked: msg.booked,
+                    chat_type: None,
+                    chat_msgs: None,
+                    version: 1,
+                    createdAt: now,
+                })
+                .collect();
+            let task = InstantiatedTask {
+                _id: oids[&node.key],
+                title: &node.title,
+                parent_id,
+                start_date: shift(body.start_date, node.start_offset)?,
+                end_date: shift(body.start_date, node.end_offset)?,
+                milestone: node.milestone,
+                estimate_minutes: node.estimate_minutes,
+                priority: node.priority,
+                auto_progress: node.auto_progress,
+                chat_msgs,
+            };
+            docs.push(TaskService::create_doc(user, &task)?);
+        }
+
+        task::insert_tree(db, docs, user).await?;
+        if let Some(parent_oid) = parent_oid {
+            task::roll_up(db, &[parent_oid], user).await?;
+        }
+
+        let root = template
+            .nodes
+            .iter()
+            .find(|node| node.parent.is_none())
+            .map(|node| oids[&node.key])
+            .ok_or(TypedError(format!("template {} has no root task", id)))?;
+        TaskService::fetch_subtree(db, &root.to_hex(), None, user).await
+    }
+}
+
+// tasks[0]이 root이고 나머지는 depth 순서(parent가 앞에 옴)
+fn snapshot_nodes(tasks: &[TaskModel]) -> Vec<TemplateNode> {
+    let anchor = tasks
+        .first()
+        .and_then(|root| root.start_date)
+        .or_else(|| tasks.iter().filter_map(|task| task.start_date).min());
+    let offset = |date: Option<NaiveDate>| {
+        anchor
+            .zip(date)
+            .map(|(anchor, date)| (date - anchor).num_days())
+    };
+
+    let mut keys: HashMap<ObjectId, u32> = HashMap::new();
+    let mut nodes = Vec::with_capacity(tasks.len());
+    for (index, task) in tasks.iter().enumerate() {
+        let parent = match index {
+            0 => None,
+            _ => match task.parent_id.and_then(|id| keys.get(&id)) {
+                Some(key) => Some(*key),
+                None => continue,
+            },
+        };
+        let key = nodes.len() as u32;
+        keys.insert(task.id, key);
+        nodes.push(TemplateNode {
+            key,
+            parent,
+            title: task.title.to_owned(),
+            start_offset: offset(task.start_date),
+            end_offset: offset(task.end_date),
+            milestone: task.milestone,
+            estimate_minutes: task.estimate_minutes,
+            priority: task.priority,
+            auto_progress: task.auto_progress,
+            chat_msgs: task
+                .chat_msgs
+                .iter()
+                .flatten()
+                .map(|msg| TemplateMsg {
+                    msg_type: msg.msg_type.clone(),
+                    content: msg.content.to_owned(),
+                    booked: msg.booked,
+                })
+                .collect(),
+        });
+    }
+    nodes
+}
+
+fn shift(start_date: NaiveDate, offset: Option<i64>) -> Result<Option<NaiveDate>> {
+    let Some(offset) = offset else {
+        return Ok(None);
+    };
+    task::shift_date(start_date, offset).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::sub::chat::ChatMsgService;
+    use crate::domain::test_utils::{create_task, date, task_req, OTHER_USER, USER};
+    use crate::infra::db::MongoDB;
+    use crate::infra::types::TaskTreeItem;
+    use crate::interface::dto::sub::chat::req::CreateMsgReq;
+    use crate::interface::dto::task::req::CreateTaskReq;
+
+    async fn create_dated(
+        db: &dyn Storage,
+        title: &str,
+        parent_id: Option<&str>,
+        (start, end): (&str, &str),
+    ) -> String {
+        let body = CreateTaskReq {
+            parent_id: parent_id.map(str::to_string),
+            start_date: Some(date(start)),
+            end_date: Some(date(end)),
+            ..task_req(title)
+        };
+        create_task(db, &body, &USER).await
+    }
+
+    // 릴리즈 > (설계 > API 설계, 출시) tree를 만들고 (릴리즈, 설계) id를 반환
+    async fn create_release(db: &dyn Storage) -> (String, String) {
+        let root = create_dated(db, "릴리즈", None, ("2024-03-04", "2024-03-29")).await;
+        let design = create_dated(db, "설계", Some(&root), ("2024-03-04", "2024-03-08")).await;
+        create_dated(db, "API 설계", Some(&design), ("2024-03-05", "2024-03-06")).await;
+        let body = CreateTaskReq {
+            parent_id: Some(root.to_owned()),
+            start_date: Some(date("2024-03-29")),
+            end_date: Some(date("2024-03-29")),
+            priority: Some(Priority::Urgent),
+            ..task_req("출시")
+        };
+        create_task(db, &body, &USER).await;
+        let msg = CreateMsgReq {
+            msg_type: MsgType::Text,
+            content: "체크리스트 확인".to_string(),
+            booked: true,
+        };
+        ChatMsgService::<TaskModel>::add_msg(db, &design, &msg, &USER)
+            .await
+            .unwrap();
+        (root, design)
+    }
+
+    async fn create_release_template(db: &dyn Storage) -> (String, TaskTemplateRes) {
+        let (root, _) = create_release(db).await;
+        let body = CreateTaskTemplateReq {
+            task_id: root.to_owned(),
+            name: "릴리즈 절차".to_string(),
+            description: None,
+        };
+        let res = TaskTemplateService::create_template(db, &body, &USER)
+            .await
+            .unwrap();
+        (root, res.data.template.into_inner())
+    }
+
+    // 2분기 task 아래에 2024-05-06부터 생성
+    async fn instantiate(db: &dyn Storage, template_id: &str) -> (String, TaskTreeItem) {
+        let parent = create_dated(db, "2분기", None, ("2024-04-01", "2024-06-30")).await;
+        let body = InstantiateTemplateReq {
+            start_date: date("2024-05-06"),
+            parent_id: Some(parent.to_owned()),
+        };
+        let tree = TaskTemplateService::instantiate_template(db, template_id, &body, &USER)
+            .await
+            .unwrap()
+            .task;
+        (parent, tree)
+    }
+
+    // (title, parent title, start_date, end_date)
+    type Row = (String, Option<String>, Option<NaiveDate>, Option<NaiveDate>);
+
+    fn flatten(item: &TaskTreeItem, parent: Option<&str>) -> Vec<Row> {
+        let task = &item.task;
+        let mut rows = vec![(
+            task.title.to_owned(),
+            parent.map(str::to_string),
+            task.start_date,
+            task.end_date,
+        )];
+        for subtask in &item.subtasks {
+            rows.extend(flatten(subtask, Some(&task.title)));
+        }
+        rows
+    }
+
+    #[tokio::test]
+    async fn test_create_template() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let (_, template) = create_release_template(db).await;
+
+        let titles: HashMap<u32, &str> = template
+            .nodes
+            .iter()
+            .map(|node| (node.key, node.title.as_str()))
+            .collect();
+        let mut nodes: Vec<_> = template
+            .nodes
+            .iter()
+            .map(|node| {
+                let parent = node.parent.map(|key| titles[&key]);
+                (
+                    node.title.as_str(),
+                    parent,
+                    node.start_offset,
+                    node.end_offset,
+                    node.priority,
+                )
+            })
+            .collect();
+        nodes.sort();
+        assert_eq!(
+            nodes,
+            vec![
+                ("API 설계", Some("설계"), Some(1), Some(2), None),
+                ("릴리즈", None, Some(0), Some(25), None),
+                ("설계", Some("릴리즈"), Some(0), Some(4), None),
+                (
+                    "출시",
+                    Some("릴리즈"),
+                    Some(25),
+                    Some(25),
+                    Some(Priority::Urgent)
+                ),
+            ]
+        );
+        let design_node = template.nodes.iter().find(|node| node.title == "설계");
+        assert_eq!(design_node.unwrap().chat_msgs.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_instantiate_template() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let (root, template) = create_release_template(db).await;
+
+        // 다른 날짜에, 기존 task 아래로 생성
+        let (parent, tree) = instantiate(db, &template.id).await;
+        assert_ne!(tree.task.id, root);
+        assert_eq!(tree.task.parent_id.as_deref(), Some(parent.as_str()));
+
+        let mut rows = flatten(&tree, None);
+        rows.sort();
+        let expected = [
+            ("API 설계", Some("설계"), "2024-05-07", "2024-05-08"),
+            ("릴리즈", None, "2024-05-06", "2024-05-31"),
+            ("설계", Some("릴리즈"), "2024-05-06", "2024-05-10"),
+            ("출시", Some("릴리즈"), "2024-05-31", "2024-05-31"),
+        ];
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(title, parent, start, end)| {
+                let parent = parent.map(str::to_string);
+                (
+                    title.to_string(),
+                    parent,
+                    Some(date(start)),
+                    Some(date(end)),
+                )
+            })
+            .collect();
+        assert_eq!(rows, expected);
+
+        // 원래 tree는 그대로
+        let original = TaskService::fetch_subtree(db, &root, None, &USER)
+            .await
+            .unwrap();
+        assert_eq!(flatten(&original.task, None)[0].2, Some(date("2024-03-04")));
+    }
+
+    #[tokio::test]
+    async fn test_instantiate_template_chat_msgs() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let (_, template) = create_release_template(db).await;
+        let (_, tree) = instantiate(db, &template.id).await;
+
+        // seed chat msg가 새 task로 복사됨
+        let design_copy = tree
+            .subtasks
+            .iter()
+            .find(|item| item.task.title == "설계")
+            .unwrap();
+        let design_copy = base::get::<TaskService>(db, &design_copy.task.id, &USER)
+            .await
+            .unwrap();
+        let msgs = design_copy.chat_msgs.unwrap();
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].content, "체크리스트 확인");
+    }
+
+    #[tokio::test]
+    async fn test_instantiate_template_missing_parent() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let (_, template) = create_release_template(db).await;
+
+        let body = InstantiateTemplateReq {
+            start_date: date("2024-05-06"),
+            parent_id: Some(ObjectId::new().to_hex()),
+        };
+        let res = TaskTemplateService::instantiate_template(db, &template.id, &body, &USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_template_of_other_users_tasks() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let (root, template) = create_release_template(db).await;
+
+        // 다른 user의 task로 template을 만들거나, 다른 user의 template을 쓸 수 없음
+        let body = CreateTaskTemplateReq {
+            task_id: root.to_owned(),
+            name: "릴리즈 절차".to_string(),
+            description: None,
+        };
+        let res = TaskTemplateService::create_template(db, &body, &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+        let body = InstantiateTemplateReq {
+            start_date: date("2024-05-06"),
+            parent_id: None,
+        };
+        let res =
+            TaskTemplateService::instantiate_template(db, &template.id, &body, &OTHER_USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+
+        // 다른 user의 task 아래에는 만들 수 없음
+        let other = create_task(db, &task_req("다른 user"), &OTHER_USER).await;
+        let body = InstantiateTemplateReq {
+            start_date: date("2024-05-06"),
+            parent_id: Some(other),
+        };
+        let res = TaskTemplateService::instantiate_template(db, &template.id, &body, &USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+    }
+}
@@ -0,0 +1,44 @@
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::domain::repo::storage::Storage;
+use crate::domain::task::TaskService;
+use crate::infra::types::PageOptions;
+use crate::interface::dto::task::req::CreateTaskReq;
+
+pub const USER: Uuid = Uuid::from_bytes([0; 16]);
+pub const OTHER_USER: Uuid = Uuid::from_bytes([1; 16]);
+
+pub fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+pub fn page_opts() -> PageOptions {
+    PageOptions {
+        limit: 100,
+        page: 1,
+        cursor: None,
+        with_total: false,
+    }
+}
+
+// 제목만 있는 task 생성 요청. 다른 값은 struct update로 채움.
+pub fn task_req(title: &str) -> CreateTaskReq {
+    CreateTaskReq {
+        title: title.to_string(),
+        parent_id: None,
+        start_date: None,
+        end_date: None,
+        due_at: None,
+        estimate_minutes: None,
+        auto_progress: None,
+        priority: None,
+        recurrence: None,
+    }
+}
+
+// user의 task를 만들고 id를 반환.
+pub async fn create_task(db: &dyn Storage, body: &CreateTaskReq, user: &Uuid) -> String {
+    let res = TaskService::create_task(db, body, user).await.unwrap();
+    res.data.task.id.clone()
+}
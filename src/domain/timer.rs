@@ -0,0 +1,635 @@
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use chrono::{NaiveDate, Utc};
+use mongodb::bson::{self, doc, oid::ObjectId, Document};
+use mongodb::ClientSession;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::sub::daily_item::{DailyItemService, TimerResultModel};
+use crate::domain::sub::timer::TaskTimerModel;
+use crate::domain::task::TaskService;
+use crate::infra::types::TimerState;
+use crate::interface::dto::sub::daily_item::res::TimerResultRes;
+use crate::interface::dto::task::res::{SingleTaskRes, TaskData};
+use crate::interface::dto::timer::{
+    req::StartTimerReq,
+    res::{
+        CategoryTimeItem, CategoryTimeReportRes, StopTimerData, StopTimerRes, TaskTimeReportRes,
+        TimeReportDay,
+    },
+};
+
+use crate::{
+    domain::error::{Error::*, Result},
+    domain::repo::base::{self, MongoRepo},
+    domain::repo::base_array::MongoArrayRepo,
+    domain::repo::storage::{FindOpts, Storage},
+    domain::repo::transaction::Transaction,
+    infra::db::error::Error as DBError,
+};
+
+pub struct TimerService;
+
+// timer가 멈추면 timer는 null이 되고 tracked_minutes가 늘어남
+#[derive(Serialize)]
+struct TimerUpdate<'a> {
+    timer: Option<&'a TaskTimerModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tracked_minutes: Option<u32>,
+}
+
+// 보고서에는 daily의 날짜와 timer_results만 필요함
+#[derive(Deserialize)]
+struct DailyTimerResults {
+    date: NaiveDate,
+    #[serde(default)]
+    timer_results: Vec<TimerResultModel>,
+}
+
+impl TimerService {
+    /// task의 timer를 시작함. 일시정지된 timer면 다시 시작함.
+    /// 사용자마다 실행 중인 timer는 하나뿐이므로, 다른 task의 timer가 실행 중이면 TimerConflict.
+    pub async fn start_timer(
+        db: &dyn Storage,
+        id: &str,
+        body: &StartTimerReq,
+        user: &Uuid,
+    ) -> Result<SingleTaskRes> {
+        let task = base::get::<TaskService>(db, id, user).await?;
+        let now = Utc::now();
+        let timer = match task.timer.clone() {
+            Some(timer) if timer.state == TimerState::Running => {
+                return Err(TimerConflict(format!(
+                    "timer of task {} is already running",
+                    id
+                )));
+            }
+            Some(mut timer) => {
+                timer.resume(now);
+                timer
+            }
+            None => {
+                let category_id =
+                    ObjectId::from_str(&body.category_id).map_err(DBError::MongoGetOidError)?;
+                TaskTimerModel::new(category_id, body.category_color.to_owned(), now)
+            }
+        };
+
+        if let Some(running) = running_task(db, id, user).await? {
+            return Err(TimerConflict(format!(
+                "timer of task {} is already running",
+                running
+            )));
+        }
+
+        // 동시에 시작한 다른 timer는 user_running_timer unique 인덱스에 걸림
+        let update = TimerUpdate {
+            timer: Some(&timer),
+            tracked_minutes: None,
+        };
+        let task_result =
+            match base::update::<TaskService, _>(db, id, &update, Some(task.version), user).await {
+                Ok(task_result) => task_result,
+                Err(MongoDuplicateError(_)) => {
+                    return Err(TimerConflict(
+                        "another timer is already running".to_string(),
+                    ))
+                }
+                Err(e) => return Err(e),
+            };
+
+        Ok(SingleTaskRes {
+            status: "success",
+            data: TaskData {
+                task: task_result.into(),
+            },
+        })
+    }
+
+    pub async fn pause_timer(db: &dyn Storage, id: &str, user: &Uuid) -> Result<SingleTaskRes> {
+        let task = base::get::<TaskService>(db, id, user).await?;
+        let mut timer = match task.timer.clone() {
+            Some(timer) if timer.state == TimerState::Running => timer,
+            _ => return Err(TimerConflict(format!("task {} has no running timer", id))),
+        };
+        timer.pause(Utc::now());
+
+        let update = TimerUpdate {
+            timer: Some(&timer),
+            tracked_minutes: None,
+        };
+        let task_result =
+            base::update::<TaskService, _>(db, id, &update, Some(task.version), user).await?;
+
+        Ok(SingleTaskRes {
+            status: "success",
+            data: TaskData {
+                task: task_result.into(),
+            },
+        })
+    }
+
+    /// timer를 멈추고, 실행된 시간을 task의 tracked_minutes에 더한 뒤
+    /// timer를 시작한 날(UTC)의 daily 문서에 TimerResultModel로 남김.
+    pub async fn stop_timer(db: &dyn Storage, id: &str, user: &Uuid) -> Result<StopTimerRes> {
+        let task = base::get::<TaskService>(db, id, user).await?;
+        let Some(timer) = task.timer.as_ref() else {
+            return Err(TimerConflict(format!("task {} has no timer", id)));
+        };
+        let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+        let now = Utc::now();
+        let focus_secs = timer.focus_secs(now);
+        let result = TimerResultModel {
+            id: ObjectId::new(),
+            category_id: timer.category_id,
+            category_color: timer.category_color.to_owned(),
+            startAt: timer.startAt,
+            endAt: now,
+            focus_time: focus_time(focus_secs),
+            task_id: Some(oid),
+            focus_secs: Some(focus_secs),
+            version: 1,
+            createdAt: now,
+        };
+
+        // 같은 timer를 두 번 멈추지 않도록 version을 확인함
+        let update = TimerUpdate {
+            timer: None,
+            tracked_minutes: Some(task.tracked_minutes + to_minutes(focus_secs)),
+        };
+        // task와 daily 기록은 하나의 transaction으로 실행함.
+        let date = timer.startAt.date_naive();
+        let mut tx = Transaction::start(db).await?;
+        let task_result = async {
+            let task_result = base::update_with_session::<TaskService, _>(
+                db,
+                id,
+                &update,
+                Some(task.version),
+                user,
+                tx.session(),
+            )
+            .await?;
+            add_timer_result(db, date, &result, tx.session(), user).await?;
+            Ok(task_result)
+        }
+        .await;
+        let task_result = tx.finish(task_result).await?;
+
+        Ok(StopTimerRes {
+            status: "success",
+            data: StopTimerData {
+                task: task_result.into(),
+                timer_result: TimerResultRes::from_model(&result),
+            },
+        })
+    }
+
+    /// task에 기록된 시간과, daily의 timer_results로 집계한 날짜별 시간
+    pub async fn task_time_report(
+        db: &dyn Storage,
+        id: &str,
+        user: &Uuid,
+    ) -> Result<TaskTimeReportRes> {
+        let task = base::get_fields::<TaskService>(db, id, Some("title,tracked_minutes"), user)
+            .await?
+            .into_inner();
+        let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+        let filter = doc! {
+            "user": user,
+            "timer_results.task_id": oid,
+            "deletedAt": { "$exists": false },
+        };
+
+        let mut days: BTreeMap<NaiveDate, (i64, usize)> = BTreeMap::new();
+        for daily in daily_timer_results(db, filter).await? {
+            for result in daily
+                .timer_results
+                .iter()
+                .filter(|r| r.task_id == Some(oid))
+            {
+                let day = days.entry(daily.date).or_default();
+                day.0 += result_secs(result);
+                day.1 += 1;
+            }
+        }
+        let days: Vec<TimeReportDay> = days
+            .into_iter()
+            .map(|(date, (secs, sessions))| TimeReportDay {
+                date,
+                minutes: to_minutes(secs),
+                sessions,
+            })
+            .collect();
+
+        Ok(TaskTimeReportRes {
+            status: "success",
+            task_id: task.id,
+            title: task.title,
+            tracked_minutes: task.tracked_minutes,
+            results: days.len(),
+            days,
+        })
+    }
+
+    /// from~to(포함)의 daily timer_results를 category별로 합한 시간. 많은 순.
+    pub async fn category_time_report(
+        db: &dyn Storage,
+        from: NaiveDate,
+        to: NaiveDate,
+        user: &Uuid,
+    ) -> Result<CategoryTimeReportRes> {
+        if from > to {
+            return Err(DB(DBError::InvalidQuery(format!(
+                "from({}) is after to({})",
+                from, to
+            ))));
+        }
+        let filter = doc! {
+            "user": user,
+            "date": { "$gte": from.to_string(), "$lte": to.to_string() },
+            "deletedAt": { "$exists": false },
+        };
+
+        let mut total_secs = 0;
+        let mut categories: HashMap<ObjectId, (String, i64, usize)> = HashMap::new();
+        for daily in daily_timer_results(db, filter).await? {
+            for result in &daily.timer_results {
+                let secs = result_secs(result);
+                let category = categories
+                    .entry(result.category_id)
+                    .or_insert_with(|| (result.category_color.to_owned(), 0, 0));
+                category.1 += secs;
+                category.2 += 1;
+                total_secs += secs;
+            }
+        }
+        let mut categories: Vec<CategoryTimeItem> = categories
+            .into_iter()
+            .map(|(id, (category_color, secs, sessions))| CategoryTimeItem {
+                category_id: id.to_hex(),
+                category_color,
+                minutes: to_minutes(secs),
+                sessions,
+            })
+            .collect();
+        categories.sort_by(|a, b| {
+            b.minutes
+                .cmp(&a.minutes)
+                .then_with(|| a.category_id.cmp(&b.category_id))
+        });
+
+        Ok(CategoryTimeReportRes {
+            status: "success",
+            from,
+            to,
+            total_minutes: to_minutes(total_secs),
+            results: categories.len(),
+            categories,
+        })
+    }
+}
+
+// id가 아닌 task 중 timer가 실행 중인 task
+async fn running_task(db: &dyn Storage, id: &str, user: &Uuid) -> Result<Option<String>> {
+    let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+    let filter = doc! {
+        "_id": { "$ne": oid },
+        "user": user,
+        "timer.state": "Running",
+        "deletedAt": { "$exists": false },
+    };
+    let running = db
+        .find_one(
+            TaskService::COLL_NAME,
+            filter,
+            Some(doc! { "_id": 1 }),
+            None,
+        )
+        .await?;
+    Ok(running.and_then(|doc| doc.get_object_id("_id").ok().map(|id| id.to_hex())))
+}
+
+// date의 daily 문서가 없으면 만들면서, timer_results의 맨 앞에 추가함.
+// daily는 (user, date)가 unique이므로 동시에 추가해도 문서는 하나만 생김.
+async fn add_timer_result(
+    db: &dyn Storage,
+    date: NaiveDate,
+    result: &TimerResultModel,
+    session: Option<&mut ClientSession>,
+    user: &Uuid,
+) -> Result<()> {
+    let filter = doc! { "user": user, "date": date.to_string() };
+    let now = Utc::now();
+    let result = bson::to_bson(result).map_err(DBError::from)?;
+    let update = doc! {
+        "$setOnInsert": {
+            "diary": "",
+            "rating": 0,
+            "tasks": [],
+            "events": [],
+            "habits": [],
+            "createdAt": now,
+        },
+        "$push": { "timer_results": { "$each": [result], "$position": 0 } },
+        "$set": { "updatedAt": now },
+        "$inc": { "version": 1 },
+    };
+    db.upsert_one(
+        DailyItemService::<TimerResultModel>::COLL_NAME,
+        filter,
+        update,
+        session,
+    )
+    .await
+}
+
+async fn daily_timer_results(db: &dyn Storage, filter: Document) -> Result<Vec<DailyTimerResults>> {
+    let opts = FindOpts {
+        projection: Some(doc! { "date": 1, "timer_results": 1 }),
+        ..Default::default()
+    };
+    let docs = db
+        .find(
+            DailyItemService::<TimerResultModel>::COLL_NAME,
+            filter,
+            opts,
+            None,
+        )
+        .await?;
+    let mut dailies = Vec::with_capacity(docs.len());
+    for doc in docs {
+        dailies.push(bson::from_document(doc).map_err(DBError::from)?);
+    }
+    Ok(dailies)
+}
+
+// 예전 결과에는 focus_secs가 없으므로 시작~끝 시간을 씀
+fn result_secs(result: &TimerResultModel) -> i64 {
+    result
+        .focus_secs
+        .unwrap_or_else(|| (result.endAt - result.startAt).num_seconds())
+        .max(0)
+}
+
+fn to_minutes(secs: i64) -> u32 {
+    ((secs.max(0) + 30) / 60) as u32
+}
+
+fn focus_time(secs: i64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone};
+
+    use super::*;
+    use crate::domain::daily::{self, DailyModel};
+    use crate::domain::test_utils::{create_task, task_req, OTHER_USER, USER};
+    use crate::infra::db::MongoDB;
+
+    fn start_req(category_id: ObjectId) -> StartTimerReq {
+        StartTimerReq {
+            category_id: category_id.to_hex(),
+            category_color: "#f97316".to_string(),
+        }
+    }
+
+    // startAt에 시작해 elapsed_secs만큼 실행된 뒤 일시정지된 timer를 넣음
+    async fn seed_paused(
+        db: &dyn Storage,
+        id: &str,
+        category_id: ObjectId,
+        start_at: DateTime<Utc>,
+        elapsed_secs: i64,
+    ) {
+        let timer = TaskTimerModel {
+            state: TimerState::Paused,
+            category_id,
+            category_color: "#06b6d4".to_string(),
+            startAt: start_at,
+            resumedAt: None,
+            elapsed_secs,
+        };
+        let oid = ObjectId::from_str(id).unwrap();
+        let update = doc! { "$set": { "timer": bson::to_bson(&timer).unwrap() } };
+        db.update_one(TaskService::COLL_NAME, doc! { "_id": oid }, update, None)
+            .await
+            .unwrap();
+    }
+
+    // a의 timer를 work category로 25분, study category로 10분 20초 기록함
+    async fn seed_results(db: &dyn Storage, a: &str) -> (DateTime<Utc>, ObjectId, ObjectId) {
+        let start_at = Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+        let (work, study) = (ObjectId::new(), ObjectId::new());
+        seed_paused(db, a, work, start_at, 25 * 60).await;
+        TimerService::stop_timer(db, a, &USER).await.unwrap();
+        seed_paused(db, a, study, start_at, 10 * 60 + 20).await;
+        TimerService::stop_timer(db, a, &USER).await.unwrap();
+        (start_at, work, study)
+    }
+
+    #[test]
+    fn test_running_timer_index() {
+        let indexes = crate::domain::repo::index::coll_indexes::<TaskService>();
+        let index = indexes
+            .indexes
+            .iter()
+            .find(|spec| spec.name == "user_running_timer")
+            .unwrap();
+        assert_eq!(index.keys, doc! { "user": 1 });
+        assert!(index.unique);
+        assert_eq!(
+            index.partial_filter,
+            Some(doc! { "timer.state": "Running" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_timer() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_task(db, &task_req("a"), &USER).await;
+        let b = create_task(db, &task_req("b"), &USER).await;
+        let work = ObjectId::new();
+
+        // 실행 중인 timer는 하나뿐
+        let task = TimerService::start_timer(db, &a, &start_req(work), &USER)
+            .await
+            .unwrap();
+        assert_eq!(
+            task.data.task.timer.as_ref().unwrap().state,
+            TimerState::Running
+        );
+        let res = TimerService::start_timer(db, &b, &start_req(work), &USER).await;
+        assert!(matches!(res, Err(TimerConflict(_))));
+        let res = TimerService::start_timer(db, &a, &start_req(work), &USER).await;
+        assert!(matches!(res, Err(TimerConflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_pause_timer() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_task(db, &task_req("a"), &USER).await;
+        let b = create_task(db, &task_req("b"), &USER).await;
+        let work = ObjectId::new();
+        TimerService::start_timer(db, &a, &start_req(work), &USER)
+            .await
+            .unwrap();
+
+        let task = TimerService::pause_timer(db, &a, &USER).await.unwrap();
+        assert_eq!(
+            task.data.task.timer.as_ref().unwrap().state,
+            TimerState::Paused
+        );
+        let res = TimerService::pause_timer(db, &a, &USER).await;
+        assert!(matches!(res, Err(TimerConflict(_))));
+
+        // 일시정지된 동안은 다른 timer를 시작할 수 있음
+        TimerService::start_timer(db, &b, &start_req(work), &USER)
+            .await
+            .unwrap();
+        let res = TimerService::start_timer(db, &a, &start_req(work), &USER).await;
+        assert!(matches!(res, Err(TimerConflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_stop_timer() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_task(db, &task_req("a"), &USER).await;
+
+        // 일시정지를 뺀 시간이 기록됨
+        let start_at = Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+        seed_paused(db, &a, ObjectId::new(), start_at, 25 * 60).await;
+        let res = TimerService::stop_timer(db, &a, &USER).await.unwrap();
+        let task = res.data.task.into_inner();
+        assert_eq!(task.timer, None);
+        assert_eq!(task.tracked_minutes, 25);
+        let result = serde_json::to_value(&res.data.timer_result).unwrap();
+        assert_eq!(result["focus_time"], "00:25:00");
+        assert_eq!(result["task_id"], a.as_str());
+
+        let res = TimerService::stop_timer(db, &a, &USER).await;
+        assert!(matches!(res, Err(TimerConflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_time_report() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_task(db, &task_req("a"), &USER).await;
+        let (start_at, _, _) = seed_results(db, &a).await;
+
+        // 시작한 날의 daily에 남음
+        let date = start_at.date_naive();
+        let report = TimerService::task_time_report(db, &a, &USER).await.unwrap();
+        assert_eq!(report.tracked_minutes, 35);
+        assert_eq!(
+            report.days,
+            vec![TimeReportDay {
+                date,
+                minutes: 35,
+                sessions: 2,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_daily_of_timer_results() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_task(db, &task_req("a"), &USER).await;
+
+        // 같은 날의 결과는 한 daily 문서에 쌓이고, 날짜는 UTC 기준
+        let start_at = Utc.with_ymd_and_hms(2024, 3, 4, 23, 30, 0).unwrap();
+        for _ in 0..2 {
+            seed_paused(db, &a, ObjectId::new(), start_at, 60).await;
+            TimerService::stop_timer(db, &a, &USER).await.unwrap();
+        }
+        let filter = doc! { "user": &USER };
+        let docs = db
+            .find(daily::COLL_NAME, filter, FindOpts::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(docs.len(), 1);
+        let daily: DailyModel = bson::from_document(docs[0].clone()).unwrap();
+        assert_eq!(daily.date, start_at.date_naive());
+        assert_eq!(daily.timer_results.len(), 2);
+        assert_eq!(daily.version, 2);
+    }
+
+    #[tokio::test]
+    async fn test_category_time_report() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_task(db, &task_req("a"), &USER).await;
+        let (start_at, work, study) = seed_results(db, &a).await;
+
+        let date = start_at.date_naive();
+        let report = TimerService::category_time_report(db, date, date, &USER)
+            .await
+            .unwrap();
+        assert_eq!(report.total_minutes, 35);
+        assert_eq!(
+            report.categories,
+            vec![
+                CategoryTimeItem {
+                    category_id: work.to_hex(),
+                    category_color: "#06b6d4".to_string(),
+                    minutes: 25,
+                    sessions: 1,
+                },
+                CategoryTimeItem {
+                    category_id: study.to_hex(),
+                    category_color: "#06b6d4".to_string(),
+                    minutes: 10,
+                    sessions: 1,
+                },
+            ]
+        );
+
+        let res =
+            TimerService::category_time_report(db, date, date.pred_opt().unwrap(), &USER).await;
+        assert!(matches!(res, Err(DB(DBError::InvalidQuery(_)))));
+    }
+
+    #[tokio::test]
+    async fn test_timer_of_other_user() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+        let a = create_task(db, &task_req("a"), &USER).await;
+        let other = create_task(db, &task_req("other"), &OTHER_USER).await;
+        let work = ObjectId::new();
+
+        // 실행 중인 timer는 user마다 하나씩
+        TimerService::start_timer(db, &a, &start_req(work), &USER)
+            .await
+            .unwrap();
+        TimerService::start_timer(db, &other, &start_req(work), &OTHER_USER)
+            .await
+            .unwrap();
+        let res = TimerService::stop_timer(db, &a, &OTHER_USER).await;
+        assert!(matches!(res, Err(NotFoundError(_))));
+        let res = TimerService::task_time_report(db, &a, &OTHER_USER).await;
+        assert!(matches!(res, Err(NotFoundError(_))));
+
+        // 다른 user의 기록은 집계하지 않음
+        let (start_at, _, _) = seed_results(db, &a).await;
+        let date = start_at.date_naive();
+        let report = TimerService::category_time_report(db, date, date, &OTHER_USER)
+            .await
+            .unwrap();
+        assert_eq!(report.total_minutes, 0);
+        assert!(report.categories.is_empty());
+    }
+}
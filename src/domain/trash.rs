@@ -0,0 +1,521 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::domain::error::{Error::*, Result};
+use crate::domain::item_tag::ItemTagService;
+use crate::domain::repo::storage::{FindOpts, Storage};
+use crate::domain::repo::transaction::Transaction;
+use crate::domain::repo::utils::{decode_cursor, encode_cursor, keyset_filter};
+use crate::domain::task;
+use crate::infra::db::error::Error as DBError;
+use crate::infra::types::{ItemType, PageOptions, TrashKind};
+use crate::interface::dto::trash::res::{
+    SingleTrashItemRes, TrashItemData, TrashItemRes, TrashListRes,
+};
+
+// soft delete된 문서들(deletedAt)을 조회, 복구, 영구 삭제하는 서비스
+pub struct TrashService;
+
+impl TrashKind {
+    pub const ALL: [TrashKind; 5] = [
+        TrashKind::Task,
+        TrashKind::Memo,
+        TrashKind::Habit,
+        TrashKind::Note,
+        TrashKind::Category,
+    ];
+
+    pub fn coll_name(&self) -> &'static str {
+        match self {
+            TrashKind::Task => "tasks",
+            TrashKind::Memo => "memos",
+            TrashKind::Habit => "habits",
+            TrashKind::Note => "notes",
+            TrashKind::Category => "categories",
+        }
+    }
+
+    // tag를 붙일 수 있는 item이면 그 종류
+    fn item_type(&self) -> Option<ItemType> {
+        match self {
+            TrashKind::Task => Some(ItemType::Task),
+            TrashKind::Memo => Some(ItemType::Memo),
+            TrashKind::Habit => Some(ItemType::Habit),
+            TrashKind::Note => Some(ItemType::Note),
+            TrashKind::Category => None,
+        }
+    }
+
+    fn title_field(&self) -> &'static str {
+        match self {
+            TrashKind::Habit | TrashKind::Category => "name",
+            _ => "title",
+        }
+    }
+}
+
+impl TrashService {
+    /// 휴지통 목록. 다른 문서와 함께 삭제된 문서(deletedWith)는 root만 보여줌.
+    /// (deletedAt 내림차순, 종류, _id) 순서이며, 종류마다 한 페이지 분량만 읽어서 합침.
+    pub async fn fetch_trash(
+        db: &dyn Storage,
+        page_opts: PageOptions,
+        user: &Uuid,
+    ) -> Result<TrashListRes> {
+        let PageOptions {
+            limit,
+            page,
+            cursor,
+            with_total,
+        } = page_opts;
+
+        // cursor가 있으면 keyset, 없으면 기존 page 방식
+        let after = match cursor {
+            Some(cursor) => {
+                let after = match decode_cursor(&cursor) {
+                    Some((Bson::Array(keys), Bson::ObjectId(id))) => match keys.as_slice() {
+                        [Bson::DateTime(deleted_at), Bson::Int32(kind)] => {
+                            Some((*deleted_at, *kind as usize, id))
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                Some(after.ok_or(DBError::InvalidCursor(cursor.clone()))?)
+            }
+            None => None,
+        };
+        let skip = match after {
+            Some(_) => 0,
+            None => ((page - 1).max(0) * limit) as usize,
+        };
+
+        let mut items: Vec<(usize, ObjectId, TrashItemRes)> = Vec::new();
+        let mut total = 0;
+        for (index, kind) in TrashKind::ALL.into_iter().enumerate() {
+            let filter = doc! {
+                "user": user,
+                "deletedAt": { "$exists": true },
+                "deletedWith": { "$exists": false },
+            };
+            if with_total {
+                total += db.count(kind.coll_name(), filter.clone(), None).await?;
+            }
+
+            // 같은 deletedAt이면 종류, _id 순서
+            let filter = match after {
+                Some((deleted_at, after_index, id)) => {
+                    let keyset = match index.cmp(&after_index) {
+                        Ordering::Less => doc! { "deletedAt": { "$lt": deleted_at } },
+                        Ordering::Equal => keyset_filter(
+                            &doc! { "deletedAt": -1 },
+                            &[deleted_at.into()],
+                            id.into(),
+                        ),
+                        Ordering::Greater => doc! { "deletedAt": { "$lte": deleted_at } },
+                    };
+                    doc! { "$and": [filter, keyset] }
+                }
+                None => filter,
+            };
+            let find_options = FindOpts {
+                projection: Some(doc! { kind.title_field(): 1, "deletedAt": 1 }),
+                sort: Some(doc! { "deletedAt": -1, "_id": 1 }),
+                limit: Some(if limit > 0 {
+                    (skip as i64) + limit + 1
+                } else {
+                    0
+                }),
+                ..FindOpts::default()
+            };
+
+            for doc in db
+                .find(kind.coll_name(), filter, find_options, None)
+                .await?
+            {
+                let id = doc.get_object_id("_id").map_err(DBError::MongoDataError)?;
+                items.push((index, id, Self::convert_doc_to_response(kind, &doc)?));
+            }
+        }
+
+        // 최근에 삭제된 순
+        items.sort_by(|(a_index, a_id, a), (b_index, b_id, b)| {
+            b.deletedAt
+                .cmp(&a.deletedAt)
+                .then(a_index.cmp(b_index))
+                .then(a_id.cmp(b_id))
+        });
+        let mut items: Vec<(usize, ObjectId, TrashItemRes)> =
+            items.into_iter().skip(skip).collect();
+
+        let has_more = limit > 0 && items.len() as i64 > limit;
+        if has_more {
+            items.truncate(limit as usize);
+        }
+        let next_cursor = match items.last() {
+            Some((index, id, last)) if has_more => Some(encode_cursor(
+                Bson::Array(vec![
+                    Bson::DateTime(last.deletedAt.into()),
+                    Bson::Int32(*index as i32),
+                ]),
+                Bson::ObjectId(*id),
+            )),
+            _ => None,
+        };
+        let items: Vec<TrashItemRes> = items.into_iter().map(|(_, _, item)| item).collect();
+
+        Ok(TrashListRes {
+            status: "success",
+            results: items.len(),
+            items,
+            next_cursor,
+            has_more,
+            total: with_total.then_some(total),
+        })
+    }
+
+    /// 문서와, 그 문서와 함께 삭제된 문서들(task subtree 등)을 모두 복구.
+    /// task는 삭제될 때 빠진 dependency를 되돌리고 parent의 progress도 다시 계산함.
+    pub async fn restore(
+        db: &dyn Storage,
+        kind: TrashKind,
+        id: &str,
+        user: &Uuid,
+    ) -> Result<SingleTrashItemRes> {
+        let oid = ObjectId::from_str(id).map_err(DBError::MongoGetOidError)?;
+
+        let item = match db
+            .find_one(
+                kind.coll_name(),
+                doc! { "_id": oid, "user": user, "deletedAt": { "$exists": true } },
+                None,
+                None,
+            )
+            .await?
+        {
+            Some(doc) => Self::convert_doc_to_response(kind, &doc)?,
+            None => return Err(NotFoundError(id.to_string())),
+        };
+
+        let filter = doc! {
+            "user": user,
+            "$or": [{ "_id": oid }, { "deletedWith": oid }],
+        };
+        let edges = match kind {
+            TrashKind::Task => task::restorable_dependencies(db, filter.clone(), user).await?,
+            _ => vec![],
+        };
+
+        let restore_doc = doc! {
+            "$unset": { "deletedAt": 1, "deletedWith": 1, "deletedDependents": 1 },
+            "$set": { "updatedAt": Bson::DateTime(Utc::now().into()) },
+            "$inc": { "version": 1 },
+        };
+        let mut tx = Transaction::start(db).await?;
+        let result = async {
+            db.update_many(kind.coll_name(), filter, restore_doc, tx.session())
+                .await?;
+            task::restore_dependencies(db, &edges, tx.session(), user).await
+        }
+        .await;
+        tx.finish(result).await?;
+
+        if kind == TrashKind::Task {
+            let parents = task::parents_of(db, &[oid], user).await?;
+            task::roll_up(db, &parents, user).await?;
+        }
+
+        Ok(SingleTrashItemRes {
+            status: "success",
+            data: TrashItemData { item },
+        })
+    }
+
+    /// before 이전에 삭제된 문서를 영구 삭제(모든 user 대상).
+    /// postgres의 item_tags를 먼저 지우므로, 실패하면 그 종류의 문서는 다음 purge까지 남음.
+    pub async fn purge(
+        db: &dyn Storage,
+        pg: &Pool<Postgres>,
+        before: DateTime<Utc>,
+    ) -> Result<u64> {
+        let mut purged = 0;
+        for kind in TrashKind::ALL {
+            let filter = doc! { "deletedAt": { "$lt": Bson::DateTime(before.into()) } };
+            let find_options = FindOpts {
+                projection: Some(doc! { "_id": 1 }),
+                ..FindOpts::default()
+            };
+            let ids: Vec<ObjectId> = db
+                .find(kind.coll_name(), filter, find_options, None)
+                .await?
+                .iter()
+                .filter_map(|doc| doc.get_object_id("_id").ok())
+                .collect();
+            if ids.is_empty() {
+                continue;
+            }
+
+            if let Some(item_type) = kind.item_type() {
+                let item_ids: Vec<String> = ids.iter().map(|id| id.to_hex()).collect();
+                ItemTagService::remove_items(pg, item_type, &item_ids).await?;
+            }
+            purged += db
+                .delete_many(kind.coll_name(), doc! { "_id": { "$in": ids } })
+                .await?;
+        }
+        Ok(purged)
+    }
+
+    /// 한 시간마다 보관 기간이 지난 문서를 영구 삭제.
+    pub fn spawn_purge_job(
+        db: Arc<dyn Storage>,
+        pg: Pool<Postgres>,
+        retention_days: i64,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60 * 60));
+            loop {
+                interval.tick().await;
+
+                let before = Utc::now() - chrono::Duration::days(retention_days);
+                match Self::purge(db.as_ref(), &pg, before).await {
+                    Ok(0) => {}
+                    Ok(purged) => tracing::info!("trash purged: {} documents", purged),
+                    Err(e) => tracing::error!("failed to purge trash: {}", e),
+                }
+            }
+        })
+    }
+
+    fn convert_doc_to_response(kind: TrashKind, doc: &Document) -> Result<TrashItemRes> {
+        let id = doc.get_object_id("_id").map_err(DBError::MongoDataError)?;
+        let deleted_at = doc
+            .get_datetime("deletedAt")
+            .map_err(DBError::MongoDataError)?;
+
+        Ok(TrashItemRes {
+            id: id.to_hex(),
+            kind,
+            title: doc
+                .get_str(kind.title_field())
+                .unwrap_or_default()
+                .to_string(),
+            deletedAt: deleted_at.to_chrono(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::category::CategoryService;
+    use crate::domain::task::TaskService;
+    use crate::domain::test_utils::{create_task, task_req};
+    use crate::infra::db::{MongoDB, DB};
+    use crate::interface::dto::category::req::CreateCategoryReq;
+    use crate::interface::dto::task::req::{CreateTaskReq, DeleteTaskOptionReq, UpdateTaskReq};
+
+    const USER: Uuid = Uuid::from_bytes([0; 16]);
+
+    fn page_opts() -> PageOptions {
+        PageOptions {
+            limit: 20,
+            page: 1,
+            cursor: None,
+            with_total: false,
+        }
+    }
+
+    async fn create_category(db: &dyn Storage, name: &str) -> String {
+        let body = CreateCategoryReq {
+            name: name.to_string(),
+            color: "Red".to_string(),
+        };
+        CategoryService::create_category(db, &body, &USER)
+            .await
+            .unwrap()
+            .data
+            .category
+            .id
+    }
+
+    #[tokio::test]
+    async fn test_restore_category() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+
+        let id = create_category(db, "Work").await;
+        CategoryService::delete_category(db, &id, None, &USER)
+            .await
+            .unwrap();
+
+        let res = TrashService::fetch_trash(db, page_opts(), &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.results, 1);
+        assert_eq!(res.items[0].id, id);
+        assert_eq!(res.items[0].kind, TrashKind::Category);
+        assert_eq!(res.items[0].title, "Work");
+
+        let res = TrashService::restore(db, TrashKind::Category, &id, &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.data.item.title, "Work");
+
+        let res = CategoryService::get_category(db, &id, &USER).await.unwrap();
+        assert_eq!(res.data.category.name, "Work");
+        let res = TrashService::fetch_trash(db, page_opts(), &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.results, 0);
+    }
+
+    #[tokio::test]
+    async fn test_purge_category() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+
+        let deleted_id = create_category(db, "Work").await;
+        let kept_id = create_category(db, "Home").await;
+        CategoryService::delete_category(db, &deleted_id, None, &USER)
+            .await
+            .unwrap();
+
+        let pg = DB::init_lazy("postgres://localhost/tootodo_test").db;
+        let purged = TrashService::purge(db, &pg, Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(purged, 1);
+
+        let res = TrashService::restore(db, TrashKind::Category, &deleted_id, &USER).await;
+        claim::assert_matches!(res, Err(NotFoundError(_)));
+        assert!(CategoryService::get_category(db, &kept_id, &USER)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_restore_task() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+
+        // p > (a, b), c는 b에 의존
+        let body = CreateTaskReq {
+            auto_progress: Some(true),
+            ..task_req("p")
+        };
+        let p = create_task(db, &body, &USER).await;
+        let child = |title| CreateTaskReq {
+            parent_id: Some(p.clone()),
+            ..task_req(title)
+        };
+        let a = create_task(db, &child("a"), &USER).await;
+        let b = create_task(db, &child("b"), &USER).await;
+        let c = create_task(db, &task_req("c"), &USER).await;
+        TaskService::add_dependency(db, &c, &b, &USER)
+            .await
+            .unwrap();
+        let update = UpdateTaskReq {
+            title: None,
+            milestone: None,
+            parent_id: None,
+            start_date: None,
+            end_date: None,
+            due_at: None,
+            progress_rate: Some(100),
+            estimate_minutes: None,
+            auto_progress: None,
+            status: None,
+            priority: None,
+            chat_type: None,
+            recurrence: None,
+        };
+        TaskService::update_task(db, &a, &update, None, &USER)
+            .await
+            .unwrap();
+        let version = TaskService::get_task(db, &b, None, &USER)
+            .await
+            .unwrap()
+            .data
+            .task
+            .version;
+
+        TaskService::delete_task(db, &b, DeleteTaskOptionReq::DeleteOnlyTask, &USER)
+            .await
+            .unwrap();
+        let res = TaskService::get_task(db, &p, None, &USER).await.unwrap();
+        assert_eq!(res.data.task.progress_rate, 100);
+        let res = TaskService::get_task(db, &c, None, &USER).await.unwrap();
+        assert_eq!(res.data.task.depends_on, Some(vec![]));
+
+        // 복구하면 version이 올라가고, 빠졌던 dependency와 parent의 progress도 되돌아옴
+        TrashService::restore(db, TrashKind::Task, &b, &USER)
+            .await
+            .unwrap();
+        let res = TaskService::get_task(db, &b, None, &USER).await.unwrap();
+        assert_eq!(res.data.task.version, version + 2);
+        let res = TaskService::get_task(db, &c, None, &USER).await.unwrap();
+        assert_eq!(res.data.task.depends_on, Some(vec![b.clone()]));
+        assert_eq!(res.data.task.blocked, Some(true));
+        let res = TaskService::get_task(db, &p, None, &USER).await.unwrap();
+        assert_eq!(res.data.task.progress_rate, 50);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_trash_cursor() {
+        let mongodb = MongoDB::init_memory();
+        let db = &mongodb.db;
+
+        let mut deleted = Vec::new();
+        for name in ["a", "b", "c"] {
+            let id = create_category(db, name).await;
+            CategoryService::delete_category(db, &id, None, &USER)
+                .await
+                .unwrap();
+            deleted.push(id);
+        }
+        let id = create_task(db, &task_req("d"), &USER).await;
+        TaskService::delete_task(db, &id, DeleteTaskOptionReq::DeleteOnlyTask, &USER)
+            .await
+            .unwrap();
+        deleted.push(id);
+
+        let mut opts = PageOptions {
+            limit: 3,
+            with_total: true,
+            ..page_opts()
+        };
+        let res = TrashService::fetch_trash(db, opts.clone(), &USER)
+            .await
+            .unwrap();
+        assert_eq!(res.results, 3);
+        assert_eq!(res.total, Some(4));
+        assert!(res.has_more);
+        let mut ids: Vec<String> = res.items.iter().map(|item| item.id.clone()).collect();
+
+        opts.cursor = res.next_cursor;
+        let res = TrashService::fetch_trash(db, opts, &USER).await.unwrap();
+        assert_eq!(res.results, 1);
+        assert!(!res.has_more);
+        assert_eq!(res.next_cursor, None);
+        ids.extend(res.items.iter().map(|item| item.id.clone()));
+
+        // 같은 시각에 삭제되었더라도 빠짐없이 한 번씩
+        ids.sort();
+        deleted.sort();
+        assert_eq!(ids, deleted);
+
+        let opts = PageOptions {
+            cursor: Some("invalid".to_string()),
+            ..page_opts()
+        };
+        let res = TrashService::fetch_trash(db, opts, &USER).await;
+        claim::assert_matches!(res, Err(DB(DBError::InvalidCursor(_))));
+    }
+}
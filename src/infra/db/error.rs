@@ -13,9 +13,10 @@ pub enum Error {
     // postgresql
     FailToCreatePool(String),
     MigrationError(String),
-    #[from]
     Sqlx(sqlx::Error),
     Fetch(sqlx::Error),
+    // unique 제약(user, name 등) 위반. 제약 이름을 담음
+    UniqueViolation(String),
     EntityNotFound {
 		entity: &'static str,
 		id: Uuid,
@@ -24,6 +25,12 @@ pub enum Error {
 		max: i64,
 		actual: i64,
 	},
+	InvalidCursor(String),
+	InvalidSyncToken(String),
+	// 허용되지 않은 filter/sort
+	InvalidQuery(String),
+	// 자기 자신으로의 merge, group 순환 등
+	InvalidRelation(String),
 
     // mongodb
     #[from]
@@ -40,6 +47,9 @@ pub enum Error {
     #[from]
     MongoDeserializeBsonError(mongodb::bson::de::Error),
 
+    // memory storage(테스트용)가 지원하지 않는 연산자 등
+    MemoryStorageError(String),
+
 }
 
 impl IntoResponse for Error {
@@ -59,6 +69,34 @@ impl IntoResponse for Error {
                     message: format!("List limit over max: max:{}, actual:{}", max, actual),
                 },
             ),
+            Error::InvalidCursor(cursor) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    status: "fail".to_string(),
+                    message: format!("Invalid cursor: {}", cursor),
+                },
+            ),
+            Error::InvalidSyncToken(token) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    status: "fail".to_string(),
+                    message: format!("Invalid sync token: {}", token),
+                },
+            ),
+            Error::InvalidQuery(message) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    status: "fail".to_string(),
+                    message: format!("Invalid query: {}", message),
+                },
+            ),
+            Error::InvalidRelation(message) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    status: "fail".to_string(),
+                    message: format!("Invalid relation: {}", message),
+                },
+            ),
             Error::MongoGetOidError(e) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 ErrorResponse {
@@ -66,6 +104,13 @@ impl IntoResponse for Error {
                     message: format!("MongoDB error: {}", e),
                 },
             ),
+            Error::UniqueViolation(constraint) => (
+                StatusCode::CONFLICT,
+                ErrorResponse {
+                    status: "fail".to_string(),
+                    message: format!("Already exists: {}", constraint),
+                },
+            ),
             Error::Sqlx(e) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 ErrorResponse {
@@ -137,11 +182,31 @@ impl IntoResponse for Error {
                     message: format!("MongoDB error: {}", e),
                 },
             ),
+            Error::MemoryStorageError(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorResponse {
+                    status: "error".to_string(),
+                    message: format!("Memory storage error: {}", e),
+                },
+            ),
         };
         (status, Json(serde_json::to_value(error_response).unwrap())).into_response()
     }
 }
 
+impl From<sqlx::Error> for Error {
+    fn from(e: sqlx::Error) -> Self {
+        // 23505: unique_violation
+        if let Some(db_err) = e.as_database_error() {
+            if db_err.code().as_deref() == Some("23505") {
+                let constraint = db_err.constraint().unwrap_or_default().to_string();
+                return Error::UniqueViolation(constraint);
+            }
+        }
+        Error::Sqlx(e)
+    }
+}
+
 // region:    --- Error Boilerplate
 impl core::fmt::Display for Error {
     fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
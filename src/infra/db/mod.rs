@@ -1,10 +1,17 @@
 pub mod error;
 use crate::config::Config;
+use crate::domain::indexes;
+use crate::domain::repo::index::{self, IndexDrift};
+use crate::domain::repo::memory::MemoryStorage;
+use crate::domain::repo::storage::Storage;
 use error::{Error, Result};
+use mongodb::bson::{doc, Document};
 use mongodb::Database;
 use mongodb::{options::ClientOptions, Client};
 use sqlx::postgres::PgPoolOptions;
 use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use std::time::Duration;
 
 pub struct DB {
     pub db: Pool<Postgres>,
@@ -37,11 +44,22 @@ impl DB {
 
         Ok(Self { db: pool })
     }
+
+    // 첫 쿼리 전까지 연결하지 않는 pool(테스트용).
+    pub fn init_lazy(database_url: &str) -> Self {
+        let pool = PgPoolOptions::new()
+            .max_connections(10)
+            .acquire_timeout(Duration::from_secs(1))
+            .connect_lazy(database_url)
+            .expect("invalid database url");
+
+        Self { db: pool }
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct MongoDB {
-    pub db: Database,
+    pub db: Arc<dyn Storage>,
 }
 
 impl MongoDB {
@@ -65,7 +83,93 @@ impl MongoDB {
 
         println!("✅ Mongo Database connected successfully");
 
-        Ok(Self { db })
+        Self::sync_indexes(&db).await;
+        Self::migrate_task_parent_ids(&db).await;
+        Self::backfill_task_status(&db).await;
+
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    // 선언된 인덱스를 한번만 맞추고, drift는 로그로 남김.
+    async fn sync_indexes(db: &Database) {
+        match index::sync_indexes(db, indexes::registry()).await {
+            Ok(drifts) => {
+                for drift in &drifts {
+                    match drift {
+                        IndexDrift::Created { coll, name } => {
+                            tracing::info!("index created: {}.{}", coll, name)
+                        }
+                        IndexDrift::Mismatched { coll, name } => {
+                            tracing::warn!("index differs from declaration: {}.{}", coll, name)
+                        }
+                        IndexDrift::Undeclared { coll, name } => {
+                            tracing::warn!("index not declared: {}.{}", coll, name)
+                        }
+                    }
+                }
+                println!("✅ Mongo indexes synced ({} drift)", drifts.len());
+            }
+            Err(e) => {
+                tracing::error!("failed to sync mongo indexes: {}", e);
+            }
+        }
+    }
+
+    // 예전 task들은 parent_id를 문자열로 저장했음. tree 조회($graphLookup)는 _id(ObjectId)와
+    // 비교하므로 ObjectId로 바꾸고, 빈 문자열은 지움.
+    async fn migrate_task_parent_ids(db: &Database) {
+        let tasks = db.collection::<Document>("tasks");
+        let converted = tasks
+            .update_many(
+                doc! { "parent_id": { "$type": "string", "$regex": "^[0-9a-fA-F]{24}$" } },
+                vec![doc! { "$set": { "parent_id": { "$toObjectId": "$parent_id" } } }],
+                None,
+            )
+            .await;
+        let cleared = tasks
+            .update_many(
+                doc! { "parent_id": "" },
+                doc! { "$unset": { "parent_id": "" } },
+                None,
+            )
+            .await;
+        match (converted, cleared) {
+            (Ok(converted), Ok(cleared)) => {
+                let count = converted.modified_count + cleared.modified_count;
+                if count > 0 {
+                    tracing::info!("task parent_id migrated: {}", count);
+                }
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::error!("failed to migrate task parent_id: {}", e);
+            }
+        }
+    }
+
+    // status가 없는 예전 task는 progress_rate로 Done/Todo를 정함
+    async fn backfill_task_status(db: &Database) {
+        let tasks = db.collection::<Document>("tasks");
+        let mut count = 0;
+        for (filter, status) in [
+            (doc! { "progress_rate": { "$gte": 100 } }, "Done"),
+            (doc! { "progress_rate": { "$lt": 100 } }, "Todo"),
+        ] {
+            let mut filter = filter;
+            filter.insert("status", doc! { "$exists": false });
+            match tasks
+                .update_many(filter, doc! { "$set": { "status": status } }, None)
+                .await
+            {
+                Ok(result) => count += result.modified_count,
+                Err(e) => {
+                    tracing::error!("failed to backfill task status: {}", e);
+                    return;
+                }
+            }
+        }
+        if count > 0 {
+            tracing::info!("task status backfilled: {}", count);
+        }
     }
 
     pub async fn init_test() -> Result<Self> {
@@ -83,6 +187,13 @@ impl MongoDB {
 
         // println!("✅ Mongo <Test> Database connected successfully");
 
-        Ok(Self { db })
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    // Mongo 없이 동작하는 memory 저장소(테스트용).
+    pub fn init_memory() -> Self {
+        Self {
+            db: Arc::new(MemoryStorage::default()),
+        }
     }
 }
@@ -1,5 +1,7 @@
-use mongodb::bson::Document;
-use serde::{Deserialize, Serialize};
+use mongodb::bson::{oid::ObjectId, Document};
+use serde::{Deserialize, Serialize, Serializer};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 
 use crate::interface::dto::task::res::TaskRes;
 
@@ -8,13 +10,159 @@ use crate::interface::dto::task::res::TaskRes;
 pub struct FilterOptions {
     pub page: Option<usize>,
     pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub with_total: Option<bool>,
+    pub filter: Option<String>,
+    pub sort: Option<String>,
+    pub fields: Option<String>,
+    pub tag: Option<String>,
+    pub tag_group: Option<String>,
 }
+
+impl FilterOptions {
+    pub fn page_opts(&self, default_limit: usize) -> PageOptions {
+        PageOptions {
+            limit: self.limit.unwrap_or(default_limit) as i64,
+            page: self.page.unwrap_or(1) as i64,
+            cursor: self.cursor.clone(),
+            with_total: self.with_total.unwrap_or(false),
+        }
+    }
+
+    pub fn query_opts(&self) -> QueryOptions {
+        QueryOptions {
+            filter: self.filter.clone(),
+            sort: self.sort.clone(),
+            fields: self.fields.clone(),
+            tag: self.tag.clone(),
+            tag_group: self.tag_group.clone(),
+            ids: None,
+        }
+    }
+}
+
+// ?filter=progress_rate>=50,milestone=true&sort=-due_at&fields=title,due_at&tag=work
+// repo마다 허용된 필드만 사용할 수 있음(domain/repo/query.rs).
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct QueryOptions {
+    pub filter: Option<String>,
+    pub sort: Option<String>,
+    pub fields: Option<String>,
+    // tag/tag group의 id 혹은 이름. ','로 여러 개를 주면 그 중 하나라도 붙은 item
+    pub tag: Option<String>,
+    pub tag_group: Option<String>,
+    // tag, tag_group을 postgres에서 item id로 바꾼 결과(domain/item_tag.rs)
+    #[serde(skip)]
+    pub ids: Option<Vec<ObjectId>>,
+}
+
+// ?fields=만 받는 단건 조회
+#[derive(Deserialize, Debug, Default)]
+pub struct FieldsOptions {
+    pub fields: Option<String>,
+}
+
+// pagination
+// cursor가 주어지면 page는 무시하고 keyset 방식으로 다음 목록을 가져옴.
+// limit이 0이면 전체를 가져옴.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PageOptions {
+    pub limit: i64,
+    pub page: i64,
+    pub cursor: Option<String>,
+    pub with_total: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+    pub total: Option<u64>,
+}
+
+impl<T> Page<T> {
+    pub fn empty() -> Self {
+        Page {
+            items: Vec::new(),
+            next_cursor: None,
+            has_more: false,
+            total: None,
+        }
+    }
+}
+
+/// ?fields=가 주어진 응답 item. id와 선택된 필드만 직렬화하고, fields가 없으면 전체를 직렬화함.
+/// db에서 읽지 않은 필드가 기본값으로 응답에 섞이지 않도록 함.
+#[derive(Debug, Clone)]
+pub struct Partial<T> {
+    item: T,
+    fields: Option<Arc<[&'static str]>>,
+}
+
+impl<T> Partial<T> {
+    pub fn new(item: T, fields: Option<Arc<[&'static str]>>) -> Self {
+        Partial { item, fields }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.item
+    }
+}
+
+impl<T> From<T> for Partial<T> {
+    fn from(item: T) -> Self {
+        Partial::new(item, None)
+    }
+}
+
+impl<T> Deref for Partial<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.item
+    }
+}
+
+impl<T> DerefMut for Partial<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.item
+    }
+}
+
+impl<T: Serialize> Serialize for Partial<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(fields) = &self.fields else {
+            return self.item.serialize(serializer);
+        };
+
+        match serde_json::to_value(&self.item).map_err(serde::ser::Error::custom)? {
+            serde_json::Value::Object(mut map) => {
+                // occurrence_date는 id와 함께 반복 task의 occurrence를 구분하므로 항상 남김
+                map.retain(|key, _| {
+                    key == "id" || key == "occurrence_date" || fields.contains(&key.as_str())
+                });
+                map.serialize(serializer)
+            }
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+/// bulk 작업에서 요청 순서(index)별 결과. 실패한 경우 그 이유를 담음.
+#[derive(Debug, Clone)]
+pub struct BatchResult<T> {
+    pub index: usize,
+    pub id: Option<String>,
+    pub outcome: std::result::Result<T, String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct QueryFilterOptions {
     pub find_filter: Option<Document>,
     pub proj_opts: Option<Document>,
-    pub limit: i64,
-    pub page: i64,
+    pub page_opts: PageOptions,
+    pub query_opts: QueryOptions,
 }
 // schedule
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -53,6 +201,7 @@ pub enum ChatType {
     Ask,
     Event,
     Task,
+    Note,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -69,6 +218,52 @@ pub enum MsgType {
 }
 
 // task
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum TaskStatus {
+    #[default]
+    Todo,
+    Doing,
+    Done,
+    Cancelled,
+}
+
+// task timer. 멈춘(stop) timer는 task에 남지 않음
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum TimerState {
+    Running,
+    Paused,
+}
+
+/// 숫자가 클수록 급함. 정렬할 수 있도록 숫자(1~4)로 저장함.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum Priority {
+    Low = 1,
+    Medium = 2,
+    High = 3,
+    Urgent = 4,
+}
+
+impl TryFrom<u8> for Priority {
+    type Error = String;
+
+    fn try_from(level: u8) -> std::result::Result<Self, Self::Error> {
+        match level {
+            1 => Ok(Priority::Low),
+            2 => Ok(Priority::Medium),
+            3 => Ok(Priority::High),
+            4 => Ok(Priority::Urgent),
+            _ => Err(format!("priority must be 1~4: {}", level)),
+        }
+    }
+}
+
+impl From<Priority> for u8 {
+    fn from(priority: Priority) -> Self {
+        priority as u8
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum BlockType {
     Editor,
@@ -84,8 +279,76 @@ pub enum PropValueType {
     Single(String),
 }
 
+// trash
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashKind {
+    Task,
+    Memo,
+    Habit,
+    Note,
+    Category,
+}
+
+// tag를 붙일 수 있는 mongodb item
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemType {
+    Task,
+    Memo,
+    Habit,
+    Note,
+}
+
+// sync
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncKind {
+    Task,
+    Memo,
+    Habit,
+    Daily,
+    Schedule,
+}
+
+// pull 결과의 변경 종류
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncChange {
+    Created,
+    Updated,
+    Deleted,
+}
+
+// client가 보낸 변경 종류
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncOp {
+    Create,
+    Update,
+    Delete,
+}
+
+// 충돌 처리 방식
+// lww: updatedAt이 더 늦은 쪽이 이김, merge: 필드 단위로 합치고 양쪽이 바꾼 필드만 충돌로 봄
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    #[default]
+    Lww,
+    Merge,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictResolution {
+    Client,
+    Server,
+    Merged,
+}
+
 #[derive(Serialize,  Debug, Clone)]
 pub struct TaskTreeItem {
-    pub task: TaskRes,
+    pub task: Partial<TaskRes>,
     pub subtasks: Vec<TaskTreeItem>, 
 }
\ No newline at end of file
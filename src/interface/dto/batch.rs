@@ -0,0 +1,89 @@
+pub mod req {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct BatchUpdateReq<T> {
+        pub id: String,
+        // If-Match와 같은 역할. 주어지면 version이 같을 때만 업데이트함.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub version: Option<i64>,
+        #[serde(flatten)]
+        pub body: T,
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct BatchDeleteReq {
+        pub ids: Vec<String>,
+    }
+}
+
+pub mod res {
+    use serde::Serialize;
+
+    use crate::infra::types::BatchResult;
+
+    #[derive(Serialize, Debug)]
+    pub struct BatchItemRes<T> {
+        pub index: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub id: Option<String>,
+        pub status: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub data: Option<T>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub message: Option<String>,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct BatchRes<T> {
+        pub status: &'static str,
+        pub results: usize,
+        pub succeeded: usize,
+        pub failed: usize,
+        pub items: Vec<BatchItemRes<T>>,
+    }
+
+    impl<T> BatchRes<T> {
+        pub fn from_results(results: Vec<BatchResult<T>>) -> Self {
+            let items: Vec<BatchItemRes<T>> = results
+                .into_iter()
+                .map(|result| match result.outcome {
+                    Ok(data) => BatchItemRes {
+                        index: result.index,
+                        id: result.id,
+                        status: "success",
+                        data: Some(data),
+                        message: None,
+                    },
+                    Err(message) => BatchItemRes {
+                        index: result.index,
+                        id: result.id,
+                        status: "fail",
+                        data: None,
+                        message: Some(message),
+                    },
+                })
+                .collect();
+            let succeeded = items.iter().filter(|item| item.status == "success").count();
+
+            Self {
+                status: "success",
+                results: items.len(),
+                succeeded,
+                failed: items.len() - succeeded,
+                items,
+            }
+        }
+    }
+
+    impl BatchRes<()> {
+        // delete처럼 돌려줄 data가 없는 경우
+        pub fn without_data(results: Vec<BatchResult<()>>) -> Self {
+            let mut res = Self::from_results(results);
+            for item in res.items.iter_mut() {
+                item.data = None;
+            }
+            res
+        }
+    }
+}
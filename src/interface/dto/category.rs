@@ -0,0 +1,83 @@
+pub mod req {
+    use crate::infra::types::StatusType;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct CreateCategoryReq {
+        pub name: String,
+        pub color: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct UpdateCategoryReq {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub name: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub color: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub status: Option<StatusType>,
+    }
+}
+
+pub mod res {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    use crate::domain::category::CategoryModel;
+    use crate::infra::types::{Partial, StatusType};
+    use crate::interface::dto::sub::property::res::PropertyRes;
+
+    #[allow(non_snake_case)]
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct CategoryRes {
+        pub id: String,
+        pub user: Uuid,
+        pub name: String,
+        pub color: String,
+        pub status: StatusType,
+        pub props: Vec<PropertyRes>,
+        pub createdAt: DateTime<Utc>,
+        pub updatedAt: DateTime<Utc>,
+        pub version: i64,
+    }
+
+    impl CategoryRes {
+        pub fn from_model(category: &CategoryModel) -> Self {
+            Self {
+                id: category.id.to_hex(),
+                user: category.user,
+                name: category.name.to_owned(),
+                color: category.color.to_owned(),
+                status: category.status.to_owned(),
+                props: category.props.iter().map(PropertyRes::from_model).collect(),
+                createdAt: category.createdAt,
+                updatedAt: category.updatedAt,
+                version: category.version,
+            }
+        }
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct CategoryData {
+        pub category: CategoryRes,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct SingleCategoryRes {
+        pub status: &'static str,
+        pub data: CategoryData,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct CategoryListRes {
+        pub status: &'static str,
+        pub results: usize,
+        pub categories: Vec<Partial<CategoryRes>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub next_cursor: Option<String>,
+        pub has_more: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total: Option<u64>,
+    }
+}
@@ -1,3 +1,52 @@
 pub mod req {}
 
-pub mod res {}
+pub mod res {
+    use chrono::{DateTime, NaiveDate, Utc};
+    use serde::Serialize;
+    use uuid::Uuid;
+
+    use crate::domain::daily::DailyModel;
+    use crate::interface::dto::sub::daily_item::res::{
+        DailyEventRes, DailyHabitRes, DailyTaskRes, TimerResultRes,
+    };
+
+    #[allow(non_snake_case)]
+    #[derive(Serialize, Debug)]
+    pub struct DailyRes {
+        pub id: String,
+        pub user: Uuid,
+        pub date: NaiveDate,
+        pub diary: String,
+        pub rating: i8,
+        pub tasks: Vec<DailyTaskRes>,
+        pub events: Vec<DailyEventRes>,
+        pub habits: Vec<DailyHabitRes>,
+        pub timer_results: Vec<TimerResultRes>,
+        pub version: i64,
+        pub createdAt: DateTime<Utc>,
+        pub updatedAt: DateTime<Utc>,
+    }
+
+    impl DailyRes {
+        pub fn from_model(daily: &DailyModel) -> Self {
+            Self {
+                id: daily.id.to_hex(),
+                user: daily.user,
+                date: daily.date,
+                diary: daily.diary.to_owned(),
+                rating: daily.rating,
+                tasks: daily.tasks.iter().map(DailyTaskRes::from_model).collect(),
+                events: daily.events.iter().map(DailyEventRes::from_model).collect(),
+                habits: daily.habits.iter().map(DailyHabitRes::from_model).collect(),
+                timer_results: daily
+                    .timer_results
+                    .iter()
+                    .map(TimerResultRes::from_model)
+                    .collect(),
+                version: daily.version,
+                createdAt: daily.createdAt,
+                updatedAt: daily.updatedAt,
+            }
+        }
+    }
+}
@@ -7,8 +7,15 @@ pub mod req {
 	pub struct HabitFilterOptions {
 		pub page: Option<usize>,
 		pub limit: Option<usize>,
+		pub cursor: Option<String>,
+		pub with_total: Option<bool>,
 		pub start_month: Option<NaiveDate>,
 		pub end_month: Option<NaiveDate>,
+		pub filter: Option<String>,
+		pub sort: Option<String>,
+		pub fields: Option<String>,
+		pub tag: Option<String>,
+		pub tag_group: Option<String>,
 	}
 
 	#[derive(Serialize, Deserialize, Debug)]
@@ -38,7 +45,7 @@ pub mod res {
 
 	use crate::domain::habit::HabitModel;
 	use crate::domain::sub::habit_record::HabitRecord;
-	use crate::infra::types::StatusType;
+	use crate::infra::types::{Partial, StatusType};
 
 	#[allow(non_snake_case)]
 	#[derive(Deserialize, Serialize, Debug)]
@@ -52,6 +59,7 @@ pub mod res {
 		pub status: StatusType,
 		pub createdAt: DateTime<Utc>,
 		pub updatedAt: DateTime<Utc>,
+		pub version: i64,
 	}
 
 	impl HabitRes {
@@ -66,13 +74,14 @@ pub mod res {
 					status: habit.status.to_owned(),
 					createdAt: habit.createdAt,
 					updatedAt: habit.updatedAt,
+					version: habit.version,
 				}
 		}
 	}
 
 	#[derive(Serialize, Debug)]
 	pub struct HabitData {
-		pub habit: HabitRes,
+		pub habit: Partial<HabitRes>,
 	}
 
 	#[derive(Serialize, Debug)]
@@ -85,6 +94,11 @@ pub mod res {
 	pub struct HabitListRes {
 		pub status: &'static str,
 		pub results: usize,
-		pub habits: Vec<HabitRes>,
+		pub habits: Vec<Partial<HabitRes>>,
+		#[serde(skip_serializing_if = "Option::is_none")]
+		pub next_cursor: Option<String>,
+		pub has_more: bool,
+		#[serde(skip_serializing_if = "Option::is_none")]
+		pub total: Option<u64>,
 	}
 }
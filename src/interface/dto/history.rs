@@ -0,0 +1,83 @@
+pub mod res {
+    use chrono::{DateTime, Utc};
+    use mongodb::bson::Bson;
+    use serde::Serialize;
+    use serde_json::Value;
+    use uuid::Uuid;
+
+    use crate::domain::repo::history::{ChangeModel, FieldDiff};
+    use crate::infra::types::Page;
+
+    #[derive(Serialize, Debug)]
+    pub struct FieldDiffRes {
+        pub field: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub elem_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub position: Option<i64>,
+        // 값이 없던(제거된) 쪽은 null
+        pub before: Option<Value>,
+        pub after: Option<Value>,
+    }
+
+    impl FieldDiffRes {
+        pub fn from_model(diff: &FieldDiff) -> Self {
+            Self {
+                field: diff.field.to_owned(),
+                elem_id: diff.elem_id.map(|id| id.to_hex()),
+                position: diff.position,
+                before: diff.before.clone().map(Bson::into_relaxed_extjson),
+                after: diff.after.clone().map(Bson::into_relaxed_extjson),
+            }
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[derive(Serialize, Debug)]
+    pub struct ChangeRes {
+        // revert에 사용하는 revision id
+        pub id: String,
+        pub actor: Uuid,
+        // 변경 후 문서의 version
+        pub version: i64,
+        pub diff: Vec<FieldDiffRes>,
+        pub createdAt: DateTime<Utc>,
+    }
+
+    impl ChangeRes {
+        pub fn from_model(change: &ChangeModel) -> Self {
+            Self {
+                id: change.id.to_hex(),
+                actor: change.actor,
+                version: change.version,
+                diff: change.diff.iter().map(FieldDiffRes::from_model).collect(),
+                createdAt: change.createdAt,
+            }
+        }
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct ChangeListRes {
+        pub status: &'static str,
+        pub results: usize,
+        pub changes: Vec<ChangeRes>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub next_cursor: Option<String>,
+        pub has_more: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total: Option<u64>,
+    }
+
+    impl ChangeListRes {
+        pub fn from_page(page: Page<ChangeModel>) -> Self {
+            Self {
+                status: "success",
+                results: page.items.len(),
+                changes: page.items.iter().map(ChangeRes::from_model).collect(),
+                next_cursor: page.next_cursor,
+                has_more: page.has_more,
+                total: page.total,
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+pub mod req {
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct TagItemReq {
+        pub tag_id: Uuid,
+    }
+}
+
+pub mod res {
+    use serde::{Deserialize, Serialize};
+
+    // item에 붙은 tag. 이름은 조회 시점의 tags에서 가져옴
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct ItemTagRes {
+        pub id: String,
+        pub name: String,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct ItemTagListRes {
+        pub status: &'static str,
+        pub results: usize,
+        pub tags: Vec<ItemTagRes>,
+    }
+}
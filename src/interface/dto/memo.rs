@@ -24,6 +24,8 @@ pub mod res {
     use uuid::Uuid;
 
     use crate::domain::memo::MemoModel;
+    use crate::infra::types::Partial;
+    use crate::interface::dto::item_tag::res::ItemTagRes;
 
     #[allow(non_snake_case)]
     #[derive(Deserialize, Serialize, Debug)]
@@ -35,6 +37,10 @@ pub mod res {
         pub color: String,
         pub createdAt: DateTime<Utc>,
         pub updatedAt: DateTime<Utc>,
+        pub version: i64,
+        // postgres의 item_tags에서 채움(domain/item_tag.rs)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub tags: Option<Vec<ItemTagRes>>,
     }
 
     impl MemoRes {
@@ -47,12 +53,14 @@ pub mod res {
                 color: memo.color.to_owned(),
                 createdAt: memo.createdAt,
                 updatedAt: memo.updatedAt,
+                version: memo.version,
+                tags: None,
             }
         }
     }
     #[derive(Serialize, Debug)]
     pub struct MemoData {
-        pub memo: MemoRes,
+        pub memo: Partial<MemoRes>,
     }
 
     #[derive(Serialize, Debug)]
@@ -65,6 +73,11 @@ pub mod res {
     pub struct MemoListRes {
         pub status: &'static str,
         pub results: usize,
-        pub memos: Vec<MemoRes>,
+        pub memos: Vec<Partial<MemoRes>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub next_cursor: Option<String>,
+        pub has_more: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total: Option<u64>,
     }
 }
@@ -1,10 +1,19 @@
+pub mod batch;
+pub mod category;
 pub mod daily;
 pub mod task;
+pub mod task_template;
+pub mod timer;
 pub mod habit;
+pub mod history;
+pub mod item_tag;
 pub mod memo;
 pub mod schedule;
+pub mod settings;
 pub mod sub;
-// pub mod note;
+pub mod sync;
+pub mod note;
 pub mod tag_group;
 pub mod tag;
+pub mod trash;
 pub mod relation;
\ No newline at end of file
@@ -1,17 +1,17 @@
 pub mod req {
-    use crate::domain::{
-        note::NoteModel, sub::{note_page::PageModel, note_propV::PropValueModel}
-    };
+    use crate::domain::sub::{note_page::PageModel, note_propV::PropValueModel};
+    use std::str::FromStr;
     use uuid::Uuid;
 
     use crate::infra::types::ChatType;
     use chrono::{DateTime, Utc};
-    use mongodb::bson::{Document};
-    use serde::{Deserialize, Serialize};
+    use mongodb::bson::{oid::ObjectId, Document};
+    use serde::{Deserialize, Serialize, Serializer};
 
     #[derive(Serialize, Deserialize, Debug)]
     pub struct CreateNoteReq {
         pub title: String,
+        #[serde(serialize_with = "serialize_oid")]
         pub category_id: String,
         pub category_color: String,
         pub category_name: String,
@@ -19,7 +19,10 @@ pub mod req {
         pub prop_values: Option<Vec<PropValueModel>>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub pages: Option<Vec<PageModel>>,
-        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "serialize_opt_oid"
+        )]
         pub parent_id: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub chat_type: Option<ChatType>,
@@ -29,22 +32,45 @@ pub mod req {
     pub struct UpdateNoteReq {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub title: Option<String>,
-        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "serialize_opt_oid"
+        )]
         pub category_id: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub category_color: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub category_name: Option<String>,
 
-        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "serialize_opt_oid"
+        )]
         pub parent_id: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub pages: Option<Vec<NoteModel>>,
+        pub pages: Option<Vec<PageModel>>,
 
         #[serde(skip_serializing_if = "Option::is_none")]
         pub chat_type: Option<ChatType>,
     }
 
+    // category_id, parent_id는 _id와 비교하므로 ObjectId로 저장함. 빈 문자열은 null
+    fn serialize_oid<S: Serializer>(id: &str, serializer: S) -> Result<S::Ok, S::Error> {
+        ObjectId::from_str(id)
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+
+    fn serialize_opt_oid<S: Serializer>(
+        id: &Option<String>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match id.as_deref().filter(|id| !id.is_empty()) {
+            Some(id) => serialize_oid(id, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
     #[allow(non_snake_case)]
     #[derive(Deserialize, Serialize, Debug)]
     pub struct NoteFetchOptions {
@@ -66,14 +92,20 @@ pub mod req {
         pub fn build_projection() -> Document {
             let mut projection = Document::new();
 
+            // 목록에서는 chat_msgs, blocks를 읽지 않음
             let fields = vec![
                 "_id",
                 "user",
                 "title",
                 "category_id",
+                "category_color",
+                "category_name",
                 "prop_values",
                 "pages",
+                "connected_task",
                 "parent_id",
+                "chat_type",
+                "version",
                 "createdAt",
                 "updatedAt",
             ];
@@ -89,9 +121,11 @@ pub mod req {
 
 pub mod res {
     use crate::domain::{
-        note::NoteModel, sub::{chat::MsgModel,  note_page::PageModel, note_propV::PropValueModel}
+        note::NoteModel,
+        sub::{chat::MsgModel, note_page::PageModel},
     };
-    use crate::infra::types::ChatType;
+    use crate::infra::types::{ChatType, Partial};
+    use crate::interface::dto::sub::note_propV::res::PropValueRes;
 
     use chrono::{DateTime, Utc};
     use serde::{Serialize};
@@ -107,7 +141,7 @@ pub mod res {
         pub category_id: String,
         pub category_color: String,
         pub category_name: String,
-        pub prop_values: Vec<PropValueModel>,
+        pub prop_values: Vec<PropValueRes>,
 
         pub pages: Vec<PageModel>,
         pub connected_task: Option<String>,
@@ -118,6 +152,7 @@ pub mod res {
 
         pub createdAt: DateTime<Utc>,
         pub updatedAt: DateTime<Utc>,
+        pub version: i64,
     }
 
     impl NoteRes {
@@ -129,7 +164,11 @@ pub mod res {
                 category_id: note.category_id.to_hex(),
                 category_color: note.category_color.to_owned(),
                 category_name: note.category_name.to_owned(),
-                prop_values: note.prop_values.clone(),
+                prop_values: note
+                    .prop_values
+                    .iter()
+                    .map(PropValueRes::from_model)
+                    .collect(),
                 pages: note.pages.to_owned(),
                 connected_task: note.connected_task.as_ref().map(|id| id.to_hex()),
                 parent_id: note.parent_id.as_ref().map(|id| id.to_hex()),
@@ -137,6 +176,7 @@ pub mod res {
                 chat_msgs: note.chat_msgs.to_owned(),
                 createdAt: note.createdAt,
                 updatedAt: note.updatedAt,
+                version: note.version,
             }
         }
     }
@@ -156,6 +196,11 @@ pub mod res {
     pub struct NoteListRes {
         pub status: &'static str,
         pub results: usize,
-        pub notes: Vec<NoteRes>,
+        pub notes: Vec<Partial<NoteRes>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub next_cursor: Option<String>,
+        pub has_more: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total: Option<u64>,
     }
 }
@@ -1,3 +1,58 @@
 pub mod req {}
 
-pub mod res {}
+pub mod res {
+    use chrono::{DateTime, Utc};
+    use serde::Serialize;
+    use uuid::Uuid;
+
+    use crate::domain::schedule::ScheduleModel;
+    use crate::interface::dto::sub::schedule_item::res::{
+        ScheduledAtRes, ScheduledEventRes, ScheduledHabitRes, ScheduledTaskRes,
+    };
+
+    #[allow(non_snake_case)]
+    #[derive(Serialize, Debug)]
+    pub struct ScheduleRes {
+        pub id: String,
+        pub user: Uuid,
+        pub tasks: Vec<ScheduledTaskRes>,
+        pub events: Vec<ScheduledEventRes>,
+        pub habits: Vec<ScheduledHabitRes>,
+        pub scheduled_times: Vec<ScheduledAtRes>,
+        pub version: i64,
+        pub createdAt: DateTime<Utc>,
+        pub updatedAt: DateTime<Utc>,
+    }
+
+    impl ScheduleRes {
+        pub fn from_model(schedule: &ScheduleModel) -> Self {
+            Self {
+                id: schedule.id.to_hex(),
+                user: schedule.user,
+                tasks: schedule
+                    .tasks
+                    .iter()
+                    .map(ScheduledTaskRes::from_model)
+                    .collect(),
+                events: schedule
+                    .events
+                    .iter()
+                    .map(ScheduledEventRes::from_model)
+                    .collect(),
+                habits: schedule
+                    .habits
+                    .iter()
+                    .map(ScheduledHabitRes::from_model)
+                    .collect(),
+                scheduled_times: schedule
+                    .scheduled_times
+                    .iter()
+                    .map(ScheduledAtRes::from_model)
+                    .collect(),
+                version: schedule.version,
+                createdAt: schedule.createdAt,
+                updatedAt: schedule.updatedAt,
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+pub mod req {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct UpdateSettingsReq {
+        // 하루에 일정을 잡을 수 있는 시간(분)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub daily_capacity_minutes: Option<u32>,
+    }
+}
+
+pub mod res {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    use crate::domain::settings::SettingsModel;
+
+    #[allow(non_snake_case)]
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct SettingsRes {
+        pub id: String,
+        pub user: Uuid,
+        pub daily_capacity_minutes: u32,
+        pub createdAt: DateTime<Utc>,
+        pub updatedAt: DateTime<Utc>,
+        pub version: i64,
+    }
+
+    impl SettingsRes {
+        pub fn from_model(settings: &SettingsModel) -> Self {
+            Self {
+                id: settings.id.to_hex(),
+                user: settings.user,
+                daily_capacity_minutes: settings.daily_capacity_minutes,
+                createdAt: settings.createdAt,
+                updatedAt: settings.updatedAt,
+                version: settings.version,
+            }
+        }
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct SettingsData {
+        pub settings: SettingsRes,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct SingleSettingsRes {
+        pub status: &'static str,
+        pub data: SettingsData,
+    }
+}
@@ -0,0 +1,14 @@
+// 배열 원소의 순서 변경/이동에 공통으로 사용하는 요청
+pub mod req {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct MoveElemReq {
+        pub index: usize,
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct TransferElemReq {
+        pub dst_id: String,
+    }
+}
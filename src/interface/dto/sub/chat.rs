@@ -55,6 +55,7 @@ pub mod res {
         pub booked: bool,
         pub chat_type: Option<ChatType>,
         pub chat_msgs: Option<Vec<MsgModel>>,
+        pub version: i64,
     }
 
     impl MsgRes {
@@ -67,6 +68,7 @@ pub mod res {
                 booked: msg.booked,
                 chat_type: msg.chat_type.to_owned(),
                 chat_msgs: msg.chat_msgs.clone(),
+                version: msg.version,
             }
         }
     }
@@ -87,5 +89,10 @@ pub mod res {
         pub status: &'static str,
         pub results: usize,
         pub msgs: Vec<MsgRes>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub next_cursor: Option<String>,
+        pub has_more: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total: Option<u64>,
     }
 }
@@ -94,76 +94,98 @@ pub mod res {
     #[allow(non_snake_case)]
     #[derive(Serialize, Deserialize, Debug)]
     pub struct DailyTaskRes {
+        id: String,
         task_id: String,
         title: String,
         done: bool,
         doneAt: Option<DateTime<Utc>>,
+        version: i64,
     }
     impl DailyTaskRes {
         pub fn from_model(task: &DailyTaskModel) -> Self {
             Self {
+                id: task.id.to_hex(),
                 task_id: task.task_id.to_hex(),
                 title: task.title.clone(),
                 done: task.done,
                 doneAt: task.doneAt,
+                version: task.version,
             }
         }
     }
     #[allow(non_snake_case)]
     #[derive(Serialize, Deserialize, Debug)]
     pub struct DailyEventRes {
+        id: String,
         event_id: String,
         title: String,
         done: bool,
         doneAt: Option<DateTime<Utc>>,
+        version: i64,
     }
     impl DailyEventRes {
         pub fn from_model(event: &DailyEventModel) -> Self {
             Self {
+                id: event.id.to_hex(),
                 event_id: event.event_id.to_hex(),
                 title: event.title.clone(),
                 done: event.done,
                 doneAt: event.doneAt,
+                version: event.version,
             }
         }
     }
     #[allow(non_snake_case)]
     #[derive(Serialize, Deserialize, Debug)]
     pub struct DailyHabitRes {
+        id: String,
         habit_id: String,
         icon: String,
         name: String,
         done: bool,
         doneAt: Option<DateTime<Utc>>,
+        version: i64,
     }
     impl DailyHabitRes {
         pub fn from_model(habit: &DailyHabitModel) -> Self {
             Self {
+                id: habit.id.to_hex(),
                 habit_id: habit.habit_id.to_hex(),
                 icon: habit.icon.clone(),
                 name: habit.name.clone(),
                 done: habit.done,
                 doneAt: habit.doneAt,
+                version: habit.version,
             }
         }
     }
     #[allow(non_snake_case)]
     #[derive(Serialize, Deserialize, Debug)]
     pub struct TimerResultRes {
+        id: String,
         category_id: String,
         category_color: String,
         startAt: DateTime<Utc>,
         endAt: DateTime<Utc>,
         focus_time: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        task_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        focus_secs: Option<i64>,
+        version: i64,
     }
     impl TimerResultRes {
         pub fn from_model(timer_result: &TimerResultModel) -> Self {
             Self {
+                id: timer_result.id.to_hex(),
                 category_id: timer_result.category_id.to_hex(),
                 category_color: timer_result.category_color.clone(),
                 startAt: timer_result.startAt,
                 endAt: timer_result.endAt,
                 focus_time: timer_result.focus_time.clone(),
+                task_id: timer_result.task_id.map(|id| id.to_hex()),
+                focus_secs: timer_result.focus_secs,
+                version: timer_result.version,
             }
         }
     }
@@ -183,5 +205,10 @@ pub mod res {
         pub status: &'static str,
         pub results: usize,
         pub items: Vec<T>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub next_cursor: Option<String>,
+        pub has_more: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total: Option<u64>,
     }
 }
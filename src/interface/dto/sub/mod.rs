@@ -1,7 +1,9 @@
+pub mod array;
 pub mod chat;
 pub mod daily_item;
 pub mod schedule_item;
 
-// pub mod note_block;
-// pub mod note_propV;
-// pub mod property;
+pub mod note_block;
+#[allow(non_snake_case)]
+pub mod note_propV;
+pub mod property;
@@ -1,16 +1,22 @@
 pub mod req {
     use crate::infra::types::BlockType;
-    use serde::{Deserialize, Serialize};
+    use mongodb::bson::oid::ObjectId;
+    use serde::{Deserialize, Serialize, Serializer};
+    use std::str::FromStr;
 
     #[derive(Serialize, Deserialize, Debug)]
     pub struct CreateBlockReq {
+        #[serde(serialize_with = "serialize_oid")]
         pub src_task_id: String,
         pub block_type: BlockType,
     }
 
     #[derive(Serialize, Deserialize, Debug)]
     pub struct UpdateBlockReq {
-        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "serialize_opt_oid"
+        )]
         pub src_task_id: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub block_type: Option<BlockType>,
@@ -25,6 +31,23 @@ pub mod req {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub block_type: Option<BlockType>,
     }
+
+    // BlockModel의 src_task_id는 ObjectId로 저장함
+    fn serialize_oid<S: Serializer>(id: &str, serializer: S) -> Result<S::Ok, S::Error> {
+        ObjectId::from_str(id)
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+
+    fn serialize_opt_oid<S: Serializer>(
+        id: &Option<String>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match id {
+            Some(id) => serialize_oid(id, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
 }
 
 pub mod res {
@@ -13,9 +13,9 @@ pub mod req {
     #[derive(Serialize, Deserialize, Debug)]
     pub struct UpdatePropValueReq {
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub name: Option<String>,
+        pub prop_name: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub values: Option<Vec<PropValueType>>,
+        pub values: Option<PropValueType>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub prop_type: Option<PropertyType>,
     }
@@ -98,6 +98,7 @@ pub mod res {
         title: String,
         category_id: String,
         category_color: String,
+        version: i64,
     }
 
     impl ScheduledTaskRes {
@@ -107,6 +108,7 @@ pub mod res {
                 title: task.title.clone(),
                 category_id: task.category_id.to_hex(),
                 category_color: task.category_color.clone(),
+                version: task.version,
             }
         }
     }
@@ -117,6 +119,7 @@ pub mod res {
         title: String,
         start_date: Option<NaiveDate>,
         due_at: Option<DateTime<Utc>>,
+        version: i64,
     }
 
     impl ScheduledEventRes {
@@ -126,6 +129,7 @@ pub mod res {
                 title: event.title.clone(),
                 start_date: event.start_date,
                 due_at: event.due_at,
+                version: event.version,
             }
         }
     }
@@ -135,6 +139,7 @@ pub mod res {
         habit_id: String,
         name: String,
         icon: String,
+        version: i64,
     }
 
     impl ScheduledHabitRes {
@@ -143,6 +148,7 @@ pub mod res {
                 habit_id: habit.habit_id.to_hex(),
                 name: habit.name.clone(),
                 icon: habit.icon.clone(),
+                version: habit.version,
             }
         }
     }
@@ -155,6 +161,7 @@ pub mod res {
         weekday: Weekday,
         startAt: Option<NaiveTime>,
         endAt: Option<NaiveTime>,
+        version: i64,
     }
 
     impl ScheduledAtRes {
@@ -165,6 +172,7 @@ pub mod res {
                 weekday: scheduled_at.weekday,
                 startAt: scheduled_at.startAt,
                 endAt: scheduled_at.endAt,
+                version: scheduled_at.version,
             }
         }
     }
@@ -185,5 +193,10 @@ pub mod res {
         pub status: &'static str,
         pub results: usize,
         pub items: Vec<T>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub next_cursor: Option<String>,
+        pub has_more: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total: Option<u64>,
     }
 }
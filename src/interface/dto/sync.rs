@@ -0,0 +1,135 @@
+pub mod req {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::infra::types::{MergeStrategy, SyncKind, SyncOp};
+
+    #[derive(Deserialize, Debug, Default)]
+    pub struct SyncQuery {
+        pub since: Option<String>,
+        pub limit: Option<usize>,
+    }
+
+    #[allow(non_snake_case)]
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct SyncMutationReq {
+        pub kind: SyncKind,
+        pub op: SyncOp,
+        // create도 client가 만든 ObjectId를 사용함(오프라인에서 생성한 문서를 바로 참조하기 위함).
+        pub id: String,
+        // client가 마지막으로 받은 문서의 version
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub base_version: Option<i64>,
+        // client에서 변경한 시각(lww 비교에 사용)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub updatedAt: Option<DateTime<Utc>>,
+        // create는 Create*Req, update는 Update*Req 형식
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub data: Option<Value>,
+        // merge에서 사용. data의 필드들이 base_version일 때 가지고 있던 값(Update*Req 형식)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub base: Option<Value>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct SyncPushReq {
+        #[serde(default)]
+        pub strategy: MergeStrategy,
+        pub mutations: Vec<SyncMutationReq>,
+    }
+}
+
+pub mod res {
+    use chrono::{DateTime, Utc};
+    use serde::Serialize;
+
+    use crate::infra::types::{ConflictResolution, SyncChange, SyncKind};
+    use crate::interface::dto::{
+        daily::res::DailyRes, habit::res::HabitRes, memo::res::MemoRes, schedule::res::ScheduleRes,
+        task::res::TaskRes,
+    };
+
+    #[derive(Serialize, Debug)]
+    #[serde(untagged)]
+    pub enum SyncData {
+        // 다른 variant보다 훨씬 큼
+        Task(Box<TaskRes>),
+        Memo(MemoRes),
+        Habit(HabitRes),
+        Daily(DailyRes),
+        Schedule(ScheduleRes),
+    }
+
+    impl From<TaskRes> for SyncData {
+        fn from(res: TaskRes) -> Self {
+            SyncData::Task(Box::new(res))
+        }
+    }
+
+    impl From<MemoRes> for SyncData {
+        fn from(res: MemoRes) -> Self {
+            SyncData::Memo(res)
+        }
+    }
+
+    impl From<HabitRes> for SyncData {
+        fn from(res: HabitRes) -> Self {
+            SyncData::Habit(res)
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[derive(Serialize, Debug)]
+    pub struct SyncChangeRes {
+        pub kind: SyncKind,
+        pub op: SyncChange,
+        pub id: String,
+        pub version: i64,
+        pub updatedAt: DateTime<Utc>,
+        // deleted(tombstone)이면 없음
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub data: Option<SyncData>,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct SyncPullRes {
+        pub status: &'static str,
+        pub results: usize,
+        pub changes: Vec<SyncChangeRes>,
+        // 다음 요청의 since로 사용
+        pub next_token: String,
+        // true면 next_token으로 남은 변경을 이어서 받아야 함
+        pub has_more: bool,
+        // since가 휴지통 보관 기간보다 오래되어 tombstone을 보장할 수 없음.
+        // client는 로컬 데이터를 버리고 changes로 다시 채워야 함.
+        pub full_resync: bool,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct SyncMutationRes {
+        pub index: usize,
+        pub id: String,
+        // applied, conflict, fail
+        pub status: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub resolution: Option<ConflictResolution>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        pub conflict_fields: Vec<String>,
+        // 처리 후 서버의 문서(삭제되었으면 없음)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub data: Option<SyncData>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub message: Option<String>,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct SyncPushRes {
+        pub status: &'static str,
+        pub results: usize,
+        pub applied: usize,
+        pub conflicts: usize,
+        pub failed: usize,
+        pub items: Vec<SyncMutationRes>,
+    }
+}
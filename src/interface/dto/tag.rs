@@ -15,6 +15,19 @@ pub mod req {
         pub name: Option<String>
     }
 
+    // source tag의 모든 relation을 target으로 옮기고 source는 삭제함
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct MergeTagReq {
+        pub target_id: Uuid,
+    }
+
+    // 자동완성. q로 시작하는 tag를 사용 빈도와 최근 사용 순으로
+    #[derive(Deserialize, Debug, Default)]
+    pub struct TagSuggestionQuery {
+        pub q: Option<String>,
+        pub limit: Option<i64>,
+    }
+
     #[derive( Serialize, Deserialize, Debug,FromRow)]
     pub struct FilterTagReq {
         pub user: Uuid,
@@ -25,6 +38,7 @@ pub mod req {
 
 pub mod res {
     use crate::domain::tag::TagModel;
+    use chrono::{DateTime, Utc};
     use serde::{Deserialize, Serialize};
     use sqlx::FromRow;
     use uuid::Uuid;
@@ -66,5 +80,26 @@ pub mod res {
         pub status: &'static str,
         pub results: usize,
         pub tags: Vec<TagRes>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub next_cursor: Option<String>,
+        pub has_more: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total: Option<u64>,
+    }
+
+    // tag가 붙은 item 수와 마지막으로 붙인 시각
+    #[derive(Deserialize, Serialize, Debug, FromRow)]
+    pub struct TagStatRes {
+        pub id: String,
+        pub name: String,
+        pub usage_count: i64,
+        pub last_used_at: Option<DateTime<Utc>>,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct TagStatListRes {
+        pub status: &'static str,
+        pub results: usize,
+        pub tags: Vec<TagStatRes>,
     }
 }
@@ -8,6 +8,9 @@ pub mod req {
     pub struct CreateTagGroupReq {
         pub name: String,
         pub color: String,
+        // 상위 group
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub parent_id: Option<Uuid>,
     }
 
     #[derive(Fields, Serialize, Deserialize, Debug,FromRow)]
@@ -18,6 +21,12 @@ pub mod req {
         pub color: Option<String>,
     }
 
+    // null이면 최상위 group으로 옮김
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct MoveTagGroupReq {
+        pub parent_id: Option<Uuid>,
+    }
+
     #[derive( Serialize, Deserialize, Debug,FromRow)]
     pub struct FilterTagGroupReq {
         pub user: Uuid,
@@ -31,16 +40,17 @@ pub mod req {
 pub mod res {
     use crate::domain::tag_group::TagGroupModel;
     use serde::{Deserialize, Serialize};
-    use sqlx::{Decode, FromRow};
+    use sqlx::FromRow;
     use uuid::Uuid;
 
     #[allow(non_snake_case)]
-    #[derive(Deserialize, Serialize, Debug,FromRow, Decode)]
+    #[derive(Deserialize, Serialize, Debug,FromRow)]
     pub struct TagGroupRes {
         pub id: String,
         pub user: Uuid,
         pub name: String,
         pub color: String,
+        pub parent_id: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub tags: Option<Vec<String>>,
     }
@@ -52,6 +62,7 @@ pub mod res {
                 user: tag_group.user,
                 name: tag_group.name.to_owned(),
                 color: tag_group.color.to_owned(),
+                parent_id: tag_group.parent_id.map(|id| id.to_string()),
                 tags: None,
             }
         }
@@ -73,5 +84,10 @@ pub mod res {
         pub status: &'static str,
         pub results: usize,
         pub tag_groups: Vec<TagGroupRes>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub next_cursor: Option<String>,
+        pub has_more: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total: Option<u64>,
     }
 }
@@ -1,23 +1,35 @@
 pub mod req {
     use chrono::{DateTime, Local, NaiveDate, Utc};
-    use mongodb::bson::Document;
-    use serde::{Deserialize, Serialize};
+    use std::str::FromStr;
+
+    use mongodb::bson::{oid::ObjectId, Document};
+    use serde::{Deserialize, Serialize, Serializer};
     use uuid::Uuid;
 
-    use crate::infra::types::ChatType;
+    use crate::infra::types::{ChatType, Priority, TaskStatus};
 
     #[derive(Deserialize, Debug, Default)]
     pub struct TaskFilterOptions {
         pub page: Option<usize>,
         pub limit: Option<usize>,
+        pub cursor: Option<String>,
+        pub with_total: Option<bool>,
         pub start_date: Option<NaiveDate>,
         pub end_date: Option<NaiveDate>,
+        pub filter: Option<String>,
+        pub sort: Option<String>,
+        pub fields: Option<String>,
+        pub tag: Option<String>,
+        pub tag_group: Option<String>,
     }
 
     #[derive(Serialize, Deserialize, Debug)]
     pub struct CreateTaskReq {
         pub title: String,
-        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "serialize_parent_id"
+        )]
         pub parent_id: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub start_date: Option<NaiveDate>,
@@ -25,6 +37,35 @@ pub mod req {
         pub end_date: Option<NaiveDate>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub due_at: Option<DateTime<Local>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub estimate_minutes: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub auto_progress: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub priority: Option<Priority>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub recurrence: Option<RecurrenceReq>,
+    }
+
+    // tree 조회 시 _id와 비교하므로 parent_id는 ObjectId로 저장함. 빈 문자열은 null(parent 없음)
+    fn serialize_parent_id<S: Serializer>(
+        id: &Option<String>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match id.as_deref().filter(|id| !id.is_empty()) {
+            Some(id) => ObjectId::from_str(id)
+                .map_err(serde::ser::Error::custom)?
+                .serialize(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    // 반복 규칙. start_date가 규칙의 기준일이 됨
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct RecurrenceReq {
+        pub rrule: String,
+        #[serde(default)]
+        pub exdates: Vec<NaiveDate>,
     }
 
     #[allow(non_snake_case)]
@@ -34,7 +75,10 @@ pub mod req {
         pub title: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub milestone: Option<bool>,
-        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(
+            skip_serializing_if = "Option::is_none",
+            serialize_with = "serialize_parent_id"
+        )]
         pub parent_id: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub start_date: Option<NaiveDate>,
@@ -45,7 +89,77 @@ pub mod req {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub progress_rate: Option<u8>,
         #[serde(skip_serializing_if = "Option::is_none")]
+        pub estimate_minutes: Option<u32>,
+        // true면 progress_rate를 subtask들로부터 계산함
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub auto_progress: Option<bool>,
+        // 전이가 허용되지 않으면 InvalidTransition. Done이 되면 progress_rate는 100이 됨
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub status: Option<TaskStatus>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub priority: Option<Priority>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         pub chat_type: Option<ChatType>,
+        // 규칙을 바꾸면 기존 예외(exdates, overrides)는 사라짐
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub recurrence: Option<RecurrenceReq>,
+    }
+
+    /// 반복 task의 occurrence를 바꾸거나 지울 때의 범위
+    #[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+    pub enum OccurrenceScope {
+        #[default]
+        #[serde(rename = "THIS")]
+        This,
+        #[serde(rename = "THIS_AND_FOLLOWING")]
+        ThisAndFollowing,
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct UpdateOccurrenceReq {
+        #[serde(default)]
+        pub scope: OccurrenceScope,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub title: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub start_date: Option<NaiveDate>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub end_date: Option<NaiveDate>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub progress_rate: Option<u8>,
+    }
+
+    #[derive(Deserialize, Debug, Default)]
+    pub struct OccurrenceScopeOptions {
+        pub scope: Option<OccurrenceScope>,
+    }
+
+    // id의 task가 depends_on의 task가 끝나야 시작할 수 있음
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct AddDependencyReq {
+        pub depends_on: String,
+    }
+
+    // parent_id가 없으면 최상위 task가 됨
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct MoveTaskReq {
+        pub parent_id: Option<String>,
+    }
+
+    // 복사본은 원래 task와 같은 parent 아래에 생성됨
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    pub struct CloneTaskReq {
+        // chat_msgs도 복사함(새 _id로)
+        #[serde(default)]
+        pub include_chat_msgs: bool,
+        // 모든 날짜를 이만큼(일) 옮김
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub shift_days: Option<i64>,
+    }
+
+    #[derive(Deserialize, Debug, Default)]
+    pub struct CriticalPathOptions {
+        pub root: Option<String>,
     }
 
     #[derive(Serialize, Deserialize, Debug)]
@@ -93,8 +207,20 @@ pub mod req {
                 "start_date",
                 "end_date",
                 "due_at",
+                "estimate_minutes",
+                "depends_on",
+                "auto_progress",
+                "milestone_reached_at",
+                "status",
+                "priority",
+                "doneAt",
+                "cancelledAt",
+                "timer",
+                "tracked_minutes",
+                "recurrence",
                 "createdAt",
                 "updatedAt",
+                "version",
             ];
 
             for field in fields {
@@ -107,8 +233,12 @@ pub mod req {
 }
 
 pub mod res {
-    use crate::domain::{sub::chat::MsgModel, task::TaskModel};
-    use crate::infra::types::{ChatType, TaskTreeItem};
+    use crate::domain::{
+        sub::{chat::MsgModel, recurrence::RecurrenceModel, timer::TaskTimerModel},
+        task::TaskModel,
+    };
+    use crate::infra::types::{ChatType, Partial, Priority, TaskStatus, TaskTreeItem};
+    use crate::interface::dto::item_tag::res::ItemTagRes;
     use chrono::{DateTime, Local, NaiveDate, Utc};
     use serde::Serialize;
     use uuid::Uuid;
@@ -127,8 +257,37 @@ pub mod res {
         pub start_date: Option<NaiveDate>,
         pub end_date: Option<NaiveDate>,
         pub due_at: Option<DateTime<Local>>,
+        pub estimate_minutes: Option<u32>,
+        pub auto_progress: bool,
+        // milestone의 모든 subtask가 끝난 시각
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub milestone_reached_at: Option<DateTime<Utc>>,
+        pub status: TaskStatus,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub priority: Option<Priority>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub doneAt: Option<DateTime<Utc>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub cancelledAt: Option<DateTime<Utc>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub timer: Option<TaskTimerModel>,
+        pub tracked_minutes: u32,
         pub createdAt: DateTime<Utc>,
         pub updatedAt: DateTime<Utc>,
+        pub version: i64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub depends_on: Option<Vec<String>>,
+        // depends_on 중 Done도 Cancelled도 아닌 task가 있음. 조회 시 계산함
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub blocked: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub recurrence: Option<RecurrenceModel>,
+        // 반복 task를 조회 기간에 맞춰 펼친 occurrence의 원래 날짜
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub occurrence_date: Option<NaiveDate>,
+        // postgres의 item_tags에서 채움(domain/item_tag.rs)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub tags: Option<Vec<ItemTagRes>>,
     }
 
     impl TaskRes {
@@ -147,6 +306,24 @@ pub mod res {
                 progress_rate: task.progress_rate,
                 milestone: task.milestone,
                 end_date: task.end_date.to_owned(),
+                version: task.version,
+                estimate_minutes: task.estimate_minutes,
+                auto_progress: task.auto_progress.unwrap_or(false),
+                milestone_reached_at: task.milestone_reached_at,
+                status: task.status,
+                priority: task.priority,
+                doneAt: task.doneAt,
+                cancelledAt: task.cancelledAt,
+                timer: task.timer.to_owned(),
+                tracked_minutes: task.tracked_minutes.unwrap_or(0),
+                depends_on: task
+                    .depends_on
+                    .as_ref()
+                    .map(|ids| ids.iter().map(|id| id.to_hex()).collect()),
+                blocked: None,
+                recurrence: task.recurrence.to_owned(),
+                occurrence_date: None,
+                tags: None,
             }
         }
 
@@ -165,13 +342,28 @@ pub mod res {
                 due_at: None,
                 createdAt: Utc::now(),
                 updatedAt: Utc::now(),
+                version: 0,
+                estimate_minutes: None,
+                auto_progress: false,
+                milestone_reached_at: None,
+                status: TaskStatus::Todo,
+                priority: None,
+                doneAt: None,
+                cancelledAt: None,
+                timer: None,
+                tracked_minutes: 0,
+                depends_on: None,
+                blocked: None,
+                recurrence: None,
+                occurrence_date: None,
+                tags: None,
             }
         }
     }
 
     #[derive(Serialize, Debug)]
     pub struct TaskData {
-        pub task: TaskRes,
+        pub task: Partial<TaskRes>,
     }
 
     #[derive(Serialize, Debug)]
@@ -184,13 +376,76 @@ pub mod res {
     pub struct TaskListRes {
         pub status: &'static str,
         pub results: usize,
+        pub tasks: Vec<Partial<TaskRes>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub next_cursor: Option<String>,
+        pub has_more: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total: Option<u64>,
+    }
+
+    /// root(없으면 전체)까지 이어지는 가장 긴 dependency chain. 앞의 task부터 순서대로.
+    #[derive(Serialize, Debug)]
+    pub struct CriticalPathRes {
+        pub status: &'static str,
+        pub results: usize,
+        pub total_minutes: u64,
         pub tasks: Vec<TaskRes>,
     }
 
+    /// 하루에 배정된 estimate_minutes의 합. capacity를 넘으면 over.
+    #[derive(Serialize, Debug, PartialEq)]
+    pub struct CapacityDay {
+        pub date: NaiveDate,
+        pub minutes: u32,
+        pub capacity: u32,
+        pub over: bool,
+        // 이 날에 시간이 배정된 task들
+        pub task_ids: Vec<String>,
+    }
+
+    /// start_date ~ end_date(미포함)의 날짜별 작업량
+    #[derive(Serialize, Debug)]
+    pub struct CapacityRes {
+        pub status: &'static str,
+        pub start_date: NaiveDate,
+        pub end_date: NaiveDate,
+        pub daily_capacity: u32,
+        pub total_minutes: u32,
+        pub over_days: usize,
+        pub days: Vec<CapacityDay>,
+    }
+
     #[derive(Serialize, Debug)]
     pub struct TaskListTreeRes {
         pub status: &'static str,
         pub results: usize,
         pub tasks: Vec<TaskTreeItem>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub next_cursor: Option<String>,
+        pub has_more: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total: Option<u64>,
+    }
+
+    /// task 하나와 그 아래의 모든 subtask
+    #[derive(Serialize, Debug)]
+    pub struct SingleTaskTreeRes {
+        pub status: &'static str,
+        pub task: TaskTreeItem,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct TaskPathItem {
+        pub id: String,
+        pub title: String,
+    }
+
+    /// 최상위 task부터 해당 task까지(breadcrumb)
+    #[derive(Serialize, Debug)]
+    pub struct TaskPathRes {
+        pub status: &'static str,
+        pub results: usize,
+        pub path: Vec<TaskPathItem>,
     }
 }
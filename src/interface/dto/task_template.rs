@@ -0,0 +1,91 @@
+pub mod req {
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct CreateTaskTemplateReq {
+        // template으로 저장할 subtree의 root task
+        pub task_id: String,
+        pub name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub description: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct UpdateTaskTemplateReq {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub name: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub description: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct InstantiateTemplateReq {
+        // root task의 start_date. 다른 날짜들은 template의 offset만큼 옮겨짐
+        pub start_date: NaiveDate,
+        // 주어지면 root task를 이 task의 subtask로 생성
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub parent_id: Option<String>,
+    }
+}
+
+pub mod res {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    use crate::domain::task_template::{TaskTemplateModel, TemplateNode};
+    use crate::infra::types::Partial;
+
+    #[allow(non_snake_case)]
+    #[derive(Deserialize, Serialize, Debug)]
+    pub struct TaskTemplateRes {
+        pub id: String,
+        pub user: Uuid,
+        pub name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub description: Option<String>,
+        pub nodes: Vec<TemplateNode>,
+        pub createdAt: DateTime<Utc>,
+        pub updatedAt: DateTime<Utc>,
+        pub version: i64,
+    }
+
+    impl TaskTemplateRes {
+        pub fn from_model(template: &TaskTemplateModel) -> Self {
+            Self {
+                id: template.id.to_hex(),
+                user: template.user,
+                name: template.name.to_owned(),
+                description: template.description.to_owned(),
+                nodes: template.nodes.to_owned(),
+                createdAt: template.createdAt,
+                updatedAt: template.updatedAt,
+                version: template.version,
+            }
+        }
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct TaskTemplateData {
+        pub template: Partial<TaskTemplateRes>,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct SingleTaskTemplateRes {
+        pub status: &'static str,
+        pub data: TaskTemplateData,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct TaskTemplateListRes {
+        pub status: &'static str,
+        pub results: usize,
+        pub templates: Vec<Partial<TaskTemplateRes>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub next_cursor: Option<String>,
+        pub has_more: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total: Option<u64>,
+    }
+}
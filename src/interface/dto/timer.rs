@@ -0,0 +1,75 @@
+pub mod req {
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Serialize};
+
+    // 일시정지된 timer를 다시 시작할 때는 처음의 category를 그대로 씀
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct StartTimerReq {
+        pub category_id: String,
+        pub category_color: String,
+    }
+
+    // from, to 모두 포함
+    #[derive(Deserialize, Debug)]
+    pub struct TimeReportOptions {
+        pub from: NaiveDate,
+        pub to: NaiveDate,
+    }
+}
+
+pub mod res {
+    use chrono::NaiveDate;
+    use serde::Serialize;
+
+    use crate::infra::types::Partial;
+    use crate::interface::dto::sub::daily_item::res::TimerResultRes;
+    use crate::interface::dto::task::res::TaskRes;
+
+    #[derive(Serialize, Debug)]
+    pub struct StopTimerData {
+        pub task: Partial<TaskRes>,
+        // 그 날의 daily에 추가된 결과
+        pub timer_result: TimerResultRes,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct StopTimerRes {
+        pub status: &'static str,
+        pub data: StopTimerData,
+    }
+
+    #[derive(Serialize, Debug, PartialEq)]
+    pub struct TimeReportDay {
+        pub date: NaiveDate,
+        pub minutes: u32,
+        pub sessions: usize,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct TaskTimeReportRes {
+        pub status: &'static str,
+        pub task_id: String,
+        pub title: String,
+        pub tracked_minutes: u32,
+        pub results: usize,
+        pub days: Vec<TimeReportDay>,
+    }
+
+    #[derive(Serialize, Debug, PartialEq)]
+    pub struct CategoryTimeItem {
+        pub category_id: String,
+        pub category_color: String,
+        pub minutes: u32,
+        pub sessions: usize,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct CategoryTimeReportRes {
+        pub status: &'static str,
+        pub from: NaiveDate,
+        pub to: NaiveDate,
+        pub total_minutes: u32,
+        pub results: usize,
+        pub categories: Vec<CategoryTimeItem>,
+    }
+}
@@ -0,0 +1,38 @@
+pub mod res {
+    use chrono::{DateTime, Utc};
+    use serde::Serialize;
+
+    use crate::infra::types::TrashKind;
+
+    #[allow(non_snake_case)]
+    #[derive(Serialize, Debug)]
+    pub struct TrashItemRes {
+        pub id: String,
+        pub kind: TrashKind,
+        pub title: String,
+        pub deletedAt: DateTime<Utc>,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct TrashItemData {
+        pub item: TrashItemRes,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct SingleTrashItemRes {
+        pub status: &'static str,
+        pub data: TrashItemData,
+    }
+
+    #[derive(Serialize, Debug)]
+    pub struct TrashListRes {
+        pub status: &'static str,
+        pub results: usize,
+        pub items: Vec<TrashItemRes>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub next_cursor: Option<String>,
+        pub has_more: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total: Option<u64>,
+    }
+}
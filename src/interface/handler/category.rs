@@ -17,8 +17,11 @@ use crate::{
     },
     infra::types::FilterOptions,
     interface::dto::{
-        tag_group::req::{CreateCategoryReq, UpdateCategoryReq},
-        sub::property::req::{CreatePropertyReq, UpdatePropertyReq},
+        category::req::{CreateCategoryReq, UpdateCategoryReq},
+        sub::{
+            array::req::{MoveElemReq, TransferElemReq},
+            property::req::{CreatePropertyReq, UpdatePropertyReq},
+        },
     },
     AppState,
 };
@@ -47,6 +50,14 @@ pub fn category_router(app_state: Arc<AppState>) -> Router {
                 .delete(remove_property_handler)
                 .put(update_property_handler),
         )
+        .route(
+            "/api/categories/:category_id/properties/:property_id/move",
+            post(move_property_handler),
+        )
+        .route(
+            "/api/categories/:category_id/properties/:property_id/transfer",
+            post(transfer_property_handler),
+        )
         .with_state(app_state)
 }
 
@@ -57,12 +68,14 @@ pub async fn category_list_handler(
 ) -> Result<impl IntoResponse> {
     let Query(opts) = opts.unwrap_or_default();
 
-    let limit = opts.limit.unwrap_or(10) as i64;
-    let page = opts.page.unwrap_or(1) as i64;
-
-    match CategoryService::fetch_categories(&app_state.mongodb.db, limit, page, &jwtauth.user.id)
-        .await
-        .map_err(Error::from)
+    match CategoryService::fetch_categories(
+        &app_state.mongodb.db,
+        opts.page_opts(10),
+        opts.query_opts(),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
@@ -109,12 +122,13 @@ pub async fn update_category_handler(
     {
         Ok(res) => {
             // Update related notes
-            if let (Some(name), Some(color)) = (&body.name, &body.color) {
+            if body.name.is_some() || body.color.is_some() {
+                let category = &res.data.category;
                 NoteService::update_notes_for_category_change(
                     &app_state.mongodb.db,
                     &id,
-                    name,
-                    color,
+                    &category.name,
+                    &category.color,
                     &jwtauth.user.id,
                 )
                 .await
@@ -130,8 +144,9 @@ pub async fn update_category_handler(
 pub async fn delete_category_handler(
     Path(id): Path<String>,
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
 ) -> Result<impl IntoResponse> {
-    match CategoryService::delete_category(&app_state.mongodb.db, &id, None)
+    match CategoryService::delete_category(&app_state.mongodb.db, &id, None, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -143,11 +158,17 @@ pub async fn delete_category_handler(
 // Property Handlers
 pub async fn get_property_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((category_id, property_id)): Path<(String, String)>,
 ) -> Result<impl IntoResponse> {
-    match PropertyService::get_property(&app_state.mongodb.db, &category_id, &property_id)
-        .await
-        .map_err(Error::from)
+    match PropertyService::get_property(
+        &app_state.mongodb.db,
+        &category_id,
+        &property_id,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
@@ -156,12 +177,18 @@ pub async fn get_property_handler(
 
 pub async fn add_property_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((category_id,)): Path<(String,)>,
     Json(new_property): Json<CreatePropertyReq>,
 ) -> Result<impl IntoResponse> {
-    match PropertyService::add_property(&app_state.mongodb.db, &category_id, &new_property)
-        .await
-        .map_err(Error::from)
+    match PropertyService::add_property(
+        &app_state.mongodb.db,
+        &category_id,
+        &new_property,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
@@ -170,11 +197,17 @@ pub async fn add_property_handler(
 
 pub async fn remove_property_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((category_id, property_id)): Path<(String, String)>,
 ) -> Result<impl IntoResponse> {
-    match PropertyService::remove_property(&app_state.mongodb.db, &category_id, &property_id)
-        .await
-        .map_err(Error::from)
+    match PropertyService::remove_property(
+        &app_state.mongodb.db,
+        &category_id,
+        &property_id,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e),
@@ -192,19 +225,20 @@ pub async fn update_property_handler(
         &category_id,
         &prop_id,
         &update_req,
+        &jwtauth.user.id,
     )
     .await
     .map_err(Error::from)
     {
         Ok(res) => {
             // Update related notes
-            if let (Some(name), Some(prop_type)) = (&update_req.name, &update_req.prop_type) {
+            if update_req.name.is_some() || update_req.prop_type.is_some() {
                 NoteService::update_notes_for_property_change(
                     &app_state.mongodb.db,
                     &category_id,
                     &prop_id,
-                    name,
-                    prop_type,
+                    update_req.name.as_deref(),
+                    update_req.prop_type.as_ref(),
                     &jwtauth.user.id,
                 )
                 .await
@@ -219,9 +253,10 @@ pub async fn update_property_handler(
 
 pub async fn fetch_properties_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((category_id,)): Path<(String,)>,
 ) -> Result<impl IntoResponse> {
-    match PropertyService::fetch_properties(&app_state.mongodb.db, &category_id)
+    match PropertyService::fetch_properties(&app_state.mongodb.db, &category_id, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -229,3 +264,309 @@ pub async fn fetch_properties_handler(
         Err(e) => Err(e),
     }
 }
+
+pub async fn move_property_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Path((category_id, prop_id)): Path<(String, String)>,
+    Json(body): Json<MoveElemReq>,
+) -> Result<impl IntoResponse> {
+    match PropertyService::move_property(
+        &app_state.mongodb.db,
+        &category_id,
+        &prop_id,
+        body.index,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn transfer_property_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Path((category_id, prop_id)): Path<(String, String)>,
+    Json(body): Json<TransferElemReq>,
+) -> Result<impl IntoResponse> {
+    match PropertyService::transfer_property(
+        &app_state.mongodb.db,
+        &category_id,
+        &body.dst_id,
+        &prop_id,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Method;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::interface::handler::{
+        note::note_router,
+        test_utils::{app_as, send, OTHER_USER, USER},
+    };
+
+    fn app() -> Router {
+        crate::interface::handler::test_utils::app(&[category_router, note_router])
+    }
+
+    async fn create_category(app: &Router, name: &str) -> String {
+        let body = json!({ "name": name, "color": "#71717a" });
+        let (status, body) = send(app, Method::POST, "/api/categories/", Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"]["category"]["id"].as_str().unwrap().to_string()
+    }
+
+    async fn add_property(app: &Router, category_id: &str, name: &str) -> String {
+        let uri = format!("/api/categories/{}/properties/", category_id);
+        let body = json!({ "name": name, "prop_type": "Text" });
+        let (status, body) = send(app, Method::POST, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"]["prop"]["id"].as_str().unwrap().to_string()
+    }
+
+    fn prop_names(category: &Value) -> Vec<&str> {
+        category["props"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|prop| prop["name"].as_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_create_category() {
+        let app = app();
+
+        let body = json!({ "name": "Work", "color": "#71717a" });
+        let (status, body) = send(&app, Method::POST, "/api/categories/", Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["category"]["name"], "Work");
+        assert_eq!(body["data"]["category"]["status"], "InProgress");
+        // 기본 Tags property가 있음
+        assert_eq!(prop_names(&body["data"]["category"]), vec!["Tags"]);
+    }
+
+    #[tokio::test]
+    async fn test_category_list() {
+        let app = app();
+        create_category(&app, "Work").await;
+        create_category(&app, "Personal").await;
+
+        let uri = "/api/categories?filter=name=Work";
+        let (status, body) = send(&app, Method::GET, uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["results"], 1);
+        assert_eq!(body["categories"][0]["name"], "Work");
+    }
+
+    #[tokio::test]
+    async fn test_get_category() {
+        let app = app();
+        let id = create_category(&app, "Work").await;
+
+        let uri = format!("/api/categories/{}", id);
+        let (status, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["category"]["id"], id.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_update_category_renames_notes() {
+        let app = app();
+        let id = create_category(&app, "Work").await;
+        let note = json!({
+            "title": "meeting",
+            "category_id": id,
+            "category_color": "#71717a",
+            "category_name": "Work",
+        });
+        let (status, body) = send(&app, Method::POST, "/api/notes/", Some(note)).await;
+        assert_eq!(status, StatusCode::OK);
+        let note_id = body["data"]["note"]["id"].as_str().unwrap().to_string();
+
+        let uri = format!("/api/categories/{}", id);
+        let body = json!({ "name": "Job" });
+        let (status, body) = send(&app, Method::PATCH, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["category"]["name"], "Job");
+
+        let uri = format!("/api/notes/{}", note_id);
+        let (_, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(body["data"]["note"]["category_name"], "Job");
+        assert_eq!(body["data"]["note"]["category_color"], "#71717a");
+    }
+
+    #[tokio::test]
+    async fn test_delete_category() {
+        let app = app();
+        let id = create_category(&app, "Work").await;
+
+        let uri = format!("/api/categories/{}", id);
+        let (status, _) = send(&app, Method::DELETE, &uri, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (status, _) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_add_property() {
+        let app = app();
+        let id = create_category(&app, "Work").await;
+
+        let uri = format!("/api/categories/{}/properties/", id);
+        let body = json!({ "name": "Deadline", "prop_type": "DateTime" });
+        let (status, body) = send(&app, Method::POST, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["prop"]["name"], "Deadline");
+        assert_eq!(body["data"]["prop"]["prop_type"], "DateTime");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_properties() {
+        let app = app();
+        let id = create_category(&app, "Work").await;
+        add_property(&app, &id, "Deadline").await;
+
+        let uri = format!("/api/categories/{}/properties", id);
+        let (status, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["results"], 2);
+        // 최신 원소가 앞
+        assert_eq!(body["props"][0]["name"], "Deadline");
+    }
+
+    #[tokio::test]
+    async fn test_get_property() {
+        let app = app();
+        let id = create_category(&app, "Work").await;
+        let prop_id = add_property(&app, &id, "Deadline").await;
+
+        let uri = format!("/api/categories/{}/properties/{}", id, prop_id);
+        let (status, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["prop"]["id"], prop_id.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_update_property_renames_note_values() {
+        let app = app();
+        let id = create_category(&app, "Work").await;
+        let prop_id = add_property(&app, &id, "Deadline").await;
+        let note = json!({
+            "title": "meeting",
+            "category_id": id,
+            "category_color": "#71717a",
+            "category_name": "Work",
+        });
+        let (_, body) = send(&app, Method::POST, "/api/notes/", Some(note)).await;
+        let note_id = body["data"]["note"]["id"].as_str().unwrap().to_string();
+
+        let uri = format!("/api/categories/{}/properties/{}", id, prop_id);
+        let body = json!({ "name": "Due" });
+        let (status, body) = send(&app, Method::PUT, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["prop"]["name"], "Due");
+
+        let uri = format!("/api/notes/{}/prop_values/{}", note_id, prop_id);
+        let (status, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["propV"]["prop_name"], "Due");
+    }
+
+    #[tokio::test]
+    async fn test_remove_property() {
+        let app = app();
+        let id = create_category(&app, "Work").await;
+        let prop_id = add_property(&app, &id, "Deadline").await;
+
+        let uri = format!("/api/categories/{}/properties/{}", id, prop_id);
+        let (status, _) = send(&app, Method::DELETE, &uri, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (status, _) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_move_property() {
+        let app = app();
+        let id = create_category(&app, "Work").await;
+        let prop_id = add_property(&app, &id, "Deadline").await;
+
+        let uri = format!("/api/categories/{}/properties/{}/move", id, prop_id);
+        let (status, _) = send(&app, Method::POST, &uri, Some(json!({ "index": 1 }))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let uri = format!("/api/categories/{}", id);
+        let (_, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(
+            prop_names(&body["data"]["category"]),
+            vec!["Tags", "Deadline"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transfer_property() {
+        let app = app();
+        let id = create_category(&app, "Work").await;
+        let dst_id = create_category(&app, "Personal").await;
+        let prop_id = add_property(&app, &id, "Deadline").await;
+
+        let uri = format!("/api/categories/{}/properties/{}/transfer", id, prop_id);
+        let body = json!({ "dst_id": dst_id });
+        let (status, _) = send(&app, Method::POST, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let uri = format!("/api/categories/{}", id);
+        let (_, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(prop_names(&body["data"]["category"]), vec!["Tags"]);
+        let uri = format!("/api/categories/{}", dst_id);
+        let (_, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(
+            prop_names(&body["data"]["category"]),
+            vec!["Deadline", "Tags"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_category_of_other_user() {
+        let app_state = Arc::new(AppState::in_memory());
+        let app = app_as(&app_state, USER, &[category_router]);
+        let other = app_as(&app_state, OTHER_USER, &[category_router]);
+        let id = create_category(&app, "Work").await;
+        let prop_id = add_property(&app, &id, "Deadline").await;
+
+        let uri = format!("/api/categories/{}", id);
+        let (status, _) = send(&other, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let body = json!({ "name": "Mine" });
+        let (status, _) = send(&other, Method::PATCH, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&other, Method::DELETE, &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let uri = format!("/api/categories/{}/properties/{}", id, prop_id);
+        let (status, _) = send(&other, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&other, Method::DELETE, &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["prop"]["name"], "Deadline");
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::post,
+    Extension, Json, Router,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    auth::utils::auth::JWTAuthMiddleware,
+    domain::{
+        error::{Error, Result},
+        repo::ElemInfo,
+        sub::daily_item::{
+            DailyEventModel, DailyHabitModel, DailyItemService, DailyTaskModel, TimerResultModel,
+        },
+    },
+    interface::dto::sub::array::req::{MoveElemReq, TransferElemReq},
+    AppState,
+};
+
+pub fn daily_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route(
+            "/api/daily/:daily_id/tasks/:elem_id/move",
+            post(move_daily_item_handler::<DailyTaskModel>),
+        )
+        .route(
+            "/api/daily/:daily_id/tasks/:elem_id/transfer",
+            post(transfer_daily_item_handler::<DailyTaskModel>),
+        )
+        .route(
+            "/api/daily/:daily_id/events/:elem_id/move",
+            post(move_daily_item_handler::<DailyEventModel>),
+        )
+        .route(
+            "/api/daily/:daily_id/events/:elem_id/transfer",
+            post(transfer_daily_item_handler::<DailyEventModel>),
+        )
+        .route(
+            "/api/daily/:daily_id/habits/:elem_id/move",
+            post(move_daily_item_handler::<DailyHabitModel>),
+        )
+        .route(
+            "/api/daily/:daily_id/habits/:elem_id/transfer",
+            post(transfer_daily_item_handler::<DailyHabitModel>),
+        )
+        .route(
+            "/api/daily/:daily_id/timer_results/:elem_id/move",
+            post(move_daily_item_handler::<TimerResultModel>),
+        )
+        .route(
+            "/api/daily/:daily_id/timer_results/:elem_id/transfer",
+            post(transfer_daily_item_handler::<TimerResultModel>),
+        )
+        .with_state(app_state)
+}
+
+pub async fn move_daily_item_handler<Elem>(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Path((daily_id, elem_id)): Path<(String, String)>,
+    Json(body): Json<MoveElemReq>,
+) -> Result<impl IntoResponse>
+where
+    Elem: DeserializeOwned + Serialize + Unpin + Send + Sync + ElemInfo,
+{
+    match DailyItemService::<Elem>::move_elem(
+        &app_state.mongodb.db,
+        &daily_id,
+        &elem_id,
+        body.index,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn transfer_daily_item_handler<Elem>(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Path((daily_id, elem_id)): Path<(String, String)>,
+    Json(body): Json<TransferElemReq>,
+) -> Result<impl IntoResponse>
+where
+    Elem: DeserializeOwned + Serialize + Unpin + Send + Sync + ElemInfo,
+{
+    match DailyItemService::<Elem>::transfer_elem(
+        &app_state.mongodb.db,
+        &daily_id,
+        &body.dst_id,
+        &elem_id,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
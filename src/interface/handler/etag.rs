@@ -0,0 +1,44 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderName},
+};
+
+use crate::domain::error::Error;
+
+/// 문서의 version을 ETag 헤더로 변환.
+pub fn etag(version: i64) -> [(HeaderName, String); 1] {
+    [(header::ETAG, format!("\"{}\"", version))]
+}
+
+/// If-Match 헤더의 version.
+/// 헤더가 없거나 `*`이면 None이며, version 확인 없이 업데이트함.
+pub struct IfMatch(pub Option<i64>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for IfMatch
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(header::IF_MATCH) else {
+            return Ok(IfMatch(None));
+        };
+
+        let value = value.to_str().unwrap_or_default().trim();
+        if value == "*" {
+            return Ok(IfMatch(None));
+        }
+
+        // "3" 혹은 W/"3"
+        let version = value
+            .trim_start_matches("W/")
+            .trim_matches('"')
+            .parse::<i64>()
+            .map_err(|_| Error::PreconditionFailed(value.to_string()))?;
+
+        Ok(IfMatch(Some(version)))
+    }
+}
@@ -15,8 +15,12 @@ use crate::{
     domain::{
         error::{Error, Result},
         habit::HabitService,
+        item_tag::ItemTagService,
     },
+    infra::types::{FieldsOptions, FilterOptions, ItemType, PageOptions, QueryOptions},
+    interface::dto::batch::req::{BatchDeleteReq, BatchUpdateReq},
     interface::dto::habit::req::{CreateHabitReq, HabitFilterOptions, UpdateHabitReq},
+    interface::handler::etag::{etag, IfMatch},
     AppState,
 };
 
@@ -24,12 +28,23 @@ pub fn habit_router(app_state: Arc<AppState>) -> Router {
     Router::new()
         .route("/api/habits/", post(create_habit_handler))
         .route("/api/habits", get(habit_list_handler))
+        .route(
+            "/api/habits/batch",
+            post(batch_create_habit_handler)
+                .patch(batch_update_habit_handler)
+                .delete(batch_delete_habit_handler),
+        )
         .route(
             "/api/habits/:id",
             get(get_habit_handler)
                 .patch(update_habit_handler)
                 .delete(delete_habit_handler),
         )
+        .route("/api/habits/:id/history", get(habit_history_handler))
+        .route(
+            "/api/habits/:id/history/:change_id/revert",
+            post(revert_habit_handler),
+        )
         // .route("/api/habits/:id/records/", post(add_habit_record_handler))
         // .route("/api/habits/:id/records", get(fetch_habit_records_handler))
         // .route(
@@ -52,8 +67,23 @@ pub async fn habit_list_handler(
 ) -> Result<impl IntoResponse> {
     let Query(opts) = opts.unwrap_or_default();
 
-    let limit = opts.limit.unwrap_or(25) as i64;
-    let page = opts.page.unwrap_or(1) as i64;
+    let page_opts = PageOptions {
+        limit: opts.limit.unwrap_or(25) as i64,
+        page: opts.page.unwrap_or(1) as i64,
+        cursor: opts.cursor,
+        with_total: opts.with_total.unwrap_or(false),
+    };
+    let mut query_opts = QueryOptions {
+        filter: opts.filter,
+        sort: opts.sort,
+        fields: opts.fields,
+        tag: opts.tag,
+        tag_group: opts.tag_group,
+        ids: None,
+    };
+
+    ItemTagService::resolve_filter(&app_state.db, &jwtauth.user.id, ItemType::Habit, &mut query_opts)
+        .await?;
 
     // 날짜 입력 없으면 모든 habits 가져옴.
     let start_month = opts.start_month.map(|d| d.to_string()).unwrap_or_default();
@@ -61,8 +91,8 @@ pub async fn habit_list_handler(
 
     match HabitService::fetch_habits(
         &app_state.mongodb.db,
-        limit,
-        page,
+        page_opts,
+        query_opts,
         &start_month,
         &end_month,
         &jwtauth.user.id,
@@ -83,18 +113,46 @@ pub async fn create_habit_handler(
     match HabitService::create_habit(&app_state.mongodb.db, &body, &jwtauth.user.id)
         .await
         .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.habit.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn batch_create_habit_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<Vec<CreateHabitReq>>,
+) -> Result<impl IntoResponse> {
+    match HabitService::bulk_create_habits(&app_state.mongodb.db, &body, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
     }
 }
 
-pub async fn get_habit_handler(
-    Path(id): Path<String>,
+pub async fn batch_update_habit_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<Vec<BatchUpdateReq<UpdateHabitReq>>>,
+) -> Result<impl IntoResponse> {
+    match HabitService::bulk_update_habits(&app_state.mongodb.db, &body, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn batch_delete_habit_handler(
     State(app_state): State<Arc<AppState>>,
     Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<BatchDeleteReq>,
 ) -> Result<impl IntoResponse> {
-    match HabitService::get_habit(&app_state.mongodb.db, &id, &jwtauth.user.id)
+    match HabitService::bulk_delete_habits(&app_state.mongodb.db, &body.ids, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -103,26 +161,86 @@ pub async fn get_habit_handler(
     }
 }
 
+pub async fn get_habit_handler(
+    Path(id): Path<String>,
+    opts: Option<Query<FieldsOptions>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match HabitService::get_habit(
+        &app_state.mongodb.db,
+        &id,
+        opts.fields.as_deref(),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.habit.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
 pub async fn update_habit_handler(
     Path(id): Path<String>,
     State(app_state): State<Arc<AppState>>,
     Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    IfMatch(if_match): IfMatch,
     Json(body): Json<UpdateHabitReq>,
 ) -> Result<impl IntoResponse> {
-    match HabitService::update_habit(&app_state.mongodb.db, &id, &body, &jwtauth.user.id)
+    match HabitService::update_habit(&app_state.mongodb.db, &id, &body, if_match, &jwtauth.user.id)
         .await
         .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.habit.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn habit_history_handler(
+    Path(id): Path<String>,
+    opts: Option<Query<FilterOptions>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match HabitService::fetch_habit_history(
+        &app_state.mongodb.db,
+        &id,
+        opts.page_opts(20),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
     }
 }
 
+pub async fn revert_habit_handler(
+    Path((id, change_id)): Path<(String, String)>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match HabitService::revert_habit(&app_state.mongodb.db, &id, &change_id, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.habit.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
 pub async fn delete_habit_handler(
     Path(id): Path<String>,
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
 ) -> Result<impl IntoResponse> {
-    match HabitService::delete_habit(&app_state.mongodb.db, &id)
+    match HabitService::delete_habit(&app_state.mongodb.db, &id, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get},
+    Extension, Json, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::utils::auth::JWTAuthMiddleware,
+    domain::{
+        error::{Error, Result},
+        item_tag::ItemTagService,
+    },
+    infra::types::ItemType,
+    interface::dto::item_tag::req::TagItemReq,
+    AppState,
+};
+
+pub fn item_tag_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route(
+            "/api/items/:item_type/:item_id/tags",
+            get(item_tag_list_handler).post(tag_item_handler),
+        )
+        .route(
+            "/api/items/:item_type/:item_id/tags/:tag_id",
+            delete(untag_item_handler),
+        )
+        .with_state(app_state)
+}
+
+pub async fn item_tag_list_handler(
+    Path((item_type, item_id)): Path<(ItemType, String)>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match ItemTagService::fetch_item_tags(&app_state.db, &jwtauth.user.id, item_type, &item_id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn tag_item_handler(
+    Path((item_type, item_id)): Path<(ItemType, String)>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<TagItemReq>,
+) -> Result<impl IntoResponse> {
+    match ItemTagService::tag_item(
+        &app_state.db,
+        &app_state.mongodb.db,
+        &jwtauth.user.id,
+        item_type,
+        &item_id,
+        body.tag_id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn untag_item_handler(
+    Path((item_type, item_id, tag_id)): Path<(ItemType, String, Uuid)>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match ItemTagService::untag_item(&app_state.db, &jwtauth.user.id, item_type, &item_id, tag_id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(e),
+    }
+}
@@ -1,8 +1,10 @@
 use std::sync::Arc;
 
-use crate::domain::memo::MemoService;
-use crate::infra::types::FilterOptions;
+use crate::domain::{item_tag::ItemTagService, memo::MemoService};
+use crate::infra::types::{FieldsOptions, FilterOptions, ItemType};
+use crate::interface::dto::batch::req::{BatchDeleteReq, BatchUpdateReq};
 use crate::interface::dto::memo::req::{CreateMemoReq, UpdateMemoReq};
+use crate::interface::handler::etag::{etag, IfMatch};
 use crate::{
     auth::utils::auth::JWTAuthMiddleware,
     domain::error::{Error, Result},
@@ -20,12 +22,23 @@ pub fn memo_router(app_state: Arc<AppState>) -> Router {
     Router::new()
         .route("/api/memos/", post(create_memo_handler))
         .route("/api/memos", get(memo_list_handler))
+        .route(
+            "/api/memos/batch",
+            post(batch_create_memo_handler)
+                .patch(batch_update_memo_handler)
+                .delete(batch_delete_memo_handler),
+        )
         .route(
             "/api/memos/:id",
             get(get_memo_handler)
                 .patch(update_memo_handler)
                 .delete(delete_memo_handler),
         )
+        .route("/api/memos/:id/history", get(memo_history_handler))
+        .route(
+            "/api/memos/:id/history/:change_id/revert",
+            post(revert_memo_handler),
+        )
         .with_state(app_state)
 }
 
@@ -36,14 +49,31 @@ pub async fn memo_list_handler(
 ) -> Result<impl IntoResponse> {
     let Query(opts) = opts.unwrap_or_default();
 
-    let limit = opts.limit.unwrap_or(10) as i64;
-    let page = opts.page.unwrap_or(1) as i64;
+    let mut query_opts = opts.query_opts();
+    ItemTagService::resolve_filter(&app_state.db, &jwtauth.user.id, ItemType::Memo, &mut query_opts)
+        .await?;
 
-    match MemoService::fetch_memos(&app_state.mongodb.db, limit, page, &jwtauth.user.id)
-        .await
-        .map_err(Error::from)
+    match MemoService::fetch_memos(
+        &app_state.mongodb.db,
+        opts.page_opts(10),
+        query_opts,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
-        Ok(res) => Ok(Json(res)),
+        Ok(mut res) => {
+            let memos = res.memos.iter_mut().map(|memo| &mut **memo).collect();
+            ItemTagService::attach_tags(
+                &app_state.db,
+                &jwtauth.user.id,
+                ItemType::Memo,
+                opts.fields.as_deref(),
+                memos,
+            )
+            .await;
+            Ok(Json(res))
+        }
         Err(e) => Err(e),
     }
 }
@@ -56,18 +86,46 @@ pub async fn create_memo_handler(
     match MemoService::create_memo(&app_state.mongodb.db, &body, &jwtauth.user.id)
         .await
         .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.memo.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn batch_create_memo_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<Vec<CreateMemoReq>>,
+) -> Result<impl IntoResponse> {
+    match MemoService::bulk_create_memos(&app_state.mongodb.db, &body, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
     }
 }
 
-pub async fn get_memo_handler(
-    Path(id): Path<String>,
+pub async fn batch_update_memo_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<Vec<BatchUpdateReq<UpdateMemoReq>>>,
+) -> Result<impl IntoResponse> {
+    match MemoService::bulk_update_memos(&app_state.mongodb.db, &body, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn batch_delete_memo_handler(
     State(app_state): State<Arc<AppState>>,
     Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<BatchDeleteReq>,
 ) -> Result<impl IntoResponse> {
-    match MemoService::get_memo(&app_state.mongodb.db, &id, &jwtauth.user.id)
+    match MemoService::bulk_delete_memos(&app_state.mongodb.db, &body.ids, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -76,26 +134,97 @@ pub async fn get_memo_handler(
     }
 }
 
+pub async fn get_memo_handler(
+    Path(id): Path<String>,
+    opts: Option<Query<FieldsOptions>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match MemoService::get_memo(
+        &app_state.mongodb.db,
+        &id,
+        opts.fields.as_deref(),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(mut res) => {
+            let memo = vec![&mut *res.data.memo];
+            ItemTagService::attach_tags(
+                &app_state.db,
+                &jwtauth.user.id,
+                ItemType::Memo,
+                opts.fields.as_deref(),
+                memo,
+            )
+            .await;
+            Ok((etag(res.data.memo.version), Json(res)))
+        }
+        Err(e) => Err(e),
+    }
+}
+
 pub async fn update_memo_handler(
     Path(id): Path<String>,
     State(app_state): State<Arc<AppState>>,
     Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    IfMatch(if_match): IfMatch,
     Json(body): Json<UpdateMemoReq>,
 ) -> Result<impl IntoResponse> {
-    match MemoService::update_memo(&app_state.mongodb.db, &id, &body, &jwtauth.user.id)
+    match MemoService::update_memo(&app_state.mongodb.db, &id, &body, if_match, &jwtauth.user.id)
         .await
         .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.memo.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn memo_history_handler(
+    Path(id): Path<String>,
+    opts: Option<Query<FilterOptions>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match MemoService::fetch_memo_history(
+        &app_state.mongodb.db,
+        &id,
+        opts.page_opts(20),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
     }
 }
 
+pub async fn revert_memo_handler(
+    Path((id, change_id)): Path<(String, String)>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match MemoService::revert_memo(&app_state.mongodb.db, &id, &change_id, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.memo.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
 pub async fn delete_memo_handler(
     Path(id): Path<String>,
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
 ) -> Result<impl IntoResponse> {
-    match MemoService::delete_memo(&app_state.mongodb.db, &id)
+    match MemoService::delete_memo(&app_state.mongodb.db, &id, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -103,3 +232,53 @@ pub async fn delete_memo_handler(
         Err(e) => Err(e),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Method;
+    use serde_json::json;
+
+    use super::*;
+    use crate::interface::handler::test_utils::send;
+
+    fn app() -> Router {
+        crate::interface::handler::test_utils::app(&[memo_router])
+    }
+
+    #[tokio::test]
+    async fn test_memo_crud() {
+        let app = app();
+
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/api/memos/",
+            Some(json!({ "title": "first", "color": "#71717a" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let id = body["data"]["memo"]["id"].as_str().unwrap().to_string();
+
+        let uri = format!("/api/memos/{}", id);
+        let (status, body) = send(
+            &app,
+            Method::PATCH,
+            &uri,
+            Some(json!({ "title": "renamed" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["memo"]["title"], "renamed");
+        assert_eq!(body["data"]["memo"]["version"], 2);
+
+        let (status, body) = send(&app, Method::GET, "/api/memos?limit=10", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["results"], 1);
+
+        let (status, _) = send(&app, Method::DELETE, &uri, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (status, _) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -1,9 +1,19 @@
+pub mod category;
 pub mod daily;
+pub mod etag;
 pub mod task;
+pub mod task_template;
+pub mod timer;
 pub mod habit;
+pub mod item_tag;
 pub mod memo;
 pub mod schedule;
-// pub mod note;
+pub mod settings;
+pub mod sync;
+pub mod note;
 pub mod tag;
 pub mod tag_group;
+#[cfg(test)]
+pub(crate) mod test_utils;
+pub mod trash;
 
@@ -17,12 +17,14 @@ use crate::{
     infra::types::FilterOptions,
     interface::dto::{
         sub::{
+            array::req::{MoveElemReq, TransferElemReq},
             chat::req::{CreateMsgReq, UpdateMsgReq},
             note_block::req::{CreateBlockReq, UpdateBlockReq},
             note_propV::req::{CreatePropValueReq, UpdatePropValueReq},
         },
         note::req::{CreateNoteReq, UpdateNoteReq},
     },
+    interface::handler::etag::{etag, IfMatch},
     AppState,
 };
 
@@ -36,13 +38,15 @@ pub fn note_router(app_state: Arc<AppState>) -> Router {
                 .patch(update_note_handler)
                 .delete(delete_note_handler),
         )
+        .route("/api/notes/:id/pages/", post(add_page_handler))
+        .route("/api/notes/:id/subnotes", get(subnotes_handler))
         .route("/api/notes/:note_id/chat/", post(add_note_msg_handler))
         .route("/api/notes/:note_id/chat", get(fetch_note_msgs_handler))
         .route(
             "/api/notes/:note_id/chat/:msg_id",
             get(get_note_msg_handler)
                 .delete(remove_note_msg_handler)
-                .put(update_note_msg_handler),
+                .patch(update_note_msg_handler),
         )
         .route("/api/notes/:note_id/blocks/", post(add_block_handler))
         .route("/api/notes/:note_id/blocks", get(fetch_blocks_handler))
@@ -52,19 +56,27 @@ pub fn note_router(app_state: Arc<AppState>) -> Router {
                 .delete(remove_block_handler)
                 .put(update_block_handler),
         )
+        .route(
+            "/api/notes/:note_id/blocks/:block_id/move",
+            post(move_block_handler),
+        )
+        .route(
+            "/api/notes/:note_id/blocks/:block_id/transfer",
+            post(transfer_block_handler),
+        )
         .route(
             "/api/notes/:note_id/prop_values/",
-            post(add_note_propV_handler),
+            post(add_note_prop_value_handler),
         )
         .route(
             "/api/notes/:note_id/prop_values",
-            get(fetch_note_propVs_handler),
+            get(fetch_note_prop_values_handler),
         )
         .route(
             "/api/notes/:note_id/prop_values/:prop_id",
-            get(get_note_propV_handler)
-                .delete(remove_note_propV_handler)
-                .put(update_note_propV_handler),
+            get(get_note_prop_value_handler)
+                .delete(remove_note_prop_value_handler)
+                .put(update_note_prop_value_handler),
         )
         .with_state(app_state)
 }
@@ -77,12 +89,14 @@ pub async fn note_list_handler(
 ) -> Result<impl IntoResponse> {
     let Query(opts) = opts.unwrap_or_default();
 
-    let limit = opts.limit.unwrap_or(10) as i64;
-    let page = opts.page.unwrap_or(1) as i64;
-
-    match NoteService::fetch_notes(&app_state.mongodb.db, limit, page, &jwtauth.user.id)
-        .await
-        .map_err(Error::from)
+    match NoteService::fetch_notes(
+        &app_state.mongodb.db,
+        opts.page_opts(10),
+        opts.query_opts(),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
@@ -135,8 +149,9 @@ pub async fn update_note_handler(
 pub async fn delete_note_handler(
     Path(id): Path<String>,
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
 ) -> Result<impl IntoResponse> {
-    match NoteService::delete_note(&app_state.mongodb.db, &id)
+    match NoteService::delete_note(&app_state.mongodb.db, &id, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -145,50 +160,70 @@ pub async fn delete_note_handler(
     }
 }
 
-// Chat Handlers for Task
+// Chat Handlers for Note
 pub async fn get_note_msg_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id, msg_id)): Path<(String, String)>,
 ) -> Result<impl IntoResponse> {
-    match ChatMsgService::<NoteModel>::get_msg(&app_state.mongodb.db, &note_id, &msg_id)
-        .await
-        .map_err(Error::from)
+    match ChatMsgService::<NoteModel>::get_msg(
+        &app_state.mongodb.db,
+        &note_id,
+        &msg_id,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
-        Ok(res) => Ok(Json(res)),
+        Ok(res) => Ok((etag(res.data.msg.version), Json(res))),
         Err(e) => Err(e),
     }
 }
 
 pub async fn add_note_msg_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id,)): Path<(String,)>,
     Json(new_msg): Json<CreateMsgReq>,
 ) -> Result<impl IntoResponse> {
-    match ChatMsgService::<NoteModel>::add_msg(&app_state.mongodb.db, &note_id, &new_msg)
-        .await
-        .map_err(Error::from)
+    match ChatMsgService::<NoteModel>::add_msg(
+        &app_state.mongodb.db,
+        &note_id,
+        &new_msg,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
-        Ok(res) => Ok(Json(res)),
+        Ok(res) => Ok((etag(res.data.msg.version), Json(res))),
         Err(e) => Err(e),
     }
 }
 
 pub async fn remove_note_msg_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id, msg_id)): Path<(String, String)>,
 ) -> Result<impl IntoResponse> {
-    match ChatMsgService::<NoteModel>::remove_msg(&app_state.mongodb.db, &note_id, &msg_id)
-        .await
-        .map_err(Error::from)
+    match ChatMsgService::<NoteModel>::remove_msg(
+        &app_state.mongodb.db,
+        &note_id,
+        &msg_id,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
-        Ok(res) => Ok(Json(res)),
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e),
     }
 }
 
 pub async fn update_note_msg_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id, msg_id)): Path<(String, String)>,
+    IfMatch(if_match): IfMatch,
     Json(update_req): Json<UpdateMsgReq>,
 ) -> Result<impl IntoResponse> {
     match ChatMsgService::<NoteModel>::update_msg(
@@ -196,11 +231,13 @@ pub async fn update_note_msg_handler(
         &note_id,
         &msg_id,
         &update_req,
+        if_match,
+        &jwtauth.user.id,
     )
     .await
     .map_err(Error::from)
     {
-        Ok(res) => Ok(Json(res)),
+        Ok(res) => Ok((etag(res.data.msg.version), Json(res))),
         Err(e) => Err(e),
     }
 }
@@ -208,14 +245,46 @@ pub async fn update_note_msg_handler(
 pub async fn fetch_note_msgs_handler(
     opts: Option<Query<FilterOptions>>,
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id,)): Path<(String,)>,
 ) -> Result<impl IntoResponse> {
     let Query(opts) = opts.unwrap_or_default();
 
-    let limit = opts.limit.unwrap_or(10) as i64;
-    let page = opts.page.unwrap_or(1) as i64;
+    match ChatMsgService::<NoteModel>::fetch_msgs(
+        &app_state.mongodb.db,
+        &note_id,
+        opts.page_opts(10),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
 
-    match ChatMsgService::<NoteModel>::fetch_msgs(&app_state.mongodb.db, &note_id, limit, page)
+// Subnote Handlers
+pub async fn add_page_handler(
+    Path(id): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match NoteService::add_page(&app_state.mongodb.db, &id, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn subnotes_handler(
+    Path(id): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match NoteService::get_subnotes(&app_state.mongodb.db, &id, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -227,9 +296,10 @@ pub async fn fetch_note_msgs_handler(
 // Task Block Handlers
 pub async fn get_block_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id, block_id)): Path<(String, String)>,
 ) -> Result<impl IntoResponse> {
-    match BlockService::get_block(&app_state.mongodb.db, &note_id, &block_id)
+    match BlockService::get_block(&app_state.mongodb.db, &note_id, &block_id, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -240,10 +310,11 @@ pub async fn get_block_handler(
 
 pub async fn add_block_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id,)): Path<(String,)>,
     Json(new_block): Json<CreateBlockReq>,
 ) -> Result<impl IntoResponse> {
-    match BlockService::add_block(&app_state.mongodb.db, &note_id, &new_block)
+    match BlockService::add_block(&app_state.mongodb.db, &note_id, &new_block, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -254,9 +325,10 @@ pub async fn add_block_handler(
 
 pub async fn remove_block_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id, block_id)): Path<(String, String)>,
 ) -> Result<impl IntoResponse> {
-    match BlockService::remove_block(&app_state.mongodb.db, &note_id, &block_id)
+    match BlockService::remove_block(&app_state.mongodb.db, &note_id, &block_id, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -267,12 +339,19 @@ pub async fn remove_block_handler(
 
 pub async fn update_block_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id, block_id)): Path<(String, String)>,
     Json(update_req): Json<UpdateBlockReq>,
 ) -> Result<impl IntoResponse> {
-    match BlockService::update_block(&app_state.mongodb.db, &note_id, &block_id, &update_req)
-        .await
-        .map_err(Error::from)
+    match BlockService::update_block(
+        &app_state.mongodb.db,
+        &note_id,
+        &block_id,
+        &update_req,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
@@ -281,9 +360,10 @@ pub async fn update_block_handler(
 
 pub async fn fetch_blocks_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id,)): Path<(String,)>,
 ) -> Result<impl IntoResponse> {
-    match BlockService::fetch_blocks(&app_state.mongodb.db, &note_id)
+    match BlockService::fetch_blocks(&app_state.mongodb.db, &note_id, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -293,11 +373,12 @@ pub async fn fetch_blocks_handler(
 }
 
 // Task Property Value Handlers
-pub async fn get_note_propV_handler(
+pub async fn get_note_prop_value_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id, prop_id)): Path<(String, String)>,
 ) -> Result<impl IntoResponse> {
-    match PropValueService::get_propV(&app_state.mongodb.db, &note_id, &prop_id)
+    match PropValueService::get_propV(&app_state.mongodb.db, &note_id, &prop_id, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -306,52 +387,72 @@ pub async fn get_note_propV_handler(
     }
 }
 
-pub async fn add_note_propV_handler(
+pub async fn add_note_prop_value_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id,)): Path<(String,)>,
-    Json(new_propV): Json<CreatePropValueReq>,
+    Json(new_prop_value): Json<CreatePropValueReq>,
 ) -> Result<impl IntoResponse> {
-    match PropValueService::add_propV(&app_state.mongodb.db, &note_id, new_propV)
-        .await
-        .map_err(Error::from)
+    match PropValueService::add_propV(
+        &app_state.mongodb.db,
+        &note_id,
+        new_prop_value,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
     }
 }
 
-pub async fn remove_note_propV_handler(
+pub async fn remove_note_prop_value_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id, prop_id)): Path<(String, String)>,
 ) -> Result<impl IntoResponse> {
-    match PropValueService::remove_propV(&app_state.mongodb.db, &note_id, &prop_id)
-        .await
-        .map_err(Error::from)
+    match PropValueService::remove_propV(
+        &app_state.mongodb.db,
+        &note_id,
+        &prop_id,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e),
     }
 }
 
-pub async fn update_note_propV_handler(
+pub async fn update_note_prop_value_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id, prop_id)): Path<(String, String)>,
     Json(update_req): Json<UpdatePropValueReq>,
 ) -> Result<impl IntoResponse> {
-    match PropValueService::update_propV(&app_state.mongodb.db, &note_id, &prop_id, &update_req)
-        .await
-        .map_err(Error::from)
+    match PropValueService::update_propV(
+        &app_state.mongodb.db,
+        &note_id,
+        &prop_id,
+        &update_req,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
     }
 }
 
-pub async fn fetch_note_propVs_handler(
+pub async fn fetch_note_prop_values_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((note_id,)): Path<(String,)>,
 ) -> Result<impl IntoResponse> {
-    match PropValueService::fetch_propVs(&app_state.mongodb.db, &note_id)
+    match PropValueService::fetch_propVs(&app_state.mongodb.db, &note_id, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -359,3 +460,535 @@ pub async fn fetch_note_propVs_handler(
         Err(e) => Err(e),
     }
 }
+
+pub async fn move_block_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Path((note_id, block_id)): Path<(String, String)>,
+    Json(body): Json<MoveElemReq>,
+) -> Result<impl IntoResponse> {
+    match BlockService::move_block(
+        &app_state.mongodb.db,
+        &note_id,
+        &block_id,
+        body.index,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn transfer_block_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Path((note_id, block_id)): Path<(String, String)>,
+    Json(body): Json<TransferElemReq>,
+) -> Result<impl IntoResponse> {
+    match BlockService::transfer_block(
+        &app_state.mongodb.db,
+        &note_id,
+        &body.dst_id,
+        &block_id,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{header, Method, Request};
+    use mongodb::bson::oid::ObjectId;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::interface::handler::{
+        category::category_router,
+        test_utils::{app_as, send, OTHER_USER, USER},
+    };
+
+    fn app() -> Router {
+        crate::interface::handler::test_utils::app(&[category_router, note_router])
+    }
+
+    async fn create_category(app: &Router) -> String {
+        let body = json!({ "name": "Work", "color": "#71717a" });
+        let (status, body) = send(app, Method::POST, "/api/categories/", Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"]["category"]["id"].as_str().unwrap().to_string()
+    }
+
+    fn new_note(title: &str, category_id: &str) -> Value {
+        json!({
+            "title": title,
+            "category_id": category_id,
+            "category_color": "#71717a",
+            "category_name": "Work",
+        })
+    }
+
+    // category와 그 category의 note를 만들고 note를 반환
+    async fn create_note(app: &Router) -> Value {
+        let category_id = create_category(app).await;
+        let body = new_note("meeting", &category_id);
+        let (status, body) = send(app, Method::POST, "/api/notes/", Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"]["note"].clone()
+    }
+
+    async fn add_block(app: &Router, note_id: &str) -> String {
+        let uri = format!("/api/notes/{}/blocks/", note_id);
+        let body = json!({ "src_task_id": ObjectId::new().to_hex(), "block_type": "Editor" });
+        let (status, body) = send(app, Method::POST, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"]["block"]["id"].as_str().unwrap().to_string()
+    }
+
+    async fn add_msg(app: &Router, note_id: &str) -> String {
+        let uri = format!("/api/notes/{}/chat/", note_id);
+        let body = json!({ "msg_type": "Text", "content": "hello", "booked": false });
+        let (status, body) = send(app, Method::POST, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        body["data"]["msg"]["id"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_create_note() {
+        let app = app();
+        let category_id = create_category(&app).await;
+
+        let body = new_note("meeting", &category_id);
+        let (status, body) = send(&app, Method::POST, "/api/notes/", Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        let note = &body["data"]["note"];
+        assert_eq!(note["category_id"], category_id.as_str());
+        assert_eq!(note["chat_type"], "Note");
+        assert_eq!(note["version"], 1);
+        // category의 property마다 빈 값이 만들어짐
+        assert_eq!(note["prop_values"][0]["prop_name"], "Tags");
+        assert_eq!(note["prop_values"][0]["value"], Value::Null);
+
+        // 없는 category
+        let body = new_note("meeting", &ObjectId::new().to_hex());
+        let (status, _) = send(&app, Method::POST, "/api/notes/", Some(body)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_note_list() {
+        let app = app();
+        let note = create_note(&app).await;
+        let body = new_note("review", note["category_id"].as_str().unwrap());
+        send(&app, Method::POST, "/api/notes/", Some(body)).await;
+
+        let uri = "/api/notes?filter=title=review";
+        let (status, body) = send(&app, Method::GET, uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["results"], 1);
+        assert_eq!(body["notes"][0]["title"], "review");
+    }
+
+    #[tokio::test]
+    async fn test_get_note() {
+        let app = app();
+        let note = create_note(&app).await;
+
+        let uri = format!("/api/notes/{}", note["id"].as_str().unwrap());
+        let (status, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["note"]["title"], "meeting");
+    }
+
+    #[tokio::test]
+    async fn test_update_note() {
+        let app = app();
+        let note = create_note(&app).await;
+
+        let uri = format!("/api/notes/{}", note["id"].as_str().unwrap());
+        let body = json!({ "title": "renamed" });
+        let (status, body) = send(&app, Method::PATCH, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["note"]["title"], "renamed");
+        assert_eq!(body["data"]["note"]["version"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_delete_note() {
+        let app = app();
+        let note = create_note(&app).await;
+
+        let uri = format!("/api/notes/{}", note["id"].as_str().unwrap());
+        let (status, _) = send(&app, Method::DELETE, &uri, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (status, _) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_add_page() {
+        let app = app();
+        let note = create_note(&app).await;
+
+        let uri = format!("/api/notes/{}/pages/", note["id"].as_str().unwrap());
+        let (status, body) = send(&app, Method::POST, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["note"]["pages"].as_array().unwrap().len(), 1);
+        assert_eq!(body["data"]["note"]["version"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_subnotes() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let category_id = note["category_id"].as_str().unwrap();
+
+        // note <- child <- grandchild
+        let mut parent_id = note_id.to_string();
+        for title in ["child", "grandchild"] {
+            let mut body = new_note(title, category_id);
+            body["parent_id"] = json!(parent_id);
+            let (_, body) = send(&app, Method::POST, "/api/notes/", Some(body)).await;
+            parent_id = body["data"]["note"]["id"].as_str().unwrap().to_string();
+        }
+
+        let uri = format!("/api/notes/{}/subnotes", note_id);
+        let (status, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["results"], 2);
+        assert_eq!(body["notes"][0]["title"], "child");
+        assert_eq!(body["notes"][1]["title"], "grandchild");
+    }
+
+    #[tokio::test]
+    async fn test_add_note_msg() {
+        let app = app();
+        let note = create_note(&app).await;
+
+        let uri = format!("/api/notes/{}/chat/", note["id"].as_str().unwrap());
+        let body = json!({ "msg_type": "Text", "content": "hello", "booked": false });
+        let (status, body) = send(&app, Method::POST, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["msg"]["content"], "hello");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_note_msgs() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        add_msg(&app, note_id).await;
+
+        let uri = format!("/api/notes/{}/chat", note_id);
+        let (status, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["results"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_note_msg() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let msg_id = add_msg(&app, note_id).await;
+
+        let uri = format!("/api/notes/{}/chat/{}", note_id, msg_id);
+        let (status, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["msg"]["id"], msg_id.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_update_note_msg() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let msg_id = add_msg(&app, note_id).await;
+
+        let uri = format!("/api/notes/{}/chat/{}", note_id, msg_id);
+        let body = json!({ "content": "bye" });
+        let (status, body) = send(&app, Method::PATCH, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["msg"]["content"], "bye");
+        assert_eq!(body["data"]["msg"]["version"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_update_note_msg_with_stale_version() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let msg_id = add_msg(&app, note_id).await;
+
+        let uri = format!("/api/notes/{}/chat/{}", note_id, msg_id);
+        let body = Body::from(json!({ "content": "bye" }).to_string());
+        let request = Request::builder()
+            .method(Method::PATCH)
+            .uri(&uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::IF_MATCH, "\"5\"")
+            .body(body)
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[tokio::test]
+    async fn test_remove_note_msg() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let msg_id = add_msg(&app, note_id).await;
+
+        let uri = format!("/api/notes/{}/chat/{}", note_id, msg_id);
+        let (status, _) = send(&app, Method::DELETE, &uri, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (status, _) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_add_block() {
+        let app = app();
+        let note = create_note(&app).await;
+        let src_task_id = ObjectId::new().to_hex();
+
+        let uri = format!("/api/notes/{}/blocks/", note["id"].as_str().unwrap());
+        let body = json!({ "src_task_id": src_task_id, "block_type": "Code" });
+        let (status, body) = send(&app, Method::POST, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["block"]["src_task_id"], src_task_id.as_str());
+        assert_eq!(body["data"]["block"]["block_type"], "Code");
+        assert_eq!(body["data"]["block"]["body"], "");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_blocks() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        add_block(&app, note_id).await;
+        add_block(&app, note_id).await;
+
+        let uri = format!("/api/notes/{}/blocks", note_id);
+        let (status, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["results"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_block() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let block_id = add_block(&app, note_id).await;
+
+        let uri = format!("/api/notes/{}/blocks/{}", note_id, block_id);
+        let (status, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["block"]["id"], block_id.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_update_block() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let block_id = add_block(&app, note_id).await;
+
+        let uri = format!("/api/notes/{}/blocks/{}", note_id, block_id);
+        let body = json!({ "block_type": "Table" });
+        let (status, body) = send(&app, Method::PUT, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["block"]["block_type"], "Table");
+    }
+
+    #[tokio::test]
+    async fn test_remove_block() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let block_id = add_block(&app, note_id).await;
+
+        let uri = format!("/api/notes/{}/blocks/{}", note_id, block_id);
+        let (status, _) = send(&app, Method::DELETE, &uri, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (status, _) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_move_block() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let first = add_block(&app, note_id).await;
+        let second = add_block(&app, note_id).await;
+
+        // [second, first] -> [first, second]
+        let uri = format!("/api/notes/{}/blocks/{}/move", note_id, second);
+        let (status, _) = send(&app, Method::POST, &uri, Some(json!({ "index": 1 }))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let uri = format!("/api/notes/{}/blocks", note_id);
+        let (_, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(body["blocks"][0]["id"], first.as_str());
+        assert_eq!(body["blocks"][1]["id"], second.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_transfer_block() {
+        let app = app();
+        let note = create_note(&app).await;
+        let dst = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let dst_id = dst["id"].as_str().unwrap();
+        let block_id = add_block(&app, note_id).await;
+
+        let uri = format!("/api/notes/{}/blocks/{}/transfer", note_id, block_id);
+        let body = json!({ "dst_id": dst_id });
+        let (status, _) = send(&app, Method::POST, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let uri = format!("/api/notes/{}/blocks", note_id);
+        let (_, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(body["results"], 0);
+        let uri = format!("/api/notes/{}/blocks/{}", dst_id, block_id);
+        let (status, _) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_add_note_prop_value() {
+        let app = app();
+        let note = create_note(&app).await;
+        let prop_id = ObjectId::new().to_hex();
+
+        let uri = format!("/api/notes/{}/prop_values/", note["id"].as_str().unwrap());
+        let body = json!({
+            "prop_id": prop_id,
+            "prop_name": "Deadline",
+            "value": "2024-06-01",
+            "prop_type": "DateTime",
+        });
+        let (status, body) = send(&app, Method::POST, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["propV"]["id"], prop_id.as_str());
+        assert_eq!(body["data"]["propV"]["value"], "2024-06-01");
+
+        // MultiSelect에는 여러 값만 넣을 수 있음
+        let body = json!({
+            "prop_id": ObjectId::new().to_hex(),
+            "prop_name": "Labels",
+            "value": "one",
+            "prop_type": "MultiSelect",
+        });
+        let (status, _) = send(&app, Method::POST, &uri, Some(body)).await;
+        assert_ne!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_note_prop_values() {
+        let app = app();
+        let note = create_note(&app).await;
+
+        let uri = format!("/api/notes/{}/prop_values", note["id"].as_str().unwrap());
+        let (status, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["results"], 1);
+        assert_eq!(body["propVs"][0]["prop_name"], "Tags");
+    }
+
+    #[tokio::test]
+    async fn test_get_note_prop_value() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let prop_id = note["prop_values"][0]["id"].as_str().unwrap();
+        let uri = format!("/api/notes/{}/prop_values/{}", note_id, prop_id);
+        let (status, body) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["propV"]["prop_type"], "MultiSelect");
+    }
+
+    #[tokio::test]
+    async fn test_update_note_prop_value() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let prop_id = note["prop_values"][0]["id"].as_str().unwrap();
+        let uri = format!("/api/notes/{}/prop_values/{}", note_id, prop_id);
+
+        let body = json!({ "values": ["urgent", "work"] });
+        let (status, body) = send(&app, Method::PUT, &uri, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["propV"]["value"], json!(["urgent", "work"]));
+
+        // 저장된 prop_type(MultiSelect)에 맞지 않는 값
+        let body = json!({ "values": "urgent" });
+        let (status, _) = send(&app, Method::PUT, &uri, Some(body)).await;
+        assert_ne!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_remove_note_prop_value() {
+        let app = app();
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let prop_id = note["prop_values"][0]["id"].as_str().unwrap();
+        let uri = format!("/api/notes/{}/prop_values/{}", note_id, prop_id);
+
+        let (status, _) = send(&app, Method::DELETE, &uri, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (status, _) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_note_of_other_user() {
+        let app_state = Arc::new(AppState::in_memory());
+        let app = app_as(&app_state, USER, &[category_router, note_router]);
+        let other = app_as(&app_state, OTHER_USER, &[note_router]);
+        let note = create_note(&app).await;
+        let note_id = note["id"].as_str().unwrap();
+        let block_id = add_block(&app, note_id).await;
+        let msg_id = add_msg(&app, note_id).await;
+        let prop_id = note["prop_values"][0]["id"].as_str().unwrap();
+
+        for uri in [
+            format!("/api/notes/{}", note_id),
+            format!("/api/notes/{}/subnotes", note_id),
+            format!("/api/notes/{}/chat/{}", note_id, msg_id),
+            format!("/api/notes/{}/blocks/{}", note_id, block_id),
+            format!("/api/notes/{}/prop_values/{}", note_id, prop_id),
+        ] {
+            let (status, _) = send(&other, Method::GET, &uri, None).await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{}", uri);
+            let (status, _) = send(&other, Method::DELETE, &uri, None).await;
+            assert_ne!(status, StatusCode::NO_CONTENT, "{}", uri);
+        }
+
+        // 다른 user의 category로는 note를 만들 수 없음
+        let body = new_note("mine", note["category_id"].as_str().unwrap());
+        let (status, _) = send(&other, Method::POST, "/api/notes/", Some(body)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let uri = format!("/api/notes/{}", note_id);
+        let (status, _) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
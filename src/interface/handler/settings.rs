@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use crate::domain::settings::SettingsService;
+use crate::interface::dto::settings::req::UpdateSettingsReq;
+use crate::interface::handler::etag::{etag, IfMatch};
+use crate::{
+    auth::utils::auth::JWTAuthMiddleware,
+    domain::error::{Error, Result},
+    AppState,
+};
+use axum::{extract::State, response::IntoResponse, routing::get, Extension, Json, Router};
+
+pub fn settings_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route(
+            "/api/settings",
+            get(get_settings_handler).patch(update_settings_handler),
+        )
+        .with_state(app_state)
+}
+
+pub async fn get_settings_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match SettingsService::get_settings(&app_state.mongodb.db, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.settings.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn update_settings_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    IfMatch(if_match): IfMatch,
+    Json(body): Json<UpdateSettingsReq>,
+) -> Result<impl IntoResponse> {
+    match SettingsService::update_settings(&app_state.mongodb.db, &body, if_match, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.settings.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use crate::domain::sync::{SyncService, PULL_LIMIT_MAX};
+use crate::interface::dto::sync::req::{SyncPushReq, SyncQuery};
+use crate::{
+    auth::utils::auth::JWTAuthMiddleware,
+    domain::error::{Error, Result},
+    AppState,
+};
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    routing::get,
+    Extension, Json, Router,
+};
+
+pub fn sync_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/api/sync", get(sync_pull_handler).post(sync_push_handler))
+        .with_state(app_state)
+}
+
+pub async fn sync_pull_handler(
+    opts: Option<Query<SyncQuery>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match SyncService::pull(
+        &app_state.mongodb.db,
+        opts.since.as_deref(),
+        opts.limit.unwrap_or(PULL_LIMIT_MAX),
+        app_state.env.trash_retention_days,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn sync_push_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<SyncPushReq>,
+) -> Result<impl IntoResponse> {
+    match SyncService::push(&app_state.mongodb.db, &body, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
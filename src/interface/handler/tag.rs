@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     middleware,
     response::IntoResponse,
@@ -20,7 +20,8 @@ use crate::{
         error::{Error, Result},
         tag::TagService,
     },
-    interface::dto::tag::req::{CreateTagReq, UpdateTagReq},
+    infra::types::FilterOptions,
+    interface::dto::tag::req::{CreateTagReq, MergeTagReq, TagSuggestionQuery, UpdateTagReq},
     AppState,
 };
 
@@ -32,6 +33,9 @@ pub fn tag_router(app_state: Arc<AppState>) -> Router {
             get(tag_group_list_w_tag_handler).post(assign_group),
         )
         .route("/api/tags", get(tag_list_handler))
+        .route("/api/tags/stats", get(tag_stats_handler))
+        .route("/api/tags/suggestions", get(tag_suggestion_handler))
+        .route("/api/tags/:id/merge", post(merge_tag_handler))
         .route(
             "/api/tags/:id",
             get(get_tag_handler)
@@ -47,10 +51,67 @@ pub fn tag_router(app_state: Arc<AppState>) -> Router {
 }
 
 pub async fn tag_list_handler(
+    opts: Option<Query<FilterOptions>>,
     State(app_state): State<Arc<AppState>>,
     Extension(jwtauth): Extension<JWTAuthMiddleware>,
 ) -> Result<impl IntoResponse> {
-    match TagService::fetch_tags(&app_state.db, &jwtauth.user.id)
+    let Query(opts) = opts.unwrap_or_default();
+
+    match TagService::fetch_tags(
+        &app_state.db,
+        &jwtauth.user.id,
+        opts.page_opts(100),
+        opts.query_opts(),
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn tag_stats_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match TagService::fetch_tag_stats(&app_state.db, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn tag_suggestion_handler(
+    opts: Option<Query<TagSuggestionQuery>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match TagService::suggest_tags(
+        &app_state.db,
+        &jwtauth.user.id,
+        opts.q.as_deref(),
+        opts.limit,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn merge_tag_handler(
+    Path(id): Path<Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<MergeTagReq>,
+) -> Result<impl IntoResponse> {
+    match TagService::merge_tag(&app_state.db, &jwtauth.user.id, id, body.target_id)
         .await
         .map_err(Error::from)
     {
@@ -146,20 +207,13 @@ pub async fn assign_group(
 pub async fn delete_tag_handler(
     Path(id): Path<Uuid>,
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
 ) -> Result<impl IntoResponse> {
-    match TagService::delete_tag(&app_state.db, id)
+    match TagService::delete_tag(&app_state.db, &jwtauth.user.id, id)
         .await
         .map_err(Error::from)
     {
-        Ok(_) => {
-            match TagRelationService::delete_relation(&app_state.db, id)
-                .await
-                .map_err(Error::from)
-            {
-                Ok(_) => Ok(StatusCode::NO_CONTENT),
-                Err(e) => Err(e),
-            }
-        }
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e),
     }
 }
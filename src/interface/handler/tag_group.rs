@@ -1,11 +1,11 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     middleware,
     response::IntoResponse,
-    routing::{get, post},
+    routing::{get, post, put},
     Extension, Json, Router,
 };
 use uuid::Uuid;
@@ -20,7 +20,8 @@ use crate::{
         error::{Error, Result},
         tag_group::TagGroupService,
     },
-    interface::dto::tag_group::req::{CreateTagGroupReq, UpdateTagGroupReq},
+    infra::types::FilterOptions,
+    interface::dto::tag_group::req::{CreateTagGroupReq, MoveTagGroupReq, UpdateTagGroupReq},
     AppState,
 };
 
@@ -38,6 +39,7 @@ pub fn tag_group_router(app_state: Arc<AppState>) -> Router {
                 .patch(update_tag_group_handler)
                 .delete(delete_tag_group_handler),
         )
+        .route("/api/tag_groups/:id/parent", put(move_tag_group_handler))
         .route(
             "/api/tag_groups_all/:id",
             get(get_tag_group_w_tag_handler),
@@ -50,12 +52,20 @@ pub fn tag_group_router(app_state: Arc<AppState>) -> Router {
 }
 
 pub async fn tag_group_list_handler(
+    opts: Option<Query<FilterOptions>>,
     State(app_state): State<Arc<AppState>>,
     Extension(jwtauth): Extension<JWTAuthMiddleware>,
 ) -> Result<impl IntoResponse> {
-    match TagGroupService::fetch_groups(&app_state.db, &jwtauth.user.id)
-        .await
-        .map_err(Error::from)
+    let Query(opts) = opts.unwrap_or_default();
+
+    match TagGroupService::fetch_groups(
+        &app_state.db,
+        &jwtauth.user.id,
+        opts.page_opts(100),
+        opts.query_opts(),
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
@@ -132,6 +142,21 @@ pub async fn update_tag_group_handler(
     }
 }
 
+pub async fn move_tag_group_handler(
+    Path(id): Path<Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<MoveTagGroupReq>,
+) -> Result<impl IntoResponse> {
+    match TagGroupService::move_group(&app_state.db, &jwtauth.user.id, id, body.parent_id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
 pub async fn add_tag_to_group(
     State(app_state): State<Arc<AppState>>,
     Extension(jwtauth): Extension<JWTAuthMiddleware>,
@@ -149,20 +174,13 @@ pub async fn add_tag_to_group(
 pub async fn delete_tag_group_handler(
     Path(id): Path<Uuid>,
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
 ) -> Result<impl IntoResponse> {
-    match TagGroupService::delete_group(&app_state.db, id)
+    match TagGroupService::delete_group(&app_state.db, &jwtauth.user.id, id)
         .await
         .map_err(Error::from)
     {
-        Ok(_) => {
-            match TagRelationService::delete_relation(&app_state.db, id)
-                .await
-                .map_err(Error::from)
-            {
-                Ok(_) => Ok(StatusCode::NO_CONTENT),
-                Err(e) => Err(e),
-            }
-        },
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => Err(e),
     }
 }
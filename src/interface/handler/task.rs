@@ -1,23 +1,29 @@
-use std::{
-    collections::{hash_map, HashMap},
-    sync::Arc,
-};
+use std::sync::Arc;
 
 use crate::{
     auth::utils::auth::{auth_request, JWTAuthMiddleware},
     domain::{
         error::{Error, Result},
         sub::chat::ChatMsgService,
+        item_tag::ItemTagService,
         task::{TaskModel, TaskService},
     },
-    infra::types::{FilterOptions, TaskTreeItem},
+    infra::types::{
+        FieldsOptions, FilterOptions, ItemType, PageOptions, QueryOptions, TaskTreeItem,
+    },
     interface::dto::{
+        batch::req::{BatchDeleteReq, BatchUpdateReq},
         sub::chat::req::{CreateMsgReq, UpdateMsgReq},
         task::{
-            req::{CreateTaskReq, DeleteTaskOptionReq, TaskFilterOptions, UpdateTaskReq},
+            req::{
+                AddDependencyReq, CloneTaskReq, CreateTaskReq, CriticalPathOptions,
+                DeleteTaskOptionReq, MoveTaskReq, OccurrenceScopeOptions, TaskFilterOptions,
+                UpdateOccurrenceReq, UpdateTaskReq,
+            },
             res::{TaskListRes, TaskListTreeRes, TaskRes},
         },
     },
+    interface::handler::etag::{etag, IfMatch},
     AppState,
 };
 use axum::{
@@ -25,22 +31,47 @@ use axum::{
     http::StatusCode,
     middleware,
     response::IntoResponse,
-    routing::{get, post,delete,patch},
+    routing::{get, post,delete,patch,put},
     Extension, Json, Router,
 };
-use mongodb::Database;
-use uuid::Uuid;
+use chrono::NaiveDate;
 
 pub fn task_router(app_state: Arc<AppState>) -> Router {
     Router::new()
         .route("/api/tasks/", post(create_task_handler))
         .route("/api/tasks", get(task_list_handler))
+        .route(
+            "/api/tasks/batch",
+            post(batch_create_task_handler)
+                .patch(batch_update_task_handler)
+                .delete(batch_delete_task_handler),
+        )
         .route(
             "/api/tasks/:id",
             get(get_task_handler)
                 .patch(update_task_handler)
                 .delete(delete_task_handler),
         )
+        .route("/api/tasks/critical-path", get(critical_path_handler))
+        .route("/api/tasks/capacity", get(capacity_handler))
+        .route("/api/tasks/:id/dependencies", post(add_dependency_handler))
+        .route(
+            "/api/tasks/:id/dependencies/:depends_on",
+            delete(remove_dependency_handler),
+        )
+        .route(
+            "/api/tasks/:id/occurrences/:date",
+            patch(update_occurrence_handler).delete(delete_occurrence_handler),
+        )
+        .route("/api/tasks/:id/parent", put(move_task_handler))
+        .route("/api/tasks/:id/path", get(task_path_handler))
+        .route("/api/tasks/:id/subtree", get(task_subtree_handler))
+        .route("/api/tasks/:id/clone", post(clone_task_handler))
+        .route("/api/tasks/:id/history", get(task_history_handler))
+        .route(
+            "/api/tasks/:id/history/:change_id/revert",
+            post(revert_task_handler),
+        )
         .route("/api/tasks/:task_id/chat/", post(add_task_msg_handler))
         .route("/api/tasks/:task_id/chat", get(fetch_msgs_handler))
         .route(
@@ -67,8 +98,23 @@ pub async fn task_list_handler(
 ) -> Result<impl IntoResponse> {
     let Query(opts) = opts.unwrap_or_default();
 
-    let limit = opts.limit.unwrap_or(100) as i64;
-    let page = opts.page.unwrap_or(1) as i64;
+    let page_opts = PageOptions {
+        limit: opts.limit.unwrap_or(100) as i64,
+        page: opts.page.unwrap_or(1) as i64,
+        cursor: opts.cursor,
+        with_total: opts.with_total.unwrap_or(false),
+    };
+    let mut query_opts = QueryOptions {
+        filter: opts.filter,
+        sort: opts.sort,
+        fields: opts.fields.clone(),
+        tag: opts.tag,
+        tag_group: opts.tag_group,
+        ids: None,
+    };
+
+    ItemTagService::resolve_filter(&app_state.db, &jwtauth.user.id, ItemType::Task, &mut query_opts)
+        .await?;
 
     // 날짜 입력 없으면 모든 tasks 가져옴.
     let start_date = opts.start_date.map(|d| d.to_string()).unwrap_or_default();
@@ -76,8 +122,8 @@ pub async fn task_list_handler(
 
     match TaskService::fetch_tasks(
         &app_state.mongodb.db,
-        limit,
-        page,
+        page_opts,
+        query_opts,
         &start_date,
         &end_date,
         &jwtauth.user.id,
@@ -86,12 +132,30 @@ pub async fn task_list_handler(
     .map_err(Error::from)
     {
         Ok(res) => {
-            let task_tree =
-                build_task_tree(&app_state.mongodb.db, &jwtauth.user.id, res.tasks).await?;
+            let mut task_tree = TaskService::build_tree(
+                &app_state.mongodb.db,
+                &jwtauth.user.id,
+                res.tasks,
+                opts.fields.as_deref(),
+            )
+            .await?;
+            let mut tasks = Vec::new();
+            collect_tree_tasks(&mut task_tree, &mut tasks);
+            ItemTagService::attach_tags(
+                &app_state.db,
+                &jwtauth.user.id,
+                ItemType::Task,
+                opts.fields.as_deref(),
+                tasks,
+            )
+            .await;
             Ok(Json(TaskListTreeRes {
                 status: "success",
                 results: task_tree.len(),
                 tasks: task_tree,
+                next_cursor: res.next_cursor,
+                has_more: res.has_more,
+                total: res.total,
             }))
         }
         Err(e) => Err(e),
@@ -106,18 +170,46 @@ pub async fn create_task_handler(
     match TaskService::create_task(&app_state.mongodb.db, &body, &jwtauth.user.id)
         .await
         .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.task.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn batch_create_task_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<Vec<CreateTaskReq>>,
+) -> Result<impl IntoResponse> {
+    match TaskService::bulk_create_tasks(&app_state.mongodb.db, &body, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
     }
 }
 
-pub async fn get_task_handler(
-    Path(id): Path<String>,
+pub async fn batch_update_task_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<Vec<BatchUpdateReq<UpdateTaskReq>>>,
+) -> Result<impl IntoResponse> {
+    match TaskService::bulk_update_tasks(&app_state.mongodb.db, &body, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn batch_delete_task_handler(
     State(app_state): State<Arc<AppState>>,
     Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<BatchDeleteReq>,
 ) -> Result<impl IntoResponse> {
-    match TaskService::get_task(&app_state.mongodb.db, &id, &jwtauth.user.id)
+    match TaskService::bulk_delete_tasks(&app_state.mongodb.db, &body.ids, &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -126,13 +218,97 @@ pub async fn get_task_handler(
     }
 }
 
+pub async fn get_task_handler(
+    Path(id): Path<String>,
+    opts: Option<Query<FieldsOptions>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match TaskService::get_task(
+        &app_state.mongodb.db,
+        &id,
+        opts.fields.as_deref(),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(mut res) => {
+            let task = vec![&mut *res.data.task];
+            ItemTagService::attach_tags(
+                &app_state.db,
+                &jwtauth.user.id,
+                ItemType::Task,
+                opts.fields.as_deref(),
+                task,
+            )
+            .await;
+            Ok((etag(res.data.task.version), Json(res)))
+        }
+        Err(e) => Err(e),
+    }
+}
+
 pub async fn update_task_handler(
     Path(id): Path<String>,
     State(app_state): State<Arc<AppState>>,
     Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    IfMatch(if_match): IfMatch,
     Json(body): Json<UpdateTaskReq>,
 ) -> Result<impl IntoResponse> {
-    match TaskService::update_task(&app_state.mongodb.db, &id, &body, &jwtauth.user.id)
+    match TaskService::update_task(&app_state.mongodb.db, &id, &body, if_match, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.task.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn add_dependency_handler(
+    Path(id): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<AddDependencyReq>,
+) -> Result<impl IntoResponse> {
+    match TaskService::add_dependency(
+        &app_state.mongodb.db,
+        &id,
+        &body.depends_on,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.task.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn remove_dependency_handler(
+    Path((id, depends_on)): Path<(String, String)>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match TaskService::remove_dependency(&app_state.mongodb.db, &id, &depends_on, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.task.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn critical_path_handler(
+    opts: Option<Query<CriticalPathOptions>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match TaskService::critical_path(&app_state.mongodb.db, opts.root.as_deref(), &jwtauth.user.id)
         .await
         .map_err(Error::from)
     {
@@ -141,6 +317,207 @@ pub async fn update_task_handler(
     }
 }
 
+pub async fn capacity_handler(
+    opts: Option<Query<TaskFilterOptions>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    let mut query_opts = QueryOptions {
+        filter: opts.filter,
+        sort: None,
+        fields: None,
+        tag: opts.tag,
+        tag_group: opts.tag_group,
+        ids: None,
+    };
+    ItemTagService::resolve_filter(&app_state.db, &jwtauth.user.id, ItemType::Task, &mut query_opts)
+        .await?;
+
+    match TaskService::capacity(
+        &app_state.mongodb.db,
+        query_opts,
+        opts.start_date,
+        opts.end_date,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn move_task_handler(
+    Path(id): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<MoveTaskReq>,
+) -> Result<impl IntoResponse> {
+    match TaskService::move_task(
+        &app_state.mongodb.db,
+        &id,
+        body.parent_id.as_deref(),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.task.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn task_path_handler(
+    Path(id): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match TaskService::task_path(&app_state.mongodb.db, &id, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn task_subtree_handler(
+    Path(id): Path<String>,
+    opts: Option<Query<FieldsOptions>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match TaskService::fetch_subtree(
+        &app_state.mongodb.db,
+        &id,
+        opts.fields.as_deref(),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(mut res) => {
+            let mut tasks = Vec::new();
+            collect_tree_tasks(std::slice::from_mut(&mut res.task), &mut tasks);
+            ItemTagService::attach_tags(
+                &app_state.db,
+                &jwtauth.user.id,
+                ItemType::Task,
+                opts.fields.as_deref(),
+                tasks,
+            )
+            .await;
+            Ok(Json(res))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// body가 없으면 chat_msgs 없이 같은 날짜로 복사
+pub async fn clone_task_handler(
+    Path(id): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    body: Option<Json<CloneTaskReq>>,
+) -> Result<impl IntoResponse> {
+    let Json(body) = body.unwrap_or_default();
+
+    match TaskService::clone_task(&app_state.mongodb.db, &id, &body, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn update_occurrence_handler(
+    Path((id, date)): Path<(String, NaiveDate)>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    IfMatch(if_match): IfMatch,
+    Json(body): Json<UpdateOccurrenceReq>,
+) -> Result<impl IntoResponse> {
+    match TaskService::update_occurrence(
+        &app_state.mongodb.db,
+        &id,
+        date,
+        &body,
+        if_match,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn delete_occurrence_handler(
+    Path((id, date)): Path<(String, NaiveDate)>,
+    opts: Option<Query<OccurrenceScopeOptions>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match TaskService::delete_occurrence(
+        &app_state.mongodb.db,
+        &id,
+        date,
+        opts.scope.unwrap_or_default(),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn task_history_handler(
+    Path(id): Path<String>,
+    opts: Option<Query<FilterOptions>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match TaskService::fetch_task_history(
+        &app_state.mongodb.db,
+        &id,
+        opts.page_opts(20),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn revert_task_handler(
+    Path((id, change_id)): Path<(String, String)>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match TaskService::revert_task(&app_state.mongodb.db, &id, &change_id, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.task.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
 pub async fn delete_task_handler(
     Path(id): Path<String>,
     State(app_state): State<Arc<AppState>>,
@@ -160,38 +537,56 @@ pub async fn delete_task_handler(
 // Chat Handlers for Event
 pub async fn get_task_msg_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((task_id, msg_id)): Path<(String, String)>,
 ) -> Result<impl IntoResponse> {
-    match ChatMsgService::<TaskModel>::get_msg(&app_state.mongodb.db, &task_id, &msg_id)
-        .await
-        .map_err(Error::from)
+    match ChatMsgService::<TaskModel>::get_msg(
+        &app_state.mongodb.db,
+        &task_id,
+        &msg_id,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
-        Ok(res) => Ok(Json(res)),
+        Ok(res) => Ok((etag(res.data.msg.version), Json(res))),
         Err(e) => Err(e),
     }
 }
 
 pub async fn add_task_msg_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((task_id,)): Path<(String,)>,
     Json(new_msg): Json<CreateMsgReq>,
 ) -> Result<impl IntoResponse> {
-    match ChatMsgService::<TaskModel>::add_msg(&app_state.mongodb.db, &task_id, &new_msg)
-        .await
-        .map_err(Error::from)
+    match ChatMsgService::<TaskModel>::add_msg(
+        &app_state.mongodb.db,
+        &task_id,
+        &new_msg,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
-        Ok(res) => Ok(Json(res)),
+        Ok(res) => Ok((etag(res.data.msg.version), Json(res))),
         Err(e) => Err(e),
     }
 }
 
 pub async fn remove_task_msg_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((task_id, msg_id)): Path<(String, String)>,
 ) -> Result<impl IntoResponse> {
-    match ChatMsgService::<TaskModel>::remove_msg(&app_state.mongodb.db, &task_id, &msg_id)
-        .await
-        .map_err(Error::from)
+    match ChatMsgService::<TaskModel>::remove_msg(
+        &app_state.mongodb.db,
+        &task_id,
+        &msg_id,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
@@ -200,7 +595,9 @@ pub async fn remove_task_msg_handler(
 
 pub async fn update_task_msg_handler(
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((task_id, msg_id)): Path<(String, String)>,
+    IfMatch(if_match): IfMatch,
     Json(update_req): Json<UpdateMsgReq>,
 ) -> Result<impl IntoResponse> {
     match ChatMsgService::<TaskModel>::update_msg(
@@ -208,11 +605,13 @@ pub async fn update_task_msg_handler(
         &task_id,
         &msg_id,
         &update_req,
+        if_match,
+        &jwtauth.user.id,
     )
     .await
     .map_err(Error::from)
     {
-        Ok(res) => Ok(Json(res)),
+        Ok(res) => Ok((etag(res.data.msg.version), Json(res))),
         Err(e) => Err(e),
     }
 }
@@ -220,16 +619,19 @@ pub async fn update_task_msg_handler(
 pub async fn fetch_msgs_handler(
     opts: Option<Query<FilterOptions>>,
     State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
     Path((task_id,)): Path<(String,)>,
 ) -> Result<impl IntoResponse> {
     let Query(opts) = opts.unwrap_or_default();
 
-    let limit = opts.limit.unwrap_or(10) as i64;
-    let page = opts.page.unwrap_or(1) as i64;
-
-    match ChatMsgService::<TaskModel>::fetch_msgs(&app_state.mongodb.db, &task_id, limit, page)
-        .await
-        .map_err(Error::from)
+    match ChatMsgService::<TaskModel>::fetch_msgs(
+        &app_state.mongodb.db,
+        &task_id,
+        opts.page_opts(10),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
     {
         Ok(res) => Ok(Json(res)),
         Err(e) => Err(e),
@@ -249,64 +651,10 @@ pub async fn fetch_msgs_handler(
 //     Json(messages)
 // }
 
-async fn build_task_tree(
-    db: &Database,
-    user: &Uuid,
-    tasks: Vec<TaskRes>,
-) -> Result<Vec<TaskTreeItem>> {
-    if tasks.is_empty() {
-        return Ok(vec![]);
+// tree의 모든 task(subtask 포함)
+fn collect_tree_tasks<'a>(items: &'a mut [TaskTreeItem], tasks: &mut Vec<&'a mut TaskRes>) {
+    for item in items {
+        tasks.push(&mut item.task);
+        collect_tree_tasks(&mut item.subtasks, tasks);
     }
-
-    let mut task_item_map: HashMap<String, TaskTreeItem> = HashMap::new();
-    for task in tasks {
-        /*
-          * tasks를 fetch할 때 sort옵션을 parent_id:1로 두어 parent_id가 없는 task가 먼저 나오도록 함
-        parent_id가 없는 경우 HashMap에 추가
-        parent_id가 있는 경우, map에서 parent_id를 찾아서 parent의 subtasks에 추가
-        parent_id가 있는데, 해당 parent가 아직 map에 없다면? -> db에서 task검색하여 추가(fetch를 날짜에 대해 진행했으므로 입력받은 tasks에는 parent가 없을 수 있음)
-         */
-        if let Some(parent_id) = task.parent_id.clone().filter(|id| !id.is_empty()) {            
-            tracing::info!("child task: {:?}, ", &task.start_date);
-            let parent_task_item = match task_item_map.entry(parent_id.clone()) {
-                hash_map::Entry::Occupied(entry) => entry.into_mut(),
-                hash_map::Entry::Vacant(entry) => {
-                    match TaskService::get_task(&db, &parent_id, user)
-                        .await
-                        .map_err(Error::from)
-                    {
-                        Ok(res) => entry.insert(TaskTreeItem {
-                            task: res.data.task,
-                            subtasks: Vec::new(),
-                        }),
-                        Err(e) => {
-                            return Err(e);
-                        }
-                    }
-                }
-            };
-            parent_task_item.subtasks.push(TaskTreeItem {
-                task,
-                subtasks: Vec::new(),
-            });
-        } else {
-            tracing::info!("task: {:?}, ", &task.start_date);
-            task_item_map.insert(
-                task.id.clone(),
-                TaskTreeItem {
-                    task: task.clone(),
-                    subtasks: Vec::new(),
-                },
-            );
-        };
-    }
-
-	 //  정렬된 결과 반환
-    let mut task_tree: Vec<TaskTreeItem> = task_item_map.into_values().collect();
-    task_tree.sort_by(|a, b| {
-        a.task.start_date.cmp(&b.task.start_date)
-            .then_with(|| b.task.end_date.cmp(&a.task.end_date))
-    });
-	 
-    Ok(task_tree)
 }
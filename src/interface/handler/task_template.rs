@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use crate::domain::task_template::TaskTemplateService;
+use crate::infra::types::{FieldsOptions, FilterOptions};
+use crate::interface::dto::task_template::req::{
+    CreateTaskTemplateReq, InstantiateTemplateReq, UpdateTaskTemplateReq,
+};
+use crate::interface::handler::etag::{etag, IfMatch};
+use crate::{
+    auth::utils::auth::JWTAuthMiddleware,
+    domain::error::{Error, Result},
+    AppState,
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+
+pub fn task_template_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/api/task_templates/", post(create_template_handler))
+        .route("/api/task_templates", get(template_list_handler))
+        .route(
+            "/api/task_templates/:id",
+            get(get_template_handler)
+                .patch(update_template_handler)
+                .delete(delete_template_handler),
+        )
+        .route(
+            "/api/task_templates/:id/instantiate",
+            post(instantiate_template_handler),
+        )
+        .with_state(app_state)
+}
+
+pub async fn template_list_handler(
+    opts: Option<Query<FilterOptions>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match TaskTemplateService::fetch_templates(
+        &app_state.mongodb.db,
+        opts.page_opts(10),
+        opts.query_opts(),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn create_template_handler(
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<CreateTaskTemplateReq>,
+) -> Result<impl IntoResponse> {
+    match TaskTemplateService::create_template(&app_state.mongodb.db, &body, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.template.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn get_template_handler(
+    Path(id): Path<String>,
+    opts: Option<Query<FieldsOptions>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match TaskTemplateService::get_template(
+        &app_state.mongodb.db,
+        &id,
+        opts.fields.as_deref(),
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.template.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn update_template_handler(
+    Path(id): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    IfMatch(if_match): IfMatch,
+    Json(body): Json<UpdateTaskTemplateReq>,
+) -> Result<impl IntoResponse> {
+    match TaskTemplateService::update_template(
+        &app_state.mongodb.db,
+        &id,
+        &body,
+        if_match,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.template.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn delete_template_handler(
+    Path(id): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match TaskTemplateService::delete_template(&app_state.mongodb.db, &id, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn instantiate_template_handler(
+    Path(id): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<InstantiateTemplateReq>,
+) -> Result<impl IntoResponse> {
+    match TaskTemplateService::instantiate_template(
+        &app_state.mongodb.db,
+        &id,
+        &body,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Method, Request, StatusCode};
+use axum::{Extension, Router};
+use serde_json::Value;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::auth::{model::User, utils::auth::JWTAuthMiddleware};
+use crate::AppState;
+
+pub const USER: Uuid = Uuid::from_bytes([0; 16]);
+pub const OTHER_USER: Uuid = Uuid::from_bytes([1; 16]);
+
+type RouterFn = fn(Arc<AppState>) -> Router;
+
+// auth layer 대신 인증된 user를 직접 넣은 router. 모든 router가 같은 저장소를 사용함.
+pub fn app(routers: &[RouterFn]) -> Router {
+    app_as(&Arc::new(AppState::in_memory()), USER, routers)
+}
+
+// 같은 저장소를 다른 user로 접근할 때 사용.
+pub fn app_as(app_state: &Arc<AppState>, user: Uuid, routers: &[RouterFn]) -> Router {
+    let jwtauth = JWTAuthMiddleware {
+        user: User {
+            id: user,
+            name: "tester".to_string(),
+            email: "tester@tootodo.life".to_string(),
+            password: None,
+            role: "user".to_string(),
+            photo: "".to_string(),
+            verified: true,
+            provider: "local".to_string(),
+            created_at: None,
+            updated_at: None,
+        },
+        access_token_uuid: Uuid::new_v4(),
+    };
+
+    routers
+        .iter()
+        .fold(Router::new(), |app, router| {
+            app.merge(router(app_state.clone()))
+        })
+        .layer(Extension(jwtauth))
+}
+
+pub async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json");
+    let request = match body {
+        Some(body) => request.body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, body)
+}
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use crate::domain::timer::TimerService;
+use crate::interface::dto::timer::req::{StartTimerReq, TimeReportOptions};
+use crate::interface::handler::etag::etag;
+use crate::{
+    auth::utils::auth::JWTAuthMiddleware,
+    domain::error::{Error, Result},
+    AppState,
+};
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+
+pub fn timer_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/api/tasks/:id/timer/start", post(start_timer_handler))
+        .route("/api/tasks/:id/timer/pause", post(pause_timer_handler))
+        .route("/api/tasks/:id/timer/stop", post(stop_timer_handler))
+        .route("/api/tasks/:id/time_report", get(task_time_report_handler))
+        .route("/api/time_report", get(category_time_report_handler))
+        .with_state(app_state)
+}
+
+pub async fn start_timer_handler(
+    Path(id): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<StartTimerReq>,
+) -> Result<impl IntoResponse> {
+    match TimerService::start_timer(&app_state.mongodb.db, &id, &body, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.task.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn pause_timer_handler(
+    Path(id): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match TimerService::pause_timer(&app_state.mongodb.db, &id, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.task.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn stop_timer_handler(
+    Path(id): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match TimerService::stop_timer(&app_state.mongodb.db, &id, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok((etag(res.data.task.version), Json(res))),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn task_time_report_handler(
+    Path(id): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match TimerService::task_time_report(&app_state.mongodb.db, &id, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn category_time_report_handler(
+    Query(opts): Query<TimeReportOptions>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match TimerService::category_time_report(
+        &app_state.mongodb.db,
+        opts.from,
+        opts.to,
+        &jwtauth.user.id,
+    )
+    .await
+    .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use crate::domain::trash::TrashService;
+use crate::infra::types::{FilterOptions, TrashKind};
+use crate::{
+    auth::utils::auth::JWTAuthMiddleware,
+    domain::error::{Error, Result},
+    AppState,
+};
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+
+pub fn trash_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/api/trash", get(trash_list_handler))
+        .route("/api/trash/:kind/:id/restore", post(restore_trash_handler))
+        .with_state(app_state)
+}
+
+pub async fn trash_list_handler(
+    opts: Option<Query<FilterOptions>>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    let Query(opts) = opts.unwrap_or_default();
+
+    match TrashService::fetch_trash(&app_state.mongodb.db, opts.page_opts(20), &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn restore_trash_handler(
+    Path((kind, id)): Path<(TrashKind, String)>,
+    State(app_state): State<Arc<AppState>>,
+    Extension(jwtauth): Extension<JWTAuthMiddleware>,
+) -> Result<impl IntoResponse> {
+    match TrashService::restore(&app_state.mongodb.db, kind, &id, &jwtauth.user.id)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(res) => Ok(Json(res)),
+        Err(e) => Err(e),
+    }
+}
@@ -3,18 +3,29 @@ use std::sync::Arc;
 use axum::{middleware, Router};
 
 use super::handler::{
-    task::task_router, habit::habit_router, memo::memo_router,
-    // note::note_router, tag_group::tag_group_router,
+    task::task_router, habit::habit_router, memo::memo_router, trash::trash_router,
+    daily::daily_router, sync::sync_router, tag::tag_router, tag_group::tag_group_router,
+    item_tag::item_tag_router, task_template::task_template_router, timer::timer_router,
+    settings::settings_router, category::category_router, note::note_router,
 };
 use crate::{auth::utils::auth::auth_request, AppState};
 
 pub fn create_router(app_state: Arc<AppState>) -> Router {
     Router::new()
-        // .merge(tag_group_router(app_state.clone()))
-        // .merge(note_router(app_state.clone()))
+        .merge(tag_router(app_state.clone()))
+        .merge(tag_group_router(app_state.clone()))
+        .merge(item_tag_router(app_state.clone()))
+        .merge(category_router(app_state.clone()))
+        .merge(note_router(app_state.clone()))
         .merge(habit_router(app_state.clone()))
         .merge(memo_router(app_state.clone()))
         .merge(task_router(app_state.clone()))
+        .merge(task_template_router(app_state.clone()))
+        .merge(timer_router(app_state.clone()))
+        .merge(settings_router(app_state.clone()))
+        .merge(trash_router(app_state.clone()))
+        .merge(daily_router(app_state.clone()))
+        .merge(sync_router(app_state.clone()))
         .route_layer(middleware::from_fn_with_state(
             app_state.clone(),
             auth_request,
@@ -43,6 +43,23 @@ pub struct AppState {
     // pub redis_client: Client,
 }
 
+impl AppState {
+    // Mongo/Postgres 없이 동작하는 AppState(테스트용).
+    // mongodb는 MemoryStorage를 사용하며, postgres pool은 실제 쿼리 전까지 연결하지 않음.
+    pub fn in_memory() -> Self {
+        let postgredb = DB::init_lazy("postgres://localhost/tootodo_test");
+
+        Self {
+            db: postgredb.db,
+            mongodb: MongoDB::init_memory(),
+            env: Config {
+                trash_retention_days: 30,
+                ..Config::default()
+            },
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args: Vec<String> = std::env::args().collect();
@@ -78,6 +95,12 @@ async fn main() -> Result<()> {
     let postgredb = DB::init().await?;
     let mongodb = MongoDB::init().await?;
 
+    domain::trash::TrashService::spawn_purge_job(
+        mongodb.db.clone(),
+        postgredb.db.clone(),
+        config.trash_retention_days,
+    );
+
     let app_state = Arc::new(AppState {
         db: postgredb.db.clone(),
         mongodb: mongodb.clone(),